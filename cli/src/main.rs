@@ -0,0 +1,200 @@
+/*
+    main.rs
+    Entry point for the headless command-line runner
+*/
+
+use std::{io::{self, Write}, path::Path, process::ExitCode};
+
+use lib::{cache::{Cache, CacheConfig}, console::StdioConsole, cpu_enum::{Architecture, UndefinedPolicy}, machine::Machine, memory::{self, AlignmentPolicy, Memory}, options::Options};
+
+const USAGE: &str = "\
+usage: armsim-cli [--mem <bytes>] [--alignment <policy>] [--arch <arch>] [--undefined <policy>] [--icache <config>] [--dcache <config>] [--traceall] [--trace-cycles] [--trace-file <path>] <elf-file>
+
+loads an ELF file, runs it until it halts and writes the trace log
+terminal output is printed to stdout and input prompts are read from stdin
+an undefined instruction or abort with no handler installed halts with exit code 1
+
+options:
+  -m, --mem <bytes>        number of bytes in the simulated RAM
+      --alignment <policy> unaligned load/store behavior: rotate (ARMv4, default), fault or unaligned (ARMv6)
+      --arch <arch>        instruction set to decode: armv4t (default) or armv5te
+      --undefined <policy> undefined instructions: exception (enter the handler, default) or halt
+      --icache <config>    simulate an instruction cache and print its hit/miss counts after the run
+      --dcache <config>    simulate a data cache and print its hit/miss counts after the run;
+                           <config> is <size>,<line size>,<associativity>[,lru|fifo|random[,writeback|writethrough]]
+                           e.g. 16k,32,4,lru,writeback
+      --traceall           enable trace logging for all processor modes (default: only log SYS)
+      --trace-cycles       append the total ARM7TDMI cycle count to each trace line
+      --trace-file <path>  path of the trace log (default: trace.log)
+  -h, --help               print this message";
+
+// parses the process arguments into Options, or returns an error message
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut opts = Options::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0)
+            }
+            "-m" | "--mem" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                let size = value.parse::<usize>().map_err(|_| format!("invalid memory size {}", value))?;
+                if size > memory::MAX_MEMORY_SIZE {
+                    return Err(format!("memory size {} is larger than {} bytes", size, memory::MAX_MEMORY_SIZE))
+                }
+                opts.set_arg("mem", &value)?;
+            }
+            "--alignment" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                value.parse::<AlignmentPolicy>()?;
+                opts.set_arg("alignment", &value)?;
+            }
+            "--arch" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                value.parse::<Architecture>()?;
+                opts.set_arg("arch", &value)?;
+            }
+            "--undefined" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                value.parse::<UndefinedPolicy>()?;
+                opts.set_arg("undefined", &value)?;
+            }
+            "--icache" | "--dcache" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                value.parse::<CacheConfig>()?;
+                opts.set_arg(&arg[2..], &value)?;
+            }
+            "--trace-file" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                opts.set_arg("trace-file", &value)?;
+            }
+            "--traceall" => opts.set_arg("traceall", "")?,
+            "--trace-cycles" => opts.set_arg("trace-cycles", "")?,
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ => {
+                if opts.elf_file.is_some() {
+                    return Err(format!("unexpected argument {}", arg))
+                }
+                opts.set_arg("elf-file", &arg)?;
+            }
+        }
+    }
+
+    if opts.elf_file.is_none() {
+        return Err(String::from("no ELF file given"))
+    }
+
+    Ok(opts)
+}
+
+fn main() -> ExitCode {
+    let opts = match parse_args(std::env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("armsim-cli: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2)
+        }
+    };
+
+    let mut machine = Machine::new(opts.memory_size.unwrap_or(memory::DEFAULT_MEMORY_SIZE));
+    machine.cpu.toggle_trace();
+    if opts.traceall { machine.trace.set_traceall(); }
+    if opts.trace_cycles { machine.trace.set_cycles(); }
+    if let Some(path) = &opts.trace_file { machine.trace.set_path(path); }
+    if let Some(policy) = opts.alignment { machine.ram.set_alignment_policy(policy); }
+    if let Some(architecture) = opts.architecture { machine.cpu.set_architecture(architecture); }
+    if let Some(policy) = opts.undefined { machine.cpu.set_undefined_policy(policy); }
+    if let Some(config) = opts.icache { machine.ram.icache = Some(Cache::new(config)); }
+    if let Some(config) = opts.dcache { machine.ram.dcache = Some(Cache::new(config)); }
+    machine.set_console(StdioConsole);
+
+    let elf_file = opts.elf_file.unwrap_or_default();
+    if let Err(e) = machine.load_elf(Path::new(&elf_file)) {
+        eprintln!("armsim-cli: failed to load {}: {}", elf_file, e);
+        return ExitCode::FAILURE
+    }
+
+    if let Some(e) = machine.trace.open_trace_file() {
+        eprintln!("armsim-cli: failed to open trace file {}: {}", machine.trace.get_path(), e);
+        return ExitCode::FAILURE
+    }
+    machine.run();
+    machine.trace.close_trace_file();
+
+    io::stdout().flush().unwrap_or(());
+    eprintln!("armsim-cli: {}", machine.cpu.get_cycles());
+    if let Some(icache) = &machine.ram.icache { eprintln!("armsim-cli: icache: {}", icache.get_stats()); }
+    if let Some(dcache) = &machine.ram.dcache { eprintln!("armsim-cli: dcache: {}", dcache.get_stats()); }
+    match machine.cpu.take_fault() {
+        Some(fault) if !fault.handled => {
+            eprintln!("armsim-cli: {}", fault);
+            ExitCode::FAILURE
+        },
+        _ => ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_elf_file() {
+        assert_eq!(parse(&["x.elf"]).unwrap().elf_file, Some(String::from("x.elf")));
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["x.elf", "y.elf"]).is_err());
+        assert!(parse(&["--bogus", "x.elf"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_mem() {
+        assert_eq!(parse(&["--mem", "4096", "x.elf"]).unwrap().memory_size, Some(4096));
+        assert_eq!(parse(&["-m", "1024000", "x.elf"]).unwrap().memory_size, Some(memory::MAX_MEMORY_SIZE));
+        assert!(parse(&["--mem", "abc", "x.elf"]).is_err());
+        assert!(parse(&["--mem", "-1", "x.elf"]).is_err());
+        assert!(parse(&["--mem", "1024001", "x.elf"]).is_err());
+        assert!(parse(&["x.elf", "--mem"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_policies() {
+        let opts = parse(&["--alignment", "fault", "--arch", "armv5te", "--undefined", "halt", "x.elf"]).unwrap();
+        assert_eq!(opts.alignment, Some(AlignmentPolicy::Fault));
+        assert_eq!(opts.architecture, Some(Architecture::ARMv5TE));
+        assert_eq!(opts.undefined, Some(UndefinedPolicy::Halt));
+
+        assert!(parse(&["--alignment", "sideways", "x.elf"]).is_err());
+        assert!(parse(&["--arch", "armv7", "x.elf"]).is_err());
+        assert!(parse(&["--undefined", "ignore", "x.elf"]).is_err());
+        assert!(parse(&["x.elf", "--arch"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_caches() {
+        let opts = parse(&["--icache", "16k,32,4,lru,writeback", "--dcache", "1k,16,1", "x.elf"]).unwrap();
+        assert!(opts.icache.is_some());
+        assert!(opts.dcache.is_some());
+
+        assert!(parse(&["--icache", "16k,32", "x.elf"]).is_err());
+        assert!(parse(&["--dcache", "16k,32,4,mru", "x.elf"]).is_err());
+        assert!(parse(&["x.elf", "--dcache"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_trace() {
+        let opts = parse(&["--traceall", "--trace-cycles", "--trace-file", "out.log", "x.elf"]).unwrap();
+        assert!(opts.traceall);
+        assert!(opts.trace_cycles);
+        assert_eq!(opts.trace_file, Some(String::from("out.log")));
+
+        let opts = parse(&["x.elf"]).unwrap();
+        assert!(!opts.traceall && !opts.trace_cycles && opts.trace_file.is_none());
+        assert!(parse(&["x.elf", "--trace-file"]).is_err());
+    }
+}
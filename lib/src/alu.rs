@@ -0,0 +1,161 @@
+use crate::{cpu_enum::DataOpcode, memory::Word};
+
+/*
+Data-processing ALU (ARM DDI 0100E, A4.1)
+
+Every arithmetic opcode is an AddWithCarry of Rn and the shifter operand, with either
+operand inverted for subtraction: Rn - op = Rn + NOT(op) + 1, so C is NOT borrow. The
+logical opcodes take C from the shifter and leave V unchanged
+*/
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AluOutput {
+    pub result: Word,
+    pub n: bool,
+    pub z: bool,
+    pub c: bool,
+    pub v: bool
+}
+
+// x + y + carry_in; returns the 32-bit result, the unsigned carry out and the signed overflow
+pub fn add_with_carry(x: Word, y: Word, carry_in: bool) -> (Word, bool, bool) {
+    let unsigned_sum = x as u64 + y as u64 + carry_in as u64;
+    let signed_sum = x as i32 as i64 + y as i32 as i64 + carry_in as i64;
+    let result = unsigned_sum as Word;
+
+    (result, unsigned_sum != result as u64, signed_sum != result as i32 as i64)
+}
+
+// TST, TEQ, CMP and CMN only update the flags
+pub fn is_comparison(opcode: DataOpcode) -> bool {
+    matches!(opcode, DataOpcode::TST | DataOpcode::TEQ | DataOpcode::CMP | DataOpcode::CMN)
+}
+
+// c and v are the current flags: C is the carry in of ADC, SBC and RSC, and V is kept by the logical opcodes
+pub fn execute(opcode: DataOpcode, rn: Word, shifter_operand: Word, shifter_carry_out: bool, c: bool, v: bool) -> AluOutput {
+    let logical = |result: Word| (result, shifter_carry_out, v);
+
+    let (result, c, v) = match opcode {
+        DataOpcode::AND | DataOpcode::TST => logical(rn & shifter_operand),
+        DataOpcode::EOR | DataOpcode::TEQ => logical(rn ^ shifter_operand),
+        DataOpcode::ORR => logical(rn | shifter_operand),
+        DataOpcode::BIC => logical(rn & !shifter_operand),
+        DataOpcode::MOV => logical(shifter_operand),
+        DataOpcode::MVN => logical(!shifter_operand),
+        DataOpcode::ADD | DataOpcode::CMN => add_with_carry(rn, shifter_operand, false),
+        DataOpcode::ADC => add_with_carry(rn, shifter_operand, c),
+        DataOpcode::SUB | DataOpcode::CMP => add_with_carry(rn, !shifter_operand, true),
+        DataOpcode::SBC => add_with_carry(rn, !shifter_operand, c),
+        DataOpcode::RSB => add_with_carry(shifter_operand, !rn, true),
+        DataOpcode::RSC => add_with_carry(shifter_operand, !rn, c),
+    };
+
+    AluOutput { result, n: result >> 31 == 1, z: result == 0, c, v }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::FromPrimitive;
+
+    use super::*;
+
+    // (opcode, rn, shifter operand, carry in, result, n, z, c, v)
+    type EdgeCase = (DataOpcode, Word, Word, bool, Word, bool, bool, bool, bool);
+
+    const EDGES: [Word; 9] = [0, 1, 2, 0x7ffffffe, 0x7fffffff, 0x80000000, 0x80000001, 0xfffffffe, 0xffffffff];
+
+    // the flags from wide integer arithmetic, independently of add_with_carry
+    fn reference(opcode: DataOpcode, rn: Word, op: Word, carry: bool) -> Option<(Word, bool, bool)> {
+        let (unsigned, signed): (i128, i128) = match opcode {
+            DataOpcode::ADD | DataOpcode::CMN => (rn as i128 + op as i128, rn as i32 as i128 + op as i32 as i128),
+            DataOpcode::ADC => (rn as i128 + op as i128 + carry as i128, rn as i32 as i128 + op as i32 as i128 + carry as i128),
+            DataOpcode::SUB | DataOpcode::CMP => (rn as i128 - op as i128, rn as i32 as i128 - op as i32 as i128),
+            DataOpcode::SBC => (rn as i128 - op as i128 - !carry as i128, rn as i32 as i128 - op as i32 as i128 - !carry as i128),
+            DataOpcode::RSB => (op as i128 - rn as i128, op as i32 as i128 - rn as i32 as i128),
+            DataOpcode::RSC => (op as i128 - rn as i128 - !carry as i128, op as i32 as i128 - rn as i32 as i128 - !carry as i128),
+            _ => return None
+        };
+        let result = unsigned as Word;
+        let subtract = matches!(opcode, DataOpcode::SUB | DataOpcode::CMP | DataOpcode::SBC | DataOpcode::RSB | DataOpcode::RSC);
+        // subtraction sets C when there is no borrow
+        let c = if subtract { unsigned >= 0 } else { unsigned > Word::MAX as i128 };
+        let v = signed != result as i32 as i128;
+
+        Some((result, c, v))
+    }
+
+    #[test]
+    fn test_add_with_carry() {
+        assert_eq!(add_with_carry(0, 0, false), (0, false, false));
+        assert_eq!(add_with_carry(0xffffffff, 1, false), (0, true, false));
+        assert_eq!(add_with_carry(0x7fffffff, 1, false), (0x80000000, false, true));
+        assert_eq!(add_with_carry(0x80000000, 0x80000000, false), (0, true, true));
+        assert_eq!(add_with_carry(0xffffffff, 0xffffffff, true), (0xffffffff, true, false));
+        assert_eq!(add_with_carry(0x7fffffff, 0, true), (0x80000000, false, true));
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        let cases: [EdgeCase; 22] = [
+            (DataOpcode::SUB, 5, 5, false, 0, false, true, true, false),
+            (DataOpcode::SUB, 0, 1, false, 0xffffffff, true, false, false, false),
+            (DataOpcode::SUB, 0x80000000, 1, false, 0x7fffffff, false, false, true, true),
+            (DataOpcode::SUB, 0x7fffffff, 0xffffffff, false, 0x80000000, true, false, false, true),
+            (DataOpcode::CMP, 0, 0, false, 0, false, true, true, false),
+            (DataOpcode::CMP, 1, 2, false, 0xffffffff, true, false, false, false),
+            (DataOpcode::ADD, 1, 0xffffffff, false, 0, false, true, true, false),
+            (DataOpcode::ADD, 5, 3, false, 8, false, false, false, false),
+            (DataOpcode::ADD, 3, 5, false, 8, false, false, false, false),
+            (DataOpcode::ADD, 0x7fffffff, 0x7fffffff, false, 0xfffffffe, true, false, false, true),
+            (DataOpcode::CMN, 0x80000000, 0x80000000, false, 0, false, true, true, true),
+            (DataOpcode::ADC, 0xffffffff, 0xffffffff, true, 0xffffffff, true, false, true, false),
+            (DataOpcode::ADC, 0, 0xffffffff, true, 0, false, true, true, false),
+            (DataOpcode::ADC, 0x7fffffff, 0, true, 0x80000000, true, false, false, true),
+            (DataOpcode::SBC, 0, 0, false, 0xffffffff, true, false, false, false),
+            (DataOpcode::SBC, 0, 0, true, 0, false, true, true, false),
+            (DataOpcode::SBC, 0x80000000, 0, false, 0x7fffffff, false, false, true, true),
+            (DataOpcode::SBC, 0, 0xffffffff, false, 0, false, true, false, false),
+            (DataOpcode::RSB, 1, 0, false, 0xffffffff, true, false, false, false),
+            (DataOpcode::RSB, 0x80000000, 0, false, 0x80000000, true, false, false, true),
+            (DataOpcode::RSC, 0, 0, false, 0xffffffff, true, false, false, false),
+            (DataOpcode::RSC, 0xffffffff, 0, true, 1, false, false, false, false),
+        ];
+
+        for (opcode, rn, op, carry, result, n, z, c, v) in cases {
+            let out = execute(opcode, rn, op, false, carry, false);
+            assert_eq!(out, AluOutput { result, n, z, c, v }, "{:?} 0x{:x}, 0x{:x}, carry {}", opcode, rn, op, carry);
+        }
+    }
+
+    #[test]
+    fn test_logical() {
+        // C comes from the shifter and V is kept
+        let out = execute(DataOpcode::AND, 0xf0f0f0f0, 0x0f0f0f0f, true, false, true);
+        assert_eq!(out, AluOutput { result: 0, n: false, z: true, c: true, v: true });
+        assert_eq!(execute(DataOpcode::EOR, 0xffff0000, 0x0000ffff, false, true, false).result, 0xffffffff);
+        assert!(execute(DataOpcode::TEQ, 0x80000000, 0, false, true, false).n);
+        assert!(execute(DataOpcode::TST, 0x80000000, 0x7fffffff, true, false, false).z);
+        assert_eq!(execute(DataOpcode::ORR, 0x1, 0x80000000, false, false, false).result, 0x80000001);
+        assert_eq!(execute(DataOpcode::BIC, 0xffffffff, 0x0000ffff, false, false, false).result, 0xffff0000);
+        assert_eq!(execute(DataOpcode::MOV, 0x1234, 0, false, true, true), AluOutput { result: 0, n: false, z: true, c: false, v: true });
+        assert_eq!(execute(DataOpcode::MVN, 0, 0, false, false, false).result, 0xffffffff);
+        assert!(is_comparison(DataOpcode::CMN) && !is_comparison(DataOpcode::RSC));
+    }
+
+    #[test]
+    fn test_arithmetic_exhaustive() {
+        // every arithmetic opcode over every pair of edge values and carry in
+        for opcode in (0..16).filter_map(DataOpcode::from_u32) {
+            for rn in EDGES {
+                for op in EDGES {
+                    for carry in [false, true] {
+                        let Some((result, c, v)) = reference(opcode, rn, op, carry) else { continue };
+                        let out = execute(opcode, rn, op, false, carry, false);
+                        let expected = AluOutput { result, n: result >> 31 == 1, z: result == 0, c, v };
+                        assert_eq!(out, expected, "{:?} 0x{:x}, 0x{:x}, carry {}", opcode, rn, op, carry);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use log::trace;
+
+use crate::{console::HostConsole, memory::{AddressSize, Byte, HalfWord, Word}};
+
+// interrupt lines a device asserts from its tick
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct InterruptRequest {
+    pub irq: bool,
+    pub fiq: bool
+}
+
+impl InterruptRequest {
+    pub const NONE: InterruptRequest = InterruptRequest { irq: false, fiq: false };
+    pub const IRQ: InterruptRequest = InterruptRequest { irq: true, fiq: false };
+    pub const FIQ: InterruptRequest = InterruptRequest { irq: false, fiq: true };
+}
+
+// a memory-mapped peripheral; offsets are relative to the base address the device is mapped at
+// byte-wide registers are zero-extended on wider reads and truncated on wider writes,
+// devices with wider registers override the half word/word accessors
+pub trait Device: Send {
+    fn read_byte(&mut self, offset: AddressSize) -> Byte;
+    fn write_byte(&mut self, offset: AddressSize, value: Byte);
+
+    fn read_half_word(&mut self, offset: AddressSize) -> HalfWord {
+        self.read_byte(offset) as HalfWord
+    }
+
+    fn write_half_word(&mut self, offset: AddressSize, value: HalfWord) {
+        self.write_byte(offset, value as Byte)
+    }
+
+    fn read_word(&mut self, offset: AddressSize) -> Word {
+        self.read_byte(offset) as Word
+    }
+
+    fn write_word(&mut self, offset: AddressSize, value: Word) {
+        self.write_byte(offset, value as Byte)
+    }
+
+    // called once per retired instruction so the device can talk to the host;
+    // returns the interrupt lines the device is asserting
+    fn tick(&mut self, _console: &mut dyn HostConsole) -> InterruptRequest {
+        InterruptRequest::NONE
+    }
+
+    // called when a new program is loaded
+    fn reset(&mut self) { }
+}
+
+struct MappedDevice {
+    base: AddressSize,
+    size: AddressSize,
+    device: Box<dyn Device>
+}
+
+// routes CPU data accesses to the device mapped at the address, if any;
+// each accessor returns None (or false) when the address is not mapped so the caller falls back to RAM
+#[derive(Default)]
+pub struct DeviceBus {
+    devices: Vec<MappedDevice>
+}
+
+impl DeviceBus {
+    // maps a device over [base, base + size)
+    pub fn map<D: Device + 'static>(&mut self, base: AddressSize, size: AddressSize, device: D) {
+        if self.devices.iter().any(|d| base < d.base + d.size && d.base < base + size) {
+            panic!("DeviceBus[map]: device at {:x} overlaps an existing device", base);
+        }
+
+        trace!("map: device at {:x}..{:x}", base, base + size);
+        self.devices.push(MappedDevice { base, size, device: Box::new(device) });
+    }
+
+    pub fn is_mapped(&self, addr: AddressSize) -> bool {
+        self.devices.iter().any(|d| addr >= d.base && addr - d.base < d.size)
+    }
+
+    fn find(&mut self, addr: AddressSize) -> Option<(&mut Box<dyn Device>, AddressSize)> {
+        self.devices.iter_mut()
+            .find(|d| addr >= d.base && addr - d.base < d.size)
+            .map(|d| (&mut d.device, addr - d.base))
+    }
+
+    pub fn read_byte(&mut self, addr: AddressSize) -> Option<Byte> {
+        self.find(addr).map(|(device, offset)| device.read_byte(offset))
+    }
+
+    pub fn write_byte(&mut self, addr: AddressSize, value: Byte) -> bool {
+        self.find(addr).map(|(device, offset)| device.write_byte(offset, value)).is_some()
+    }
+
+    pub fn read_half_word(&mut self, addr: AddressSize) -> Option<HalfWord> {
+        self.find(addr).map(|(device, offset)| device.read_half_word(offset))
+    }
+
+    pub fn write_half_word(&mut self, addr: AddressSize, value: HalfWord) -> bool {
+        self.find(addr).map(|(device, offset)| device.write_half_word(offset, value)).is_some()
+    }
+
+    pub fn read_word(&mut self, addr: AddressSize) -> Option<Word> {
+        self.find(addr).map(|(device, offset)| device.read_word(offset))
+    }
+
+    pub fn write_word(&mut self, addr: AddressSize, value: Word) -> bool {
+        self.find(addr).map(|(device, offset)| device.write_word(offset, value)).is_some()
+    }
+
+    // ticks every device; returns the combined interrupt lines
+    pub fn tick(&mut self, console: &mut dyn HostConsole) -> InterruptRequest {
+        let mut request = InterruptRequest::NONE;
+        for d in self.devices.iter_mut() {
+            let r = d.device.tick(console);
+            request.irq |= r.irq;
+            request.fiq |= r.fiq;
+        }
+        request
+    }
+
+    pub fn reset(&mut self) {
+        for d in self.devices.iter_mut() {
+            d.device.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Latch {
+        value: Word
+    }
+
+    impl Device for Latch {
+        fn read_byte(&mut self, offset: AddressSize) -> Byte {
+            (self.value >> (offset * 8)) as Byte
+        }
+
+        fn write_byte(&mut self, _offset: AddressSize, value: Byte) {
+            self.value = value as Word;
+        }
+
+        fn read_word(&mut self, _offset: AddressSize) -> Word {
+            self.value
+        }
+
+        fn write_word(&mut self, _offset: AddressSize, value: Word) {
+            self.value = value;
+        }
+    }
+
+    #[test]
+    fn test_routing() {
+        let mut bus = DeviceBus::default();
+        bus.map(0x2000, 4, Latch::default());
+
+        assert!(bus.write_word(0x2000, 0x11223344));
+        assert_eq!(bus.read_word(0x2000), Some(0x11223344));
+        assert_eq!(bus.read_byte(0x2001), Some(0x33));
+        // byte-wide default for half words
+        assert_eq!(bus.read_half_word(0x2002), Some(0x22));
+
+        assert!(!bus.write_word(0x2004, 0));
+        assert_eq!(bus.read_word(0x1ffc), None);
+        assert_eq!(bus.tick(&mut crate::console::NullConsole), InterruptRequest::NONE);
+        assert!(bus.is_mapped(0x2003));
+        assert!(!bus.is_mapped(0x2004));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_map_overlap_error() {
+        let mut bus = DeviceBus::default();
+        bus.map(0x2000, 4, Latch::default());
+        bus.map(0x2002, 4, Latch::default());
+    }
+}
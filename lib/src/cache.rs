@@ -0,0 +1,417 @@
+use core::{fmt, str::FromStr};
+
+use log::trace;
+
+use crate::memory::AddressSize;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ReplacementPolicy {
+    LRU,
+    FIFO,
+    Random
+}
+
+impl FromStr for ReplacementPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lru" => Ok(ReplacementPolicy::LRU),
+            "fifo" => Ok(ReplacementPolicy::FIFO),
+            "random" => Ok(ReplacementPolicy::Random),
+            _ => Err(format!("unknown replacement policy {} (expected lru, fifo or random)", s))
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WritePolicy {
+    // write-allocate; dirty lines are written back when evicted
+    WriteBack,
+    // no write-allocate; every store goes to memory
+    WriteThrough
+}
+
+impl FromStr for WritePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "writeback" => Ok(WritePolicy::WriteBack),
+            "writethrough" => Ok(WritePolicy::WriteThrough),
+            _ => Err(format!("unknown write policy {} (expected writeback or writethrough)", s))
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CacheConfig {
+    pub size: usize,
+    pub line_size: usize,
+    pub associativity: usize,
+    pub replacement: ReplacementPolicy,
+    pub write_policy: WritePolicy
+}
+
+impl CacheConfig {
+    pub fn get_sets(&self) -> usize {
+        self.size / (self.line_size * self.associativity)
+    }
+}
+
+// parsed from "<size>,<line size>,<associativity>[,<replacement>[,<write policy>]]",
+//  sizes in bytes with an optional k suffix, e.g. "16k,32,4,lru,writeback"
+impl FromStr for CacheConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split(',').map(|field| field.trim()).collect();
+        if fields.len() < 3 || fields.len() > 5 {
+            return Err(format!("invalid cache configuration {} (expected <size>,<line size>,<associativity>[,<replacement>[,<write policy>]])", s))
+        }
+
+        let parse_size = |field: &str| -> Result<usize, String> {
+            let (digits, scale) = match field.strip_suffix('k') {
+                Some(digits) => (digits, 1024),
+                None => (field, 1)
+            };
+            match digits.parse::<usize>().ok().and_then(|n| n.checked_mul(scale)) {
+                Some(size) if size.is_power_of_two() => Ok(size),
+                _ => Err(format!("invalid cache size {} (expected a power of two)", field))
+            }
+        };
+
+        let config = CacheConfig {
+            size: parse_size(fields[0])?,
+            line_size: parse_size(fields[1])?,
+            associativity: parse_size(fields[2])?,
+            replacement: match fields.get(3) {
+                Some(field) => field.parse()?,
+                None => ReplacementPolicy::LRU
+            },
+            write_policy: match fields.get(4) {
+                Some(field) => field.parse()?,
+                None => WritePolicy::WriteBack
+            }
+        };
+
+        if config.line_size < 4 || config.line_size * config.associativity > config.size {
+            return Err(format!("invalid cache configuration {} (lines must be at least 4 bytes and fit in the cache)", s))
+        }
+
+        Ok(config)
+    }
+}
+
+// 16KB, 32-byte lines, 4-way set associative, LRU, write-back
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            size: 16384,
+            line_size: 32,
+            associativity: 4,
+            replacement: ReplacementPolicy::LRU,
+            write_policy: WritePolicy::WriteBack
+        }
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Debug, serde::Serialize)]
+pub struct CacheStats {
+    pub read_hits: u64,
+    pub read_misses: u64,
+    pub write_hits: u64,
+    pub write_misses: u64,
+    pub writebacks: u64 // dirty lines written back on eviction
+}
+
+impl CacheStats {
+    pub fn get_accesses(&self) -> u64 {
+        self.read_hits + self.read_misses + self.write_hits + self.write_misses
+    }
+
+    pub fn get_hit_rate(&self) -> f64 {
+        match self.get_accesses() {
+            0 => 0.0,
+            n => (self.read_hits + self.write_hits) as f64 / n as f64
+        }
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} read hits, {} read misses, {} write hits, {} write misses, {} writebacks ({:.1}% hit rate)",
+            self.read_hits, self.read_misses, self.write_hits, self.write_misses, self.writebacks, self.get_hit_rate() * 100.0)
+    }
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Debug, serde::Serialize)]
+pub struct CacheLine {
+    pub set: usize,
+    pub address: AddressSize, // address of the first byte of the line
+    pub valid: bool,
+    pub dirty: bool
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct CachePayload {
+    pub stats: CacheStats,
+    pub sets: usize,
+    pub ways: usize,
+    pub lines: Vec<CacheLine> // valid lines only
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct CachesPayload {
+    pub icache: Option<CachePayload>,
+    pub dcache: Option<CachePayload>
+}
+
+// a set-associative cache model; it only tracks which lines are resident,
+// so it never changes the result of an access
+pub struct Cache {
+    config: CacheConfig,
+    lines: Vec<CacheLine>, // set-major, associativity lines per set
+    stamps: Vec<u64>,      // last use (LRU) or fill time (FIFO) of each line
+    clock: u64,
+    random: u32,           // xorshift state, fixed seed so runs are reproducible
+    stats: CacheStats
+}
+
+impl Cache {
+    pub fn new(config: CacheConfig) -> Self {
+        let mut cache = Cache {
+            config,
+            lines: vec![],
+            stamps: vec![],
+            clock: 0,
+            random: 0,
+            stats: CacheStats::default()
+        };
+        cache.reset();
+        cache
+    }
+
+    pub fn get_config(&self) -> CacheConfig {
+        self.config
+    }
+
+    pub fn get_stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn get_lines(&self) -> &[CacheLine] {
+        &self.lines
+    }
+
+    pub fn get_payload(&self) -> CachePayload {
+        CachePayload {
+            stats: self.stats,
+            sets: self.config.get_sets(),
+            ways: self.config.associativity,
+            lines: self.lines.iter().filter(|line| line.valid).copied().collect()
+        }
+    }
+
+    pub fn contains(&self, addr: AddressSize) -> bool {
+        self.find(addr).is_some()
+    }
+
+    // returns true on a hit
+    pub fn read(&mut self, addr: AddressSize) -> bool {
+        match self.find(addr) {
+            Some(index) => {
+                self.stats.read_hits += 1;
+                self.touch(index);
+                true
+            },
+            None => {
+                self.stats.read_misses += 1;
+                self.fill(addr);
+                false
+            }
+        }
+    }
+
+    pub fn write(&mut self, addr: AddressSize) -> bool {
+        let write_back = self.config.write_policy == WritePolicy::WriteBack;
+        match self.find(addr) {
+            Some(index) => {
+                self.stats.write_hits += 1;
+                self.touch(index);
+                self.lines[index].dirty = write_back;
+                true
+            },
+            None => {
+                self.stats.write_misses += 1;
+                if write_back {
+                    let index = self.fill(addr);
+                    self.lines[index].dirty = true;
+                }
+                false
+            }
+        }
+    }
+
+    // drops every line without writing back, e.g. for CP15 c7 invalidate operations
+    pub fn invalidate(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.valid = false;
+            line.dirty = false;
+        }
+    }
+
+    // invalidates every line and clears the statistics
+    pub fn reset(&mut self) {
+        let sets = self.config.get_sets();
+        let ways = self.config.associativity;
+        self.lines = (0..sets * ways).map(|i| CacheLine { set: i / ways, ..Default::default() }).collect();
+        self.stamps = vec![0; sets * ways];
+        self.clock = 0;
+        self.random = 0x2545f491;
+        self.stats = CacheStats::default();
+    }
+
+    fn get_line_address(&self, addr: AddressSize) -> AddressSize {
+        addr & !(self.config.line_size as AddressSize - 1)
+    }
+
+    fn get_set_range(&self, addr: AddressSize) -> std::ops::Range<usize> {
+        let set = (addr as usize / self.config.line_size) % self.config.get_sets();
+        set * self.config.associativity..(set + 1) * self.config.associativity
+    }
+
+    fn find(&self, addr: AddressSize) -> Option<usize> {
+        let address = self.get_line_address(addr);
+        self.get_set_range(addr).find(|i| self.lines[*i].valid && self.lines[*i].address == address)
+    }
+
+    fn touch(&mut self, index: usize) {
+        self.clock += 1;
+        if self.config.replacement == ReplacementPolicy::LRU {
+            self.stamps[index] = self.clock;
+        }
+    }
+
+    // allocates the line for addr, evicting a line from its set if it is full
+    fn fill(&mut self, addr: AddressSize) -> usize {
+        let range = self.get_set_range(addr);
+        let index = match range.clone().find(|i| !self.lines[*i].valid) {
+            Some(index) => index,
+            None => match self.config.replacement {
+                ReplacementPolicy::LRU | ReplacementPolicy::FIFO => range.min_by_key(|i| self.stamps[*i]).unwrap(),
+                ReplacementPolicy::Random => {
+                    self.random ^= self.random << 13;
+                    self.random ^= self.random >> 17;
+                    self.random ^= self.random << 5;
+                    range.start + self.random as usize % self.config.associativity
+                }
+            }
+        };
+
+        let address = self.get_line_address(addr);
+        let line = &mut self.lines[index];
+        if line.valid {
+            trace!("fill: evicting line at 0x{:x}", line.address);
+            if line.dirty {
+                self.stats.writebacks += 1;
+            }
+        }
+        *line = CacheLine { set: line.set, address, valid: true, dirty: false };
+
+        self.clock += 1;
+        self.stamps[index] = self.clock;
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 4 sets of 2 16-byte lines
+    fn small_cache(replacement: ReplacementPolicy, write_policy: WritePolicy) -> Cache {
+        Cache::new(CacheConfig { size: 128, line_size: 16, associativity: 2, replacement, write_policy })
+    }
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!("16k,32,4".parse::<CacheConfig>(), Ok(CacheConfig::default()));
+        assert_eq!("1k,16,1,fifo,writethrough".parse::<CacheConfig>(), Ok(CacheConfig {
+            size: 1024,
+            line_size: 16,
+            associativity: 1,
+            replacement: ReplacementPolicy::FIFO,
+            write_policy: WritePolicy::WriteThrough
+        }));
+        assert!("16k,32".parse::<CacheConfig>().is_err());
+        assert!("16k,24,4".parse::<CacheConfig>().is_err());
+        assert!("64,32,4".parse::<CacheConfig>().is_err());
+        assert!("16k,32,4,mru".parse::<CacheConfig>().is_err());
+        assert!("0,32,4".parse::<CacheConfig>().is_err());
+        // n * 1024 overflows a usize
+        assert!("18014398509481984k,16,2".parse::<CacheConfig>().is_err());
+        assert!(format!("{}k,16,2", usize::MAX).parse::<CacheConfig>().is_err());
+    }
+
+    #[test]
+    fn test_lru() {
+        let mut cache = small_cache(ReplacementPolicy::LRU, WritePolicy::WriteBack);
+
+        // 0x000, 0x040 and 0x080 all map to set 0
+        assert!(!cache.read(0x000));
+        assert!(cache.read(0x00c));
+        assert!(!cache.read(0x040));
+        assert!(cache.read(0x004));
+        assert!(!cache.read(0x080)); // evicts 0x040
+        assert!(cache.contains(0x000));
+        assert!(!cache.contains(0x040));
+        assert_eq!(cache.get_stats(), CacheStats { read_hits: 2, read_misses: 3, ..Default::default() });
+        assert_eq!(cache.get_payload().lines.len(), 2);
+    }
+
+    #[test]
+    fn test_fifo() {
+        let mut cache = small_cache(ReplacementPolicy::FIFO, WritePolicy::WriteBack);
+
+        cache.read(0x000);
+        cache.read(0x040);
+        cache.read(0x000);
+        cache.read(0x080); // evicts 0x000, the oldest fill
+        assert!(!cache.contains(0x000));
+        assert!(cache.contains(0x040));
+    }
+
+    #[test]
+    fn test_write_policies() {
+        let mut cache = small_cache(ReplacementPolicy::LRU, WritePolicy::WriteBack);
+        assert!(!cache.write(0x000)); // write-allocate
+        assert!(cache.read(0x000));
+        cache.read(0x040);
+        cache.read(0x080); // evicts the dirty line
+        assert_eq!(cache.get_stats().writebacks, 1);
+
+        let mut cache = small_cache(ReplacementPolicy::LRU, WritePolicy::WriteThrough);
+        assert!(!cache.write(0x000)); // no write-allocate
+        assert!(!cache.contains(0x000));
+        cache.read(0x000);
+        assert!(cache.write(0x000));
+        assert!(!cache.get_lines().iter().any(|line| line.dirty));
+
+        cache.invalidate();
+        assert!(!cache.contains(0x000));
+        cache.reset();
+        assert_eq!(cache.get_stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn test_random() {
+        let mut cache = small_cache(ReplacementPolicy::Random, WritePolicy::WriteBack);
+        for i in 0..16 {
+            cache.read(i * 0x40);
+        }
+        // every set 0 access misses, and the set never holds more than two lines
+        assert_eq!(cache.get_stats().read_misses, 16);
+        assert_eq!(cache.get_payload().lines.len(), 2);
+    }
+}
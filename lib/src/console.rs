@@ -0,0 +1,129 @@
+use std::{collections::VecDeque, io::{self, BufRead, Write}, sync::{Arc, Mutex}};
+
+use crate::memory::Word;
+
+// host side of the simulated terminal, used by SWI putchar/readline, the display device
+// and keyboard interrupts; the core never talks to the frontend directly, so the same
+// program can be driven from the Tauri app, a terminal or a test harness
+pub trait HostConsole: Send {
+    // prints a character written by the program
+    fn write_char(&mut self, c: char);
+
+    // blocks until the host returns a line of input of at most max_bytes characters
+    fn read_line(&mut self, max_bytes: Word) -> String;
+
+    // returns a pending keypress without blocking; the CPU raises an IRQ for each key
+    fn poll_key(&mut self) -> Option<char>;
+}
+
+// discards output and never has any input
+#[derive(Default)]
+pub struct NullConsole;
+
+impl HostConsole for NullConsole {
+    fn write_char(&mut self, _c: char) { }
+
+    fn read_line(&mut self, _max_bytes: Word) -> String {
+        String::new()
+    }
+
+    fn poll_key(&mut self) -> Option<char> {
+        None
+    }
+}
+
+// routes terminal I/O to the process's stdout and stdin
+#[derive(Default)]
+pub struct StdioConsole;
+
+impl HostConsole for StdioConsole {
+    fn write_char(&mut self, c: char) {
+        let mut stdout = io::stdout();
+        write!(stdout, "{}", c).unwrap_or(());
+        stdout.flush().unwrap_or(());
+    }
+
+    fn read_line(&mut self, max_bytes: Word) -> String {
+        let mut line = String::new();
+        // treat EOF or a read error as an empty line
+        io::stdin().lock().read_line(&mut line).unwrap_or(0);
+        line.trim_end_matches(&['\r', '\n'] as &[_]).chars().take(max_bytes as usize).collect()
+    }
+
+    // stdin is line-buffered, so keypresses are only delivered through read_line
+    fn poll_key(&mut self) -> Option<char> {
+        None
+    }
+}
+
+#[derive(Default)]
+struct ScriptedConsoleState {
+    output: String,
+    input: VecDeque<String>,
+    keys: VecDeque<char>
+}
+
+// replays scripted input lines and keypresses and records all output;
+// clones share the same buffers so a test can keep a handle after giving one to the machine
+#[derive(Clone, Default)]
+pub struct ScriptedConsole {
+    state: Arc<Mutex<ScriptedConsoleState>>
+}
+
+impl ScriptedConsole {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_input(&self, line: &str) {
+        self.state.lock().unwrap().input.push_back(String::from(line));
+    }
+
+    pub fn push_key(&self, c: char) {
+        self.state.lock().unwrap().keys.push_back(c);
+    }
+
+    pub fn get_output(&self) -> String {
+        self.state.lock().unwrap().output.clone()
+    }
+}
+
+impl HostConsole for ScriptedConsole {
+    fn write_char(&mut self, c: char) {
+        self.state.lock().unwrap().output.push(c);
+    }
+
+    // returns an empty line once the script runs out
+    fn read_line(&mut self, max_bytes: Word) -> String {
+        let line = self.state.lock().unwrap().input.pop_front().unwrap_or_default();
+        line.chars().take(max_bytes as usize).collect()
+    }
+
+    fn poll_key(&mut self) -> Option<char> {
+        self.state.lock().unwrap().keys.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_console() {
+        let console = ScriptedConsole::new();
+        let mut handle = console.clone();
+
+        console.push_input("hello world");
+        console.push_key('a');
+
+        handle.write_char('h');
+        handle.write_char('i');
+        assert_eq!(console.get_output(), "hi");
+
+        assert_eq!(handle.read_line(5), "hello");
+        assert_eq!(handle.read_line(5), "");
+
+        assert_eq!(handle.poll_key(), Some('a'));
+        assert_eq!(handle.poll_key(), None);
+    }
+}
@@ -0,0 +1,372 @@
+use log::trace;
+
+use crate::memory::Word;
+
+// operands shared by the coprocessor instructions (MCR/MRC, CDP, LDC/STC);
+// the ARM register of MCR/MRC and the base register of LDC/STC stay in the Instruction
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct CoprocessorOp {
+    pub coprocessor: Word,
+    pub opcode_1: Word,
+    pub opcode_2: Word,
+    pub crd: Word,
+    pub crn: Word,
+    pub crm: Word,
+    pub long: bool // N bit of LDC/STC
+}
+
+// a coprocessor attached to the CPU; every method defaults to "not implemented",
+// which the executors turn into an Undefined Instruction exception
+pub trait Coprocessor: Send {
+    // MRC: None when the register does not exist
+    fn read(&mut self, _op: &CoprocessorOp) -> Option<Word> {
+        None
+    }
+
+    // MCR: false when the register does not exist or is read-only
+    fn write(&mut self, _op: &CoprocessorOp, _value: Word) -> bool {
+        false
+    }
+
+    // CDP: false when the operation is not implemented
+    fn operation(&mut self, _op: &CoprocessorOp) -> bool {
+        false
+    }
+
+    // LDC/STC: the number of words transferred to or from CRd, None when unsupported
+    fn transfer_length(&self, _op: &CoprocessorOp) -> Option<usize> {
+        None
+    }
+
+    fn load(&mut self, _op: &CoprocessorOp, _words: &[Word]) { }
+
+    fn store(&mut self, _op: &CoprocessorOp) -> Vec<Word> {
+        vec![]
+    }
+
+    // called when a new program is loaded
+    fn reset(&mut self) { }
+}
+
+// CP15 identifies as an ARM920T (ARMv4T core with an MMU and 16KB I/D caches)
+pub const CP15_MAIN_ID: Word    = 0x41129200;
+pub const CP15_CACHE_TYPE: Word = 0x0d172172;
+
+// B2.4: control register bits
+pub const CONTROL_M: Word = 1 << 0;  // MMU enable
+pub const CONTROL_A: Word = 1 << 1;  // alignment fault checking
+pub const CONTROL_C: Word = 1 << 2;  // data cache enable
+pub const CONTROL_W: Word = 1 << 3;  // write buffer enable
+pub const CONTROL_B: Word = 1 << 7;  // big-endian
+pub const CONTROL_S: Word = 1 << 8;  // system protection
+pub const CONTROL_R: Word = 1 << 9;  // ROM protection
+pub const CONTROL_I: Word = 1 << 12; // instruction cache enable
+pub const CONTROL_V: Word = 1 << 13; // high exception vectors
+pub const CONTROL_RR: Word = 1 << 14; // round robin cache replacement
+
+// bits 6:4 should be one on ARMv4 (32-bit exception handlers and address space)
+const CONTROL_SBO: Word = 0x70;
+const CONTROL_WRITABLE: Word = CONTROL_M | CONTROL_A | CONTROL_C | CONTROL_W | CONTROL_B | CONTROL_S
+    | CONTROL_R | CONTROL_I | CONTROL_V | CONTROL_RR | 0xc000_0000;
+
+pub const HIGH_VECTOR_BASE: Word = 0xffff0000;
+
+// B4.6: fault status encodings
+pub const FAULT_STATUS_ALIGNMENT: Word = 0b0001;
+pub const FAULT_STATUS_EXTERNAL: Word  = 0b1000;
+
+// CP15 system control coprocessor (ARM DDI 0100E, B2.3)
+// c7 cache and c8 TLB operations are accepted here and applied by RAM::write_coprocessor
+pub struct SystemControl {
+    control: Word,
+    translation_table_base: Word,
+    domain_access: Word,
+    data_fault_status: Word,
+    instruction_fault_status: Word,
+    fault_address: Word,
+    process_id: Word
+}
+
+impl SystemControl {
+    pub fn get_control(&self) -> Word {
+        self.control
+    }
+
+    pub fn is_mmu_enabled(&self) -> bool {
+        self.control & CONTROL_M != 0
+    }
+
+    pub fn is_dcache_enabled(&self) -> bool {
+        self.control & CONTROL_C != 0
+    }
+
+    pub fn is_icache_enabled(&self) -> bool {
+        self.control & CONTROL_I != 0
+    }
+
+    pub fn is_big_endian(&self) -> bool {
+        self.control & CONTROL_B != 0
+    }
+
+    pub fn set_big_endian(&mut self, big_endian: bool) {
+        if big_endian {
+            self.control |= CONTROL_B;
+        } else {
+            self.control &= !CONTROL_B;
+        }
+    }
+
+    // exception vectors are at 0xffff0000 when the V bit is set
+    pub fn get_vector_base(&self) -> Word {
+        if self.control & CONTROL_V != 0 { HIGH_VECTOR_BASE } else { 0 }
+    }
+
+    pub fn get_translation_table_base(&self) -> Word {
+        self.translation_table_base
+    }
+
+    pub fn get_domain_access(&self) -> Word {
+        self.domain_access
+    }
+
+    pub fn get_fault_status(&self) -> Word {
+        self.data_fault_status
+    }
+
+    pub fn get_instruction_fault_status(&self) -> Word {
+        self.instruction_fault_status
+    }
+
+    pub fn get_fault_address(&self) -> Word {
+        self.fault_address
+    }
+
+    // FCSE process ID, bits [31:25]
+    pub fn get_process_id(&self) -> Word {
+        self.process_id
+    }
+
+    // records a data abort in the FSR and FAR
+    pub fn data_abort(&mut self, status: Word, domain: Word, address: Word) {
+        trace!("SystemControl: data abort, status 0b{:b} at 0x{:x}", status, address);
+        self.data_fault_status = (domain << 4) | status;
+        self.fault_address = address;
+    }
+
+    // prefetch aborts only update the instruction FSR
+    pub fn prefetch_abort(&mut self, status: Word, domain: Word) {
+        self.instruction_fault_status = (domain << 4) | status;
+    }
+}
+
+impl Coprocessor for SystemControl {
+    fn read(&mut self, op: &CoprocessorOp) -> Option<Word> {
+        if op.opcode_1 != 0 {
+            return None
+        }
+
+        match (op.crn, op.opcode_2) {
+            // unimplemented c0 registers read as the main ID
+            (0, 1)  => Some(CP15_CACHE_TYPE),
+            (0, _)  => Some(CP15_MAIN_ID),
+            (1, 0)  => Some(self.control),
+            (2, 0)  => Some(self.translation_table_base),
+            (3, 0)  => Some(self.domain_access),
+            (5, 0)  => Some(self.data_fault_status),
+            (5, 1)  => Some(self.instruction_fault_status),
+            (6, 0)  => Some(self.fault_address),
+            (13, 0) => Some(self.process_id),
+            _ => None
+        }
+    }
+
+    fn write(&mut self, op: &CoprocessorOp, value: Word) -> bool {
+        if op.opcode_1 != 0 {
+            return false
+        }
+
+        match (op.crn, op.opcode_2) {
+            (1, 0)  => self.control = (value & CONTROL_WRITABLE) | CONTROL_SBO,
+            (2, 0)  => self.translation_table_base = value & 0xffffc000,
+            (3, 0)  => self.domain_access = value,
+            (5, 0)  => self.data_fault_status = value & 0xff,
+            (5, 1)  => self.instruction_fault_status = value & 0xff,
+            (6, 0)  => self.fault_address = value,
+            (7, _) | (8, _) => trace!("SystemControl: c{} operation c{}, {}", op.crn, op.crm, op.opcode_2),
+            (13, 0) => self.process_id = value & 0xfe000000,
+            _ => return false
+        }
+        true
+    }
+
+    fn reset(&mut self) {
+        *self = SystemControl::default();
+    }
+}
+
+impl Default for SystemControl {
+    fn default() -> Self {
+        Self {
+            control: CONTROL_SBO,
+            translation_table_base: 0,
+            domain_access: 0,
+            data_fault_status: 0,
+            instruction_fault_status: 0,
+            fault_address: 0,
+            process_id: 0
+        }
+    }
+}
+
+// the coprocessors attached to the CPU; CP15 is always present,
+// the other slots are empty unless a coprocessor is attached
+pub struct Coprocessors {
+    pub cp15: SystemControl,
+    attached: Vec<Option<Box<dyn Coprocessor>>>
+}
+
+impl Coprocessors {
+    pub fn attach<C: Coprocessor + 'static>(&mut self, number: Word, coprocessor: C) {
+        if number >= 15 || self.attached[number as usize].is_some() {
+            panic!("Coprocessors[attach]: p{} is not available", number);
+        }
+
+        trace!("attach: coprocessor p{}", number);
+        self.attached[number as usize] = Some(Box::new(coprocessor));
+    }
+
+    fn get(&mut self, number: Word) -> Option<&mut dyn Coprocessor> {
+        match number {
+            15 => Some(&mut self.cp15),
+            _ => match self.attached.get_mut(number as usize) {
+                Some(Some(coprocessor)) => Some(coprocessor.as_mut()),
+                _ => None
+            }
+        }
+    }
+
+    pub fn read(&mut self, op: &CoprocessorOp) -> Option<Word> {
+        self.get(op.coprocessor)?.read(op)
+    }
+
+    pub fn write(&mut self, op: &CoprocessorOp, value: Word) -> bool {
+        self.get(op.coprocessor).is_some_and(|cp| cp.write(op, value))
+    }
+
+    pub fn operation(&mut self, op: &CoprocessorOp) -> bool {
+        self.get(op.coprocessor).is_some_and(|cp| cp.operation(op))
+    }
+
+    pub fn transfer_length(&mut self, op: &CoprocessorOp) -> Option<usize> {
+        self.get(op.coprocessor)?.transfer_length(op)
+    }
+
+    pub fn load(&mut self, op: &CoprocessorOp, words: &[Word]) {
+        if let Some(cp) = self.get(op.coprocessor) {
+            cp.load(op, words);
+        }
+    }
+
+    pub fn store(&mut self, op: &CoprocessorOp) -> Vec<Word> {
+        self.get(op.coprocessor).map_or(vec![], |cp| cp.store(op))
+    }
+
+    pub fn reset(&mut self) {
+        self.cp15.reset();
+        for cp in self.attached.iter_mut().flatten() {
+            cp.reset();
+        }
+    }
+}
+
+impl Default for Coprocessors {
+    fn default() -> Self {
+        Self {
+            cp15: SystemControl::default(),
+            attached: (0..15).map(|_| None).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cp15(crn: Word, opcode_2: Word) -> CoprocessorOp {
+        CoprocessorOp { coprocessor: 15, crn, opcode_2, ..Default::default() }
+    }
+
+    #[derive(Default)]
+    struct Accumulator {
+        value: Word
+    }
+
+    impl Coprocessor for Accumulator {
+        fn read(&mut self, _op: &CoprocessorOp) -> Option<Word> {
+            Some(self.value)
+        }
+
+        fn operation(&mut self, op: &CoprocessorOp) -> bool {
+            self.value += op.opcode_1;
+            true
+        }
+
+        fn transfer_length(&self, op: &CoprocessorOp) -> Option<usize> {
+            Some(if op.long { 2 } else { 1 })
+        }
+
+        fn load(&mut self, _op: &CoprocessorOp, words: &[Word]) {
+            self.value = words.iter().sum();
+        }
+    }
+
+    #[test]
+    fn test_system_control() {
+        let mut cps = Coprocessors::default();
+
+        assert_eq!(cps.read(&cp15(0, 0)), Some(CP15_MAIN_ID));
+        assert_eq!(cps.read(&cp15(0, 1)), Some(CP15_CACHE_TYPE));
+        assert_eq!(cps.read(&cp15(1, 0)), Some(0x70));
+        // c0 is read-only, c7 is write-only
+        assert!(!cps.write(&cp15(0, 0), 0));
+        assert_eq!(cps.read(&cp15(7, 0)), None);
+        assert!(cps.write(&cp15(7, 0), 0));
+
+        assert!(cps.write(&cp15(1, 0), CONTROL_M | CONTROL_I | CONTROL_V | 0x0000_0400));
+        assert_eq!(cps.read(&cp15(1, 0)), Some(CONTROL_M | CONTROL_I | CONTROL_V | 0x70));
+        assert!(cps.cp15.is_mmu_enabled() && cps.cp15.is_icache_enabled() && !cps.cp15.is_dcache_enabled());
+        assert_eq!(cps.cp15.get_vector_base(), HIGH_VECTOR_BASE);
+
+        cps.cp15.data_abort(FAULT_STATUS_ALIGNMENT, 0, 0x1001);
+        assert_eq!(cps.read(&cp15(5, 0)), Some(FAULT_STATUS_ALIGNMENT));
+        assert_eq!(cps.read(&cp15(6, 0)), Some(0x1001));
+
+        // only opcode_1 = 0 is defined
+        assert_eq!(cps.read(&CoprocessorOp { opcode_1: 1, ..cp15(0, 0) }), None);
+
+        cps.reset();
+        assert_eq!(cps.cp15.get_vector_base(), 0);
+    }
+
+    #[test]
+    fn test_attach() {
+        let mut cps = Coprocessors::default();
+        let op = CoprocessorOp { coprocessor: 2, opcode_1: 3, long: true, ..Default::default() };
+        assert_eq!(cps.read(&op), None);
+        assert!(!cps.operation(&op));
+
+        cps.attach(2, Accumulator::default());
+        assert!(cps.operation(&op));
+        assert_eq!(cps.read(&op), Some(3));
+        assert_eq!(cps.transfer_length(&op), Some(2));
+        cps.load(&op, &[4, 5]);
+        assert_eq!(cps.read(&op), Some(9));
+        assert_eq!(cps.store(&op), vec![]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_attach_cp15_error() {
+        Coprocessors::default().attach(15, Accumulator::default());
+    }
+}
@@ -1,648 +1,1098 @@
-use log::trace;
-use tauri::{AppHandle, Manager};
-use bitmatch::bitmatch;
-use tokio::sync::MutexGuard;
-
-use crate::{memory::{Registers, RAM, Memory, Word, AddressSize, Byte, DISPLAY_ADDR, Register}, state::{RAMState, RegistersState, CPUThreadWatcherState, TraceFileState}, instruction::*, cpu_enum::{Mode, Condition, InstrExecuteCondition}, util};
-
-pub struct CPUThreadWatcher {
-    running: bool,
-    prompt_flag: bool,
-    prompt_input: String,
-    irq_flag: bool,
-    irq_last_char: char
-}
-
-impl CPUThreadWatcher {
-    pub fn set_running(&mut self, state: bool) {
-        self.running = state;
-    }
-
-    pub fn is_running(&self) -> bool {
-        self.running
-    }
-
-    pub fn set_prompt_input(&mut self, value: String) {
-        self.prompt_flag = true;
-        self.prompt_input = value;
-    }
-
-    pub fn get_prompt_input(&self) -> String {
-        self.prompt_input.clone()
-    }
-
-    pub fn get_prompt_flag(&self) -> bool {
-        self.prompt_flag
-    }
-
-    pub fn clear_prompt_flag(&mut self) {
-        self.prompt_flag = false;
-    }
-
-    pub fn set_irq_flag(&mut self) {
-        self.irq_flag = true;
-    }
-
-    pub fn clear_irq_flag(&mut self) {
-        self.irq_flag = false;
-    }
-
-    pub fn get_irq_flag(&self) -> bool {
-        self.irq_flag
-    }
-
-    pub fn set_irq_last_char(&mut self, c: char) {
-        self.irq_last_char = c;
-    }
-
-    pub fn get_irq_last_char(&self) -> char {
-        self.irq_last_char
-    }
-}
-
-impl Default for CPUThreadWatcher {
-    fn default() -> Self {
-        Self {
-            running: false,
-            prompt_flag: false,
-            prompt_input: String::new(),
-            irq_flag: false,
-            irq_last_char: '\0'
-        }
-    }
-}
-
-#[derive(Clone, serde::Serialize)]
-pub struct CPUPayload {
-    pub trace: bool,
-    pub mode: Mode,
-}
-
-#[derive(Clone, serde::Serialize)]
-pub struct TerminalPutcharPayload {
-    pub char: char
-}
-
-#[derive(Clone, serde::Serialize)]
-pub struct TerminalReadlinePayload {
-    pub max_bytes: Word
-}
-
-pub struct CPU {
-    breakpoints: Vec<AddressSize>,
-    trace: bool,
-    trace_step: Word
-}
-
-impl CPU {
-    pub fn new() -> Self {
-        Self {
-            breakpoints: vec![0; 0],
-            trace: false,
-            trace_step: 1
-        }
-    }
-
-    pub fn get_trace(&self) -> bool {
-        self.trace
-    }
-
-    pub fn toggle_trace(&mut self) -> bool {
-        self.trace = !self.trace;
-
-        self.trace
-    }
-
-    pub fn reset_trace_step(&mut self) {
-        self.trace_step = 1
-    }
-
-    pub async fn stop(&self, app_handle: AppHandle) {
-        let cpu_thread_state: CPUThreadWatcherState = app_handle.state();
-        cpu_thread_state.lock().await.set_running(false);
-        trace!("stop: set running flag to false")
-    }
-
-    pub fn fetch(&self, ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>) -> Word {
-        // return read word from RAM address specified by value of PC register
-        ram_lock.read_word(registers_lock.get_pc_current_address())
-    }
-
-    #[bitmatch]
-    pub fn decode(&self, instr: Word) -> Instruction {
-        // get instruction data from bits
-        // the bitmatcher matches a bit pattern to a specific instruction factory
-        #[bitmatch]
-        match instr {
-            "11100001101000000000000000000000"              => instr_nop(),
-            "cccc_00010_r_00_1111_dddd_000000000000"        => instr_mrs(c, r, d),
-            "cccc_00110_r_10_ffff_1111_rrrr_iiiiiiii"       => instr_msr_imm(c, r, f, r, i),
-            "cccc_00010_r_10_ffff_1111_00000000_mmmm"       => instr_msr_reg(c, r, f, m),
-            "cccc_00010010_111111111111_0001_mmmm"          => instr_bx(c, m),
-            "cccc_000_oooo_s_nnnn_dddd_iiiii_tt_0_mmmm"     => instr_data_reg_imm(c, o, s, n, d, i, t, m),
-            "cccc_000_oooo_s_nnnn_dddd_ssss_0_tt_1_mmmm"    => instr_data_reg_reg(c, o, s, n, d, s, t, m),
-            "cccc_001_oooo_s_nnnn_dddd_rrrr_iiiiiiii"       => instr_data_imm(c, o, s, n, d, r, i),
-            "cccc_011_1_ubwl_nnnn_dddd_iiiii_tt_0_mmmm"     => instr_ldrstr_shifted_reg_pre(c, u, b, w, l, n, d, i, t, m),
-            "cccc_011_0_ubwl_nnnn_dddd_iiiii_tt_0_mmmm"     => instr_ldrstr_shifted_reg_post(c, u, b, w, l, n, d, i, t, m),
-            "cccc_011_1_ubwl_nnnn_dddd_00000000_mmmm"       => instr_ldrstr_reg_pre(c, u, b, w, l, n, d, m),
-            "cccc_011_0_ubwl_nnnn_dddd_00000000_mmmm"       => instr_ldrstr_reg_post(c, u, b, w, l, n, d, m),
-            "cccc_010_1_ubwl_nnnn_dddd_ssssssssssss"        => instr_ldrstr_imm_pre(c, u, b, w, l, n, d, s),
-            "cccc_010_0_ubwl_nnnn_dddd_ssssssssssss"        => instr_ldrstr_imm_post(c, u, b, w, l, n, d, s),
-            "cccc_000_1_u1wl_nnnn_dddd_hhhh_1_ss_1_iiii"    => instr_ldrhstrh_imm_pre(c, u, w, l, n, d, h, s, i),
-            "cccc_000_0_u1wl_nnnn_dddd_hhhh_1_ss_1_iiii"    => instr_ldrhstrh_imm_post(c, u, w, l, n, d, h, s, i),
-            "cccc_000_1_u0wl_nnnn_dddd_0000_1_ss_1_mmmm"    => instr_ldrhstrh_reg_pre(c, u, w, l, n, d, s, m),
-            "cccc_000_0_u0wl_nnnn_dddd_0000_1_ss_1_mmmm"    => instr_ldrhstrh_reg_post(c, u, w, l, n, d, s, m),
-            "cccc_101_l_oooooooooooooooooooooooo"           => instr_b(c, l, o),
-            "cccc_100_uuswl_nnnn_rrrrrrrrrrrrrrrr"          => instr_ldmstm(c, u, s, w, l, n, r),
-            "cccc_000_0000_s_dddd_0000_ssss_1001_mmmm"      => instr_mul(c, s, d, s, m),
-            "cccc_1111_ssssssssssssssssssssssss"            => instr_swi(c, s),
-            "????????????????????????????????"              => instr_nop(),
-        }
-    }
-
-    pub fn execute(&self, ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: &mut Instruction) -> InstrExecuteCondition {
-        let (n, z, c, v) = registers_lock.get_nzcv_tuple();
-
-        // check if condition passed and that the next instructions conditions allow if it did not pass
-        let exec = match instr.get_condition() {
-            Condition::EQ =>     z,
-            Condition::NE =>    !z,
-            Condition::CSHS =>   c,
-            Condition::CCLO =>  !c,
-            Condition::MI =>     n,
-            Condition::PL =>    !n,
-            Condition::VS =>     v,
-            Condition::VC =>    !v,
-            Condition::HI =>     c && !z,
-            Condition::LS =>    !c || z,
-            Condition::GE =>    (n && v) || (!n && !v),
-            Condition::LT =>    (n && !v) || (!n && v),
-            Condition::GT =>    !z && n == v,
-            Condition::LE =>     z || (n && !v) || (!n && v),
-            Condition::AL =>    true,
-        };
-
-        if exec {
-            // grab the execute method for the specific instruction and pass the state objects
-            instr.get_execute()(ram_lock, registers_lock, *instr)
-        } else {
-            InstrExecuteCondition::NOP
-        }
-    }
-
-    pub fn add_breakpoint(&mut self, address: AddressSize) {
-        trace!("add_breakpoint: {}", address);
-        self.breakpoints.push(address)
-    }
-
-    pub fn remove_breakpoint(&mut self, address: AddressSize) {
-        trace!("add_breakpoint: {}", address);
-        // https://stackoverflow.com/a/26243276
-        let index = self.breakpoints.iter().position(|breakpoint| *breakpoint == address).unwrap();
-        self.breakpoints.remove(index);
-    }
-
-    pub fn is_breakpoint(&self, address: &AddressSize) -> bool {
-        self.breakpoints.contains(&address)
-    }
-
-    fn putchar(&self, arg_char: Word, app_handle: AppHandle) {
-        // originally, this was put here because I thought that the Rust backend
-        //  was sending events too quickly to the frontend and causing the main thread
-        //  to stall. I probably spent around 4 1/2 hours debugging why all the threads 
-        //  were locking up, because I was sure that it wasn't my fault and that I had carefully
-        //  managed all the thread locks. after doing some intensive investigation, I settled on this
-        //  since it seemed to work, although definitely not ideal. Eventually, though,
-        //  I discovered that the root cause of the issue was that all the logs being sent
-        //  through the IPC mechanism to Edge's frontend was beginning to clog the entire thread.
-        //  the bug never appeared in debug mode since the output to the stdout console delayed
-        //  the thread enough so that only a few logs would be sent to Edge. surpisingly,
-        //  once i disabled the log streaming functionality for release mode, everything suddenly *worked*.
-        //
-        //  so this is left here, as a memorial.
-        //
-        // thread::sleep(time::Duration::from_millis(5));
-
-        app_handle.emit_all("terminal_append", TerminalPutcharPayload {
-            char: char::from_u32(arg_char).unwrap_or('\0')
-        }).unwrap();
-    }
-
-    async fn readline(&self, ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, app_handle: AppHandle) {
-        let arg_dest_addr = registers_lock.get_reg_register(Register::r1);
-        let arg_max_bytes = registers_lock.get_reg_register(Register::r2);
-
-        app_handle.emit_all("terminal_prompt", TerminalReadlinePayload {
-            max_bytes: arg_max_bytes - 1 // fit null terminator
-        }).unwrap();
-
-        // wait for frontend to return
-        // frontend will update CPUThreadWatcher state
-        loop {
-            // check thread state and drop immediately
-            {
-                let cpu_thread_watcher_state: CPUThreadWatcherState = app_handle.state();
-                if cpu_thread_watcher_state.lock().await.get_prompt_flag() {
-                    break;
-                }
-            }
-        }
-
-        // get thread state and drop immediately
-        let mut input;
-        {
-            let cpu_thread_watcher_state: CPUThreadWatcherState = app_handle.state();
-            let cpu_thread_watcher_lock = &mut cpu_thread_watcher_state.lock().await;
-            input = cpu_thread_watcher_lock.get_prompt_input();
-            cpu_thread_watcher_lock.clear_prompt_flag();
-        }
-
-        trace!("step: input received: {} {}bytes {}dest", input, arg_max_bytes, arg_dest_addr);
-
-        // append CR if received bytes < (arg_max_bytes + \0)
-        if input.len() < (arg_max_bytes - 1) as usize {
-            input.push(0xd as char);
-        }
-        input.push('\0');
-
-        let mut i = 0;
-        for c in input.chars() {
-            ram_lock.write_byte(arg_dest_addr + i, c as Byte);
-            i += 1;
-        }
-    }
-    
-    pub async fn run(&mut self, app_handle: AppHandle) {
-
-        // update thread state and drop immediately
-        {
-            let cpu_thread_state: CPUThreadWatcherState = app_handle.state();
-            cpu_thread_state.lock().await.set_running(true);
-        }
-        
-        // fetch-decode-execute
-        loop {
-            // stop when thread flag is updated
-            {
-                let cpu_thread_state: CPUThreadWatcherState = app_handle.state();
-                if !cpu_thread_state.lock().await.is_running() { break }
-            }
-
-            trace!("run: stepping...");
-            if self.step(app_handle.clone()).await == InstrExecuteCondition::HLT {
-                // stop when HLT instruction or other exception
-                trace!("run: hit HLT or exception");
-                break
-            }
-
-            // stop when pc hits breakpoint address
-            {
-                let registers_state: RegistersState = app_handle.state();
-                if self.is_breakpoint(&(registers_state.lock().await).get_pc_current_address()) { 
-                    trace!("run: hit breakpoint");
-                    break
-                }
-            }
-        }
-
-        self.stop(app_handle.clone()).await;
-
-        trace!("run: cpu stopped");
-    }
-
-    // returns true if HLT
-    pub async fn step(&mut self, app_handle: AppHandle) -> InstrExecuteCondition {
-        let ram_state: RAMState = app_handle.state();
-        let registers_state: RegistersState = app_handle.state();
-        let ram_lock = &mut ram_state.lock().await;
-        let registers_lock = &mut registers_state.lock().await;
-        let trace_state: TraceFileState = app_handle.state();
-        let trace_lock = &mut trace_state.lock().await;
-
-        trace!("step: trace_step: {}", self.trace_step);
-        trace!("step: cpsr: {}", registers_lock.get_cpsr());
-
-        // save PC before fetch begins for logging after execute
-        let saved_pc = registers_lock.get_pc_current_address();
-
-        let instr_raw = self.fetch(ram_lock, registers_lock);
-        trace!("step: {}pc = {:x}", registers_lock.get_pc_current_address(), instr_raw);
-
-        // halt when instruction is HLT (0)
-        if instr_raw == 0 {
-            registers_lock.inc_pc();
-            return InstrExecuteCondition::HLT;
-        }
-
-        // check irq and thread state here since the state is used in the decode and later steps, then drop lock immediately
-        // CPUThreadWatcherState cannot be shared across entire function since interrupt may occur mid-step
-        //  or while step is waiting for prompt event from frontend
-        let irq;
-        let last_char: char;
-        {
-            let cpu_thread_watcher_state: CPUThreadWatcherState = app_handle.state();
-            irq = cpu_thread_watcher_state.lock().await.get_irq_flag();
-            last_char = cpu_thread_watcher_state.lock().await.get_irq_last_char();
-        }
-
-        // get the instruction struct from the raw Word
-        let mut instr: Instruction = self.decode(instr_raw);
-
-        // inject possibly needed information into instruction before executing
-
-        // pc for branch instructions
-        instr.set_pc_address(registers_lock.get_pc());
-        // manually map any display hardware events before executing instruction
-        // rn = DISPLAY_ADDR
-        // rd = value to write
-        if util::is_write_instr(instr) && instr.get_rn().is_some() && registers_lock.get_reg_register(instr.get_rn().unwrap()) == DISPLAY_ADDR {
-            let arg_char = registers_lock.get_reg_register(instr.get_rd().unwrap());
-            trace!("step: mapping hardware display event, char = 0x{:x}", arg_char as Byte);
-
-            self.putchar(arg_char, app_handle.clone());
-        }
-        // inject last character from keyboard event if loading from keyboard hardware address
-        instr.set_last_char(last_char);
-
-        // pass the necessary state objects and instruction struct;
-        // state guards need to be passed so that the execute method can properly access/modify
-        // the application state
-        // exit if HLT
-        trace!("step: instr = {}", instr.to_string());
-        let exec_result: InstrExecuteCondition = self.execute(ram_lock, registers_lock, &mut instr);
-
-        // increment program counter
-        registers_lock.inc_pc();
-
-        // logging: get all registers and remove r15
-        let mut reg_all = registers_lock.get_all();
-        reg_all.pop();
-        trace_lock.append_trace_file_line(
-            self.trace_step,
-            saved_pc,
-            ram_lock.get_checksum(),
-            registers_lock.get_n_flag() as Byte,
-            registers_lock.get_z_flag() as Byte,
-            registers_lock.get_c_flag() as Byte,
-            registers_lock.get_v_flag() as Byte,
-            registers_lock.get_cpsr_mode(),
-            reg_all
-        );
-        self.trace_step += 1;
-
-        // handle instruction SWI exceptions
-        match exec_result {
-            InstrExecuteCondition::HLT => {
-                trace!("step: hit SWI HLT");
-                // decrement PC to stop at HLT instruction in disassembly
-                registers_lock.dec_pc();
-                return InstrExecuteCondition::HLT
-            },
-            InstrExecuteCondition::SWI => {
-                // processed here so that we can properly access the app thread
-                trace!("step: processing SWI event 0x{:x}swi", instr.get_swi().unwrap());
-
-                let cpsr = registers_lock.get_cpsr();
-                let pc = registers_lock.get_pc();
-
-                registers_lock.set_reg_register(Register::r14_svc, pc - 4);
-                registers_lock.set_spsr_svc(cpsr);
-                registers_lock.set_cpsr_mode(Mode::SVC);
-                registers_lock.set_cpsr_flag(5, false); // ARM state
-                registers_lock.set_i_flag(true);  // disable interrupts
-                registers_lock.set_pc(0x08+8); // add 8 bytes for PC
-
-                match instr.get_swi().unwrap() {
-                    0x0 => {
-                        let arg_char = registers_lock.get_reg_register(Register::r0);
-                        self.putchar(arg_char, app_handle.clone());
-                    },
-                    0x6a => self.readline(ram_lock, registers_lock, app_handle.clone()).await,
-                    _ => ()
-                }
-            },
-            InstrExecuteCondition::NOP => (),
-        }
-
-        // proccess IRQ interrupt from IRQ input line
-        // only when IRQ interrupts are not disabled
-        if irq && !registers_lock.get_i_flag() {
-            // A2.6.8
-            trace!("step: received IRQ event for char {}, handling...", last_char);
-
-            // clear the IRQ flag
-            {
-                let cpu_thread_watcher_state: CPUThreadWatcherState = app_handle.state();
-                cpu_thread_watcher_state.lock().await.clear_irq_flag();
-            }
-
-            let pc = registers_lock.get_pc();
-            let cpsr = registers_lock.get_cpsr();
-            registers_lock.set_reg_register(Register::r14_irq, pc - 4);
-            registers_lock.set_spsr_irq(cpsr);
-            registers_lock.set_cpsr_mode(Mode::IRQ);
-            registers_lock.set_i_flag(true);  // disable interrupts
-            registers_lock.set_cpsr_flag(8, true);  // disable imprecise data aborts
-            registers_lock.set_pc(0x18+8); // add 8 bytes for PC
-        }
-
-        return exec_result
-    }
-}
-
-impl Default for CPU {
-    fn default() -> Self {
-        Self {
-            breakpoints: vec![0; 0],
-            trace: false,
-            trace_step: 1
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::{cpu_enum::{DataOpcode, LDMCode, ShiftType, InstrType}, memory::Register};
-
-    use super::*;
-
-    #[test]
-    fn test_decode_mov() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe3a02030);
-        assert_eq!(instr.get_type(), InstrType::DataImm);
-        assert_eq!(instr.get_data_opcode().unwrap(), DataOpcode::MOV);
-        assert!(!instr.get_s_bit().unwrap());
-        assert_eq!(instr.get_rn().unwrap(), Register::r0);
-        assert_eq!(instr.get_rd().unwrap(), Register::r2);
-        assert_eq!(instr.get_imm().unwrap(), 48);
-    }
-
-    #[test]
-    fn test_decode_ldr_imm_pre() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe5925000);
-        assert_eq!(instr.get_type(), InstrType::LDRSTRImmPre);
-        assert_eq!(instr.get_byte_word().unwrap(), false);
-        assert_eq!(instr.get_add_sub().unwrap(), true);
-        assert_eq!(instr.get_writeback().unwrap(), false);
-        assert_eq!(instr.get_ldr_str().unwrap(), true);
-        assert_eq!(instr.get_rd().unwrap(), Register::r5);
-        assert_eq!(instr.get_rn().unwrap(), Register::r2);
-        assert_eq!(instr.get_imm_shift().unwrap(), 0);
-    }
-
-    #[test]
-    fn test_decode_str_imm_pre() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe5021004);
-        assert_eq!(instr.get_type(), InstrType::LDRSTRImmPre);
-        assert_eq!(instr.get_byte_word().unwrap(), false);
-        assert_eq!(instr.get_add_sub().unwrap(), false);
-        assert_eq!(instr.get_writeback().unwrap(), false);
-        assert_eq!(instr.get_ldr_str().unwrap(), false);
-        assert_eq!(instr.get_rd().unwrap(), Register::r1);
-        assert_eq!(instr.get_rn().unwrap(), Register::r2);
-        assert_eq!(instr.get_imm_shift().unwrap(), 4);
-    }
-
-    #[test]
-    fn test_decode_ldrb_imm_pre() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe5d2a00a);
-        assert_eq!(instr.get_type(), InstrType::LDRSTRImmPre);
-        assert_eq!(instr.get_byte_word().unwrap(), true);
-        assert_eq!(instr.get_add_sub().unwrap(), true);
-        assert_eq!(instr.get_writeback().unwrap(), false);
-        assert_eq!(instr.get_ldr_str().unwrap(), true);
-        assert_eq!(instr.get_rd().unwrap(), Register::r10);
-        assert_eq!(instr.get_rn().unwrap(), Register::r2);
-        assert_eq!(instr.get_imm_shift().unwrap(), 10);
-    }
-
-    #[test]
-    fn test_decode_strb_imm_pre() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe5c2100a);
-        assert_eq!(instr.get_type(), InstrType::LDRSTRImmPre);
-        assert_eq!(instr.get_byte_word().unwrap(), true);
-        assert_eq!(instr.get_add_sub().unwrap(), true);
-        assert_eq!(instr.get_writeback().unwrap(), false);
-        assert_eq!(instr.get_ldr_str().unwrap(), false);
-        assert_eq!(instr.get_rd().unwrap(), Register::r1);
-        assert_eq!(instr.get_rn().unwrap(), Register::r2);
-        assert_eq!(instr.get_imm_shift().unwrap(), 10);
-    }
-
-    #[test]
-    fn test_decode_ldr_shift_reg_pre() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe79290c4);
-        assert_eq!(instr.get_type(), InstrType::LDRSTRShiftRegPre);
-        assert_eq!(instr.get_byte_word().unwrap(), false);
-        assert_eq!(instr.get_add_sub().unwrap(), true);
-        assert_eq!(instr.get_writeback().unwrap(), false);
-        assert_eq!(instr.get_ldr_str().unwrap(), true);
-        assert_eq!(instr.get_rd().unwrap(), Register::r9);
-        assert_eq!(instr.get_rn().unwrap(), Register::r2);
-        assert_eq!(instr.get_rm().unwrap(), Register::r4);
-        assert_eq!(instr.get_shift_type().unwrap(), ShiftType::ASR);
-        assert_eq!(instr.get_imm_shift().unwrap(), 1);
-    }
-
-    #[test]
-    fn test_decode_str_shift_reg_pre() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe78210c4);
-        assert_eq!(instr.get_type(), InstrType::LDRSTRShiftRegPre);
-        assert_eq!(instr.get_byte_word().unwrap(), false);
-        assert_eq!(instr.get_add_sub().unwrap(), true);
-        assert_eq!(instr.get_writeback().unwrap(), false);
-        assert_eq!(instr.get_ldr_str().unwrap(), false);
-        assert_eq!(instr.get_rd().unwrap(), Register::r1);
-        assert_eq!(instr.get_rn().unwrap(), Register::r2);
-        assert_eq!(instr.get_rm().unwrap(), Register::r4);
-        assert_eq!(instr.get_shift_type().unwrap(), ShiftType::ASR);
-        assert_eq!(instr.get_imm_shift().unwrap(), 1);
-    }
-
-    #[test]
-    fn test_decode_ldm() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe8bd000a);
-        assert_eq!(instr.get_type(), InstrType::LDMSTM);
-        assert_eq!(instr.get_ldm().unwrap(), LDMCode::IncAfter);
-        assert_eq!(instr.get_s_bit().unwrap(), false);
-        assert_eq!(instr.get_writeback().unwrap(), true);
-        assert_eq!(instr.get_ldr_str().unwrap(), true);
-        assert_eq!(instr.get_rn().unwrap(), Register::r13);
-        assert_eq!(instr.get_reg_list().unwrap(), 0b1010);
-    }
-
-    #[test]
-    fn test_decode_stm() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe92d0016);
-        assert_eq!(instr.get_type(), InstrType::LDMSTM);
-        assert_eq!(instr.get_ldm().unwrap(), LDMCode::DecBefore);
-        assert_eq!(instr.get_s_bit().unwrap(), false);
-        assert_eq!(instr.get_writeback().unwrap(), true);
-        assert_eq!(instr.get_ldr_str().unwrap(), false);
-        assert_eq!(instr.get_rn().unwrap(), Register::r13);
-        assert_eq!(instr.get_reg_list().unwrap(), 0b10110);
-    }
-
-    #[test]
-    fn test_decode_mul() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xe92d0016);
-        assert_eq!(instr.get_type(), InstrType::LDMSTM);
-        assert_eq!(instr.get_ldm().unwrap(), LDMCode::DecBefore);
-        assert_eq!(instr.get_s_bit().unwrap(), false);
-        assert_eq!(instr.get_writeback().unwrap(), true);
-        assert_eq!(instr.get_ldr_str().unwrap(), false);
-        assert_eq!(instr.get_rn().unwrap(), Register::r13);
-        assert_eq!(instr.get_reg_list().unwrap(), 0b10110);
-    }
-
-    #[test]
-    fn test_decode_branch() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xeb000006);
-        assert_eq!(instr.get_type(), InstrType::B);
-        assert_eq!(instr.get_l_bit().unwrap(), true);
-        assert_eq!(instr.get_offset().unwrap(), 32);
-    }
-
-    #[test]
-    fn test_decode_swi() {
-        let cpu = CPU::default();
-        let instr = cpu.decode(0xef000011);
-        assert_eq!(instr.get_type(), InstrType::SWI);
-        assert_eq!(instr.get_swi().unwrap(), 17);
-    }
-
-    #[test]
-    fn test_add_breakpoint() {
-        let mut cpu = CPU::default();
-        cpu.add_breakpoint(1);
-        assert_eq!(1, cpu.breakpoints[0])
-    }
-
-    #[test]
-    fn test_is_breakpoint() {
-        let mut cpu = CPU::default();
-        cpu.breakpoints.push(1);
-        assert_eq!(true, cpu.is_breakpoint(&1))
-    }
-
-    #[test]
-    fn test_remove_breakpoint() {
-        let mut cpu = CPU::default();
-        cpu.breakpoints.push(1);
-        cpu.breakpoints.push(2);
-        cpu.breakpoints.push(3);
-        cpu.remove_breakpoint(2);
-        assert_eq!(false, cpu.is_breakpoint(&2))
-    }
+use std::{fmt, sync::Mutex};
+
+use log::{trace, warn, error};
+use bitmatch::bitmatch;
+
+use crate::{console::HostConsole, timing::{self, Cycles}, pipeline::{Pipeline, PipelineEntry, Flush}, memory::{Registers, RAM, Memory, Word, AddressSize, Byte, Register}, trace::{TraceFile, TraceLine}, instruction::*, cpu_enum::{Mode, Condition, InstrExecuteCondition, InstrType, Fault, Architecture, UndefinedPolicy}};
+
+// exception vector addresses
+pub const VECTOR_UND: Word  = 0x04;
+pub const VECTOR_SWI: Word  = 0x08;
+pub const VECTOR_PABT: Word = 0x0c;
+pub const VECTOR_DABT: Word = 0x10;
+pub const VECTOR_IRQ: Word  = 0x18;
+pub const VECTOR_FIQ: Word  = 0x1c;
+
+pub struct CPUThreadWatcher {
+    running: bool,
+    prompt_flag: bool,
+    prompt_input: String,
+    irq_flag: bool,
+    fiq_flag: bool
+}
+
+impl CPUThreadWatcher {
+    pub fn set_running(&mut self, state: bool) {
+        self.running = state;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn set_prompt_input(&mut self, value: String) {
+        self.prompt_flag = true;
+        self.prompt_input = value;
+    }
+
+    pub fn get_prompt_input(&self) -> String {
+        self.prompt_input.clone()
+    }
+
+    pub fn get_prompt_flag(&self) -> bool {
+        self.prompt_flag
+    }
+
+    pub fn clear_prompt_flag(&mut self) {
+        self.prompt_flag = false;
+    }
+
+    pub fn set_irq_flag(&mut self) {
+        self.irq_flag = true;
+    }
+
+    pub fn clear_irq_flag(&mut self) {
+        self.irq_flag = false;
+    }
+
+    pub fn get_irq_flag(&self) -> bool {
+        self.irq_flag
+    }
+
+    pub fn set_fiq_flag(&mut self) {
+        self.fiq_flag = true;
+    }
+
+    pub fn clear_fiq_flag(&mut self) {
+        self.fiq_flag = false;
+    }
+
+    pub fn get_fiq_flag(&self) -> bool {
+        self.fiq_flag
+    }
+}
+
+impl Default for CPUThreadWatcher {
+    fn default() -> Self {
+        Self {
+            running: false,
+            prompt_flag: false,
+            prompt_input: String::new(),
+            irq_flag: false,
+            fiq_flag: false
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct CPUPayload {
+    pub trace: bool,
+    pub mode: Mode,
+    pub cycles: Cycles,
+}
+
+// describes the last undefined instruction or abort; sent to the frontend as cpu_fault
+#[derive(Clone, PartialEq, Debug, serde::Serialize)]
+pub struct FaultPayload {
+    pub fault: Fault,
+    pub address: AddressSize,     // faulting data address, or the instruction address for UND and PABT
+    pub pc: AddressSize,          // address of the faulting instruction
+    pub instruction: Option<Word>, // raw word of an undefined instruction
+    pub handled: bool             // false when the CPU halted instead of entering the handler
+}
+
+impl fmt::Display for FaultPayload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.fault)?;
+        if let Some(instruction) = self.instruction {
+            write!(f, " 0x{:x}", instruction)?;
+        }
+        write!(f, " at 0x{:x} (pc = 0x{:x})", self.address, self.pc)?;
+        if !self.handled {
+            write!(f, ", halted")?;
+        }
+        Ok(())
+    }
+}
+
+// one pipeline stage as shown by the host; a failed fetch has no instruction and
+//  describes the error instead
+#[derive(Clone, PartialEq, Debug, serde::Serialize)]
+pub struct PipelineStage {
+    pub address: AddressSize,
+    pub instruction: Option<Word>,
+    pub disassembly: String,
+    pub thumb: bool
+}
+
+// the pipeline while the last instruction executed; sent to the frontend as pipeline_update
+// when flush is set, the decode and fetch stages were discarded and are refilled by the next step
+#[derive(Clone, PartialEq, Debug, serde::Serialize)]
+pub struct PipelinePayload {
+    pub fetch: Option<PipelineStage>,
+    pub decode: Option<PipelineStage>,
+    pub execute: Option<PipelineStage>,
+    pub flush: Option<Flush>
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct TerminalPutcharPayload {
+    pub char: char
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct TerminalReadlinePayload {
+    pub max_bytes: Word
+}
+
+pub struct CPU {
+    breakpoints: Vec<AddressSize>,
+    trace: bool,
+    trace_step: Word,
+    fault: Option<FaultPayload>,
+    architecture: Architecture,
+    undefined_policy: UndefinedPolicy,
+    cycles: Cycles,
+    pipeline: Pipeline
+}
+
+impl CPU {
+    pub fn new() -> Self {
+        Self {
+            breakpoints: vec![0; 0],
+            trace: false,
+            trace_step: 1,
+            fault: None,
+            architecture: Architecture::ARMv4T,
+            undefined_policy: UndefinedPolicy::Exception,
+            cycles: Cycles::default(),
+            pipeline: Pipeline::default()
+        }
+    }
+
+    pub fn get_trace(&self) -> bool {
+        self.trace
+    }
+
+    pub fn toggle_trace(&mut self) -> bool {
+        self.trace = !self.trace;
+
+        self.trace
+    }
+
+    pub fn reset_trace_step(&mut self) {
+        self.trace_step = 1
+    }
+
+    // S/N/I/C cycles executed since the last reset
+    pub fn get_cycles(&self) -> Cycles {
+        self.cycles
+    }
+
+    pub fn reset_cycles(&mut self) {
+        self.cycles = Cycles::default()
+    }
+
+    pub fn get_architecture(&self) -> Architecture {
+        self.architecture
+    }
+
+    pub fn set_architecture(&mut self, architecture: Architecture) {
+        self.architecture = architecture;
+    }
+
+    pub fn get_undefined_policy(&self) -> UndefinedPolicy {
+        self.undefined_policy
+    }
+
+    pub fn set_undefined_policy(&mut self, policy: UndefinedPolicy) {
+        self.undefined_policy = policy;
+    }
+
+    // returns the most recent fault since the last call
+    pub fn take_fault(&mut self) -> Option<FaultPayload> {
+        self.fault.take()
+    }
+
+    pub fn reset_pipeline(&mut self) {
+        self.pipeline.reset()
+    }
+
+    // the pipeline stages while the last instruction executed, disassembled for the host
+    pub fn get_pipeline_payload(&self) -> PipelinePayload {
+        PipelinePayload {
+            fetch: self.pipeline.get_fetch().map(|entry| self.build_pipeline_stage(entry)),
+            decode: self.pipeline.get_decode().map(|entry| self.build_pipeline_stage(entry)),
+            execute: self.pipeline.get_execute().map(|entry| self.build_pipeline_stage(entry)),
+            flush: self.pipeline.get_flush()
+        }
+    }
+
+    fn build_pipeline_stage(&self, entry: PipelineEntry) -> PipelineStage {
+        let (instruction, disassembly) = match entry.fetched {
+            Ok(instr_raw) => {
+                let mut instr = if entry.thumb { self.decode_thumb(instr_raw) } else { self.decode(instr_raw) };
+                let size = if entry.thumb { 2 } else { 4 };
+                instr.set_pc_address(entry.address.wrapping_add(size * 2));
+                (Some(instr_raw), instr.to_string())
+            },
+            Err(err) => (None, err.to_string())
+        };
+
+        PipelineStage { address: entry.address, instruction, disassembly, thumb: entry.thumb }
+    }
+
+    // an encoding neither instruction set allocates decodes as InstrType::Undefined and keeps
+    //  its raw word for the disassembly; executing it raises the Undefined Instruction exception
+    pub fn decode(&self, instr: Word) -> Instruction {
+        // ARMv5TE encodings overlap unallocated ARMv4 space, so they are matched first
+        let decoded = self.decode_v5te(instr).unwrap_or_else(|| self.decode_v4t(instr));
+
+        with_raw_undefined(decoded, instr)
+    }
+
+    pub fn decode_thumb(&self, instr: Word) -> Instruction {
+        let decoded = self.decode_thumb_v5te(instr).unwrap_or_else(|| self.decode_thumb_v4t(instr));
+
+        with_raw_undefined(decoded, instr)
+    }
+
+    // ARMv4T (ARM DDI 0100E, A3.1); the unallocated space is listed explicitly so that it
+    //  cannot fall through to an allocated pattern further down
+    #[bitmatch]
+    fn decode_v4t(&self, instr: Word) -> Instruction {
+        // get instruction data from bits
+        // the bitmatcher matches a bit pattern to a specific instruction factory
+        #[bitmatch]
+        match instr {
+            // the NV condition is unallocated outside of the ARMv5TE additions
+            "1111_????????????????????????????"             => instr_undefined(),
+            "11100001101000000000000000000000"              => instr_nop(),
+            "cccc_00010_r_00_1111_dddd_000000000000"        => instr_mrs(c, r, d),
+            "cccc_00110_r_10_ffff_1111_aaaa_iiiiiiii"       => instr_msr_imm(c, r, f, a, i),
+            "cccc_00010_r_10_ffff_1111_00000000_mmmm"       => instr_msr_reg(c, r, f, m),
+            "cccc_00010010_111111111111_0001_mmmm"          => instr_bx(c, m),
+            // the rest of the compare opcodes without S (A3.16): miscellaneous instructions
+            //  this architecture does not have
+            "????_00010_??0_????_????_????_???0_????"       => instr_undefined(),
+            "????_00010_??0_????_????_????_0??1_????"       => instr_undefined(),
+            "????_00110_??0_????_????_????_????????"        => instr_undefined(),
+            "cccc_000_oooo_s_nnnn_dddd_iiiii_tt_0_mmmm"     => instr_data_reg_imm(c, o, s, n, d, i, t, m),
+            "cccc_000_oooo_s_nnnn_dddd_rrrr_0_tt_1_mmmm"    => instr_data_reg_reg(c, o, s, n, d, r, t, m),
+            "cccc_001_oooo_s_nnnn_dddd_rrrr_iiiiiiii"       => instr_data_imm(c, o, s, n, d, r, i),
+            "cccc_011_1_ubwl_nnnn_dddd_iiiii_tt_0_mmmm"     => instr_ldrstr_shifted_reg_pre(c, u, b, w, l, n, d, i, t, m),
+            "cccc_011_0_ubwl_nnnn_dddd_iiiii_tt_0_mmmm"     => instr_ldrstr_shifted_reg_post(c, u, b, w, l, n, d, i, t, m),
+            "cccc_011_1_ubwl_nnnn_dddd_00000000_mmmm"       => instr_ldrstr_reg_pre(c, u, b, w, l, n, d, m),
+            "cccc_011_0_ubwl_nnnn_dddd_00000000_mmmm"       => instr_ldrstr_reg_post(c, u, b, w, l, n, d, m),
+            "cccc_010_1_ubwl_nnnn_dddd_ssssssssssss"        => instr_ldrstr_imm_pre(c, u, b, w, l, n, d, s),
+            "cccc_010_0_ubwl_nnnn_dddd_ssssssssssss"        => instr_ldrstr_imm_post(c, u, b, w, l, n, d, s),
+            "cccc_000_000a_f_dddd_nnnn_ssss_1001_mmmm"      => instr_mul(c, a, f, d, n, s, m),
+            "cccc_000_01ua_f_hhhh_llll_ssss_1001_mmmm"      => instr_mull(c, u, a, f, h, l, s, m),
+            "cccc_00010_b00_nnnn_dddd_0000_1001_mmmm"      => instr_swp(c, b, n, d, m),
+            // the remaining multiply and extra load/store space, including S = H = 0
+            "????_000_?????????????????_1_00_1_????"        => instr_undefined(),
+            "cccc_000_1_u1wl_nnnn_dddd_hhhh_1_ss_1_iiii"    => instr_ldrhstrh_imm_pre(c, u, w, l, n, d, h, s, i),
+            "cccc_000_0_u1wl_nnnn_dddd_hhhh_1_ss_1_iiii"    => instr_ldrhstrh_imm_post(c, u, w, l, n, d, h, s, i),
+            "cccc_000_1_u0wl_nnnn_dddd_0000_1_ss_1_mmmm"    => instr_ldrhstrh_reg_pre(c, u, w, l, n, d, s, m),
+            "cccc_000_0_u0wl_nnnn_dddd_0000_1_ss_1_mmmm"    => instr_ldrhstrh_reg_post(c, u, w, l, n, d, s, m),
+            "cccc_101_l_oooooooooooooooooooooooo"           => instr_b(c, l, o),
+            "cccc_100_uuswl_nnnn_rrrrrrrrrrrrrrrr"          => instr_ldmstm(c, u, s, w, l, n, r),
+            "cccc_1110_ooo_l_nnnn_dddd_pppp_qqq_1_mmmm"     => instr_mcr_mrc(c, o, l, n, d, p, q, m),
+            "cccc_1110_oooo_nnnn_dddd_pppp_qqq_0_mmmm"      => instr_cdp(c, o, n, d, p, q, m),
+            "cccc_110_auxwl_nnnn_dddd_pppp_iiiiiiii"        => instr_ldcstc(c, a, u, x, w, l, n, d, p, i),
+            "cccc_1111_ssssssssssssssssssssssss"            => instr_swi(c, s),
+            "????????????????????????????????"              => instr_undefined(),
+        }
+    }
+
+    // ARMv5TE additions (ARM DDI 0100E); None when not targeting ARMv5TE or for any other encoding
+    #[bitmatch]
+    fn decode_v5te(&self, instr: Word) -> Option<Instruction> {
+        if self.architecture != Architecture::ARMv5TE {
+            return None
+        }
+
+        #[bitmatch]
+        match instr {
+            "1111_101_h_oooooooooooooooooooooooo"           => Some(instr_blx_imm(h, o)),
+            "1111_0101_u101_nnnn_1111_iiiiiiiiiiii"         => Some(instr_pld_imm(u, n, i)),
+            "1111_0111_u101_nnnn_1111_iiiii_tt_0_mmmm"      => Some(instr_pld_reg(u, n, i, t, m)),
+            "1111_????????????????????????????"             => None,
+            "cccc_00010010_1111_1111_1111_0011_mmmm"        => Some(instr_blx_reg(c, m)),
+            "cccc_00010110_1111_dddd_1111_0001_mmmm"        => Some(instr_clz(c, d, m)),
+            "cccc_00010_oo0_nnnn_dddd_0000_0101_mmmm"       => Some(instr_qadd(c, o, n, d, m)),
+            "cccc_00010000_dddd_nnnn_ssss_1yx0_mmmm"        => Some(instr_smulxy(c, 1, d, n, s, y, x, m)),
+            "cccc_00010110_dddd_0000_ssss_1yx0_mmmm"        => Some(instr_smulxy(c, 0, d, 0, s, y, x, m)),
+            "cccc_00010010_dddd_nnnn_ssss_1y00_mmmm"        => Some(instr_smulwy(c, 1, d, n, s, y, m)),
+            "cccc_00010010_dddd_0000_ssss_1y10_mmmm"        => Some(instr_smulwy(c, 0, d, 0, s, y, m)),
+            "cccc_00010100_hhhh_llll_ssss_1yx0_mmmm"        => Some(instr_smlalxy(c, h, l, s, y, x, m)),
+            "????????????????????????????????"              => None,
+        }
+    }
+
+    // ARMv5TE Thumb additions; a BLX suffix with bit 0 set is undefined
+    #[bitmatch]
+    fn decode_thumb_v5te(&self, instr: Word) -> Option<Instruction> {
+        if self.architecture != Architecture::ARMv5TE {
+            return None
+        }
+
+        #[bitmatch]
+        match instr {
+            "010001_11_1_mmmm_000"      => Some(thumb_instr_blx_reg(m)),
+            "11101_ooooooooooo"         => match o & 1 {
+                                               0 => Some(thumb_instr_blx_suffix(o)),
+                                               _ => Some(thumb_instr_undefined())
+                                           },
+            "????????????????"          => None,
+        }
+    }
+
+    // Thumb-1 (ARMv4T) decoding; the formats follow the ARM7TDMI data sheet, section 5
+    #[bitmatch]
+    fn decode_thumb_v4t(&self, instr: Word) -> Instruction {
+        #[bitmatch]
+        match instr {
+            "00011_i_o_mmm_nnn_ddd"     => match i {
+                                               0 => thumb_instr_add_sub_reg(o, m, n, d),
+                                               _ => thumb_instr_add_sub_imm(o, m, n, d)
+                                           },
+            "000_tt_iiiii_mmm_ddd"      => thumb_instr_shift_imm(t, i, m, d),
+            "001_oo_ddd_iiiiiiii"       => thumb_instr_data_imm(o, d, i),
+            "010000_oooo_mmm_ddd"       => thumb_instr_alu(o, m, d),
+            "010001_11_?_mmmm_???"      => thumb_instr_bx(m),
+            "010001_oo_h_mmmm_ddd"      => thumb_instr_hi_reg(o, h, m, d),
+            "01001_ddd_iiiiiiii"        => thumb_instr_ldr_pc(d, i),
+            "0101_lb_0_mmm_nnn_ddd"     => thumb_instr_ldrstr_reg(l, b, m, n, d),
+            "0101_hs_1_mmm_nnn_ddd"     => thumb_instr_ldrhstrh_reg(h, s, m, n, d),
+            "011_bl_iiiii_nnn_ddd"      => thumb_instr_ldrstr_imm(b, l, i, n, d),
+            "1000_l_iiiii_nnn_ddd"      => thumb_instr_ldrhstrh_imm(l, i, n, d),
+            "1001_l_ddd_iiiiiiii"       => thumb_instr_ldrstr_sp(l, d, i),
+            "1010_s_ddd_iiiiiiii"       => thumb_instr_add_pc_sp(s, d, i),
+            "10110000_s_iiiiiii"        => thumb_instr_add_sp(s, i),
+            "1011_p_10_r_llllllll"      => thumb_instr_push_pop(p, r, l),
+            "1100_l_nnn_rrrrrrrr"       => thumb_instr_ldmstm(l, n, r),
+            "11011110_????????"         => thumb_instr_undefined(),
+            "11011111_ssssssss"         => thumb_instr_swi(s),
+            "1101_cccc_oooooooo"        => thumb_instr_b_cond(c, o),
+            "11100_ooooooooooo"         => thumb_instr_b(o),
+            "1111_h_ooooooooooo"        => thumb_instr_bl(h, o),
+            "????????????????"          => thumb_instr_undefined(),
+        }
+    }
+
+    // whether the instruction's condition passes with the current NZCV flags
+    pub fn condition_passed(&self, registers_lock: &mut Registers, condition: Condition) -> bool {
+        let (n, z, c, v) = registers_lock.get_nzcv_tuple();
+
+        match condition {
+            Condition::EQ =>     z,
+            Condition::NE =>    !z,
+            Condition::CSHS =>   c,
+            Condition::CCLO =>  !c,
+            Condition::MI =>     n,
+            Condition::PL =>    !n,
+            Condition::VS =>     v,
+            Condition::VC =>    !v,
+            Condition::HI =>     c && !z,
+            Condition::LS =>    !c || z,
+            Condition::GE =>    (n && v) || (!n && !v),
+            Condition::LT =>    (n && !v) || (!n && v),
+            Condition::GT =>    !z && n == v,
+            Condition::LE =>     z || (n && !v) || (!n && v),
+            Condition::AL =>    true,
+        }
+    }
+
+    pub fn execute(&self, ram_lock: &mut RAM, registers_lock: &mut Registers, instr: &mut Instruction) -> InstrExecuteCondition {
+        let exec = self.condition_passed(registers_lock, instr.get_condition());
+
+        if exec {
+            // grab the execute method for the specific instruction and pass the state objects
+            instr.get_execute()(ram_lock, registers_lock, *instr)
+        } else {
+            InstrExecuteCondition::NOP
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, address: AddressSize) {
+        trace!("add_breakpoint: {}", address);
+        self.breakpoints.push(address)
+    }
+
+    pub fn remove_breakpoint(&mut self, address: AddressSize) {
+        trace!("add_breakpoint: {}", address);
+        // https://stackoverflow.com/a/26243276
+        let index = self.breakpoints.iter().position(|breakpoint| *breakpoint == address).unwrap();
+        self.breakpoints.remove(index);
+    }
+
+    pub fn is_breakpoint(&self, address: &AddressSize) -> bool {
+        self.breakpoints.contains(&address)
+    }
+
+    fn putchar(&self, arg_char: Word, console: &mut dyn HostConsole) {
+        // originally, this was put here because I thought that the Rust backend
+        //  was sending events too quickly to the frontend and causing the main thread
+        //  to stall. I probably spent around 4 1/2 hours debugging why all the threads 
+        //  were locking up, because I was sure that it wasn't my fault and that I had carefully
+        //  managed all the thread locks. after doing some intensive investigation, I settled on this
+        //  since it seemed to work, although definitely not ideal. Eventually, though,
+        //  I discovered that the root cause of the issue was that all the logs being sent
+        //  through the IPC mechanism to Edge's frontend was beginning to clog the entire thread.
+        //  the bug never appeared in debug mode since the output to the stdout console delayed
+        //  the thread enough so that only a few logs would be sent to Edge. surpisingly,
+        //  once i disabled the log streaming functionality for release mode, everything suddenly *worked*.
+        //
+        //  so this is left here, as a memorial.
+        //
+        // thread::sleep(time::Duration::from_millis(5));
+
+        console.write_char(char::from_u32(arg_char).unwrap_or('\0'));
+    }
+
+    fn readline(&self, ram_lock: &mut RAM, registers_lock: &mut Registers, console: &mut dyn HostConsole) {
+        let arg_dest_addr = registers_lock.get_reg_register(Register::r1);
+        let arg_max_bytes = registers_lock.get_reg_register(Register::r2);
+
+        // wait for the host to return the input line
+        let mut input = console.read_line(arg_max_bytes - 1); // fit null terminator
+
+        trace!("step: input received: {} {}bytes {}dest", input, arg_max_bytes, arg_dest_addr);
+
+        // append CR if received bytes < (arg_max_bytes + \0)
+        if input.len() < (arg_max_bytes - 1) as usize {
+            input.push(0xd as char);
+        }
+        input.push('\0');
+
+        let mut i = 0;
+        for c in input.chars() {
+            ram_lock.write_byte(arg_dest_addr + i, c as Byte);
+            i += 1;
+        }
+    }
+    
+    // returns HLT when the program halts
+    // the watcher is shared with the host so that interrupts and stop requests
+    //  can arrive while the CPU is running
+    pub fn step(&mut self, ram_lock: &mut RAM, registers_lock: &mut Registers, trace_lock: &mut TraceFile, watcher: &Mutex<CPUThreadWatcher>, console: &mut dyn HostConsole) -> InstrExecuteCondition {
+        trace!("step: trace_step: {}", self.trace_step);
+        trace!("step: cpsr: {}", registers_lock.get_cpsr());
+
+        // save PC before fetch begins for logging after execute
+        let saved_pc = registers_lock.get_pc_current_address();
+
+        // the state the instruction is fetched and executed in; the exception return
+        //  addresses below are relative to its instruction size
+        let thumb = registers_lock.get_t_flag();
+        let size = registers_lock.get_instr_size();
+
+        // MMU permission checks depend on the mode the instruction executes in
+        ram_lock.mmu.set_privileged(registers_lock.get_cpsr_mode() != Mode::USR);
+
+        // the instruction leaving the decode stage; the fetch stage fetches the one after next
+        let entry = self.pipeline.advance(ram_lock, saved_pc, thumb);
+        let instr_raw = match entry.fetched {
+            Ok(instr_raw) => instr_raw,
+            Err(err) => {
+                // A2.6.4: the instruction is never executed; r14_abt points past it
+                //  so that the handler returns with SUBS pc, r14, #4
+                trace!("step: prefetch abort, {}", err);
+                ram_lock.prefetch_abort(&err);
+                if !self.report_fault(ram_lock, Fault::PrefetchAbort, saved_pc, saved_pc, None, VECTOR_PABT) {
+                    return InstrExecuteCondition::HLT
+                }
+
+                self.enter_exception(ram_lock, registers_lock, Mode::ABT, VECTOR_PABT, saved_pc.wrapping_add(4));
+                self.pipeline.flush(Flush::Exception);
+                self.cycles += Cycles::exception();
+                return InstrExecuteCondition::NOP
+            }
+        };
+        trace!("step: {}pc = {:x}", registers_lock.get_pc_current_address(), instr_raw);
+
+        // halt when instruction is HLT (0); 0x0000 is a valid Thumb instruction (lsls r0, r0, #0)
+        if instr_raw == 0 && !thumb {
+            registers_lock.inc_pc();
+            return InstrExecuteCondition::HLT;
+        }
+
+        // get the instruction struct from the raw Word
+        let mut instr: Instruction = if thumb {
+            self.decode_thumb(instr_raw)
+        } else {
+            self.decode(instr_raw)
+        };
+
+        // inject possibly needed information into instruction before executing
+
+        // pc for branch instructions
+        instr.set_pc_address(registers_lock.get_pc());
+
+        // pass the necessary state objects and instruction struct;
+        // state guards need to be passed so that the execute method can properly access/modify
+        // the application state
+        // exit if HLT
+        trace!("step: instr = {}", instr.to_string());
+        let executed = self.condition_passed(registers_lock, instr.get_condition());
+        let mut cycles = if executed { self.instruction_cycles(ram_lock, registers_lock, &instr) } else { Cycles::new(1, 0, 0, 0) };
+        let pc = registers_lock.get_pc();
+        let exec_result: InstrExecuteCondition = self.execute(ram_lock, registers_lock, &mut instr);
+
+        // a branch or any other write to the PC flushes and refills the pipeline, and so does
+        //  entering the SWI, undefined instruction or data abort handler; the entry is counted
+        //  here so that the trace line shows it
+        let exception = matches!(exec_result, InstrExecuteCondition::SWI | InstrExecuteCondition::UND | InstrExecuteCondition::DABT(_));
+        if executed && (timing::is_branch(&instr) || registers_lock.get_pc() != pc || exception) {
+            cycles += Cycles::refill();
+            self.pipeline.flush(if exception { Flush::Exception } else { Flush::Branch });
+        }
+        self.cycles += cycles;
+
+        // increment program counter
+        registers_lock.inc_pc();
+
+        // logging: get all registers and remove r15
+        let mut reg_all = registers_lock.get_all();
+        reg_all.pop();
+        trace_lock.append_trace_file_line(TraceLine {
+            trace_step: self.trace_step,
+            pc: saved_pc,
+            checksum: ram_lock.get_checksum(),
+            n: registers_lock.get_n_flag() as Byte,
+            z: registers_lock.get_z_flag() as Byte,
+            c: registers_lock.get_c_flag() as Byte,
+            v: registers_lock.get_v_flag() as Byte,
+            mode: registers_lock.get_cpsr_mode(),
+            regs: reg_all,
+            cycles: self.cycles.get_total()
+        });
+        self.trace_step += 1;
+
+        // handle instruction SWI exceptions
+        match exec_result {
+            InstrExecuteCondition::HLT => {
+                trace!("step: hit SWI HLT");
+                // decrement PC to stop at HLT instruction in disassembly
+                registers_lock.dec_pc();
+                return InstrExecuteCondition::HLT
+            },
+            InstrExecuteCondition::SWI => {
+                // processed here so that we can properly access the app thread
+                trace!("step: processing SWI event 0x{:x}swi", instr.get_swi().unwrap());
+
+                // r14_svc is the address of the next instruction
+                self.enter_exception(ram_lock, registers_lock, Mode::SVC, VECTOR_SWI, saved_pc.wrapping_add(size));
+
+                match instr.get_swi().unwrap() {
+                    0x0 => {
+                        let arg_char = registers_lock.get_reg_register(Register::r0);
+                        self.putchar(arg_char, console);
+                    },
+                    0x6a => self.readline(ram_lock, registers_lock, console),
+                    _ => ()
+                }
+            },
+            InstrExecuteCondition::UND => {
+                // A2.6.3
+                warn!("step: undefined instruction 0x{:x} at 0x{:x}", instr_raw, saved_pc);
+                if !self.report_fault(ram_lock, Fault::Undefined, saved_pc, saved_pc, Some(instr_raw), VECTOR_UND) {
+                    registers_lock.dec_pc();
+                    return InstrExecuteCondition::HLT
+                }
+
+                // r14_und is the address of the next instruction
+                self.enter_exception(ram_lock, registers_lock, Mode::UND, VECTOR_UND, saved_pc.wrapping_add(size));
+            },
+            InstrExecuteCondition::DABT(address) => {
+                // A2.6.5: r14_abt points two instructions past the faulting one
+                //  so that the handler can retry it with SUBS pc, r14, #8
+                trace!("step: data abort at 0x{:x}", address);
+                if !self.report_fault(ram_lock, Fault::DataAbort, address, saved_pc, None, VECTOR_DABT) {
+                    registers_lock.dec_pc();
+                    return InstrExecuteCondition::HLT
+                }
+
+                self.enter_exception(ram_lock, registers_lock, Mode::ABT, VECTOR_DABT, saved_pc.wrapping_add(8));
+            },
+            InstrExecuteCondition::NOP => (),
+        }
+
+        // clock the memory-mapped devices once per retired instruction; this flushes display
+        //  output and polls the keyboard, either of which may need the watcher, so tick before locking
+        let device_request = ram_lock.devices.tick(console);
+
+        // check interrupt state here then drop lock immediately
+        // CPUThreadWatcherState cannot be shared across entire function since interrupt may occur mid-step
+        //  or while step is waiting for prompt event from frontend
+        let irq;
+        let fiq;
+        {
+            let mut watcher_lock = watcher.lock().unwrap();
+            if device_request.irq { watcher_lock.set_irq_flag(); }
+            if device_request.fiq { watcher_lock.set_fiq_flag(); }
+            irq = watcher_lock.get_irq_flag();
+            fiq = watcher_lock.get_fiq_flag();
+        }
+
+        // FIQ has priority over IRQ
+        // only when FIQ interrupts are not disabled
+        if fiq && !registers_lock.get_f_flag() {
+            // A2.6.9
+            trace!("step: received FIQ event, handling...");
+
+            // clear the FIQ flag
+            watcher.lock().unwrap().clear_fiq_flag();
+
+            // the handler returns with SUBS pc, lr, #4 to the next instruction
+            let next = registers_lock.get_pc_current_address();
+            self.enter_exception(ram_lock, registers_lock, Mode::FIQ, VECTOR_FIQ, next.wrapping_add(4));
+            self.pipeline.flush(Flush::Exception);
+            self.cycles += Cycles::exception();
+        // proccess IRQ interrupt from IRQ input line
+        // only when IRQ interrupts are not disabled
+        } else if irq && !registers_lock.get_i_flag() {
+            // A2.6.8
+            trace!("step: received IRQ event, handling...");
+
+            // clear the IRQ flag
+            watcher.lock().unwrap().clear_irq_flag();
+
+            // the handler returns with SUBS pc, lr, #4 to the next instruction
+            let next = registers_lock.get_pc_current_address();
+            self.enter_exception(ram_lock, registers_lock, Mode::IRQ, VECTOR_IRQ, next.wrapping_add(4));
+            self.pipeline.flush(Flush::Exception);
+            self.cycles += Cycles::exception();
+        }
+
+        return exec_result
+    }
+
+    // gathers the operands the timing depends on: Rs for multiplies, which must be read
+    //  before the instruction overwrites it, and the number of words an LDC/STC transfers
+    fn instruction_cycles(&self, ram_lock: &mut RAM, registers_lock: &mut Registers, instr: &Instruction) -> Cycles {
+        let rs = match instr.get_type() {
+            InstrType::MUL | InstrType::MULL => registers_lock.get_reg_register(instr.get_rs().unwrap()),
+            _ => 0
+        };
+        let transfer_length = match instr.get_type() {
+            InstrType::LDCSTC => ram_lock.coprocessors.transfer_length(&instr.get_coprocessor().unwrap()).unwrap_or(0),
+            _ => 0
+        };
+
+        timing::instruction_cycles(instr, rs, transfer_length)
+    }
+
+    // records the fault for the host; an empty vector would execute as HLT at the vector address,
+    //  so with no handler installed the CPU halts on the faulting instruction instead, as it
+    //  does on any undefined instruction under UndefinedPolicy::Halt
+    fn report_fault(&mut self, ram_lock: &mut RAM, fault: Fault, address: AddressSize, pc: AddressSize, instruction: Option<Word>, vector: Word) -> bool {
+        let vector = ram_lock.coprocessors.cp15.get_vector_base() + vector;
+        let handled = match (fault, self.undefined_policy) {
+            (Fault::Undefined, UndefinedPolicy::Halt) => false,
+            _ => ram_lock.try_read_word(vector).is_ok_and(|instr| instr != 0)
+        };
+        let payload = FaultPayload { fault, address, pc, instruction, handled };
+        if !handled {
+            error!("CPU: {}", payload);
+        }
+        self.fault = Some(payload);
+
+        handled
+    }
+
+    // A2.6: saves the CPSR into the new mode's SPSR, stores the return address in its banked r14
+    //  and branches to the exception vector in ARM state with IRQs (and for FIQ, FIQs) disabled
+    // the return address follows the SWI convention: writing it back to the PC resumes at the
+    //  architectural return point, in either state since the handler restores T from the SPSR
+    // the vectors move to 0xffff0000 when the CP15 V bit is set
+    fn enter_exception(&self, ram_lock: &RAM, registers_lock: &mut Registers, mode: Mode, vector: Word, return_address: Word) {
+        let vector = ram_lock.coprocessors.cp15.get_vector_base() + vector;
+        let cpsr = registers_lock.get_cpsr();
+
+        registers_lock.set_cpsr_mode(mode);
+        registers_lock.set_spsr(cpsr);
+        registers_lock.set_reg_register(Register::r14, return_address);
+        registers_lock.set_t_flag(false); // ARM state
+        registers_lock.set_i_flag(true);  // disable interrupts
+        if mode == Mode::FIQ {
+            registers_lock.set_f_flag(true);
+        }
+        if mode == Mode::IRQ || mode == Mode::FIQ || mode == Mode::ABT {
+            registers_lock.set_cpsr_flag(8, true);  // disable imprecise data aborts
+        }
+        registers_lock.set_pc(vector + 8); // add 8 bytes for PC
+    }
+}
+
+// keeps the raw word of an undefined instruction so that the disassembly can show it
+fn with_raw_undefined(mut instr: Instruction, raw: Word) -> Instruction {
+    if instr.get_type() == InstrType::Undefined {
+        instr.set_raw(raw);
+    }
+
+    instr
+}
+
+impl Default for CPU {
+    fn default() -> Self {
+        Self {
+            breakpoints: vec![0; 0],
+            trace: false,
+            trace_step: 1,
+            fault: None,
+            architecture: Architecture::ARMv4T,
+            undefined_policy: UndefinedPolicy::Exception,
+            cycles: Cycles::default(),
+            pipeline: Pipeline::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cpu_enum::{DataOpcode, LDMCode, ShiftType, InstrType, LSH}, memory::Register, coprocessor::CoprocessorOp};
+
+    use super::*;
+
+    #[test]
+    fn test_decode_mov() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe3a02030);
+        assert_eq!(instr.get_type(), InstrType::DataImm);
+        assert_eq!(instr.get_data_opcode().unwrap(), DataOpcode::MOV);
+        assert!(!instr.get_s_bit().unwrap());
+        assert_eq!(instr.get_rn().unwrap(), Register::r0);
+        assert_eq!(instr.get_rd().unwrap(), Register::r2);
+        assert_eq!(instr.get_imm().unwrap(), 48);
+    }
+
+    #[test]
+    fn test_decode_ldr_imm_pre() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe5925000);
+        assert_eq!(instr.get_type(), InstrType::LDRSTRImmPre);
+        assert_eq!(instr.get_byte_word().unwrap(), false);
+        assert_eq!(instr.get_add_sub().unwrap(), true);
+        assert_eq!(instr.get_writeback().unwrap(), false);
+        assert_eq!(instr.get_ldr_str().unwrap(), true);
+        assert_eq!(instr.get_rd().unwrap(), Register::r5);
+        assert_eq!(instr.get_rn().unwrap(), Register::r2);
+        assert_eq!(instr.get_imm_shift().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_decode_str_imm_pre() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe5021004);
+        assert_eq!(instr.get_type(), InstrType::LDRSTRImmPre);
+        assert_eq!(instr.get_byte_word().unwrap(), false);
+        assert_eq!(instr.get_add_sub().unwrap(), false);
+        assert_eq!(instr.get_writeback().unwrap(), false);
+        assert_eq!(instr.get_ldr_str().unwrap(), false);
+        assert_eq!(instr.get_rd().unwrap(), Register::r1);
+        assert_eq!(instr.get_rn().unwrap(), Register::r2);
+        assert_eq!(instr.get_imm_shift().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_decode_ldrb_imm_pre() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe5d2a00a);
+        assert_eq!(instr.get_type(), InstrType::LDRSTRImmPre);
+        assert_eq!(instr.get_byte_word().unwrap(), true);
+        assert_eq!(instr.get_add_sub().unwrap(), true);
+        assert_eq!(instr.get_writeback().unwrap(), false);
+        assert_eq!(instr.get_ldr_str().unwrap(), true);
+        assert_eq!(instr.get_rd().unwrap(), Register::r10);
+        assert_eq!(instr.get_rn().unwrap(), Register::r2);
+        assert_eq!(instr.get_imm_shift().unwrap(), 10);
+    }
+
+    #[test]
+    fn test_decode_strb_imm_pre() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe5c2100a);
+        assert_eq!(instr.get_type(), InstrType::LDRSTRImmPre);
+        assert_eq!(instr.get_byte_word().unwrap(), true);
+        assert_eq!(instr.get_add_sub().unwrap(), true);
+        assert_eq!(instr.get_writeback().unwrap(), false);
+        assert_eq!(instr.get_ldr_str().unwrap(), false);
+        assert_eq!(instr.get_rd().unwrap(), Register::r1);
+        assert_eq!(instr.get_rn().unwrap(), Register::r2);
+        assert_eq!(instr.get_imm_shift().unwrap(), 10);
+    }
+
+    #[test]
+    fn test_decode_ldr_shift_reg_pre() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe79290c4);
+        assert_eq!(instr.get_type(), InstrType::LDRSTRShiftRegPre);
+        assert_eq!(instr.get_byte_word().unwrap(), false);
+        assert_eq!(instr.get_add_sub().unwrap(), true);
+        assert_eq!(instr.get_writeback().unwrap(), false);
+        assert_eq!(instr.get_ldr_str().unwrap(), true);
+        assert_eq!(instr.get_rd().unwrap(), Register::r9);
+        assert_eq!(instr.get_rn().unwrap(), Register::r2);
+        assert_eq!(instr.get_rm().unwrap(), Register::r4);
+        assert_eq!(instr.get_shift_type().unwrap(), ShiftType::ASR);
+        assert_eq!(instr.get_imm_shift().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_decode_str_shift_reg_pre() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe78210c4);
+        assert_eq!(instr.get_type(), InstrType::LDRSTRShiftRegPre);
+        assert_eq!(instr.get_byte_word().unwrap(), false);
+        assert_eq!(instr.get_add_sub().unwrap(), true);
+        assert_eq!(instr.get_writeback().unwrap(), false);
+        assert_eq!(instr.get_ldr_str().unwrap(), false);
+        assert_eq!(instr.get_rd().unwrap(), Register::r1);
+        assert_eq!(instr.get_rn().unwrap(), Register::r2);
+        assert_eq!(instr.get_rm().unwrap(), Register::r4);
+        assert_eq!(instr.get_shift_type().unwrap(), ShiftType::ASR);
+        assert_eq!(instr.get_imm_shift().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_decode_ldm() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe8bd000a);
+        assert_eq!(instr.get_type(), InstrType::LDMSTM);
+        assert_eq!(instr.get_ldm().unwrap(), LDMCode::IncAfter);
+        assert_eq!(instr.get_s_bit().unwrap(), false);
+        assert_eq!(instr.get_writeback().unwrap(), true);
+        assert_eq!(instr.get_ldr_str().unwrap(), true);
+        assert_eq!(instr.get_rn().unwrap(), Register::r13);
+        assert_eq!(instr.get_reg_list().unwrap(), 0b1010);
+    }
+
+    #[test]
+    fn test_decode_stm() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe92d0016);
+        assert_eq!(instr.get_type(), InstrType::LDMSTM);
+        assert_eq!(instr.get_ldm().unwrap(), LDMCode::DecBefore);
+        assert_eq!(instr.get_s_bit().unwrap(), false);
+        assert_eq!(instr.get_writeback().unwrap(), true);
+        assert_eq!(instr.get_ldr_str().unwrap(), false);
+        assert_eq!(instr.get_rn().unwrap(), Register::r13);
+        assert_eq!(instr.get_reg_list().unwrap(), 0b10110);
+    }
+
+    #[test]
+    fn test_decode_mul() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe0100392); // muls r0, r2, r3
+        assert_eq!(instr.get_type(), InstrType::MUL);
+        assert!(!instr.get_accumulate().unwrap());
+        assert!(instr.get_s_bit().unwrap());
+        assert_eq!(instr.get_rd().unwrap(), Register::r0);
+        assert_eq!(instr.get_rs().unwrap(), Register::r3);
+        assert_eq!(instr.get_rm().unwrap(), Register::r2);
+
+        // rs = r0 shares its encoding space with the halfword transfers
+        let instr = cpu.decode(0xe0050096); // mul r5, r6, r0
+        assert_eq!(instr.get_type(), InstrType::MUL);
+        assert_eq!(instr.get_rs().unwrap(), Register::r0);
+
+        let instr = cpu.decode(0xe0214392); // mla r1, r2, r3, r4
+        assert_eq!(instr.get_type(), InstrType::MUL);
+        assert!(instr.get_accumulate().unwrap());
+        assert!(!instr.get_s_bit().unwrap());
+        assert_eq!(instr.get_rn().unwrap(), Register::r4);
+    }
+
+    #[test]
+    fn test_decode_mull() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe0e54796); // smlal r4, r5, r6, r7
+        assert_eq!(instr.get_type(), InstrType::MULL);
+        assert!(instr.get_signed().unwrap());
+        assert!(instr.get_accumulate().unwrap());
+        assert!(!instr.get_s_bit().unwrap());
+        assert_eq!(instr.get_rn().unwrap(), Register::r4);
+        assert_eq!(instr.get_rd().unwrap(), Register::r5);
+        assert_eq!(instr.get_rm().unwrap(), Register::r6);
+        assert_eq!(instr.get_rs().unwrap(), Register::r7);
+    }
+
+    #[test]
+    fn test_decode_ldrd_strd() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xe1e140d8); // ldrd r4, r5, [r1, #8]!
+        assert_eq!(instr.get_type(), InstrType::LDRHSTRHImmPre);
+        assert_eq!(instr.get_lsh().unwrap(), LSH::LdrDoubleWord);
+        assert_eq!(instr.get_imm().unwrap(), 8);
+
+        let instr = cpu.decode(0x004120f8); // streqd r2, r3, [r1], #-8
+        assert_eq!(instr.get_type(), InstrType::LDRHSTRHImmPost);
+        assert_eq!(instr.get_lsh().unwrap(), LSH::StrDoubleWord);
+
+        // odd and r14 register pairs are unpredictable
+        assert_eq!(cpu.decode(0xe1c150d0).get_type(), InstrType::Undefined); // ldrd r5, [r1]
+        assert_eq!(cpu.decode(0xe1c1e0f0).get_type(), InstrType::Undefined); // strd r14, [r1]
+    }
+
+    #[test]
+    fn test_decode_swp() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0x11443093); // swpneb r3, r3, [r4]
+        assert_eq!(instr.get_type(), InstrType::SWP);
+        assert_eq!(instr.get_condition(), Condition::NE);
+        assert!(instr.get_byte_word().unwrap());
+        assert_eq!(instr.get_rn().unwrap(), Register::r4);
+        assert_eq!(instr.get_rd().unwrap(), Register::r3);
+        assert_eq!(instr.get_rm().unwrap(), Register::r3);
+    }
+
+    #[test]
+    fn test_decode_coprocessor() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0x1e15ff30); // mrcne p15, 0, r15, c5, c0, 1
+        assert_eq!(instr.get_type(), InstrType::MCRMRC);
+        assert_eq!(instr.get_condition(), Condition::NE);
+        assert!(instr.get_l_bit().unwrap());
+        assert_eq!(instr.get_rd().unwrap(), Register::r15);
+        assert_eq!(instr.get_coprocessor().unwrap(), CoprocessorOp { coprocessor: 15, crn: 5, opcode_2: 1, ..Default::default() });
+
+        let instr = cpu.decode(0xee2431c5); // cdp p1, 2, c3, c4, c5, 6
+        assert_eq!(instr.get_type(), InstrType::CDP);
+        assert_eq!(instr.get_coprocessor().unwrap(), CoprocessorOp { coprocessor: 1, opcode_1: 2, opcode_2: 6, crd: 3, crn: 4, crm: 5, long: false });
+
+        let instr = cpu.decode(0xece43204); // stcl p2, c3, [r4], #16
+        assert_eq!(instr.get_type(), InstrType::LDCSTC);
+        assert!(!instr.get_ldr_str().unwrap() && !instr.get_addr_mode().unwrap() && instr.get_writeback().unwrap());
+        assert_eq!(instr.get_rn().unwrap(), Register::r4);
+        assert_eq!(instr.get_imm().unwrap(), 4);
+        assert_eq!(instr.get_coprocessor().unwrap(), CoprocessorOp { coprocessor: 2, crd: 3, long: true, ..Default::default() });
+
+        // P = 0, U = 0, W = 0
+        assert_eq!(cpu.decode(0xec176504).get_type(), InstrType::Undefined);
+    }
+
+    #[test]
+    fn test_decode_undefined() {
+        let cpu = CPU::default();
+        let undefined = [
+            0xe7f000f0, // the architecturally undefined space
+            0xf5d1f000, // pld [r1] is NV-conditional
+            0xe1000000, // tst without S
+            0xe12fff30, // blx r0
+            0xe16f0f11, // clz r0, r1
+            0xe1200070, // bkpt
+            0xe3000000, // movw
+            0xe0400090, // multiply space after MULL
+            0xe1000190, // swp with a nonzero SBZ field
+            0xe1d00090, // ldr with S = H = 0
+            0xe09001b1, // ldrh with a nonzero SBZ field
+        ];
+        for instr_raw in undefined {
+            let instr = cpu.decode(instr_raw);
+            assert_eq!(instr.get_type(), InstrType::Undefined, "0x{:08x}", instr_raw);
+            assert_eq!(instr.get_raw(), Some(instr_raw));
+        }
+
+        let instr = cpu.decode_thumb(0xde01);
+        assert_eq!(instr.get_type(), InstrType::Undefined);
+        assert_eq!(instr.get_raw(), Some(0xde01));
+        assert_eq!(cpu.decode_thumb(0xe801).get_type(), InstrType::Undefined); // blx suffix
+
+        // allocated encodings keep no raw word
+        assert_eq!(cpu.decode(0xe1a00000).get_raw(), None);
+    }
+
+    #[test]
+    fn test_decode_v5te_opt_in() {
+        let mut cpu = CPU::default();
+        assert_ne!(cpu.decode(0xe16f0f11).get_type(), InstrType::CLZ); // clz r0, r1
+
+        cpu.set_architecture(Architecture::ARMv5TE);
+        let instr = cpu.decode(0xe16f0f11);
+        assert_eq!(instr.get_type(), InstrType::CLZ);
+        assert_eq!(instr.get_rd().unwrap(), Register::r0);
+        assert_eq!(instr.get_rm().unwrap(), Register::r1);
+
+        let instr = cpu.decode(0xfb000004); // blx with the H bit set
+        assert_eq!(instr.get_type(), InstrType::BLX);
+        assert_eq!(instr.get_offset().unwrap(), 18);
+
+        // the v4 encodings still decode under ARMv5TE
+        assert_eq!(cpu.decode(0xe12fff1e).get_type(), InstrType::BX);
+        assert!(!cpu.decode(0xe12fff1e).get_l_bit().unwrap());
+        assert_eq!(cpu.decode_thumb(0xe802).get_type(), InstrType::ThumbBLXSuffix);
+        assert_eq!(cpu.decode_thumb(0xe801).get_type(), InstrType::Undefined);
+    }
+
+    #[test]
+    fn test_decode_branch() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xeb000006);
+        assert_eq!(instr.get_type(), InstrType::B);
+        assert_eq!(instr.get_l_bit().unwrap(), true);
+        assert_eq!(instr.get_offset().unwrap(), 32);
+    }
+
+    #[test]
+    fn test_decode_swi() {
+        let cpu = CPU::default();
+        let instr = cpu.decode(0xef000011);
+        assert_eq!(instr.get_type(), InstrType::SWI);
+        assert_eq!(instr.get_swi().unwrap(), 17);
+    }
+
+    #[test]
+    fn test_decode_thumb_push() {
+        let cpu = CPU::default();
+        let instr = cpu.decode_thumb(0xb510);
+        assert!(instr.get_thumb());
+        assert_eq!(instr.get_type(), InstrType::LDMSTM);
+        assert_eq!(instr.get_ldm().unwrap(), LDMCode::DecBefore);
+        assert!(instr.get_writeback().unwrap());
+        assert!(!instr.get_ldr_str().unwrap());
+        assert_eq!(instr.get_rn().unwrap(), Register::r13);
+        assert_eq!(instr.get_reg_list().unwrap(), 0x4010);
+    }
+
+    #[test]
+    fn test_decode_thumb_branch() {
+        let cpu = CPU::default();
+        let instr = cpu.decode_thumb(0xd1fb);
+        assert_eq!(instr.get_type(), InstrType::B);
+        assert_eq!(instr.get_condition(), Condition::NE);
+        assert_eq!(instr.get_offset().unwrap(), -10);
+
+        let instr = cpu.decode_thumb(0xf7ff);
+        assert_eq!(instr.get_type(), InstrType::ThumbBLPrefix);
+        assert_eq!(instr.get_offset().unwrap(), -4096);
+        let instr = cpu.decode_thumb(0xf805);
+        assert_eq!(instr.get_type(), InstrType::ThumbBLSuffix);
+        assert_eq!(instr.get_offset().unwrap(), 10);
+    }
+
+    #[test]
+    fn test_add_breakpoint() {
+        let mut cpu = CPU::default();
+        cpu.add_breakpoint(1);
+        assert_eq!(1, cpu.breakpoints[0])
+    }
+
+    #[test]
+    fn test_is_breakpoint() {
+        let mut cpu = CPU::default();
+        cpu.breakpoints.push(1);
+        assert_eq!(true, cpu.is_breakpoint(&1))
+    }
+
+    #[test]
+    fn test_remove_breakpoint() {
+        let mut cpu = CPU::default();
+        cpu.breakpoints.push(1);
+        cpu.breakpoints.push(2);
+        cpu.breakpoints.push(3);
+        cpu.remove_breakpoint(2);
+        assert_eq!(false, cpu.is_breakpoint(&2))
+    }
 }
\ No newline at end of file
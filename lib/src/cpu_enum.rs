@@ -1,150 +1,227 @@
-use std::fmt;
-
-use num_derive::FromPrimitive;
-
-#[derive(Copy, Clone, PartialEq, Debug)]
-pub enum InstrType {
-    DataRegImm,
-    DataRegReg,
-    DataImm,
-    LDRSTRShiftRegPre,
-    LDRSTRShiftRegPost,
-    LDRSTRRegPre,
-    LDRSTRRegPost,
-    LDRSTRImmPre,
-    LDRSTRImmPost,
-    LDRHSTRHImmPre,
-    LDRHSTRHImmPost,
-    LDRHSTRHRegPre,
-    LDRHSTRHRegPost,
-    LDMSTM,
-    B,
-    BX,
-    SWI,
-    MUL,
-    NOP,
-    MSRImm,
-    MSRReg,
-    MRS
-}
-
-#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
-pub enum ShiftType {
-    LSL = 0,
-    LSR = 1,
-    ASR = 2,
-    ROR = 3,
-    // RRX = 3
-}
-
-impl fmt::Display for ShiftType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
-pub enum LDMCode {
-    DecAfter = 0,
-    IncAfter = 1,
-    DecBefore = 2,
-    IncBefore = 3
-}
-
-#[derive(Copy, Clone, PartialEq, Debug)]
-pub enum AddressingMode {
-    PreIndex,
-    PostIndex,
-    PreIndexWriteback
-}
-
-#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
-pub enum DataOpcode {
-    AND = 0,
-    EOR = 1,
-    SUB = 2,
-    RSB = 3,
-    ADD = 4,
-    ADC = 5,
-    SBC = 6,
-    RSC = 7,
-    TST = 8,
-    TEQ = 9,
-    CMP = 10,
-    CMN = 11,
-    ORR = 12,
-    MOV = 13,
-    BIC = 14,
-    MVN = 15
-}
-
-impl fmt::Display for DataOpcode {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
-pub enum LSH {
-    StrHalfWord = 1,
-    LdrDoubleWord = 2,
-    StrDoubleWord = 3,
-    LdrUHalfWord = 5,
-    LdrSByte = 6,
-    LdrSHalfWord = 7
-}
-
-impl fmt::Display for LSH {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
-pub enum Condition {
-    EQ = 0,
-    NE = 1,
-    CSHS = 2,
-    CCLO = 3,
-    MI = 4,
-    PL = 5,
-    VS = 6,
-    VC = 7,
-    HI = 8,
-    LS = 9,
-    GE = 10,
-    LT = 11,
-    GT = 12,
-    LE = 13,
-    AL = 14
-}
-
-impl fmt::Display for Condition {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug, serde::Serialize)]
-pub enum Mode {
-    USR = 0b10000,
-    FIQ = 0b10001,
-    IRQ = 0b10010,
-    SVC = 0b10011,
-    ABT = 0b10111,
-    UND = 0b11011,
-    SYS = 0b11111
-}
-
-impl fmt::Display for Mode {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-#[derive(Copy, Clone, FromPrimitive, PartialEq)]
-pub enum InstrExecuteCondition {
-    HLT,
-    SWI,
-    NOP
-}
\ No newline at end of file
+use std::{fmt, str::FromStr};
+
+use num_derive::FromPrimitive;
+
+use crate::memory::AddressSize;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum InstrType {
+    DataRegImm,
+    DataRegReg,
+    DataImm,
+    LDRSTRShiftRegPre,
+    LDRSTRShiftRegPost,
+    LDRSTRRegPre,
+    LDRSTRRegPost,
+    LDRSTRImmPre,
+    LDRSTRImmPost,
+    LDRHSTRHImmPre,
+    LDRHSTRHImmPost,
+    LDRHSTRHRegPre,
+    LDRHSTRHRegPost,
+    LDMSTM,
+    B,
+    BX,
+    BLX,
+    ThumbBLPrefix,
+    ThumbBLSuffix,
+    ThumbBLXSuffix,
+    SWI,
+    MUL,
+    MULL,
+    SWP,
+    CLZ,
+    QADD,
+    SMULxy,
+    SMULWy,
+    SMLALxy,
+    PLD,
+    MCRMRC,
+    CDP,
+    LDCSTC,
+    NOP,
+    Undefined,
+    MSRImm,
+    MSRReg,
+    MRS
+}
+
+#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
+pub enum ShiftType {
+    LSL = 0,
+    LSR = 1,
+    ASR = 2,
+    ROR = 3,
+    RRX = 4 // encoded as ROR #0 in an immediate shift
+}
+
+impl fmt::Display for ShiftType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
+pub enum LDMCode {
+    DecAfter = 0,
+    IncAfter = 1,
+    DecBefore = 2,
+    IncBefore = 3
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AddressingMode {
+    PreIndex,
+    PostIndex,
+    PreIndexWriteback
+}
+
+#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
+pub enum DataOpcode {
+    AND = 0,
+    EOR = 1,
+    SUB = 2,
+    RSB = 3,
+    ADD = 4,
+    ADC = 5,
+    SBC = 6,
+    RSC = 7,
+    TST = 8,
+    TEQ = 9,
+    CMP = 10,
+    CMN = 11,
+    ORR = 12,
+    MOV = 13,
+    BIC = 14,
+    MVN = 15
+}
+
+impl fmt::Display for DataOpcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
+pub enum LSH {
+    StrHalfWord = 1,
+    LdrDoubleWord = 2,
+    StrDoubleWord = 3,
+    LdrUHalfWord = 5,
+    LdrSByte = 6,
+    LdrSHalfWord = 7
+}
+
+impl fmt::Display for LSH {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
+pub enum Condition {
+    EQ = 0,
+    NE = 1,
+    CSHS = 2,
+    CCLO = 3,
+    MI = 4,
+    PL = 5,
+    VS = 6,
+    VC = 7,
+    HI = 8,
+    LS = 9,
+    GE = 10,
+    LT = 11,
+    GT = 12,
+    LE = 13,
+    AL = 14
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug, serde::Serialize)]
+pub enum Mode {
+    USR = 0b10000,
+    FIQ = 0b10001,
+    IRQ = 0b10010,
+    SVC = 0b10011,
+    ABT = 0b10111,
+    UND = 0b11011,
+    SYS = 0b11111
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, serde::Serialize)]
+pub enum Fault {
+    Undefined,
+    PrefetchAbort,
+    DataAbort
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fault::Undefined => write!(f, "undefined instruction"),
+            Fault::PrefetchAbort => write!(f, "prefetch abort"),
+            Fault::DataAbort => write!(f, "data abort")
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum InstrExecuteCondition {
+    HLT,
+    SWI,
+    NOP,
+    UND,              // undefined instruction
+    DABT(AddressSize) // data abort at the faulting address
+}
+// the instruction set the decoder accepts; ARMv5TE encodings are only recognized when opted in
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Architecture {
+    ARMv4T,
+    // adds BLX, CLZ, the saturating QADD family, the DSP multiplies and PLD
+    ARMv5TE
+}
+
+impl FromStr for Architecture {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "armv4t" => Ok(Architecture::ARMv4T),
+            "armv5te" => Ok(Architecture::ARMv5TE),
+            _ => Err(format!("unknown architecture {} (expected armv4t or armv5te)", s))
+        }
+    }
+}
+
+// what the CPU does on an undefined instruction
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum UndefinedPolicy {
+    // enter the Undefined Instruction handler, or halt when its vector is empty
+    Exception,
+    // always halt on the undefined instruction, as a debugging aid
+    Halt
+}
+
+impl FromStr for UndefinedPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exception" => Ok(UndefinedPolicy::Exception),
+            "halt" => Ok(UndefinedPolicy::Halt),
+            _ => Err(format!("unknown undefined instruction policy {} (expected exception or halt)", s))
+        }
+    }
+}
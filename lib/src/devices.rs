@@ -0,0 +1,332 @@
+use std::{collections::VecDeque, sync::{Arc, Mutex}};
+
+use log::trace;
+
+use crate::{bus::{Device, InterruptRequest}, console::HostConsole, memory::{AddressSize, Byte, HalfWord, Word}};
+
+// write-only character output at DISPLAY_ADDR;
+// characters are buffered until the next tick since stores don't have access to the host console
+#[derive(Default)]
+pub struct DisplayDevice {
+    output: VecDeque<char>
+}
+
+impl Device for DisplayDevice {
+    fn read_byte(&mut self, _offset: AddressSize) -> Byte {
+        0
+    }
+
+    fn write_byte(&mut self, _offset: AddressSize, value: Byte) {
+        trace!("DisplayDevice: char = 0x{:x}", value);
+        self.output.push_back(value as char);
+    }
+
+    fn tick(&mut self, console: &mut dyn HostConsole) -> InterruptRequest {
+        while let Some(c) = self.output.pop_front() {
+            console.write_char(c);
+        }
+        InterruptRequest::NONE
+    }
+
+    fn reset(&mut self) {
+        self.output.clear();
+    }
+}
+
+pub const KEYBOARD_DATA: AddressSize   = 0x0; // read: pop the next key
+pub const KEYBOARD_STATUS: AddressSize = 0x1; // read: KEYBOARD_STATUS_* bits, clears the overflow flag
+
+pub const KEYBOARD_STATUS_READY: Byte    = 1 << 0; // a key is waiting in the FIFO
+pub const KEYBOARD_STATUS_OVERFLOW: Byte = 1 << 1; // a key was dropped because the FIFO was full
+
+pub const KEYBOARD_FIFO_SIZE: usize = 16;
+
+#[derive(Default)]
+struct KeyboardFifoState {
+    data: VecDeque<Byte>,
+    overflow: bool
+}
+
+// receive FIFO of the keyboard device; clones share the same buffer so the host
+//  can push keys while the machine is running on another thread
+#[derive(Clone, Default)]
+pub struct KeyboardFifo {
+    state: Arc<Mutex<KeyboardFifoState>>
+}
+
+impl KeyboardFifo {
+    // drops the key and sets the overflow flag when the FIFO is full
+    pub fn push(&self, c: Byte) {
+        let state = &mut self.state.lock().unwrap();
+        if state.data.len() < KEYBOARD_FIFO_SIZE {
+            state.data.push_back(c);
+        } else {
+            trace!("KeyboardFifo: overflow, dropping 0x{:x}", c);
+            state.overflow = true;
+        }
+    }
+
+    pub fn pop(&self) -> Option<Byte> {
+        self.state.lock().unwrap().data.pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = KeyboardFifoState::default();
+    }
+}
+
+// keypresses at KEYBOARD_ADDR; reading the data register pops the FIFO (the last key is returned
+//  again once it is empty) and an IRQ is raised as long as keys are pending
+#[derive(Default)]
+pub struct KeyboardDevice {
+    fifo: KeyboardFifo,
+    last_char: Byte
+}
+
+impl KeyboardDevice {
+    pub fn get_fifo(&self) -> KeyboardFifo {
+        self.fifo.clone()
+    }
+}
+
+impl Device for KeyboardDevice {
+    fn read_byte(&mut self, offset: AddressSize) -> Byte {
+        match offset {
+            KEYBOARD_DATA => {
+                if let Some(c) = self.fifo.pop() {
+                    self.last_char = c;
+                }
+                self.last_char
+            },
+            KEYBOARD_STATUS => {
+                let state = &mut self.fifo.state.lock().unwrap();
+                let status = (if !state.data.is_empty() { KEYBOARD_STATUS_READY } else { 0 }) |
+                             (if state.overflow { KEYBOARD_STATUS_OVERFLOW } else { 0 });
+                state.overflow = false;
+                status
+            },
+            _ => 0
+        }
+    }
+
+    fn write_byte(&mut self, _offset: AddressSize, _value: Byte) { }
+
+    fn tick(&mut self, console: &mut dyn HostConsole) -> InterruptRequest {
+        while let Some(c) = console.poll_key() {
+            trace!("KeyboardDevice: host keypress {}", c);
+            self.fifo.push(c as Byte);
+        }
+
+        if self.fifo.is_empty() { InterruptRequest::NONE } else { InterruptRequest::IRQ }
+    }
+
+    fn reset(&mut self) {
+        self.fifo.clear();
+        self.last_char = 0;
+    }
+}
+
+pub const TIMER_LOAD: AddressSize    = 0x0; // reload value; writing it also restarts the count
+pub const TIMER_VALUE: AddressSize   = 0x4; // current count (read-only)
+pub const TIMER_CONTROL: AddressSize = 0x8; // TIMER_CONTROL_* bits
+pub const TIMER_CLEAR: AddressSize   = 0xc; // write: acknowledge the interrupt; read: 1 if pending
+
+pub const TIMER_CONTROL_ENABLE: Word   = 1 << 0;
+pub const TIMER_CONTROL_IRQ: Word      = 1 << 1; // raise an IRQ when the count expires
+pub const TIMER_CONTROL_PERIODIC: Word = 1 << 2; // reload from TIMER_LOAD on expiry, otherwise one-shot
+pub const TIMER_CONTROL_FIQ: Word      = 1 << 3; // raise an FIQ instead of an IRQ
+
+// down counter clocked by retired instructions rather than host time, so runs and traces are reproducible;
+// the interrupt is level-triggered and keeps raising IRQs (or FIQs) until the handler writes TIMER_CLEAR
+#[derive(Default)]
+pub struct TimerDevice {
+    load: Word,
+    value: Word,
+    control: Word,
+    pending: bool
+}
+
+impl TimerDevice {
+    fn read_register(&self, offset: AddressSize) -> Word {
+        match offset & !0x3 {
+            TIMER_LOAD => self.load,
+            TIMER_VALUE => self.value,
+            TIMER_CONTROL => self.control,
+            TIMER_CLEAR => self.pending as Word,
+            _ => 0
+        }
+    }
+
+    fn write_register(&mut self, offset: AddressSize, value: Word) {
+        match offset & !0x3 {
+            TIMER_LOAD => {
+                self.load = value;
+                self.value = value;
+            },
+            TIMER_CONTROL => self.control = value & (TIMER_CONTROL_ENABLE | TIMER_CONTROL_IRQ | TIMER_CONTROL_PERIODIC | TIMER_CONTROL_FIQ),
+            TIMER_CLEAR => self.pending = false,
+            _ => ()
+        }
+    }
+}
+
+// sub-word reads return the addressed byte lane (little endian); sub-word writes are zero-extended
+impl Device for TimerDevice {
+    fn read_byte(&mut self, offset: AddressSize) -> Byte {
+        (self.read_register(offset) >> ((offset & 0x3) * 8)) as Byte
+    }
+
+    fn write_byte(&mut self, offset: AddressSize, value: Byte) {
+        self.write_register(offset, value as Word)
+    }
+
+    fn read_half_word(&mut self, offset: AddressSize) -> HalfWord {
+        (self.read_register(offset) >> ((offset & 0x2) * 8)) as HalfWord
+    }
+
+    fn write_half_word(&mut self, offset: AddressSize, value: HalfWord) {
+        self.write_register(offset, value as Word)
+    }
+
+    fn read_word(&mut self, offset: AddressSize) -> Word {
+        self.read_register(offset)
+    }
+
+    fn write_word(&mut self, offset: AddressSize, value: Word) {
+        self.write_register(offset, value)
+    }
+
+    fn tick(&mut self, _console: &mut dyn HostConsole) -> InterruptRequest {
+        if self.control & TIMER_CONTROL_ENABLE != 0 {
+            self.value = self.value.saturating_sub(1);
+
+            if self.value == 0 {
+                trace!("TimerDevice: expired");
+                self.pending = true;
+
+                if self.control & TIMER_CONTROL_PERIODIC != 0 {
+                    self.value = self.load;
+                } else {
+                    self.control &= !TIMER_CONTROL_ENABLE;
+                }
+            }
+        }
+
+        if !self.pending || self.control & TIMER_CONTROL_IRQ == 0 {
+            InterruptRequest::NONE
+        } else if self.control & TIMER_CONTROL_FIQ != 0 {
+            InterruptRequest::FIQ
+        } else {
+            InterruptRequest::IRQ
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::NullConsole;
+
+    #[test]
+    fn test_keyboard_fifo() {
+        let mut keyboard = KeyboardDevice::default();
+        let fifo = keyboard.get_fifo();
+        let mut console = NullConsole;
+
+        assert_eq!(keyboard.tick(&mut console), InterruptRequest::NONE);
+        assert_eq!(keyboard.read_byte(KEYBOARD_STATUS), 0);
+
+        fifo.push(b'a');
+        fifo.push(b'b');
+        assert_eq!(keyboard.tick(&mut console), InterruptRequest::IRQ);
+        assert_eq!(keyboard.read_byte(KEYBOARD_STATUS), KEYBOARD_STATUS_READY);
+        assert_eq!(keyboard.read_byte(KEYBOARD_DATA), b'a');
+        assert_eq!(keyboard.tick(&mut console), InterruptRequest::IRQ);
+        assert_eq!(keyboard.read_byte(KEYBOARD_DATA), b'b');
+
+        // drained: no IRQ and the last key is returned again
+        assert_eq!(keyboard.tick(&mut console), InterruptRequest::NONE);
+        assert_eq!(keyboard.read_byte(KEYBOARD_DATA), b'b');
+    }
+
+    #[test]
+    fn test_keyboard_fifo_overflow() {
+        let mut keyboard = KeyboardDevice::default();
+        let fifo = keyboard.get_fifo();
+
+        for i in 0..=KEYBOARD_FIFO_SIZE {
+            fifo.push(b'a' + i as Byte);
+        }
+        assert_eq!(fifo.len(), KEYBOARD_FIFO_SIZE);
+        assert_eq!(keyboard.read_byte(KEYBOARD_STATUS), KEYBOARD_STATUS_READY | KEYBOARD_STATUS_OVERFLOW);
+        // overflow is cleared once the status is read
+        assert_eq!(keyboard.read_byte(KEYBOARD_STATUS), KEYBOARD_STATUS_READY);
+        // the newest key was dropped
+        for _ in 0..KEYBOARD_FIFO_SIZE - 1 { keyboard.read_byte(KEYBOARD_DATA); }
+        assert_eq!(keyboard.read_byte(KEYBOARD_DATA), b'a' + (KEYBOARD_FIFO_SIZE - 1) as Byte);
+    }
+
+    #[test]
+    fn test_timer_periodic() {
+        let mut timer = TimerDevice::default();
+        let mut console = NullConsole;
+
+        timer.write_word(TIMER_LOAD, 3);
+        timer.write_word(TIMER_CONTROL, TIMER_CONTROL_ENABLE | TIMER_CONTROL_IRQ | TIMER_CONTROL_PERIODIC);
+
+        assert_eq!(timer.tick(&mut console), InterruptRequest::NONE);
+        assert_eq!(timer.tick(&mut console), InterruptRequest::NONE);
+        assert_eq!(timer.read_word(TIMER_VALUE), 1);
+        assert_eq!(timer.tick(&mut console), InterruptRequest::IRQ);
+        assert_eq!(timer.read_word(TIMER_VALUE), 3);
+
+        // level-triggered until acknowledged
+        assert_eq!(timer.tick(&mut console), InterruptRequest::IRQ);
+        assert_eq!(timer.read_word(TIMER_CLEAR), 1);
+        timer.write_word(TIMER_CLEAR, 1);
+        assert_eq!(timer.read_word(TIMER_CLEAR), 0);
+        assert_eq!(timer.tick(&mut console), InterruptRequest::NONE);
+        assert_eq!(timer.tick(&mut console), InterruptRequest::IRQ);
+    }
+
+    #[test]
+    fn test_timer_fiq() {
+        let mut timer = TimerDevice::default();
+        let mut console = NullConsole;
+
+        timer.write_word(TIMER_LOAD, 1);
+        timer.write_word(TIMER_CONTROL, TIMER_CONTROL_ENABLE | TIMER_CONTROL_IRQ | TIMER_CONTROL_FIQ);
+        assert_eq!(timer.tick(&mut console), InterruptRequest::FIQ);
+    }
+
+    #[test]
+    fn test_timer_one_shot() {
+        let mut timer = TimerDevice::default();
+        let mut console = NullConsole;
+
+        timer.write_word(TIMER_LOAD, 2);
+        timer.write_word(TIMER_CONTROL, TIMER_CONTROL_ENABLE);
+
+        timer.tick(&mut console);
+        // expires without raising an IRQ since TIMER_CONTROL_IRQ is clear
+        assert_eq!(timer.tick(&mut console), InterruptRequest::NONE);
+        assert_eq!(timer.read_word(TIMER_CLEAR), 1);
+        assert_eq!(timer.read_word(TIMER_CONTROL) & TIMER_CONTROL_ENABLE, 0);
+
+        timer.write_word(TIMER_CLEAR, 0);
+        timer.tick(&mut console);
+        assert_eq!(timer.read_word(TIMER_CLEAR), 0);
+    }
+}
@@ -1,529 +1,997 @@
-use std::fmt;
-
-use crate::{cpu_enum::{Condition, ShiftType, LDMCode, InstrType, DataOpcode}, memory::{Word, Register, SignedWord}, instruction::{Instruction, TInstruction}, util};
-
-fn get_s_bit_str(s_bit: bool) -> String {
-    match s_bit {
-        true => "s".to_string(),
-        false => "".to_string()
-    }
-}
-
-fn get_l_bit_str(l_bit: bool) -> String {
-    match l_bit {
-        true => "l".to_string(),
-        false => "".to_string()
-    }
-}
-
-fn get_condition_str(condition: Condition) -> String {
-    match condition {
-        Condition::AL => "".to_string(),
-        _ => condition.to_string().to_lowercase()
-    }
-}
-
-fn get_byte_word_str(op: bool) -> String {
-    match op {
-        true => "b".to_string(),
-        false => "".to_string()
-    }
-}
-
-fn get_ldr_str_str(op: bool) -> String {
-    match op {
-        true => "ldr".to_string(),
-        false => "str".to_string()
-    }
-}
-
-fn get_ldm_stm_str(op: bool) -> String {
-    match op {
-        true => "ldm".to_string(),
-        false => "stm".to_string()
-    }
-}
-
-fn get_writeback_str(op: bool) -> String {
-    match op {
-        true => "!".to_string(),
-        false => "".to_string()
-    }
-}
-
-// add is true and sub is false
-fn get_imm_sign_str(imm: Word, add: bool) -> String {
-    match add {
-        true => format!("#{}", imm),
-        false => format!("#-{}", imm)
-    }
-}
-
-fn get_rm_sign_str(rm: Register, add: bool) -> String {
-    match add {
-        true => format!("{}", rm.to_string()),
-        false => format!("-{}", rm.to_string())
-    }
-}
-
-fn get_shift_str(shift_type: ShiftType, imm: Word) -> String {
-    format!("{} #{}",
-        shift_type.to_string().to_lowercase(),
-        imm
-    )
-}
-
-fn get_reg_list_str(reg_list: Word) -> String {
-    let mut regs: Vec<String> = Vec::new();
-
-    for ri in 0..=15 {
-        if (reg_list >> ri) & 0b1 == 1 {
-            let r: Register = num::FromPrimitive::from_u32(ri).unwrap();
-            regs.push(r.to_string());
-        }
-    }
-
-    regs.join(", ")
-}
-
-fn get_ldm_code_str(ldm: LDMCode) -> String {
-    match ldm {
-        LDMCode::DecAfter => "da".to_string(),
-        LDMCode::IncAfter => "ia".to_string(),
-        LDMCode::DecBefore => "db".to_string(),
-        LDMCode::IncBefore => "ib".to_string(),
-    }
-}
-
-fn get_cpsr_spsr_str(gpregister: bool) -> String {
-    match gpregister {
-        true => "SPSR".to_string(),
-        false => "CPSR".to_string()
-    }
-}
-
-fn get_fields_str(field_mask: Word) -> String {
-    let mut str = "".to_string();
-    if util::test_bit(field_mask, 3) {
-        str.push('f');
-    }
-    if util::test_bit(field_mask, 2) {
-        str.push('s');
-    }
-    if util::test_bit(field_mask, 1) {
-        str.push('x');
-    }
-    if util::test_bit(field_mask, 0) {
-        str.push('c');
-    }
-    str
-}
-
-fn get_optional_opcode_str(opcode: DataOpcode, rn: Option<Register>) -> String {
-    match opcode {
-        DataOpcode::MOV | DataOpcode::MVN => "".to_string(), 
-        _ => match rn {
-            Some(r) => format!("{},", r.to_string()),
-            None => "".to_string(),
-        }
-    }
-}
-
-// formatted output for the instructions
-// used for disassembly
-impl fmt::Display for Instruction {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        match self.get_type() {
-            InstrType::NOP => {
-                fmt.write_str("nop")?;
-            },
-            InstrType::DataImm => {
-                // mov rd, #imm8
-                // optional rn
-                let rn = get_optional_opcode_str(self.get_data_opcode().unwrap(), self.get_rn());
-
-                // get the shifted value
-                let (shifter_operand, _shifter_carry_out) = Instruction::rotate_value(
-                    self.get_rotate().unwrap(),
-                    self.get_imm().unwrap_or(0),
-                    0);
-
-                // for each instruction, produce the opcode
-                // and all information associated with it
-                fmt.write_str(
-                    format!(
-                        "{}{}{} {}, {} #{}",
-                        self.get_data_opcode().unwrap().to_string().to_lowercase(),
-                        get_condition_str(self.get_condition()),
-                        get_s_bit_str(self.get_s_bit().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        rn,
-                        shifter_operand
-                    ).as_str()
-                )?;
-            },
-            InstrType::DataRegImm => {
-                // optional rn
-                let rn = get_optional_opcode_str(self.get_data_opcode().unwrap(), self.get_rn());
-                
-                // optional operand2
-                let operand2 = match self.get_imm_shift() {
-                    Some(imm_shift) => {
-                        if imm_shift == 0 {
-                            "".to_string()
-                        } else {
-                            format!("{} #{}", self.get_shift_type().unwrap().to_string().to_lowercase(), imm_shift.to_string())
-                        }
-                    },
-                    None => "".to_string(),
-                };
-                
-                fmt.write_str(
-                    format!(
-                        "{}{}{} {}, {} {} {}",
-                        self.get_data_opcode().unwrap().to_string().to_lowercase(),
-                        get_condition_str(self.get_condition()),
-                        get_s_bit_str(self.get_s_bit().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        rn,
-                        self.get_rm().unwrap().to_string(),
-                        operand2
-                    ).as_str()
-                )?;
-            },
-            InstrType::DataRegReg => {
-                // add rd, rm, sh rs
-                // optional rn
-                let rn = get_optional_opcode_str(self.get_data_opcode().unwrap(), self.get_rn());
-                
-                // optional operand2
-                let operand2 = match self.get_imm_shift() {
-                    Some(_) => format!("{} {}", self.get_shift_type().unwrap().to_string().to_lowercase(), self.get_rs().unwrap().to_string()),
-                    None => "".to_string(),
-                };
-
-                fmt.write_str(
-                    format!(
-                        "{}{}{} {}, {} {}, {}",
-                        self.get_data_opcode().unwrap().to_string().to_lowercase(),
-                        get_condition_str(self.get_condition()),
-                        get_s_bit_str(self.get_s_bit().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        rn,
-                        self.get_rm().unwrap().to_string(),
-                        operand2
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRSTRShiftRegPre => {
-                // ex: ldralb  rd, [rn, rm, lsl -#8]!
-                //     {}{}{} {}, [{}, {}, {}]{}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}{} {}, [{}, {}, {}]{}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        get_byte_word_str(self.get_byte_word().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
-                        get_shift_str(self.get_shift_type().unwrap(), self.get_imm_shift().unwrap()),
-                        get_writeback_str(self.get_writeback().unwrap())
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRSTRImmPre => {
-                // ex: strb rd,  [rn, #8]!
-                //     {}{} {},  {{}, {}}{}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}{} {}, [{}, {}]{}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        get_byte_word_str(self.get_byte_word().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_imm_sign_str(self.get_imm_shift().unwrap(), self.get_add_sub().unwrap()),
-                        get_writeback_str(self.get_writeback().unwrap())
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRSTRImmPost => {
-                // ex: stralb rd,  [rn], #-8
-                //     {}{}{} {},   {},  {}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}{} {}, [{}], {}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        get_byte_word_str(self.get_byte_word().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_imm_sign_str(self.get_imm_shift().unwrap(), self.get_add_sub().unwrap())
-                    ).as_str()
-                )?;
-            },
-            InstrType::B => {
-                // ex: ball #20
-                //     {}{} {} 
-
-                let target_address = self.get_pc_address() as SignedWord + self.get_offset().unwrap();
-
-                fmt.write_str(
-                    format!(
-                        "b{}{} {:X}",
-                        get_condition_str(self.get_condition()),
-                        get_l_bit_str(self.get_l_bit().unwrap()),
-                        target_address
-                    ).as_str()
-                )?;
-            },
-            InstrType::BX => {
-                // ex: bxal r4
-                //       {} {} 
-
-                fmt.write_str(
-                    format!(
-                        "bx{} {}",
-                        get_condition_str(self.get_condition()),
-                        self.get_rm().unwrap().to_string()
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRSTRShiftRegPost => {
-                // ex: ldralb  rd, [rn], rm, lsl -#8
-                //     {}{}{} {}, [{}], {},  {}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}{} {}, [{}], {}, {}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        get_byte_word_str(self.get_byte_word().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
-                        get_shift_str(self.get_shift_type().unwrap(), self.get_imm_shift().unwrap()),
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRSTRRegPost => {
-                // ex: ldralb  rd, [rn], rm
-                //     {}{}{} {}, [{}], {}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}{} {}, [{}], {}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        get_byte_word_str(self.get_byte_word().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRSTRRegPre => {
-                // ex: ldralb  rd, [rn, rm]!
-                //     {}{}{} {}, [{}, {}]{}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}{} {}, [{}, {}]{}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        get_byte_word_str(self.get_byte_word().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
-                        get_writeback_str(self.get_writeback().unwrap())
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRHSTRHImmPre => {
-                // ex: ldralh rd, [rn, #n]!
-                //     {}{}   {}, [{}, {}]{}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}h {}, [{}, {}]{}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_imm_sign_str(self.get_imm().unwrap() as Word, self.get_add_sub().unwrap()),
-                        get_writeback_str(self.get_writeback().unwrap())
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRHSTRHImmPost => {
-                // ex: ldralh rd, [rn], #n
-                //     {}{}   {}, [{}], {}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}h {}, [{}], {}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_imm_sign_str(self.get_imm().unwrap() as Word, self.get_add_sub().unwrap()),
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRHSTRHRegPost => {
-                // ex: ldralh rd, [rn], rm
-                //     {}{}   {}, [{}], {}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}h {}, [{}], {}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDRHSTRHRegPre => {
-                // ex: ldralh rd, [rn, rm]!
-                //     {}{}   {}, [{}, {}]{}
-
-                fmt.write_str(
-                    format!(
-                        "{}{}h {}, [{}, {}]{}",
-                        get_ldr_str_str(self.get_ldr_str().unwrap()),
-                        get_condition_str(self.get_condition()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rn().unwrap().to_string(),
-                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
-                        get_writeback_str(self.get_writeback().unwrap())
-                    ).as_str()
-                )?;
-            },
-            InstrType::LDMSTM => {
-                // ex: ldmalda rn! , {r1, r2, r5}
-                //     {} {}{} {}{},      {}
-
-                if self.get_ldr_str().unwrap()
-                    && self.get_ldm().unwrap() == LDMCode::IncAfter
-                    && self.get_rn().unwrap() == Register::r13
-                    && self.get_writeback().unwrap() { // pop
-                    fmt.write_str(
-                        format!(
-                            "pop{} {{{}}}",
-                            get_condition_str(self.get_condition()),
-                            get_reg_list_str(self.get_reg_list().unwrap()),
-                        ).as_str()
-                    )?;
-                } else if !self.get_ldr_str().unwrap()
-                       && self.get_ldm().unwrap() == LDMCode::DecBefore
-                       && self.get_rn().unwrap() == Register::r13
-                       && self.get_writeback().unwrap() { // push
-                    fmt.write_str(
-                        format!(
-                            "push{} {{{}}}",
-                            get_condition_str(self.get_condition()),
-                            get_reg_list_str(self.get_reg_list().unwrap()),
-                        ).as_str()
-                    )?;
-                } else {
-                    fmt.write_str(
-                        format!(
-                            "{}{}{} {}{}, {{{}}}",
-                            get_ldm_stm_str(self.get_ldr_str().unwrap()),
-                            get_condition_str(self.get_condition()),
-                            get_ldm_code_str(self.get_ldm().unwrap()),
-                            self.get_rn().unwrap().to_string(),
-                            get_writeback_str(self.get_writeback().unwrap()),
-                            get_reg_list_str(self.get_reg_list().unwrap()),
-                        ).as_str()
-                    )?;
-                }
-            },
-            InstrType::MUL => {
-                // ex: mulals  rd, rm, rs
-                //        {}{} {}, {}, {}
-
-                fmt.write_str(
-                    format!(
-                        "mul{}{} {}, {}, {}",
-                        get_condition_str(self.get_condition()),
-                        get_s_bit_str(self.get_s_bit().unwrap()),
-                        self.get_rd().unwrap().to_string(),
-                        self.get_rm().unwrap().to_string(),
-                        self.get_rs().unwrap().to_string(),
-                    ).as_str()
-                )?;
-            },
-            InstrType::SWI => {
-                // ex: swial imm
-                //        {} {}
-
-                fmt.write_str(
-                    format!(
-                        "swi{} {}",
-                        get_condition_str(self.get_condition()),
-                        self.get_swi().unwrap().to_string(),
-                    ).as_str()
-                )?;
-            },
-            InstrType::MSRImm => {
-                // ex: msral CPSR_fields #imm
-                //        {} {}     {}    {}
-
-                fmt.write_str(
-                    format!(
-                        "msr{} {}_{} {}",
-                        get_condition_str(self.get_condition()),
-                        get_cpsr_spsr_str(self.get_gpregister().unwrap()),
-                        get_fields_str(self.get_field_mask().unwrap() as Word),
-                        get_imm_sign_str(self.get_imm().unwrap() as Word, true)
-                    ).as_str()
-                )?;
-            },
-            InstrType::MSRReg => {
-                // ex: msral CPSR_fc, rm
-                //        {} {}   {}  {}
-
-                fmt.write_str(
-                    format!(
-                        "msr{} {}_{}, {}",
-                        get_condition_str(self.get_condition()),
-                        get_cpsr_spsr_str(self.get_gpregister().unwrap()),
-                        get_fields_str(self.get_field_mask().unwrap() as Word),
-                        self.get_rm().unwrap().to_string()
-                    ).as_str()
-                )?;
-            },
-            InstrType::MRS => {
-                // ex: mrsal rd, CPSR
-                //        {} {}   {}
-
-                fmt.write_str(
-                    format!(
-                        "mrs{} {}, {}",
-                        get_condition_str(self.get_condition()),
-                        self.get_rd().unwrap().to_string(),
-                        get_cpsr_spsr_str(self.get_gpregister().unwrap())
-                    ).as_str()
-                )?;
-            },
-        };
-        Ok(())
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_get_reg_list_str() {
-        assert_eq!(get_reg_list_str(0b10110), "r1, r2, r4");
-    }
+use std::fmt;
+
+use crate::{alu, cpu_enum::{Condition, ShiftType, LDMCode, InstrType, DataOpcode, LSH}, memory::{Word, Register, SignedWord}, instruction::{Instruction, TInstruction}, util};
+
+fn get_s_bit_str(s_bit: bool) -> String {
+    match s_bit {
+        true => "s".to_string(),
+        false => "".to_string()
+    }
+}
+
+fn get_l_bit_str(l_bit: bool) -> String {
+    match l_bit {
+        true => "l".to_string(),
+        false => "".to_string()
+    }
+}
+
+// LDRD is encoded with L = 0, so the mnemonic comes from the LSH code rather than the L bit
+fn get_lsh_ldr_str_str(lsh: LSH) -> String {
+    match lsh {
+        LSH::StrHalfWord | LSH::StrDoubleWord => "str".to_string(),
+        _ => "ldr".to_string()
+    }
+}
+
+fn get_lsh_str(lsh: LSH) -> String {
+    match lsh {
+        LSH::StrHalfWord | LSH::LdrUHalfWord => "h".to_string(),
+        LSH::LdrSByte => "sb".to_string(),
+        LSH::LdrSHalfWord => "sh".to_string(),
+        LSH::LdrDoubleWord | LSH::StrDoubleWord => "d".to_string()
+    }
+}
+
+// the halfword selected by <x> or <y> in the DSP multiplies
+fn get_half_str(top: bool) -> String {
+    match top {
+        true => "t".to_string(),
+        false => "b".to_string()
+    }
+}
+
+fn get_condition_str(condition: Condition) -> String {
+    match condition {
+        Condition::AL => "".to_string(),
+        _ => condition.to_string().to_lowercase()
+    }
+}
+
+fn get_byte_word_str(op: bool) -> String {
+    match op {
+        true => "b".to_string(),
+        false => "".to_string()
+    }
+}
+
+fn get_ldr_str_str(op: bool) -> String {
+    match op {
+        true => "ldr".to_string(),
+        false => "str".to_string()
+    }
+}
+
+fn get_ldm_stm_str(op: bool) -> String {
+    match op {
+        true => "ldm".to_string(),
+        false => "stm".to_string()
+    }
+}
+
+fn get_writeback_str(op: bool) -> String {
+    match op {
+        true => "!".to_string(),
+        false => "".to_string()
+    }
+}
+
+// add is true and sub is false
+fn get_imm_sign_str(imm: Word, add: bool) -> String {
+    match add {
+        true => format!("#{}", imm),
+        false => format!("#-{}", imm)
+    }
+}
+
+fn get_rm_sign_str(rm: Register, add: bool) -> String {
+    match add {
+        true => format!("{}", rm.to_string()),
+        false => format!("-{}", rm.to_string())
+    }
+}
+
+// an immediate shift amount of 0 means 32 for LSR and ASR
+fn get_shift_str(shift_type: ShiftType, imm: Word) -> String {
+    match (shift_type, imm) {
+        (ShiftType::RRX, _) => "rrx".to_string(),
+        (ShiftType::LSR | ShiftType::ASR, 0) => format!("{} #32", shift_type.to_string().to_lowercase()),
+        _ => format!("{} #{}", shift_type.to_string().to_lowercase(), imm)
+    }
+}
+
+fn get_reg_list_str(reg_list: Word) -> String {
+    let mut regs: Vec<String> = Vec::new();
+
+    for ri in 0..=15 {
+        if (reg_list >> ri) & 0b1 == 1 {
+            let r: Register = num::FromPrimitive::from_u32(ri).unwrap();
+            regs.push(r.to_string());
+        }
+    }
+
+    regs.join(", ")
+}
+
+fn get_ldm_code_str(ldm: LDMCode) -> String {
+    match ldm {
+        LDMCode::DecAfter => "da".to_string(),
+        LDMCode::IncAfter => "ia".to_string(),
+        LDMCode::DecBefore => "db".to_string(),
+        LDMCode::IncBefore => "ib".to_string(),
+    }
+}
+
+fn get_cpsr_spsr_str(gpregister: bool) -> String {
+    match gpregister {
+        true => "SPSR".to_string(),
+        false => "CPSR".to_string()
+    }
+}
+
+fn get_fields_str(field_mask: Word) -> String {
+    let mut str = "".to_string();
+    if util::test_bit(field_mask, 3) {
+        str.push('f');
+    }
+    if util::test_bit(field_mask, 2) {
+        str.push('s');
+    }
+    if util::test_bit(field_mask, 1) {
+        str.push('x');
+    }
+    if util::test_bit(field_mask, 0) {
+        str.push('c');
+    }
+    str
+}
+
+// mov rd, <operand2>; cmp rn, <operand2>; add rd, rn, <operand2>
+fn get_data_str(instr: &Instruction, operand2: String) -> String {
+    let opcode = instr.get_data_opcode().unwrap();
+    let rd = instr.get_rd().unwrap();
+    let rn = instr.get_rn().unwrap();
+
+    // the compares always set the flags, so the S suffix is implied
+    let (s_bit, operands) = match opcode {
+        DataOpcode::MOV | DataOpcode::MVN => (instr.get_s_bit().unwrap(), format!("{}, {}", rd, operand2)),
+        _ if alu::is_comparison(opcode) => (false, format!("{}, {}", rn, operand2)),
+        _ => (instr.get_s_bit().unwrap(), format!("{}, {}, {}", rd, rn, operand2))
+    };
+
+    format!(
+        "{}{}{} {}",
+        opcode.to_string().to_lowercase(),
+        get_condition_str(instr.get_condition()),
+        get_s_bit_str(s_bit),
+        operands
+    )
+}
+
+// Thumb data-processing instructions are executed as their ARM equivalents (see the Thumb
+//  factories in instruction.rs) but are printed in Thumb syntax; the other Thumb
+//  instructions print the same as ARM
+fn get_thumb_data_str(instr: &Instruction) -> Option<String> {
+    let opcode = instr.get_data_opcode().map(|opcode| opcode.to_string().to_lowercase());
+    let s_bit = get_s_bit_str(instr.get_s_bit().unwrap_or(false));
+
+    let str = match instr.get_type() {
+        InstrType::DataRegImm => {
+            let rd = instr.get_rd().unwrap();
+            let rm = instr.get_rm().unwrap();
+            let imm_shift = instr.get_imm_shift().unwrap();
+            let shift_type = instr.get_shift_type().unwrap();
+
+            match instr.get_data_opcode().unwrap() {
+                // lsl #0 is the low register MOVS
+                DataOpcode::MOV if imm_shift == 0 && shift_type == ShiftType::LSL => format!("mov{} {}, {}", s_bit, rd, rm),
+                DataOpcode::MOV => format!(
+                    "{}s {}, {}, #{}",
+                    shift_type.to_string().to_lowercase(),
+                    rd,
+                    rm,
+                    if imm_shift == 0 { 32 } else { imm_shift }
+                ),
+                DataOpcode::ADD | DataOpcode::SUB if instr.get_s_bit().unwrap() => format!(
+                    "{}s {}, {}, {}",
+                    opcode.unwrap(),
+                    rd,
+                    instr.get_rn().unwrap(),
+                    rm
+                ),
+                DataOpcode::TST | DataOpcode::CMP | DataOpcode::CMN => format!("{} {}, {}", opcode.unwrap(), instr.get_rn().unwrap(), rm),
+                _ => format!("{}{} {}, {}", opcode.unwrap(), s_bit, rd, rm)
+            }
+        },
+        InstrType::DataRegReg => {
+            // lsls rd, rs
+            format!(
+                "{}s {}, {}",
+                instr.get_shift_type().unwrap().to_string().to_lowercase(),
+                instr.get_rd().unwrap(),
+                instr.get_rs().unwrap()
+            )
+        },
+        InstrType::DataImm => {
+            let (imm, _) = Instruction::rotate_value(instr.get_rotate().unwrap(), instr.get_imm().unwrap(), 0);
+            let rd = instr.get_rd().unwrap();
+            let rn = instr.get_rn().unwrap();
+
+            match instr.get_data_opcode().unwrap() {
+                DataOpcode::RSB => format!("negs {}, {}", rd, rn),
+                DataOpcode::MOV => format!("movs {}, #{}", rd, imm),
+                DataOpcode::CMP => format!("cmp {}, #{}", rn, imm),
+                _ if rd == rn => format!("{}{} {}, #{}", opcode.unwrap(), s_bit, rd, imm),
+                _ => format!("{}{} {}, {}, #{}", opcode.unwrap(), s_bit, rd, rn, imm)
+            }
+        },
+        InstrType::MUL => {
+            format!("muls {}, {}", instr.get_rd().unwrap(), instr.get_rm().unwrap())
+        },
+        _ => return None
+    };
+
+    Some(str)
+}
+
+// formatted output for the instructions
+// used for disassembly
+impl fmt::Display for Instruction {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if self.get_thumb() {
+            if let Some(str) = get_thumb_data_str(self) {
+                return fmt.write_str(str.as_str())
+            }
+        }
+
+        match self.get_type() {
+            InstrType::NOP => {
+                fmt.write_str("nop")?;
+            },
+            InstrType::Undefined => {
+                // the raw word as the assembler would emit it, like objdump
+                match self.get_raw() {
+                    Some(raw) if self.get_thumb() => write!(fmt, ".short 0x{:04x}", raw)?,
+                    Some(raw) => write!(fmt, ".word 0x{:08x}", raw)?,
+                    None => fmt.write_str("undefined")?
+                }
+            },
+            InstrType::DataImm => {
+                // ex: mov rd, #imm8
+                //     add rd, rn, #imm8
+                let (shifter_operand, _shifter_carry_out) = Instruction::rotate_value(
+                    self.get_rotate().unwrap(),
+                    self.get_imm().unwrap_or(0),
+                    0);
+
+                fmt.write_str(get_data_str(self, format!("#{}", shifter_operand)).as_str())?;
+            },
+            InstrType::DataRegImm => {
+                // ex: add rd, rn, rm
+                //     add rd, rn, rm, lsl #2
+                //     mov rd, rm, rrx
+                let rm = self.get_rm().unwrap().to_string();
+                let operand2 = match (self.get_shift_type().unwrap(), self.get_imm_shift().unwrap()) {
+                    (ShiftType::LSL, 0) => rm,
+                    (shift_type, imm) => format!("{}, {}", rm, get_shift_str(shift_type, imm))
+                };
+
+                fmt.write_str(get_data_str(self, operand2).as_str())?;
+            },
+            InstrType::DataRegReg => {
+                // ex: add rd, rn, rm, lsl rs
+                let operand2 = format!(
+                    "{}, {} {}",
+                    self.get_rm().unwrap(),
+                    self.get_shift_type().unwrap().to_string().to_lowercase(),
+                    self.get_rs().unwrap()
+                );
+
+                fmt.write_str(get_data_str(self, operand2).as_str())?;
+            },
+            InstrType::LDRSTRShiftRegPre => {
+                // ex: ldralb  rd, [rn, rm, lsl -#8]!
+                //     {}{}{} {}, [{}, {}, {}]{}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}, {}, {}]{}",
+                        get_ldr_str_str(self.get_ldr_str().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_byte_word_str(self.get_byte_word().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
+                        get_shift_str(self.get_shift_type().unwrap(), self.get_imm_shift().unwrap()),
+                        get_writeback_str(self.get_writeback().unwrap())
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDRSTRImmPre => {
+                // ex: strb rd,  [rn, #8]!
+                //     {}{} {},  {{}, {}}{}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}, {}]{}",
+                        get_ldr_str_str(self.get_ldr_str().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_byte_word_str(self.get_byte_word().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_imm_sign_str(self.get_imm_shift().unwrap(), self.get_add_sub().unwrap()),
+                        get_writeback_str(self.get_writeback().unwrap())
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDRSTRImmPost => {
+                // ex: stralb rd,  [rn], #-8
+                //     {}{}{} {},   {},  {}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}], {}",
+                        get_ldr_str_str(self.get_ldr_str().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_byte_word_str(self.get_byte_word().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_imm_sign_str(self.get_imm_shift().unwrap(), self.get_add_sub().unwrap())
+                    ).as_str()
+                )?;
+            },
+            InstrType::B => {
+                // ex: ball #20
+                //     {}{} {} 
+
+                let target_address = self.get_pc_address() as SignedWord + self.get_offset().unwrap();
+
+                fmt.write_str(
+                    format!(
+                        "b{}{} {:X}",
+                        get_condition_str(self.get_condition()),
+                        get_l_bit_str(self.get_l_bit().unwrap()),
+                        target_address
+                    ).as_str()
+                )?;
+            },
+            InstrType::ThumbBLPrefix => {
+                // ex: bl.prefix #4096
+                //     the target is only known once the suffix adds its half of the offset
+
+                fmt.write_str(format!("bl.prefix #{}", self.get_offset().unwrap()).as_str())?;
+            },
+            InstrType::ThumbBLSuffix => {
+                // ex: bl.suffix #20
+
+                fmt.write_str(format!("bl.suffix #{}", self.get_offset().unwrap()).as_str())?;
+            },
+            InstrType::ThumbBLXSuffix => {
+                // ex: blx.suffix #20
+
+                fmt.write_str(format!("blx.suffix #{}", self.get_offset().unwrap()).as_str())?;
+            },
+            InstrType::BLX => {
+                // ex: blx 1F40
+                //         {}
+
+                let target_address = self.get_pc_address() as SignedWord + self.get_offset().unwrap();
+
+                fmt.write_str(format!("blx {:X}", target_address).as_str())?;
+            },
+            InstrType::CLZ => {
+                // ex: clzal rd, rm
+                //        {} {}, {}
+
+                fmt.write_str(
+                    format!(
+                        "clz{} {}, {}",
+                        get_condition_str(self.get_condition()),
+                        self.get_rd().unwrap(),
+                        self.get_rm().unwrap(),
+                    ).as_str()
+                )?;
+            },
+            InstrType::QADD => {
+                // ex: qdsubal rd, rm, rn
+                //     q{}{}{} {}, {}, {}
+
+                fmt.write_str(
+                    format!(
+                        "q{}{}{} {}, {}, {}",
+                        if self.get_double().unwrap() { "d" } else { "" },
+                        if self.get_add_sub().unwrap() { "add" } else { "sub" },
+                        get_condition_str(self.get_condition()),
+                        self.get_rd().unwrap(),
+                        self.get_rm().unwrap(),
+                        self.get_rn().unwrap(),
+                    ).as_str()
+                )?;
+            },
+            InstrType::SMULxy => {
+                // ex: smulbtal rd, rm, rs
+                //     smlabtal rd, rm, rs, rn
+
+                let (mnemonic, rn) = match self.get_accumulate().unwrap() {
+                    true => ("smla", format!(", {}", self.get_rn().unwrap())),
+                    false => ("smul", String::new()),
+                };
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{}{} {}, {}, {}{}",
+                        mnemonic,
+                        get_half_str(self.get_top_x().unwrap()),
+                        get_half_str(self.get_top_y().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        self.get_rd().unwrap(),
+                        self.get_rm().unwrap(),
+                        self.get_rs().unwrap(),
+                        rn,
+                    ).as_str()
+                )?;
+            },
+            InstrType::SMULWy => {
+                // ex: smulwtal rd, rm, rs
+                //     smlawtal rd, rm, rs, rn
+
+                let (mnemonic, rn) = match self.get_accumulate().unwrap() {
+                    true => ("smlaw", format!(", {}", self.get_rn().unwrap())),
+                    false => ("smulw", String::new()),
+                };
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, {}, {}{}",
+                        mnemonic,
+                        get_half_str(self.get_top_y().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        self.get_rd().unwrap(),
+                        self.get_rm().unwrap(),
+                        self.get_rs().unwrap(),
+                        rn,
+                    ).as_str()
+                )?;
+            },
+            InstrType::SMLALxy => {
+                // ex: smlalbtal rdlo, rdhi, rm, rs
+
+                fmt.write_str(
+                    format!(
+                        "smlal{}{}{} {}, {}, {}, {}",
+                        get_half_str(self.get_top_x().unwrap()),
+                        get_half_str(self.get_top_y().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        self.get_rn().unwrap(),
+                        self.get_rd().unwrap(),
+                        self.get_rm().unwrap(),
+                        self.get_rs().unwrap(),
+                    ).as_str()
+                )?;
+            },
+            InstrType::PLD => {
+                // ex: pld [rn, #-8]
+                //     pld [rn, -rm, lsl #2]
+
+                let offset = match self.get_rm() {
+                    None => get_imm_sign_str(self.get_imm_shift().unwrap(), self.get_add_sub().unwrap()),
+                    Some(rm) => {
+                        let rm = get_rm_sign_str(rm, self.get_add_sub().unwrap());
+                        match (self.get_shift_type().unwrap(), self.get_imm_shift().unwrap()) {
+                            (ShiftType::LSL, 0) => rm,
+                            (shift_type, imm) => format!("{}, {}", rm, get_shift_str(shift_type, imm))
+                        }
+                    }
+                };
+
+                fmt.write_str(format!("pld [{}, {}]", self.get_rn().unwrap(), offset).as_str())?;
+            },
+            InstrType::BX => {
+                // ex: blxal r4
+                //      {} {}{} 
+
+                fmt.write_str(
+                    format!(
+                        "b{}x{} {}",
+                        get_l_bit_str(self.get_l_bit().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        self.get_rm().unwrap().to_string()
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDRSTRShiftRegPost => {
+                // ex: ldralb  rd, [rn], rm, lsl -#8
+                //     {}{}{} {}, [{}], {},  {}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}], {}, {}",
+                        get_ldr_str_str(self.get_ldr_str().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_byte_word_str(self.get_byte_word().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
+                        get_shift_str(self.get_shift_type().unwrap(), self.get_imm_shift().unwrap()),
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDRSTRRegPost => {
+                // ex: ldralb  rd, [rn], rm
+                //     {}{}{} {}, [{}], {}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}], {}",
+                        get_ldr_str_str(self.get_ldr_str().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_byte_word_str(self.get_byte_word().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDRSTRRegPre => {
+                // ex: ldralb  rd, [rn, rm]!
+                //     {}{}{} {}, [{}, {}]{}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}, {}]{}",
+                        get_ldr_str_str(self.get_ldr_str().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_byte_word_str(self.get_byte_word().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
+                        get_writeback_str(self.get_writeback().unwrap())
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDRHSTRHImmPre => {
+                // ex: ldralh rd, [rn, #n]!
+                //     {}{}{}  {}, [{}, {}]{}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}, {}]{}",
+                        get_lsh_ldr_str_str(self.get_lsh().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_lsh_str(self.get_lsh().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_imm_sign_str(self.get_imm().unwrap() as Word, self.get_add_sub().unwrap()),
+                        get_writeback_str(self.get_writeback().unwrap())
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDRHSTRHImmPost => {
+                // ex: ldralh rd, [rn], #n
+                //     {}{}{}  {}, [{}], {}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}], {}",
+                        get_lsh_ldr_str_str(self.get_lsh().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_lsh_str(self.get_lsh().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_imm_sign_str(self.get_imm().unwrap() as Word, self.get_add_sub().unwrap()),
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDRHSTRHRegPost => {
+                // ex: ldralh rd, [rn], rm
+                //     {}{}{}  {}, [{}], {}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}], {}",
+                        get_lsh_ldr_str_str(self.get_lsh().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_lsh_str(self.get_lsh().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDRHSTRHRegPre => {
+                // ex: ldralh rd, [rn, rm]!
+                //     {}{}{}  {}, [{}, {}]{}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, [{}, {}]{}",
+                        get_lsh_ldr_str_str(self.get_lsh().unwrap()),
+                        get_condition_str(self.get_condition()),
+                        get_lsh_str(self.get_lsh().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rn().unwrap().to_string(),
+                        get_rm_sign_str(self.get_rm().unwrap(), self.get_add_sub().unwrap()),
+                        get_writeback_str(self.get_writeback().unwrap())
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDMSTM => {
+                // ex: ldmalda rn! , {r1, r2, r5}^
+                //     {} {}{} {}{},      {}      {}
+                // the S bit (^) selects the user bank or, on an LDM with the pc, an exception return,
+                //  so it is never hidden behind pop/push
+
+                let s_bit = self.get_s_bit().unwrap();
+                if self.get_ldr_str().unwrap()
+                    && !s_bit
+                    && self.get_ldm().unwrap() == LDMCode::IncAfter
+                    && self.get_rn().unwrap() == Register::r13
+                    && self.get_writeback().unwrap() { // pop
+                    fmt.write_str(
+                        format!(
+                            "pop{} {{{}}}",
+                            get_condition_str(self.get_condition()),
+                            get_reg_list_str(self.get_reg_list().unwrap()),
+                        ).as_str()
+                    )?;
+                } else if !self.get_ldr_str().unwrap()
+                       && !s_bit
+                       && self.get_ldm().unwrap() == LDMCode::DecBefore
+                       && self.get_rn().unwrap() == Register::r13
+                       && self.get_writeback().unwrap() { // push
+                    fmt.write_str(
+                        format!(
+                            "push{} {{{}}}",
+                            get_condition_str(self.get_condition()),
+                            get_reg_list_str(self.get_reg_list().unwrap()),
+                        ).as_str()
+                    )?;
+                } else {
+                    fmt.write_str(
+                        format!(
+                            "{}{}{} {}{}, {{{}}}{}",
+                            get_ldm_stm_str(self.get_ldr_str().unwrap()),
+                            get_condition_str(self.get_condition()),
+                            get_ldm_code_str(self.get_ldm().unwrap()),
+                            self.get_rn().unwrap().to_string(),
+                            get_writeback_str(self.get_writeback().unwrap()),
+                            get_reg_list_str(self.get_reg_list().unwrap()),
+                            if s_bit { "^" } else { "" },
+                        ).as_str()
+                    )?;
+                }
+            },
+            InstrType::MUL => {
+                // ex: mulals  rd, rm, rs
+                //        {}{} {}, {}, {}
+                // ex: mlaals  rd, rm, rs, rn
+                //        {}{} {}, {}, {}{}
+
+                let (mnemonic, rn) = match self.get_accumulate().unwrap() {
+                    true => ("mla", format!(", {}", self.get_rn().unwrap())),
+                    false => ("mul", String::new()),
+                };
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} {}, {}, {}{}",
+                        mnemonic,
+                        get_condition_str(self.get_condition()),
+                        get_s_bit_str(self.get_s_bit().unwrap()),
+                        self.get_rd().unwrap().to_string(),
+                        self.get_rm().unwrap().to_string(),
+                        self.get_rs().unwrap().to_string(),
+                        rn,
+                    ).as_str()
+                )?;
+            },
+            InstrType::MULL => {
+                // ex: umlalals  rdlo, rdhi, rm, rs
+                //      {}{}{}{} {}, {}, {}, {}
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{}{} {}, {}, {}, {}",
+                        if self.get_signed().unwrap() { "s" } else { "u" },
+                        if self.get_accumulate().unwrap() { "mlal" } else { "mull" },
+                        get_condition_str(self.get_condition()),
+                        get_s_bit_str(self.get_s_bit().unwrap()),
+                        self.get_rn().unwrap(),
+                        self.get_rd().unwrap(),
+                        self.get_rm().unwrap(),
+                        self.get_rs().unwrap(),
+                    ).as_str()
+                )?;
+            },
+            InstrType::SWP => {
+                // ex: swpalb rd, rm, [rn]
+                //     {}{}   {}, {}, [{}]
+
+                fmt.write_str(
+                    format!(
+                        "swp{}{} {}, {}, [{}]",
+                        get_condition_str(self.get_condition()),
+                        get_byte_word_str(self.get_byte_word().unwrap()),
+                        self.get_rd().unwrap(),
+                        self.get_rm().unwrap(),
+                        self.get_rn().unwrap(),
+                    ).as_str()
+                )?;
+            },
+            InstrType::SWI => {
+                // ex: swial imm
+                //        {} {}
+
+                fmt.write_str(
+                    format!(
+                        "swi{} {}",
+                        get_condition_str(self.get_condition()),
+                        self.get_swi().unwrap().to_string(),
+                    ).as_str()
+                )?;
+            },
+            InstrType::MSRImm => {
+                // ex: msral CPSR_fields, #imm
+                //        {} {}     {},     {}
+                let (immediate, _) = Instruction::rotate_value(self.get_rotate().unwrap(), self.get_imm().unwrap(), 0);
+
+                fmt.write_str(
+                    format!(
+                        "msr{} {}_{}, {}",
+                        get_condition_str(self.get_condition()),
+                        get_cpsr_spsr_str(self.get_gpregister().unwrap()),
+                        get_fields_str(self.get_field_mask().unwrap() as Word),
+                        get_imm_sign_str(immediate, true)
+                    ).as_str()
+                )?;
+            },
+            InstrType::MSRReg => {
+                // ex: msral CPSR_fc, rm
+                //        {} {}   {}  {}
+
+                fmt.write_str(
+                    format!(
+                        "msr{} {}_{}, {}",
+                        get_condition_str(self.get_condition()),
+                        get_cpsr_spsr_str(self.get_gpregister().unwrap()),
+                        get_fields_str(self.get_field_mask().unwrap() as Word),
+                        self.get_rm().unwrap().to_string()
+                    ).as_str()
+                )?;
+            },
+            InstrType::MRS => {
+                // ex: mrsal rd, CPSR
+                //        {} {}   {}
+
+                fmt.write_str(
+                    format!(
+                        "mrs{} {}, {}",
+                        get_condition_str(self.get_condition()),
+                        self.get_rd().unwrap().to_string(),
+                        get_cpsr_spsr_str(self.get_gpregister().unwrap())
+                    ).as_str()
+                )?;
+            },
+            InstrType::MCRMRC => {
+                // ex: mrcal p15, 0, rd, c0, c0, 0
+                //     {}{}  p{}, {}, {}, c{}, c{}, {}
+                let op = self.get_coprocessor().unwrap();
+
+                fmt.write_str(
+                    format!(
+                        "{}{} p{}, {}, {}, c{}, c{}, {}",
+                        if self.get_l_bit().unwrap() { "mrc" } else { "mcr" },
+                        get_condition_str(self.get_condition()),
+                        op.coprocessor,
+                        op.opcode_1,
+                        self.get_rd().unwrap(),
+                        op.crn,
+                        op.crm,
+                        op.opcode_2
+                    ).as_str()
+                )?;
+            },
+            InstrType::CDP => {
+                // ex: cdpal p1, 2, c3, c4, c5, 6
+                //        {} p{}, {}, c{}, c{}, c{}, {}
+                let op = self.get_coprocessor().unwrap();
+
+                fmt.write_str(
+                    format!(
+                        "cdp{} p{}, {}, c{}, c{}, c{}, {}",
+                        get_condition_str(self.get_condition()),
+                        op.coprocessor,
+                        op.opcode_1,
+                        op.crd,
+                        op.crn,
+                        op.crm,
+                        op.opcode_2
+                    ).as_str()
+                )?;
+            },
+            InstrType::LDCSTC => {
+                // ex: ldcall p1, c2, [rn, #-8]!
+                //     {}{}{} p{}, c{}, {}
+                let op = self.get_coprocessor().unwrap();
+                let rn = self.get_rn().unwrap();
+                let offset = get_imm_sign_str(self.get_imm().unwrap() as Word * 4, self.get_add_sub().unwrap());
+                let address = match (self.get_addr_mode().unwrap(), self.get_writeback().unwrap()) {
+                    (true, writeback) => format!("[{}, {}]{}", rn, offset, get_writeback_str(writeback)),
+                    (false, true) => format!("[{}], {}", rn, offset),
+                    // unindexed: the offset field is an option for the coprocessor
+                    (false, false) => format!("[{}], {{{}}}", rn, self.get_imm().unwrap())
+                };
+
+                fmt.write_str(
+                    format!(
+                        "{}{}{} p{}, c{}, {}",
+                        if self.get_ldr_str().unwrap() { "ldc" } else { "stc" },
+                        get_condition_str(self.get_condition()),
+                        get_l_bit_str(op.long),
+                        op.coprocessor,
+                        op.crd,
+                        address
+                    ).as_str()
+                )?;
+            },
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cpu::CPU, cpu_enum::Architecture};
+
+    #[test]
+    fn test_get_reg_list_str() {
+        assert_eq!(get_reg_list_str(0b10110), "r1, r2, r4");
+    }
+
+    #[test]
+    fn test_disassemble_data() {
+        let cpu = CPU::default();
+        let disassemble = |instr_raw: Word| cpu.decode(instr_raw).to_string();
+
+        assert_eq!(disassemble(0xe1a00001), "mov r0, r1");
+        assert_eq!(disassemble(0xe2710000), "rsbs r0, r1, #0");
+        assert_eq!(disassemble(0xe31100ff), "tst r1, #255");
+        assert_eq!(disassemble(0xe0810102), "add r0, r1, r2, lsl #2");
+        assert_eq!(disassemble(0xe0543fe5), "subs r3, r4, r5, ror #31");
+        assert_eq!(disassemble(0xe1a00021), "mov r0, r1, lsr #32");
+        assert_eq!(disassemble(0xe1510042), "cmp r1, r2, asr #32");
+        assert_eq!(disassemble(0xe1a00061), "mov r0, r1, rrx");
+        assert_eq!(disassemble(0xe1b00211), "movs r0, r1, lsl r2");
+        assert_eq!(disassemble(0x10010372), "andne r0, r1, r2, ror r3");
+        assert_eq!(disassemble(0xe7910062), "ldr r0, [r1, r2, rrx]");
+        assert_eq!(disassemble(0xe328f20f), "msr CPSR_f, #4026531840");
+    }
+
+    #[test]
+    fn test_disassemble_multiply() {
+        let cpu = CPU::default();
+        let disassemble = |instr_raw: Word| cpu.decode(instr_raw).to_string();
+
+        assert_eq!(disassemble(0xe0100392), "muls r0, r2, r3");
+        assert_eq!(disassemble(0x00314392), "mlaeqs r1, r2, r3, r4");
+        assert_eq!(disassemble(0xe0810392), "umull r0, r1, r2, r3");
+        assert_eq!(disassemble(0xe0b10392), "umlals r0, r1, r2, r3");
+        assert_eq!(disassemble(0xe0c54796), "smull r4, r5, r6, r7");
+        assert_eq!(disassemble(0x10e54796), "smlalne r4, r5, r6, r7");
+    }
+
+    #[test]
+    fn test_disassemble_halfword_doubleword() {
+        let cpu = CPU::default();
+        let disassemble = |instr_raw: Word| cpu.decode(instr_raw).to_string();
+
+        assert_eq!(disassemble(0xe15100d3), "ldrsb r0, [r1, #-3]");
+        assert_eq!(disassemble(0xe09120f3), "ldrsh r2, [r1], r3");
+        assert_eq!(disassemble(0xe13140b3), "ldrh r4, [r1, -r3]!");
+        assert_eq!(disassemble(0xe0c140b2), "strh r4, [r1], #2");
+        assert_eq!(disassemble(0xe1e140d8), "ldrd r4, [r1, #8]!");
+        assert_eq!(disassemble(0x004120f8), "streqd r2, [r1], #-8");
+        assert_eq!(disassemble(0xe18160d3), "ldrd r6, [r1, r3]");
+    }
+
+    #[test]
+    fn test_disassemble_ldmstm() {
+        let cpu = CPU::default();
+        let disassemble = |instr_raw: Word| cpu.decode(instr_raw).to_string();
+
+        assert_eq!(disassemble(0xe8bd8010), "pop {r4, pc}");
+        assert_eq!(disassemble(0xe92d4010), "push {r4, lr}");
+        assert_eq!(disassemble(0xe8910006), "ldmia r1, {r1, r2}");
+        assert_eq!(disassemble(0xe8fd9fff), "ldmia sp!, {r0, r1, r2, r3, r4, r5, r6, r7, r8, r9, sl, fp, il, pc}^");
+        assert_eq!(disassemble(0xe8c06000), "stmia r0, {sp, lr}^");
+    }
+
+    #[test]
+    fn test_disassemble_swp() {
+        let cpu = CPU::default();
+        assert_eq!(cpu.decode(0xe1020091).to_string(), "swp r0, r1, [r2]");
+        assert_eq!(cpu.decode(0x11443093).to_string(), "swpneb r3, r3, [r4]");
+    }
+
+    #[test]
+    fn test_disassemble_coprocessor() {
+        let cpu = CPU::default();
+        let disassemble = |instr_raw: Word| cpu.decode(instr_raw).to_string();
+
+        assert_eq!(disassemble(0xee100f10), "mrc p15, 0, r0, c0, c0, 0");
+        assert_eq!(disassemble(0xee011f10), "mcr p15, 0, r1, c1, c0, 0");
+        assert_eq!(disassemble(0xee2431c5), "cdp p1, 2, c3, c4, c5, 6");
+        assert_eq!(disassemble(0xed332102), "ldc p1, c2, [r3, #-8]!");
+        assert_eq!(disassemble(0xece43204), "stcl p2, c3, [r4], #16");
+        assert_eq!(disassemble(0xec976504), "ldc p5, c6, [r7], {4}");
+        assert_eq!(disassemble(0x1d876503), "stcne p5, c6, [r7, #12]");
+    }
+
+    #[test]
+    fn test_disassemble_undefined() {
+        let cpu = CPU::default();
+        assert_eq!(cpu.decode(0xe7f000f0).to_string(), ".word 0xe7f000f0");
+        assert_eq!(cpu.decode(0x01000000).to_string(), ".word 0x01000000");
+        assert_eq!(cpu.decode_thumb(0xde01).to_string(), ".short 0xde01");
+        assert_eq!(cpu.decode(0xe1a00000).to_string(), "nop");
+    }
+
+    #[test]
+    fn test_disassemble_v5te() {
+        let mut cpu = CPU::default();
+        cpu.set_architecture(Architecture::ARMv5TE);
+        let disassemble = |instr_raw: Word| {
+            let mut instr = cpu.decode(instr_raw);
+            instr.set_pc_address(0x1028);
+            instr.to_string()
+        };
+
+        assert_eq!(disassemble(0xfb000004), "blx 103A");
+        assert_eq!(disassemble(0x112fff3e), "blxne lr");
+        assert_eq!(disassemble(0xe16f0f11), "clz r0, r1");
+        assert_eq!(disassemble(0xe1020051), "qadd r0, r1, r2");
+        assert_eq!(disassemble(0xe1220051), "qsub r0, r1, r2");
+        assert_eq!(disassemble(0x01620051), "qdsubeq r0, r1, r2");
+        assert_eq!(disassemble(0xe10032c1), "smlabt r0, r1, r2, r3");
+        assert_eq!(disassemble(0xe16406e5), "smultt r4, r5, r6");
+        assert_eq!(disassemble(0xe1203281), "smlawb r0, r1, r2, r3");
+        assert_eq!(disassemble(0xe12406e5), "smulwt r4, r5, r6");
+        assert_eq!(disassemble(0xe14103a2), "smlaltb r0, r1, r2, r3");
+        assert_eq!(disassemble(0xf551f008), "pld [r1, #-8]");
+        assert_eq!(disassemble(0xf7d2f103), "pld [r2, r3, lsl #2]");
+        assert_eq!(disassemble(0xf752f003), "pld [r2, -r3]");
+        assert_eq!(cpu.decode_thumb(0x4798).to_string(), "blx r3");
+    }
+
+    #[test]
+    fn test_disassemble_thumb() {
+        let cpu = CPU::default();
+        let disassemble = |instr_raw: Word| {
+            let mut instr = cpu.decode_thumb(instr_raw);
+            instr.set_pc_address(0x1004);
+            instr.to_string()
+        };
+
+        assert_eq!(disassemble(0x00d1), "lsls r1, r2, #3");
+        assert_eq!(disassemble(0x0811), "lsrs r1, r2, #32");
+        assert_eq!(disassemble(0x0023), "movs r3, r4");
+        assert_eq!(disassemble(0x1888), "adds r0, r1, r2");
+        assert_eq!(disassemble(0x20c8), "movs r0, #200");
+        assert_eq!(disassemble(0x2903), "cmp r1, #3");
+        assert_eq!(disassemble(0x40c8), "lsrs r0, r1");
+        assert_eq!(disassemble(0x4248), "negs r0, r1");
+        assert_eq!(disassemble(0x4348), "muls r0, r1");
+        assert_eq!(disassemble(0x4488), "add r8, r1");
+        assert_eq!(disassemble(0x4770), "bx lr");
+        assert_eq!(disassemble(0x4802), "ldr r0, [pc, #8]");
+        assert_eq!(disassemble(0x5688), "ldrsb r0, [r1, r2]");
+        assert_eq!(disassemble(0x5e88), "ldrsh r0, [r1, r2]");
+        assert_eq!(disassemble(0x87c8), "strh r0, [r1, #62]");
+        assert_eq!(disassemble(0xa802), "add r0, sp, #8");
+        assert_eq!(disassemble(0xb082), "sub sp, #8");
+        assert_eq!(disassemble(0xbd02), "pop {r1, pc}");
+        assert_eq!(disassemble(0xd003), "beq 100A");
+        assert_eq!(disassemble(0xdf11), "swi 17");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,494 @@
+use crate::{cpu_enum::{Condition, DataOpcode, InstrType, ShiftType, LSH}, instruction::{Instruction, TInstruction}, memory::{Register, Word}};
+
+/*
+Instruction encoding (ARM DDI 0100E, A3 and A6)
+
+The inverse of CPU::decode and CPU::decode_thumb: every field the factories store is put
+back at its bit position, so decoding the result gives the same instruction. Thumb
+instructions are stored as their ARM equivalents and are encoded back to the Thumb format
+they came from; the few ARM equivalents two formats share (ADDS Rd, Rd, #imm3 and CMP with
+two low registers in the high register format) encode as the shorter-range format
+*/
+
+// encoding of an undefined instruction built without a raw word: the permanently undefined
+//  space in ARM and the undefined conditional branch in Thumb
+const ARM_UNDEFINED: Word = 0xe7f000f0;
+const THUMB_UNDEFINED: Word = 0xde00;
+
+fn bit(value: bool, position: Word) -> Word {
+    (value as Word) << position
+}
+
+fn reg(register: Option<Register>) -> Word {
+    register.unwrap() as Word
+}
+
+// RRX is encoded as ROR #0
+fn shift_imm(instr: &Instruction) -> Word {
+    match instr.get_shift_type().unwrap() {
+        ShiftType::RRX => (ShiftType::ROR as Word) << 5,
+        shift_type => (instr.get_imm_shift().unwrap() << 7) | ((shift_type as Word) << 5)
+    }
+}
+
+// the condition, P, U, W and L bits and the registers shared by the load/store encodings
+fn load_store(instr: &Instruction) -> Word {
+    bit(instr.get_addr_mode().unwrap(), 24)
+        | bit(instr.get_add_sub().unwrap(), 23)
+        | bit(instr.get_writeback().unwrap(), 21)
+        | bit(instr.get_ldr_str().unwrap(), 20)
+        | (reg(instr.get_rn()) << 16)
+        | (reg(instr.get_rd()) << 12)
+}
+
+pub fn encode(instr: &Instruction) -> Word {
+    if instr.get_thumb() {
+        return encode_thumb(instr)
+    }
+
+    let condition = (instr.get_condition() as Word) << 28;
+
+    let fields = match instr.get_type() {
+        InstrType::DataRegImm | InstrType::DataRegReg | InstrType::DataImm => {
+            let operand = match instr.get_type() {
+                InstrType::DataRegImm => shift_imm(instr) | reg(instr.get_rm()),
+                InstrType::DataRegReg => (reg(instr.get_rs()) << 8) | ((instr.get_shift_type().unwrap() as Word) << 5) | (1 << 4) | reg(instr.get_rm()),
+                _ => (1 << 25) | ((instr.get_rotate().unwrap() as Word) << 8) | instr.get_imm().unwrap() as Word
+            };
+
+            ((instr.get_data_opcode().unwrap() as Word) << 21)
+                | bit(instr.get_s_bit().unwrap(), 20)
+                | (reg(instr.get_rn()) << 16)
+                | (reg(instr.get_rd()) << 12)
+                | operand
+        },
+        InstrType::LDRSTRShiftRegPre | InstrType::LDRSTRShiftRegPost => {
+            (0b011 << 25) | load_store(instr) | bit(instr.get_byte_word().unwrap(), 22) | shift_imm(instr) | reg(instr.get_rm())
+        },
+        InstrType::LDRSTRRegPre | InstrType::LDRSTRRegPost => {
+            (0b011 << 25) | load_store(instr) | bit(instr.get_byte_word().unwrap(), 22) | reg(instr.get_rm())
+        },
+        InstrType::LDRSTRImmPre | InstrType::LDRSTRImmPost => {
+            (0b010 << 25) | load_store(instr) | bit(instr.get_byte_word().unwrap(), 22) | instr.get_imm_shift().unwrap()
+        },
+        InstrType::LDRHSTRHImmPre | InstrType::LDRHSTRHImmPost => {
+            let imm = instr.get_imm().unwrap() as Word;
+            let sh = (instr.get_lsh().unwrap() as Word) & 0b11;
+            load_store(instr) | (1 << 22) | ((imm >> 4) << 8) | (1 << 7) | (sh << 5) | (1 << 4) | (imm & 0xf)
+        },
+        InstrType::LDRHSTRHRegPre | InstrType::LDRHSTRHRegPost => {
+            let sh = (instr.get_lsh().unwrap() as Word) & 0b11;
+            load_store(instr) | (1 << 7) | (sh << 5) | (1 << 4) | reg(instr.get_rm())
+        },
+        InstrType::LDMSTM => {
+            (0b100 << 25)
+                | ((instr.get_ldm().unwrap() as Word) << 23)
+                | bit(instr.get_s_bit().unwrap(), 22)
+                | bit(instr.get_writeback().unwrap(), 21)
+                | bit(instr.get_ldr_str().unwrap(), 20)
+                | (reg(instr.get_rn()) << 16)
+                | (instr.get_reg_list().unwrap() & 0xffff)
+        },
+        InstrType::B => {
+            (0b101 << 25) | bit(instr.get_l_bit().unwrap(), 24) | ((instr.get_offset().unwrap() as Word >> 2) & 0xffffff)
+        },
+        // BX and BLX Rm
+        InstrType::BX => {
+            0x012fff10 | bit(instr.get_l_bit().unwrap(), 5) | reg(instr.get_rm())
+        },
+        // BLX <target> is unconditional; the H bit is bit 1 of the offset
+        InstrType::BLX => {
+            let offset = instr.get_offset().unwrap() as Word;
+            return (0b1111101 << 25) | (((offset >> 1) & 1) << 24) | ((offset >> 2) & 0xffffff)
+        },
+        InstrType::SWI => {
+            (0b1111 << 24) | (instr.get_swi().unwrap() & 0xffffff)
+        },
+        InstrType::MUL => {
+            bit(instr.get_accumulate().unwrap(), 21)
+                | bit(instr.get_s_bit().unwrap(), 20)
+                | (reg(instr.get_rd()) << 16)
+                | (reg(instr.get_rn()) << 12)
+                | (reg(instr.get_rs()) << 8)
+                | (0b1001 << 4)
+                | reg(instr.get_rm())
+        },
+        InstrType::MULL => {
+            (1 << 23)
+                | bit(instr.get_signed().unwrap(), 22)
+                | bit(instr.get_accumulate().unwrap(), 21)
+                | bit(instr.get_s_bit().unwrap(), 20)
+                | (reg(instr.get_rd()) << 16)
+                | (reg(instr.get_rn()) << 12)
+                | (reg(instr.get_rs()) << 8)
+                | (0b1001 << 4)
+                | reg(instr.get_rm())
+        },
+        InstrType::SWP => {
+            (0b00010 << 23) | bit(instr.get_byte_word().unwrap(), 22) | (reg(instr.get_rn()) << 16) | (reg(instr.get_rd()) << 12) | (0b1001 << 4) | reg(instr.get_rm())
+        },
+        InstrType::MRS => {
+            0x010f0000 | bit(instr.get_gpregister().unwrap(), 22) | (reg(instr.get_rd()) << 12)
+        },
+        InstrType::MSRImm => {
+            0x0320f000
+                | bit(instr.get_gpregister().unwrap(), 22)
+                | ((instr.get_field_mask().unwrap() as Word) << 16)
+                | ((instr.get_rotate().unwrap() as Word) << 8)
+                | instr.get_imm().unwrap() as Word
+        },
+        InstrType::MSRReg => {
+            0x0120f000 | bit(instr.get_gpregister().unwrap(), 22) | ((instr.get_field_mask().unwrap() as Word) << 16) | reg(instr.get_rm())
+        },
+        InstrType::CLZ => {
+            0x016f0f10 | (reg(instr.get_rd()) << 12) | reg(instr.get_rm())
+        },
+        InstrType::QADD => {
+            let op = bit(instr.get_double().unwrap(), 1) | bit(!instr.get_add_sub().unwrap(), 0);
+            0x01000050 | (op << 21) | (reg(instr.get_rn()) << 16) | (reg(instr.get_rd()) << 12) | reg(instr.get_rm())
+        },
+        // SMLA<x><y> (op 00) and SMUL<x><y> (op 11)
+        InstrType::SMULxy => {
+            let op: Word = if instr.get_accumulate().unwrap() { 0b00 } else { 0b11 };
+            0x01000080
+                | (op << 21)
+                | (reg(instr.get_rd()) << 16)
+                | (reg(instr.get_rn()) << 12)
+                | (reg(instr.get_rs()) << 8)
+                | bit(instr.get_top_y().unwrap(), 6)
+                | bit(instr.get_top_x().unwrap(), 5)
+                | reg(instr.get_rm())
+        },
+        // SMLAW<y> and SMULW<y>, which has bit 5 set
+        InstrType::SMULWy => {
+            0x01200080
+                | (reg(instr.get_rd()) << 16)
+                | (reg(instr.get_rn()) << 12)
+                | (reg(instr.get_rs()) << 8)
+                | bit(instr.get_top_y().unwrap(), 6)
+                | bit(!instr.get_accumulate().unwrap(), 5)
+                | reg(instr.get_rm())
+        },
+        InstrType::SMLALxy => {
+            0x01400080
+                | (reg(instr.get_rd()) << 16)
+                | (reg(instr.get_rn()) << 12)
+                | (reg(instr.get_rs()) << 8)
+                | bit(instr.get_top_y().unwrap(), 6)
+                | bit(instr.get_top_x().unwrap(), 5)
+                | reg(instr.get_rm())
+        },
+        // PLD is unconditional; the register form has Rm
+        InstrType::PLD => {
+            let address = bit(instr.get_add_sub().unwrap(), 23) | (reg(instr.get_rn()) << 16);
+            return match instr.get_rm() {
+                Some(rm) => 0xf750f000 | address | shift_imm(instr) | rm as Word,
+                None => 0xf550f000 | address | instr.get_imm_shift().unwrap()
+            }
+        },
+        InstrType::MCRMRC => {
+            let op = instr.get_coprocessor().unwrap();
+            (0b1110 << 24)
+                | (op.opcode_1 << 21)
+                | bit(instr.get_l_bit().unwrap(), 20)
+                | (op.crn << 16)
+                | (reg(instr.get_rd()) << 12)
+                | (op.coprocessor << 8)
+                | (op.opcode_2 << 5)
+                | (1 << 4)
+                | op.crm
+        },
+        InstrType::CDP => {
+            let op = instr.get_coprocessor().unwrap();
+            (0b1110 << 24) | (op.opcode_1 << 20) | (op.crn << 16) | (op.crd << 12) | (op.coprocessor << 8) | (op.opcode_2 << 5) | op.crm
+        },
+        InstrType::LDCSTC => {
+            let op = instr.get_coprocessor().unwrap();
+            (0b110 << 25)
+                | bit(instr.get_addr_mode().unwrap(), 24)
+                | bit(instr.get_add_sub().unwrap(), 23)
+                | bit(op.long, 22)
+                | bit(instr.get_writeback().unwrap(), 21)
+                | bit(instr.get_ldr_str().unwrap(), 20)
+                | (reg(instr.get_rn()) << 16)
+                | (op.crd << 12)
+                | (op.coprocessor << 8)
+                | instr.get_imm().unwrap() as Word
+        },
+        // mov r0, r0
+        InstrType::NOP => return 0xe1a00000,
+        InstrType::Undefined => return instr.get_raw().unwrap_or(ARM_UNDEFINED),
+        InstrType::ThumbBLPrefix | InstrType::ThumbBLSuffix | InstrType::ThumbBLXSuffix => return encode_thumb(instr)
+    };
+
+    condition | fields
+}
+
+fn unencodable(instr: &Instruction) -> ! {
+    panic!("encode: {:?} {} has no Thumb encoding", instr.get_type(), instr)
+}
+
+// the Thumb halfword in the low 16 bits; panics when the ARM equivalent has no Thumb format
+fn encode_thumb(instr: &Instruction) -> Word {
+    let rd = || reg(instr.get_rd());
+    let rn = || reg(instr.get_rn());
+    let rm = || reg(instr.get_rm());
+    let s_bit = instr.get_s_bit().unwrap_or(false);
+
+    match instr.get_type() {
+        InstrType::DataRegImm => {
+            let opcode = instr.get_data_opcode().unwrap();
+            match opcode {
+                // LSL/LSR/ASR Rd, Rm, #imm5
+                DataOpcode::MOV if s_bit => ((instr.get_shift_type().unwrap() as Word) << 11) | (instr.get_imm_shift().unwrap() << 6) | (rm() << 3) | rd(),
+                // ADDS/SUBS Rd, Rn, Rm
+                DataOpcode::ADD | DataOpcode::SUB if s_bit => {
+                    0x1800 | bit(opcode == DataOpcode::SUB, 9) | (rm() << 6) | (rn() << 3) | rd()
+                },
+                // ADD/CMP/MOV with a high register
+                DataOpcode::ADD | DataOpcode::MOV | DataOpcode::CMP if !s_bit || rd() > 7 || rm() > 7 => {
+                    let op: Word = match opcode { DataOpcode::ADD => 0b00, DataOpcode::CMP => 0b01, _ => 0b10 };
+                    0x4400 | (op << 8) | ((rd() >> 3) << 7) | (rm() << 3) | (rd() & 0b111)
+                },
+                // the ALU operations that share the ARM opcode numbers
+                _ => 0x4000 | ((opcode as Word) << 6) | (rm() << 3) | rd()
+            }
+        },
+        // LSL/LSR/ASR/ROR Rd, Rs
+        InstrType::DataRegReg => {
+            let op: Word = match instr.get_shift_type().unwrap() {
+                ShiftType::LSL => 0b0010,
+                ShiftType::LSR => 0b0011,
+                ShiftType::ASR => 0b0100,
+                _ => 0b0111
+            };
+            0x4000 | (op << 6) | (reg(instr.get_rs()) << 3) | rd()
+        },
+        InstrType::DataImm => {
+            let opcode = instr.get_data_opcode().unwrap();
+            let imm = instr.get_imm().unwrap() as Word;
+            match opcode {
+                // NEG Rd, Rm is RSBS Rd, Rm, #0
+                DataOpcode::RSB => 0x4240 | (rn() << 3) | rd(),
+                // ADD Rd, PC/SP, #imm8 * 4 and ADD/SUB SP, #imm7 * 4
+                DataOpcode::ADD | DataOpcode::SUB if !s_bit => match rd() {
+                    13 => 0xb000 | bit(opcode == DataOpcode::SUB, 7) | imm,
+                    _ => 0xa000 | bit(rn() == Register::r13 as Word, 11) | (rd() << 8) | imm
+                },
+                // ADDS/SUBS Rd, Rn, #imm3
+                DataOpcode::ADD | DataOpcode::SUB if rd() != rn() => {
+                    0x1c00 | bit(opcode == DataOpcode::SUB, 9) | (imm << 6) | (rn() << 3) | rd()
+                },
+                // MOVS/CMP/ADDS/SUBS Rd, #imm8
+                _ => {
+                    let op: Word = match opcode { DataOpcode::MOV => 0b00, DataOpcode::CMP => 0b01, DataOpcode::ADD => 0b10, _ => 0b11 };
+                    0x2000 | (op << 11) | (rn() << 8) | imm
+                }
+            }
+        },
+        InstrType::MUL => 0x4340 | (rm() << 3) | rd(),
+        InstrType::BX => 0x4700 | bit(instr.get_l_bit().unwrap(), 7) | (rm() << 3),
+        InstrType::LDRSTRImmPre => {
+            let imm = instr.get_imm_shift().unwrap();
+            let l = bit(instr.get_ldr_str().unwrap(), 11);
+            match instr.get_rn().unwrap() {
+                Register::r15 => 0x4800 | (rd() << 8) | (imm >> 2),
+                Register::r13 => 0x9000 | l | (rd() << 8) | (imm >> 2),
+                _ if instr.get_byte_word().unwrap() => 0x7000 | l | (imm << 6) | (rn() << 3) | rd(),
+                _ => 0x6000 | l | ((imm >> 2) << 6) | (rn() << 3) | rd()
+            }
+        },
+        InstrType::LDRSTRRegPre => {
+            0x5000 | bit(instr.get_ldr_str().unwrap(), 11) | bit(instr.get_byte_word().unwrap(), 10) | (rm() << 6) | (rn() << 3) | rd()
+        },
+        // STRH, LDRH, LDRSB and LDRSH are the H and S bits of the LSH code
+        InstrType::LDRHSTRHRegPre => {
+            let (h, s): (Word, Word) = match instr.get_lsh().unwrap() {
+                LSH::StrHalfWord => (0, 0),
+                LSH::LdrUHalfWord => (1, 0),
+                LSH::LdrSByte => (0, 1),
+                LSH::LdrSHalfWord => (1, 1),
+                _ => unencodable(instr)
+            };
+            0x5200 | (h << 11) | (s << 10) | (rm() << 6) | (rn() << 3) | rd()
+        },
+        InstrType::LDRHSTRHImmPre => {
+            0x8000 | bit(instr.get_ldr_str().unwrap(), 11) | (((instr.get_imm().unwrap() as Word) >> 1) << 6) | (rn() << 3) | rd()
+        },
+        // PUSH/POP and LDMIA/STMIA Rn!
+        InstrType::LDMSTM => {
+            let reg_list = instr.get_reg_list().unwrap();
+            let l = instr.get_ldr_str().unwrap();
+            match instr.get_rn().unwrap() {
+                Register::r13 => 0xb400 | bit(l, 11) | (((reg_list >> if l { 15 } else { 14 }) & 1) << 8) | (reg_list & 0xff),
+                _ => 0xc000 | bit(l, 11) | (rn() << 8) | (reg_list & 0xff)
+            }
+        },
+        // the unconditional branch has the larger range
+        InstrType::B => {
+            let offset = instr.get_offset().unwrap() as Word >> 1;
+            match instr.get_condition() {
+                Condition::AL => 0xe000 | (offset & 0x7ff),
+                condition => 0xd000 | ((condition as Word) << 8) | (offset & 0xff)
+            }
+        },
+        InstrType::ThumbBLPrefix => 0xf000 | ((instr.get_offset().unwrap() as Word >> 12) & 0x7ff),
+        InstrType::ThumbBLSuffix => 0xf800 | ((instr.get_offset().unwrap() as Word >> 1) & 0x7ff),
+        InstrType::ThumbBLXSuffix => 0xe800 | ((instr.get_offset().unwrap() as Word >> 1) & 0x7ff),
+        InstrType::SWI => 0xdf00 | (instr.get_swi().unwrap() & 0xff),
+        InstrType::Undefined => instr.get_raw().unwrap_or(THUMB_UNDEFINED),
+        _ => unencodable(instr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cpu::CPU, cpu_enum::Architecture, instruction::*};
+
+    use super::*;
+
+    // xorshift32, so the sweep is the same on every run
+    fn words(count: usize) -> impl Iterator<Item = Word> {
+        let mut state: Word = 0x2545f491;
+        (0..count).map(move |_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state
+        })
+    }
+
+    #[test]
+    fn test_encode_every_type() {
+        let mut cpu = CPU::default();
+        cpu.set_architecture(Architecture::ARMv5TE);
+
+        // one encoding of each ARM instruction type
+        let arm: [(Word, InstrType); 35] = [
+            (0xe0910142, InstrType::DataRegImm),       // adds r0, r1, r2, asr #2
+            (0xe1a00061, InstrType::DataRegImm),       // rrx r0, r1
+            (0x01b00351, InstrType::DataRegReg),       // movseq r0, r1, asr r3
+            (0xe3a02030, InstrType::DataImm),          // mov r2, #48
+            (0xe7910102, InstrType::LDRSTRShiftRegPre),  // ldr r0, [r1, r2, lsl #2]
+            (0xe6410062, InstrType::LDRSTRShiftRegPost), // strb r0, [r1], -r2, rrx
+            (0xe5910004, InstrType::LDRSTRImmPre),     // ldr r0, [r1, #4]
+            (0xe4c10001, InstrType::LDRSTRImmPost),    // strb r0, [r1], #1
+            (0xe1f100fe, InstrType::LDRHSTRHImmPre),   // ldrsh r0, [r1, #14]!
+            (0xe04100b2, InstrType::LDRHSTRHImmPost),  // strh r0, [r1], #-2
+            (0xe19100d2, InstrType::LDRHSTRHRegPre),   // ldrsb r0, [r1, r2]
+            (0xe09100b2, InstrType::LDRHSTRHRegPost),  // ldrh r0, [r1], r2
+            (0xe1e140d8, InstrType::LDRHSTRHImmPre),   // ldrd r4, r5, [r1, #8]!
+            (0xe8fd8001, InstrType::LDMSTM),           // ldm sp!, {r0, pc}^
+            (0xeafffffe, InstrType::B),                // b .
+            (0x1b000010, InstrType::B),                // blne .+72
+            (0xe12fff1e, InstrType::BX),               // bx lr
+            (0x112fff3e, InstrType::BX),               // blxne lr
+            (0xfb000004, InstrType::BLX),              // blx with the H bit set
+            (0xef000011, InstrType::SWI),              // swi 0x11
+            (0xe0310392, InstrType::MUL),              // mlas r1, r2, r3, r0
+            (0xe0f21394, InstrType::MULL),             // smlals r1, r2, r4, r3
+            (0x11443093, InstrType::SWP),              // swpneb r3, r3, [r4]
+            (0xe16f0f11, InstrType::CLZ),              // clz r0, r1
+            (0x01620051, InstrType::QADD),             // qdsubeq r0, r1, r2
+            (0xe16406e5, InstrType::SMULxy),           // smultt r4, r5, r6
+            (0xe12406e5, InstrType::SMULWy),           // smulwt r4, r5, r6
+            (0xe14103a2, InstrType::SMLALxy),          // smlaltb r0, r1, r2, r3
+            (0xf7d2f103, InstrType::PLD),              // pld [r2, r3, lsl #2]
+            (0x1e15ff30, InstrType::MCRMRC),           // mrcne p15, 0, r15, c5, c0, 1
+            (0xee2431c5, InstrType::CDP),              // cdp p1, 2, c3, c4, c5, 6
+            (0xece43204, InstrType::LDCSTC),           // stcl p2, c3, [r4], #16
+            (0xe1a00000, InstrType::NOP),              // mov r0, r0
+            (0xe7f000f0, InstrType::Undefined),
+            (0xe14f0000, InstrType::MRS),              // mrs r0, spsr
+        ];
+        for (instr_raw, instr_type) in arm {
+            let instr = cpu.decode(instr_raw);
+            assert_eq!(instr.get_type(), instr_type, "0x{:08x}", instr_raw);
+            assert_eq!(instr.encode(), instr_raw, "{}", instr);
+        }
+        assert_eq!(cpu.decode(0xe328f20f).encode(), 0xe328f20f); // msr cpsr_f, #0xf0000000
+        assert_eq!(cpu.decode(0xe169f001).encode(), 0xe169f001); // msr spsr_fc, r1
+
+        // and of each Thumb format, including the BL and BLX halves
+        let thumb: [(Word, InstrType); 9] = [
+            (0x5888, InstrType::LDRSTRRegPre),         // ldr r0, [r1, r2]
+            (0xf7ff, InstrType::ThumbBLPrefix),
+            (0xfffe, InstrType::ThumbBLSuffix),
+            (0xeffe, InstrType::ThumbBLXSuffix),
+            (0xd0fe, InstrType::B),                    // beq .
+            (0xe7fe, InstrType::B),                    // b .
+            (0x4798, InstrType::BX),                   // blx r3
+            (0xdf11, InstrType::SWI),                  // swi 0x11
+            (0xde01, InstrType::Undefined),
+        ];
+        for (instr_raw, instr_type) in thumb {
+            let instr = cpu.decode_thumb(instr_raw);
+            assert_eq!(instr.get_type(), instr_type, "0x{:04x}", instr_raw);
+            assert_eq!(instr.encode(), instr_raw, "{}", instr);
+        }
+    }
+
+    #[test]
+    fn test_encode_built() {
+        // ROR #0 is decoded as RRX and RRX is encoded as ROR #0
+        let instr = instr_data_reg_imm(0b1110, DataOpcode::MOV as Word, 0, 0, 0, 0, ShiftType::ROR as Word, 1);
+        assert_eq!(instr.get_shift_type(), Some(ShiftType::RRX));
+        assert_eq!(instr.encode(), 0xe1a00061);
+
+        let mut instr = Instruction::new(InstrType::DataRegImm);
+        instr.set_condition(Condition::NE as Word);
+        instr.set_data_opcode(DataOpcode::ADD as Word);
+        instr.set_s_bit(1);
+        instr.set_rn(2);
+        instr.set_rd(1);
+        instr.set_imm_shift(0);
+        instr.set_shift_type(ShiftType::RRX as Word);
+        instr.set_rm(3);
+        assert_eq!(instr.encode(), 0x10921063); // addsne r1, r2, r3, rrx
+
+        // the ARM decoder reads an unshifted register offset as LSL #0
+        assert_eq!(instr_ldrstr_reg_post(0b1110, 0, 0, 0, 1, 1, 0, 2).encode(), 0xe6110002); // ldr r0, [r1], -r2
+        assert_eq!(instr_nop().encode(), 0xe1a00000);
+        assert_eq!(instr_undefined().encode(), ARM_UNDEFINED);
+        assert_eq!(thumb_instr_undefined().encode(), THUMB_UNDEFINED);
+        assert_eq!(instr_mull(0b1110, 0, 1, 0, 1, 2, 3, 4).encode(), 0xe0a12394); // umlal r2, r1, r4, r3
+        assert_eq!(instr_blx_imm(0, 0xfffffe).encode(), 0xfafffffe);
+        assert_eq!(thumb_instr_bl(0, 0x7ff).encode(), 0xf7ff);
+        assert_eq!(thumb_instr_push_pop(0, 1, 0x0f).encode(), 0xb50f); // push {r0-r3, lr}
+        assert_eq!(thumb_instr_add_pc_sp(1, 2, 4).encode(), 0xaa04); // add r2, sp, #16
+    }
+
+    #[test]
+    fn test_encode_round_trip_arm() {
+        // every allocated field is stored and the rest decode as undefined with the raw word
+        for architecture in [Architecture::ARMv4T, Architecture::ARMv5TE] {
+            let mut cpu = CPU::default();
+            cpu.set_architecture(architecture);
+            for instr_raw in words(200000) {
+                let instr = cpu.decode(instr_raw);
+                assert_eq!(instr.encode(), instr_raw, "{:?} {} ({:?})", instr.get_type(), instr, architecture);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_round_trip_thumb() {
+        for architecture in [Architecture::ARMv4T, Architecture::ARMv5TE] {
+            let mut cpu = CPU::default();
+            cpu.set_architecture(architecture);
+            for instr_raw in 0..=0xffff {
+                let instr = cpu.decode_thumb(instr_raw);
+                let encoded = instr.encode();
+                // the shared ARM equivalents and the bits BX ignores decode the same either way
+                let shared = (instr_raw & 0xfc00 == 0x1c00 && instr_raw & 0b111 == (instr_raw >> 3) & 0b111)
+                    || (instr_raw & 0xffc0 == 0x4500)
+                    || (instr_raw & 0xff00 == 0x4700);
+                if shared {
+                    assert_eq!(cpu.decode_thumb(encoded).to_string(), instr.to_string(), "0x{:04x}", instr_raw);
+                } else {
+                    assert_eq!(encoded, instr_raw, "{:?} {} ({:?})", instr.get_type(), instr, architecture);
+                }
+            }
+        }
+    }
+}
@@ -1,672 +1,897 @@
-use log::trace;
-use tokio::sync::MutexGuard;
-
-use crate::{memory::{Word, Registers, RAM, Byte, Memory, Register, HalfWord, KEYBOARD_ADDR, DISPLAY_ADDR, SignedWord}, instruction::{Instruction, TInstruction}, cpu_enum::{DataOpcode, LDMCode, InstrExecuteCondition::{NOP, HLT, SWI, self}, LSH}, util};
-
-// this method matches all the data operations with their appropriate operation
-// the caller is expected to resolve the operand2 ahead of time; this function
-// does the minimal amount of computation possible while modifying the app state
-fn data_match_opcode(registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction, rn: Word, shifter_operand: Word, shifter_carry_out: Word) {
-    // temp values so register_lock doesn't have to be moved
-    let c_flag = registers_lock.get_c_flag();
-
-    let rd: Option<Word> = match instr.get_data_opcode().unwrap() {
-        DataOpcode::AND => {
-            let rd = rn & shifter_operand;
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(util::word_lsb_to_bool(shifter_carry_out));
-            }
-            Some(rd)
-        },
-        DataOpcode::EOR => {
-            let rd = rn ^ shifter_operand;
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(util::word_lsb_to_bool(shifter_carry_out));
-            }
-            Some(rd)
-        },
-        DataOpcode::SUB => {
-            let (rd, overflow) = rn.overflowing_sub(shifter_operand);
-            
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(!(shifter_operand > rn)); // borrows if shifter_operand > rn
-                registers_lock.set_z_flag(overflow);
-            }
-            Some(rd)
-        },
-        DataOpcode::RSB => {
-            let (rd, overflow) = shifter_operand.overflowing_sub(rn);
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(!(rn > shifter_operand));
-                registers_lock.set_v_flag(overflow);
-            }
-            Some(rd)
-        },
-        DataOpcode::ADD => {
-            let (rd, overflow) = rn.overflowing_add(shifter_operand);
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(shifter_operand > rn);
-                registers_lock.set_v_flag(overflow);
-            }
-            Some(rd)
-        },
-        DataOpcode::ADC => {
-            let (rd, overflow) = rn.overflowing_add(shifter_operand + if c_flag { 1 } else { 0 });
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(rn > shifter_operand + if c_flag { 1 } else { 0 });
-                registers_lock.set_v_flag(overflow);
-            }
-            Some(rd)
-        },
-        DataOpcode::SBC => {
-            let (rd, overflow) = rn.overflowing_sub(shifter_operand - (if !c_flag { 1 } else { 0 }));
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(!((shifter_operand + if !c_flag { 1 } else { 0 }) > rn));
-                registers_lock.set_v_flag(overflow);
-            }
-            Some(rd)
-        },
-        DataOpcode::RSC => {
-            let (rd, overflow) = shifter_operand.overflowing_sub(rn - (if !c_flag { 1 } else { 0 }));
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(!((rn + if !c_flag { 1 } else { 0 })) > shifter_operand);
-                registers_lock.set_v_flag(overflow);
-            }
-            Some(rd)
-        },
-        DataOpcode::ORR => {
-            let rd = rn | shifter_operand;
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(util::word_lsb_to_bool(shifter_carry_out));
-            }
-            Some(rd)
-        },
-        DataOpcode::MOV => {
-            let rd = shifter_operand;
-            
-            if instr.get_s_bit().unwrap() && instr.get_rd().unwrap() == Register::r15 {
-                if registers_lock.current_mode_has_spsr() {
-                    let spsr: Word = registers_lock.get_spsr();
-                    registers_lock.set_cpsr(spsr);
-                }
-            } else if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(util::word_lsb_to_bool(shifter_carry_out));
-            }
-            Some(rd)
-        },
-        DataOpcode::BIC => {
-            let rd = rn & !shifter_operand;
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(util::word_lsb_to_bool(shifter_carry_out));
-            }
-            Some(rd)
-        },
-        DataOpcode::MVN => {
-            let rd = !shifter_operand;
-
-            if instr.get_s_bit().unwrap() {
-                registers_lock.set_c_flag(util::word_lsb_to_bool(shifter_carry_out));
-            }
-            Some(rd)
-        },
-        _ => None
-    };
-
-    if rd.is_some() {
-        if instr.get_s_bit().unwrap() {
-            registers_lock.set_n_flag(util::test_bit(rd.unwrap(), 31));
-            registers_lock.set_z_flag(rd.unwrap() == 0);
-        }
-        registers_lock.set_reg_register(instr.get_rd().unwrap(), rd.unwrap());
-    } else {
-        match instr.get_data_opcode().unwrap() {
-            DataOpcode::TST => {
-                let alu_out = rn & shifter_operand;
-                registers_lock.set_n_flag(util::test_bit(alu_out, 31));
-                registers_lock.set_z_flag(alu_out == 0);
-                registers_lock.set_c_flag(util::word_lsb_to_bool(shifter_carry_out));
-            },
-            DataOpcode::TEQ => {
-                let alu_out = rn ^ shifter_operand;
-                registers_lock.set_n_flag(util::test_bit(alu_out, 31));
-                registers_lock.set_z_flag(alu_out == 0);
-                registers_lock.set_c_flag(util::word_lsb_to_bool(shifter_carry_out));
-            },
-            DataOpcode::CMP => {
-                // needs to be signed so that overflow is properly calculated
-                let (alu_out, overflow) = (rn as SignedWord).overflowing_sub(shifter_operand as SignedWord);
-                registers_lock.set_n_flag(util::test_bit(alu_out as Word, 31));
-                registers_lock.set_z_flag(alu_out == 0);
-                registers_lock.set_c_flag(!(shifter_operand > rn)); // borrows if shifter_operand > rn
-                registers_lock.set_v_flag(overflow);
-            },
-            DataOpcode::CMN => {
-                let (alu_out, overflow) = (rn as SignedWord).overflowing_add(shifter_operand as SignedWord);
-                registers_lock.set_n_flag(util::test_bit(alu_out as Word, 31));
-                registers_lock.set_z_flag(alu_out == 0);
-                registers_lock.set_c_flag(shifter_operand > rn);
-                registers_lock.set_v_flag(overflow);
-            },
-            _ => ()
-        }
-    }
-}
-
-fn ldr_str_pre(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction, offset: Word) {
-    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
-    let rd = registers_lock.get_reg_register(instr.get_rd().unwrap());
-
-    let address = match instr.get_add_sub().unwrap() {
-        true  /* add */ => rn + offset,
-        false /* sub */ => rn - offset
-    };
-
-    match instr.get_ldr_str().unwrap() {
-        true  /* ldr */ => {
-            let data: Word = match address {
-                // map to keyboard hardware event if needed
-                // cpu injects character into instruction
-                KEYBOARD_ADDR => instr.get_last_char().unwrap() as Word,
-                _ => {
-                    match instr.get_byte_word().unwrap() {
-                        true  => ram_lock.read_byte(address) as Word,
-                        false => ram_lock.read_word(address)
-                    }
-                }
-            };
-            registers_lock.set_reg_register(instr.get_rd().unwrap(), data);
-        },
-        false /* str */ => {
-            match address {
-                // if hardware display event, CPU will handle it
-                DISPLAY_ADDR => (),
-                _ => match instr.get_byte_word().unwrap() {
-                    true  => ram_lock.write_byte(address, rd as Byte),
-                    false => ram_lock.write_word(address, rd)
-                }
-            };
-        }
-    }
-    
-    if instr.get_writeback().unwrap() {
-        registers_lock.set_reg_register(instr.get_rn().unwrap(), address);
-    }
-}
-
-fn ldr_str_post(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction, offset: Word) {
-    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
-    let rd = registers_lock.get_reg_register(instr.get_rd().unwrap());
-
-    let address = rn;
-
-    match instr.get_ldr_str().unwrap() {
-        true  /* ldr */ => {
-            let data: Word = match address {
-                // map to keyboard hardware event if needed
-                // cpu injects character into instruction
-                KEYBOARD_ADDR => instr.get_last_char().unwrap() as Word,
-                _ => {
-                    match instr.get_byte_word().unwrap() {
-                        true  => ram_lock.read_byte(address) as Word,
-                        false => ram_lock.read_word(address)
-                    }
-                }
-            };
-            registers_lock.set_reg_register(instr.get_rd().unwrap(), data);
-        },
-        false /* str */ => {
-            match address {
-                // if hardware display event, CPU will handle it
-                DISPLAY_ADDR => (),
-                _ => match instr.get_byte_word().unwrap() {
-                    true  => ram_lock.write_byte(address, rd as Byte),
-                    false => ram_lock.write_word(address, rd)
-                }
-            };
-        }
-    }
-
-    // writeback
-    registers_lock.set_reg_register(
-        instr.get_rn().unwrap(),
-        match instr.get_add_sub().unwrap() {
-            true  /* add */ => rn + offset,
-            false /* sub */ => rn - offset
-        }
-    );
-}
-
-fn ldrh_strh_pre(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction, offset: Word) {
-    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
-    let rd = registers_lock.get_reg_register(instr.get_rd().unwrap());
-    
-    let address = match instr.get_add_sub().unwrap() {
-        true  /* add */ => rn + offset,
-        false /* sub */ => rn - offset
-    };
-    
-    match instr.get_ldr_str().unwrap() {
-        true  /* ldr */ => {
-            let data: Word = match address {
-                // map to keyboard hardware event if needed
-                // cpu injects character into instruction
-                KEYBOARD_ADDR => instr.get_last_char().unwrap() as Word,
-                _ => {
-                    match instr.get_lsh().unwrap() {
-                        LSH::LdrUHalfWord => {
-                            ram_lock.read_half_word(address) as Word
-                        },
-                        LSH::LdrSByte => {
-                            (ram_lock.read_byte(address) as i8) as Word
-                        },
-                        LSH::LdrSHalfWord => {
-                            (ram_lock.read_half_word(address) as i16) as Word
-                        },
-                        _ => panic!("{}", instr.get_lsh().unwrap().to_string())
-                    }
-                }
-            };
-            registers_lock.set_reg_register(instr.get_rd().unwrap(), data);
-        },
-        false /* str */ => {
-            match address {
-                // if hardware display event, CPU will handle it
-                DISPLAY_ADDR => (),
-                _ => {
-                    match instr.get_lsh().unwrap() {
-                        LSH::StrHalfWord => ram_lock.write_half_word(address, rd as HalfWord),
-                        LSH::LdrDoubleWord => (), // NOT IMPLEMENTED
-                        LSH::StrDoubleWord => (), // NOT IMPLEMENTED
-                        _ => panic!("{}", instr.get_lsh().unwrap().to_string())
-                    };
-                }
-            };
-        }
-    }
-    
-    if instr.get_writeback().unwrap() {
-        registers_lock.set_reg_register(instr.get_rn().unwrap(), address);
-    }
-}
-
-fn ldrh_strh_post(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction, offset: Word) {
-    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
-    let rd = registers_lock.get_reg_register(instr.get_rd().unwrap());
-
-    let address = rn;
-
-    match instr.get_ldr_str().unwrap() {
-        true  /* ldr */ => {
-            let data = match address {
-                // map to keyboard hardware event if needed
-                // cpu injects character into instruction
-                KEYBOARD_ADDR => instr.get_last_char().unwrap() as Word,
-                _ => {
-                    match instr.get_lsh().unwrap() {
-                        LSH::LdrUHalfWord => {
-                            ram_lock.read_half_word(address) as Word
-                        },
-                        LSH::LdrSByte => {
-                            (ram_lock.read_byte(address) as i8) as Word
-                        },
-                        LSH::LdrSHalfWord => {
-                            (ram_lock.read_half_word(address) as i16) as Word
-                        },
-                        _ => panic!("")
-                    }
-                }
-            };
-            registers_lock.set_reg_register(instr.get_rd().unwrap(), data);
-        },
-        false /* str */ => {
-            match address {
-                // if hardware display event, CPU will handle it
-                DISPLAY_ADDR => (),
-                _ => {
-                    match instr.get_lsh().unwrap() {
-                        LSH::StrHalfWord => ram_lock.write_half_word(address, rd as HalfWord),
-                        LSH::LdrDoubleWord => (), // NOT IMPLEMENTED
-                        LSH::StrDoubleWord => (), // NOT IMPLEMENTED
-                        _ => ()
-                    };
-                }
-            }
-        }
-    }
-
-    // writeback
-    registers_lock.set_reg_register(
-        instr.get_rn().unwrap(),
-        match instr.get_add_sub().unwrap() {
-            true  /* add */ => rn + offset,
-            false /* sub */ => rn - offset
-        }
-    );
-}
-
-fn ldm(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction, start_address: Word) -> Word {
-    let mut address = start_address;
-    
-    for ri in 0..=14 {
-        if (instr.get_reg_list().unwrap() >> ri) & 0x1 == 1 {
-            registers_lock.set_register(ri, ram_lock.read_word(address));
-            address += 4;
-        }
-    }
-
-    // if bit 15 n the register list is set
-    if instr.get_reg_list().unwrap() >> 15 & 0x1 == 1 {
-        let value = ram_lock.read_word(address);
-        registers_lock.set_pc(value & 0xFFFFFFFC);
-        address += 4; 
-    }
-
-    address
-}
-
-fn stm(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction, start_address: Word) -> Word {
-    let mut address = start_address;
-
-    for ri in 0..=15 {
-        if (instr.get_reg_list().unwrap() >> ri) & 0x1 == 1 {
-            ram_lock.write_word(address, registers_lock.get_register(ri));
-            address += 4;
-        }
-    }
-
-    address
-}
-
-// each method accesses/modifies app state as necessary;
-// however, this renders the code difficult to test with the current Tauri APIs
-// since the Tauri State API cannot be mocked (yet)
-
-// each of these methods are mapped to the Instruction::execute method
-// and called from CPU::execute
-
-pub fn instr_data_reg_imm(_ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
-    let (shifter_operand, shifter_carry_out) = Instruction::shift_value_by_imm(
-        registers_lock.get_reg_register(instr.get_rm().unwrap()),
-        instr.get_imm_shift().unwrap(),
-        instr.get_shift_type().unwrap(),
-        registers_lock.get_c_flag() as Byte);
-
-    data_match_opcode(registers_lock, instr, rn, shifter_operand, shifter_carry_out);
-
-    NOP
-}
-
-pub fn instr_data_reg_reg(_ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
-    let (shifter_operand, shifter_carry_out) = Instruction::shift_value_by_reg(
-        registers_lock.get_reg_register(instr.get_rm().unwrap()),
-        registers_lock.get_reg_register(instr.get_rs().unwrap()), // grab the LSB
-        instr.get_shift_type().unwrap(),
-        registers_lock.get_c_flag() as Byte);
-
-    data_match_opcode(registers_lock, instr, rn, shifter_operand, shifter_carry_out);
-
-    NOP
-}
-
-pub fn instr_data_imm(_ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rn =  registers_lock.get_reg_register(instr.get_rn().unwrap());
-    let (shifter_operand, shifter_carry_out) = Instruction::rotate_value(
-        instr.get_rotate().unwrap(),
-        instr.get_imm().unwrap(),
-        registers_lock.get_c_flag() as Byte);
-
-    data_match_opcode(registers_lock, instr, rn, shifter_operand, shifter_carry_out);
-
-    NOP
-}
-
-// p.463
-pub fn instr_ldrstr_shifted_reg_pre(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let index = Instruction::shift_value_by_imm(
-        registers_lock.get_reg_register(instr.get_rm().unwrap()),
-        instr.get_imm_shift().unwrap(),
-        instr.get_shift_type().unwrap(),
-        registers_lock.get_c_flag() as Byte).0;
-
-    ldr_str_pre(ram_lock, registers_lock, instr, index);
-
-    NOP
-}
-
-pub fn instr_ldrstr_shifted_reg_post(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let index = Instruction::shift_value_by_imm(
-        registers_lock.get_reg_register(instr.get_rm().unwrap()),
-        instr.get_imm_shift().unwrap(),
-        instr.get_shift_type().unwrap(),
-        registers_lock.get_c_flag() as Byte).0;
-
-    ldr_str_post(ram_lock, registers_lock, instr, index);
-
-    NOP
-}
-
-pub fn instr_ldrstr_reg_pre(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
-    ldr_str_post(ram_lock, registers_lock, instr, rm);
-    
-    NOP
-}
-
-pub fn instr_ldrstr_reg_post(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
-    ldr_str_post(ram_lock, registers_lock, instr, rm);
-
-    NOP
-}
-
-pub fn instr_ldrstr_imm_pre(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let offset_12 = instr.get_imm_shift().unwrap();
-
-    ldr_str_pre(ram_lock, registers_lock, instr, offset_12);
-
-    NOP
-}
-
-pub fn instr_ldrstr_imm_post(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let offset_12 = instr.get_imm_shift().unwrap();
-
-    ldr_str_post(ram_lock, registers_lock, instr, offset_12);
-
-    NOP
-}
-
-pub fn instr_ldrhstrh_imm_pre(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let offset_8 = instr.get_imm().unwrap() as Word;
-
-    ldrh_strh_pre(ram_lock, registers_lock, instr, offset_8);
-
-    NOP
-}
-
-pub fn instr_ldrhstrh_imm_post(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let offset_8 = instr.get_imm().unwrap() as Word;
-
-    ldrh_strh_post(ram_lock, registers_lock, instr, offset_8);
-
-    NOP
-}
-
-pub fn instr_ldrhstrh_reg_pre(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
-
-    ldrh_strh_pre(ram_lock, registers_lock, instr, rm);
-
-    NOP
-}
-
-pub fn instr_ldrhstrh_reg_post(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
-
-    ldrh_strh_post(ram_lock, registers_lock, instr, rm);
-
-    NOP
-}
-
-pub fn instr_b(_ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    if instr.get_l_bit().unwrap() {
-        let address_after_branch = registers_lock.get_pc_current_address() + 4;
-        registers_lock.set_reg_register(Register::r14, address_after_branch);
-    }
-
-    // increment by an extra 4 bytes because:
-    // 1. CPU fetch
-    // 2. CPU decode
-    // 3. CPU execute branch instruction
-    //   - go to target address
-    // 4. CPU increments PC by 4
-    // -> CPU only increments 1 instruction ahead
-    // => need to increment by extra 4 bytes here to ensure CPU PC is two instructions ahead
-    let target_address: Word = (instr.get_pc_address() as SignedWord + instr.get_offset().unwrap() + 4) as Word;
-    trace!("instr_b: {}pc {}offset {}target", instr.get_pc_address(), instr.get_offset().unwrap(), target_address);
-    registers_lock.set_pc(target_address);
-
-    NOP
-}
-
-pub fn instr_bx(_ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap()) + 4;
-
-    registers_lock.set_t_flag(util::word_lsb_to_bool(rm));
-    registers_lock.set_pc(rm & 0xFFFFFFFE);
-
-    NOP
-}
-
-// p.187
-pub fn instr_ldmstm(ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
-    let number_of_set_bits_in_reg_list = instr.get_reg_list().unwrap().count_ones();
-
-    // A5.4.2
-    let start_address = match instr.get_ldm().unwrap() {
-        LDMCode::DecAfter => rn - (number_of_set_bits_in_reg_list * 4) + 4,
-        LDMCode::IncAfter => rn,
-        LDMCode::DecBefore => rn - (number_of_set_bits_in_reg_list * 4),
-        LDMCode::IncBefore => rn + 4,
-    };
-    let end_address = match instr.get_ldm().unwrap() {
-        LDMCode::DecAfter => rn,
-        LDMCode::IncAfter => rn + (number_of_set_bits_in_reg_list * 4) - 4,
-        LDMCode::DecBefore => rn - 4,
-        LDMCode::IncBefore => rn + (number_of_set_bits_in_reg_list * 4),
-    };
-
-    if instr.get_writeback().unwrap() {
-        let value = match instr.get_ldm().unwrap() {
-            LDMCode::DecAfter => rn - (number_of_set_bits_in_reg_list * 4),
-            LDMCode::IncAfter => rn + (number_of_set_bits_in_reg_list * 4),
-            LDMCode::DecBefore => rn - (number_of_set_bits_in_reg_list * 4),
-            LDMCode::IncBefore => rn + (number_of_set_bits_in_reg_list * 4),
-        };
-        registers_lock.set_reg_register(instr.get_rn().unwrap(), value);
-    }
-
-    let address = match instr.get_ldr_str().unwrap() {
-        true => ldm(ram_lock, registers_lock, instr, start_address),
-        false => stm(ram_lock, registers_lock, instr, start_address),
-    };
-
-    assert_eq!(end_address, address - 4);
-
-    NOP
-}
-
-pub fn instr_mul(_ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let rs = registers_lock.get_reg_register(instr.get_rs().unwrap());
-    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
-
-    registers_lock.set_reg_register(instr.get_rd().unwrap(), (rm as u64 * rs as u64) as Word);
-
-    if instr.get_s_bit().unwrap() {
-        let rd = registers_lock.get_reg_register(instr.get_rd().unwrap());
-        registers_lock.set_n_flag(rd >> 31 != 0);
-        registers_lock.set_z_flag(rd == 0);
-    }
-
-    NOP
-}
-
-// p.58, 360
-pub fn instr_swi(_ram_lock: &mut MutexGuard<'_, RAM>, _registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    // actual SWI processing is done post-execute by processor in CPU::step
-
-    if instr.get_swi().unwrap() == 0x11 {
-        return HLT
-    // these conditions need to be handled by the outer CPU::step context to access the app thread
-    } else if instr.get_swi().unwrap() == 0x0 || instr.get_swi().unwrap() == 0x6a {
-        return SWI
-    }
-
-    NOP
-}
-
-pub fn instr_mrs(_ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    if instr.get_gpregister().unwrap() {
-        let spsr = registers_lock.get_spsr();
-        registers_lock.set_reg_register(instr.get_rd().unwrap(), spsr);
-    } else {
-        let cpsr = registers_lock.get_cpsr();
-        registers_lock.set_reg_register(instr.get_rd().unwrap(), cpsr);
-    }
-
-    NOP
-}
-
-fn instr_msr(registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction, operand: Word) {
-    // const UNALLOC_MASK: Word = 0x06F0FC00;
-    const USER_MASK: Word    = 0xF80F0200;
-    const PRIV_MASK: Word    = 0x000001DF;
-    const STATE_MASK: Word   = 0x01000020;
-
-    let cpsr = registers_lock.get_cpsr();
-    let spsr = registers_lock.get_spsr();
-    let mask ;
-
-    let field_mask = instr.get_field_mask().unwrap() as Word;
-    let byte_mask: Word = (if util::test_bit(field_mask, 0) { 0x000000FF } else { 0x0 }) |
-                          (if util::test_bit(field_mask, 1) { 0x0000FF00 } else { 0x0 }) |
-                          (if util::test_bit(field_mask, 2) { 0x00FF0000 } else { 0x0 }) |
-                          (if util::test_bit(field_mask, 3) { 0xFF000000 } else { 0x0 });
-    if !instr.get_gpregister().unwrap() {
-        // assumed privaleged mode since working with SYS rather than USER mode
-        mask = byte_mask & (USER_MASK | PRIV_MASK);
-        registers_lock.set_cpsr((cpsr & !mask) | (operand & mask));
-    } else {
-        if registers_lock.current_mode_has_spsr() {
-            mask = byte_mask & (USER_MASK | PRIV_MASK | STATE_MASK);
-            registers_lock.set_spsr((spsr & !mask) | (operand & mask));
-        }
-    }
-}
-
-pub fn instr_msr_imm(_ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let operand = instr.get_imm().unwrap().rotate_right((instr.get_rotate().unwrap() * 2) as Word) as Word;
-    instr_msr(registers_lock, instr, operand);
-    NOP
-}
-
-pub fn instr_msr_reg(_ram_lock: &mut MutexGuard<'_, RAM>, registers_lock: &mut MutexGuard<'_, Registers>, instr: Instruction) -> InstrExecuteCondition {
-    let operand = registers_lock.get_reg_register(instr.get_rm().unwrap());
-    instr_msr(registers_lock, instr, operand);
-    NOP
-}
-
-pub fn instr_nop(_ram_lock: &mut MutexGuard<'_, RAM>, _registers_lock: &mut MutexGuard<'_, Registers>, _instr: Instruction) -> InstrExecuteCondition {
-    NOP
-}
\ No newline at end of file
+use log::trace;
+
+use crate::{alu, memory::{Word, Registers, RAM, Byte, Register, HalfWord, SignedWord, MemoryError, Memory, AlignmentPolicy}, instruction::{Instruction, TInstruction}, cpu_enum::{LDMCode, Mode, InstrExecuteCondition::{NOP, HLT, SWI, UND, DABT, self}, LSH}, util};
+
+// this method matches all the data operations with their appropriate operation
+// the caller is expected to resolve the operand2 ahead of time; the result and
+// the flags come from the ALU, this function only writes them back
+fn data_match_opcode(registers_lock: &mut Registers, instr: Instruction, rn: Word, shifter_operand: Word, shifter_carry_out: Word) {
+    let opcode = instr.get_data_opcode().unwrap();
+    let out = alu::execute(opcode, rn, shifter_operand, util::word_lsb_to_bool(shifter_carry_out),
+        registers_lock.get_c_flag(), registers_lock.get_v_flag());
+    let s_bit = instr.get_s_bit().unwrap();
+    let comparison = alu::is_comparison(opcode);
+
+    if s_bit && !comparison && instr.get_rd().unwrap() == Register::r15 {
+        // e.g. movs pc, lr or subs pc, lr, #4 return from an exception
+        restore_cpsr(registers_lock);
+    } else if s_bit || comparison {
+        registers_lock.set_n_flag(out.n);
+        registers_lock.set_z_flag(out.z);
+        registers_lock.set_c_flag(out.c);
+        registers_lock.set_v_flag(out.v);
+    }
+
+    if !comparison {
+        set_destination(registers_lock, instr.get_rd().unwrap(), out.result);
+    }
+}
+
+// an exception return copies the SPSR of the current mode to the CPSR; modes without an SPSR keep their CPSR
+// an SPSR that was never written (e.g. SPSR_svc after reset) holds no valid mode, so the current mode is kept
+fn restore_cpsr(registers_lock: &mut Registers) {
+    if registers_lock.current_mode_has_spsr() {
+        let spsr: Word = registers_lock.get_spsr();
+        let mode: Option<Mode> = num::FromPrimitive::from_u32(spsr & 0b11111);
+        let cpsr = match mode {
+            Some(_) => spsr,
+            None => (spsr & !0b11111) | (registers_lock.get_cpsr() & 0b11111)
+        };
+        registers_lock.set_cpsr(cpsr);
+    }
+}
+
+// branches to address in the current state; as with instr_b, one instruction is added
+//  so that the CPU increment leaves the PC two instructions ahead of the target
+fn set_pc_target(registers_lock: &mut Registers, address: Word) {
+    let size = registers_lock.get_instr_size();
+    let mask = if registers_lock.get_t_flag() { 0xFFFFFFFE } else { 0xFFFFFFFC };
+    registers_lock.set_pc((address & mask).wrapping_add(size));
+}
+
+// a result written to r15 is a branch
+fn set_destination(registers_lock: &mut Registers, rd: Register, value: Word) {
+    match rd {
+        Register::r15 => set_pc_target(registers_lock, value),
+        _ => registers_lock.set_reg_register(rd, value)
+    }
+}
+
+// the PC is word-aligned when used as a base address; this only matters for the Thumb
+//  PC-relative LDR and ADD, since the ARM state PC is always word-aligned
+fn get_base_register(registers_lock: &mut Registers, rn: Register) -> Word {
+    match rn {
+        Register::r15 => registers_lock.get_pc() & !0b11,
+        _ => registers_lock.get_reg_register(rn)
+    }
+}
+
+// Rs is read in an extra cycle before the other operands, so with a register-specified
+//  shift the PC reads as the address of the instruction + 12
+fn get_reg_shift_operand(registers_lock: &mut Registers, r: Register) -> Word {
+    match r {
+        Register::r15 => registers_lock.get_pc().wrapping_add(4),
+        _ => registers_lock.get_reg_register(r)
+    }
+}
+
+fn ldr_str_pre(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction, offset: Word) -> Result<(), MemoryError> {
+    let rn = get_base_register(registers_lock, instr.get_rn().unwrap());
+    let rd = registers_lock.get_reg_register(instr.get_rd().unwrap());
+
+    let address = match instr.get_add_sub().unwrap() {
+        true  /* add */ => rn.wrapping_add(offset),
+        false /* sub */ => rn.wrapping_sub(offset)
+    };
+
+    match instr.get_ldr_str().unwrap() {
+        true  /* ldr */ => {
+            let data: Word = match instr.get_byte_word().unwrap() {
+                true  => ram_lock.load_byte(address)? as Word,
+                false => ram_lock.load_word(address)?
+            };
+            set_destination(registers_lock, instr.get_rd().unwrap(), data);
+        },
+        false /* str */ => {
+            match instr.get_byte_word().unwrap() {
+                true  => ram_lock.store_byte(address, rd as Byte)?,
+                false => ram_lock.store_word(address, rd)?
+            };
+        }
+    }
+    
+    if instr.get_writeback().unwrap() {
+        registers_lock.set_reg_register(instr.get_rn().unwrap(), address);
+    }
+
+    Ok(())
+}
+
+fn ldr_str_post(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction, offset: Word) -> Result<(), MemoryError> {
+    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
+    let rd = registers_lock.get_reg_register(instr.get_rd().unwrap());
+
+    let address = rn;
+
+    match instr.get_ldr_str().unwrap() {
+        true  /* ldr */ => {
+            let data: Word = match instr.get_byte_word().unwrap() {
+                true  => ram_lock.load_byte(address)? as Word,
+                false => ram_lock.load_word(address)?
+            };
+            set_destination(registers_lock, instr.get_rd().unwrap(), data);
+        },
+        false /* str */ => {
+            match instr.get_byte_word().unwrap() {
+                true  => ram_lock.store_byte(address, rd as Byte)?,
+                false => ram_lock.store_word(address, rd)?
+            };
+        }
+    }
+
+    // writeback
+    registers_lock.set_reg_register(
+        instr.get_rn().unwrap(),
+        match instr.get_add_sub().unwrap() {
+            true  /* add */ => rn.wrapping_add(offset),
+            false /* sub */ => rn.wrapping_sub(offset)
+        }
+    );
+
+    Ok(())
+}
+
+// ARM7TDMI: under the rotate policy a halfword load from an odd address returns the aligned
+//  halfword rotated right by a byte, and a signed halfword load returns the sign-extended addressed byte
+fn load_half_word(ram_lock: &mut RAM, address: Word, signed: bool) -> Result<Word, MemoryError> {
+    if address % 2 == 1 && ram_lock.get_alignment_policy() == AlignmentPolicy::Rotate {
+        return match signed {
+            true  => Ok((ram_lock.load_byte(address)? as i8) as Word),
+            false => Ok((ram_lock.load_half_word(address)? as Word).rotate_right(8))
+        }
+    }
+
+    match signed {
+        true  => Ok((ram_lock.load_half_word(address)? as i16) as Word),
+        false => Ok(ram_lock.load_half_word(address)? as Word)
+    }
+}
+
+// LDRD/STRD move the register pair rd, rd + 1; the decoder rejects odd and r14 pairs
+fn ldrh_strh_transfer(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction, address: Word) -> Result<(), MemoryError> {
+    let rd = instr.get_rd().unwrap() as usize;
+
+    match instr.get_lsh().unwrap() {
+        LSH::LdrUHalfWord => {
+            let data = load_half_word(ram_lock, address, false)?;
+            registers_lock.set_register(rd, data);
+        },
+        LSH::LdrSByte => {
+            let data = (ram_lock.load_byte(address)? as i8) as Word;
+            registers_lock.set_register(rd, data);
+        },
+        LSH::LdrSHalfWord => {
+            let data = load_half_word(ram_lock, address, true)?;
+            registers_lock.set_register(rd, data);
+        },
+        LSH::StrHalfWord => {
+            let data = registers_lock.get_register(rd);
+            ram_lock.store_half_word(address, data as HalfWord)?;
+        },
+        LSH::LdrDoubleWord => {
+            // both words are read before either register is written so an abort leaves the pair intact
+            let low = ram_lock.load_word(address)?;
+            let high = ram_lock.load_word(address.wrapping_add(4))?;
+            registers_lock.set_register(rd, low);
+            registers_lock.set_register(rd + 1, high);
+        },
+        LSH::StrDoubleWord => {
+            let low = registers_lock.get_register(rd);
+            let high = registers_lock.get_register(rd + 1);
+            ram_lock.store_word(address, low)?;
+            ram_lock.store_word(address.wrapping_add(4), high)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn ldrh_strh_pre(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction, offset: Word) -> Result<(), MemoryError> {
+    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
+
+    let address = match instr.get_add_sub().unwrap() {
+        true  /* add */ => rn.wrapping_add(offset),
+        false /* sub */ => rn.wrapping_sub(offset)
+    };
+
+    ldrh_strh_transfer(ram_lock, registers_lock, instr, address)?;
+
+    if instr.get_writeback().unwrap() {
+        registers_lock.set_reg_register(instr.get_rn().unwrap(), address);
+    }
+
+    Ok(())
+}
+
+fn ldrh_strh_post(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction, offset: Word) -> Result<(), MemoryError> {
+    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
+
+    ldrh_strh_transfer(ram_lock, registers_lock, instr, rn)?;
+
+    // writeback
+    registers_lock.set_reg_register(
+        instr.get_rn().unwrap(),
+        match instr.get_add_sub().unwrap() {
+            true  /* add */ => rn.wrapping_add(offset),
+            false /* sub */ => rn.wrapping_sub(offset)
+        }
+    );
+
+    Ok(())
+}
+
+// with user_bank (LDM(2)/STM(2), the S bit without r15 loaded) r8-r14 are the User mode registers
+fn ldm(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction, reg_list: Word, start_address: Word, user_bank: bool) -> Result<Word, MemoryError> {
+    let mut address = start_address;
+
+    for ri in 0..=14 {
+        if (reg_list >> ri) & 0x1 == 1 {
+            let value = ram_lock.load_word(address)?;
+            match user_bank {
+                true  => registers_lock.set_user_register(ri, value),
+                false => registers_lock.set_register(ri, value)
+            }
+            address = address.wrapping_add(4);
+        }
+    }
+
+    // r15 is loaded last, so a fault on any of the loads leaves the PC and CPSR alone
+    if (reg_list >> 15) & 0x1 == 1 {
+        let value = ram_lock.load_word(address)?;
+        // LDM(3): the exception return restores the CPSR, which may switch back to Thumb state;
+        //  otherwise the state is kept, as in Thumb POP {pc}
+        if instr.get_s_bit().unwrap() {
+            restore_cpsr(registers_lock);
+        }
+        set_pc_target(registers_lock, value);
+        address = address.wrapping_add(4);
+    }
+
+    Ok(address)
+}
+
+fn stm(ram_lock: &mut RAM, registers_lock: &mut Registers, reg_list: Word, start_address: Word, user_bank: bool) -> Result<Word, MemoryError> {
+    let mut address = start_address;
+
+    for ri in 0..=15 {
+        if (reg_list >> ri) & 0x1 == 1 {
+            let value = match user_bank {
+                true  => registers_lock.get_user_register(ri),
+                false => registers_lock.get_register(ri)
+            };
+            ram_lock.store_word(address, value)?;
+            address = address.wrapping_add(4);
+        }
+    }
+
+    Ok(address)
+}
+
+// a failed load or store leaves the base register untouched and is reported to CPU::step as a Data Abort
+fn data_abort_on_error(result: Result<(), MemoryError>) -> InstrExecuteCondition {
+    match result {
+        Ok(()) => NOP,
+        Err(err) => DABT(err.get_address())
+    }
+}
+
+// each method accesses/modifies the RAM and registers owned by the Machine
+// directly, so they can be exercised without a running Tauri app
+
+// each of these methods are mapped to the Instruction::execute method
+// and called from CPU::execute
+
+pub fn instr_data_reg_imm(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap());
+    let (shifter_operand, shifter_carry_out) = Instruction::shift_value_by_imm(
+        registers_lock.get_reg_register(instr.get_rm().unwrap()),
+        instr.get_imm_shift().unwrap(),
+        instr.get_shift_type().unwrap(),
+        registers_lock.get_c_flag() as Byte);
+
+    data_match_opcode(registers_lock, instr, rn, shifter_operand, shifter_carry_out);
+
+    NOP
+}
+
+pub fn instr_data_reg_reg(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rn = get_reg_shift_operand(registers_lock, instr.get_rn().unwrap());
+    let (shifter_operand, shifter_carry_out) = Instruction::shift_value_by_reg(
+        get_reg_shift_operand(registers_lock, instr.get_rm().unwrap()),
+        registers_lock.get_reg_register(instr.get_rs().unwrap()), // grab the LSB
+        instr.get_shift_type().unwrap(),
+        registers_lock.get_c_flag() as Byte);
+
+    data_match_opcode(registers_lock, instr, rn, shifter_operand, shifter_carry_out);
+
+    NOP
+}
+
+pub fn instr_data_imm(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rn = get_base_register(registers_lock, instr.get_rn().unwrap());
+    let (shifter_operand, shifter_carry_out) = Instruction::rotate_value(
+        instr.get_rotate().unwrap(),
+        instr.get_imm().unwrap(),
+        registers_lock.get_c_flag() as Byte);
+
+    data_match_opcode(registers_lock, instr, rn, shifter_operand, shifter_carry_out);
+
+    NOP
+}
+
+// p.463
+pub fn instr_ldrstr_shifted_reg_pre(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let index = Instruction::shift_value_by_imm(
+        registers_lock.get_reg_register(instr.get_rm().unwrap()),
+        instr.get_imm_shift().unwrap(),
+        instr.get_shift_type().unwrap(),
+        registers_lock.get_c_flag() as Byte).0;
+
+    data_abort_on_error(ldr_str_pre(ram_lock, registers_lock, instr, index))
+}
+
+pub fn instr_ldrstr_shifted_reg_post(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let index = Instruction::shift_value_by_imm(
+        registers_lock.get_reg_register(instr.get_rm().unwrap()),
+        instr.get_imm_shift().unwrap(),
+        instr.get_shift_type().unwrap(),
+        registers_lock.get_c_flag() as Byte).0;
+
+    data_abort_on_error(ldr_str_post(ram_lock, registers_lock, instr, index))
+}
+
+pub fn instr_ldrstr_reg_pre(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+    data_abort_on_error(ldr_str_pre(ram_lock, registers_lock, instr, rm))
+}
+
+pub fn instr_ldrstr_reg_post(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+    data_abort_on_error(ldr_str_post(ram_lock, registers_lock, instr, rm))
+}
+
+pub fn instr_ldrstr_imm_pre(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let offset_12 = instr.get_imm_shift().unwrap();
+
+    data_abort_on_error(ldr_str_pre(ram_lock, registers_lock, instr, offset_12))
+}
+
+pub fn instr_ldrstr_imm_post(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let offset_12 = instr.get_imm_shift().unwrap();
+
+    data_abort_on_error(ldr_str_post(ram_lock, registers_lock, instr, offset_12))
+}
+
+pub fn instr_ldrhstrh_imm_pre(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let offset_8 = instr.get_imm().unwrap() as Word;
+
+    data_abort_on_error(ldrh_strh_pre(ram_lock, registers_lock, instr, offset_8))
+}
+
+pub fn instr_ldrhstrh_imm_post(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let offset_8 = instr.get_imm().unwrap() as Word;
+
+    data_abort_on_error(ldrh_strh_post(ram_lock, registers_lock, instr, offset_8))
+}
+
+pub fn instr_ldrhstrh_reg_pre(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+
+    data_abort_on_error(ldrh_strh_pre(ram_lock, registers_lock, instr, rm))
+}
+
+pub fn instr_ldrhstrh_reg_post(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+
+    data_abort_on_error(ldrh_strh_post(ram_lock, registers_lock, instr, rm))
+}
+
+pub fn instr_b(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    if instr.get_l_bit().unwrap() {
+        let address_after_branch = registers_lock.get_pc_current_address().wrapping_add(4);
+        registers_lock.set_reg_register(Register::r14, address_after_branch);
+    }
+
+    // increment by an extra 4 bytes because:
+    // 1. CPU fetch
+    // 2. CPU decode
+    // 3. CPU execute branch instruction
+    //   - go to target address
+    // 4. CPU increments PC by 4
+    // -> CPU only increments 1 instruction ahead
+    // => need to increment by extra 4 bytes here to ensure CPU PC is two instructions ahead
+    let target_address: Word = instr.get_pc_address().wrapping_add(instr.get_offset().unwrap() as Word).wrapping_add(4);
+    trace!("instr_b: {}pc {}offset {}target", instr.get_pc_address(), instr.get_offset().unwrap(), target_address);
+    registers_lock.set_pc(target_address);
+
+    NOP
+}
+
+pub fn instr_bx(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+
+    // BLX Rm: rm is read first so that BLX LR works; a Thumb return address has bit 0 set
+    if instr.get_l_bit().unwrap() {
+        let address_after_branch = match instr.get_thumb() {
+            true  => (instr.get_pc_address() - 2) | 1,
+            false => instr.get_pc_address() - 4
+        };
+        registers_lock.set_reg_register(Register::r14, address_after_branch);
+    }
+
+    // bit 0 selects the state to continue in; as with instr_b, add one instruction of the
+    //  new state so that the CPU increment leaves the PC two instructions ahead of the target
+    registers_lock.set_t_flag(util::word_lsb_to_bool(rm));
+    let size = registers_lock.get_instr_size();
+    registers_lock.set_pc((rm & 0xFFFFFFFE) + size);
+
+    NOP
+}
+
+// p.187, A4.1.20 - A4.1.22 and A4.1.97 - A4.1.98
+pub fn instr_ldmstm(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rn_register = instr.get_rn().unwrap();
+    let rn = registers_lock.get_reg_register(rn_register);
+    let load = instr.get_ldr_str().unwrap();
+
+    // as on the ARM7TDMI, an empty list transfers r15 and moves the base by 16 words
+    let (reg_list, length) = match instr.get_reg_list().unwrap() {
+        0 => (1 << 15, 64),
+        reg_list => (reg_list, reg_list.count_ones() * 4)
+    };
+    // the S bit selects the User mode registers unless it is an LDM that loads the PC
+    let user_bank = instr.get_s_bit().unwrap() && !(load && (reg_list >> 15) & 0x1 == 1);
+
+    // A5.4.2
+    let (start_address, writeback_value) = match instr.get_ldm().unwrap() {
+        LDMCode::DecAfter => (rn.wrapping_sub(length).wrapping_add(4), rn.wrapping_sub(length)),
+        LDMCode::IncAfter => (rn, rn.wrapping_add(length)),
+        LDMCode::DecBefore => (rn.wrapping_sub(length), rn.wrapping_sub(length)),
+        LDMCode::IncBefore => (rn.wrapping_add(4), rn.wrapping_add(length)),
+    };
+    // LDM/STM always transfer whole words: ARMv4 ignores the low address bits and
+    //  ARMv6 raises an alignment fault even with unaligned support enabled
+    let start_address = match ram_lock.get_alignment_policy() {
+        AlignmentPolicy::Rotate => start_address & !0b11,
+        _ if start_address % 4 != 0 => return DABT(start_address),
+        _ => start_address
+    };
+
+    // with the base in the list, a load overwrites the written back base, and a store writes the
+    //  original base only if it is the first register stored (the ARM7TDMI writes back after
+    //  the first transfer)
+    let base_first = reg_list & ((1 << rn_register as Word) - 1) == 0;
+    let writeback = instr.get_writeback().unwrap();
+    if writeback && (load || !base_first) {
+        registers_lock.set_reg_register(rn_register, writeback_value);
+    }
+
+    let result = match load {
+        true => ldm(ram_lock, registers_lock, instr, reg_list, start_address, user_bank),
+        false => stm(ram_lock, registers_lock, reg_list, start_address, user_bank),
+    };
+
+    match result {
+        Ok(_) => {
+            if writeback && !load && base_first {
+                registers_lock.set_reg_register(rn_register, writeback_value);
+            }
+            NOP
+        },
+        Err(err) => {
+            // restore the base register on abort
+            registers_lock.set_reg_register(rn_register, rn);
+            DABT(err.get_address())
+        }
+    }
+}
+
+pub fn instr_mul(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rs = registers_lock.get_reg_register(instr.get_rs().unwrap());
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+
+    let mut result = rm.wrapping_mul(rs);
+    if instr.get_accumulate().unwrap() {
+        result = result.wrapping_add(registers_lock.get_reg_register(instr.get_rn().unwrap()));
+    }
+
+    registers_lock.set_reg_register(instr.get_rd().unwrap(), result);
+
+    // C is unpredictable on ARMv4 and V is unaffected, so both are left alone
+    if instr.get_s_bit().unwrap() {
+        registers_lock.set_n_flag(result >> 31 != 0);
+        registers_lock.set_z_flag(result == 0);
+    }
+
+    NOP
+}
+
+// UMULL, UMLAL, SMULL and SMLAL produce a 64-bit RdHi:RdLo result
+pub fn instr_mull(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rs = registers_lock.get_reg_register(instr.get_rs().unwrap());
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+
+    let mut result = match instr.get_signed().unwrap() {
+        true => (rm as SignedWord as i64).wrapping_mul(rs as SignedWord as i64) as u64,
+        false => rm as u64 * rs as u64,
+    };
+    if instr.get_accumulate().unwrap() {
+        let hi = registers_lock.get_reg_register(instr.get_rd().unwrap()) as u64;
+        let lo = registers_lock.get_reg_register(instr.get_rn().unwrap()) as u64;
+        result = result.wrapping_add(hi << 32 | lo);
+    }
+
+    registers_lock.set_reg_register(instr.get_rn().unwrap(), result as Word);
+    registers_lock.set_reg_register(instr.get_rd().unwrap(), (result >> 32) as Word);
+
+    if instr.get_s_bit().unwrap() {
+        registers_lock.set_n_flag(result >> 63 != 0);
+        registers_lock.set_z_flag(result == 0);
+    }
+
+    NOP
+}
+
+// the load and store happen back to back with nothing in between, so the swap is atomic
+//  with respect to interrupts; rd may be the same register as rm
+fn swp(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> Result<(), MemoryError> {
+    let address = registers_lock.get_reg_register(instr.get_rn().unwrap());
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+
+    let data = match instr.get_byte_word().unwrap() {
+        true  => {
+            let data = ram_lock.load_byte(address)? as Word;
+            ram_lock.store_byte(address, rm as Byte)?;
+            data
+        },
+        false => {
+            let data = ram_lock.load_word(address)?;
+            ram_lock.store_word(address, rm)?;
+            data
+        }
+    };
+    registers_lock.set_reg_register(instr.get_rd().unwrap(), data);
+
+    Ok(())
+}
+
+pub fn instr_swp(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    data_abort_on_error(swp(ram_lock, registers_lock, instr))
+}
+
+// p.58, 360
+pub fn instr_swi(_ram_lock: &mut RAM, _registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    // actual SWI processing is done post-execute by processor in CPU::step
+
+    if instr.get_swi().unwrap() == 0x11 {
+        return HLT
+    // these conditions need to be handled by the outer CPU::step context to access the app thread
+    } else if instr.get_swi().unwrap() == 0x0 || instr.get_swi().unwrap() == 0x6a {
+        return SWI
+    }
+
+    NOP
+}
+
+pub fn instr_mrs(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    if instr.get_gpregister().unwrap() {
+        let spsr = registers_lock.get_spsr();
+        registers_lock.set_reg_register(instr.get_rd().unwrap(), spsr);
+    } else {
+        let cpsr = registers_lock.get_cpsr();
+        registers_lock.set_reg_register(instr.get_rd().unwrap(), cpsr);
+    }
+
+    NOP
+}
+
+fn instr_msr(registers_lock: &mut Registers, instr: Instruction, operand: Word) {
+    // const UNALLOC_MASK: Word = 0x06F0FC00;
+    const USER_MASK: Word    = 0xF80F0200;
+    const PRIV_MASK: Word    = 0x000001DF;
+    const STATE_MASK: Word   = 0x01000020;
+
+    let cpsr = registers_lock.get_cpsr();
+    let spsr = registers_lock.get_spsr();
+    let mask ;
+
+    let field_mask = instr.get_field_mask().unwrap() as Word;
+    let byte_mask: Word = (if util::test_bit(field_mask, 0) { 0x000000FF } else { 0x0 }) |
+                          (if util::test_bit(field_mask, 1) { 0x0000FF00 } else { 0x0 }) |
+                          (if util::test_bit(field_mask, 2) { 0x00FF0000 } else { 0x0 }) |
+                          (if util::test_bit(field_mask, 3) { 0xFF000000 } else { 0x0 });
+    if !instr.get_gpregister().unwrap() {
+        // assumed privaleged mode since working with SYS rather than USER mode
+        mask = byte_mask & (USER_MASK | PRIV_MASK);
+        registers_lock.set_cpsr((cpsr & !mask) | (operand & mask));
+    } else {
+        if registers_lock.current_mode_has_spsr() {
+            mask = byte_mask & (USER_MASK | PRIV_MASK | STATE_MASK);
+            registers_lock.set_spsr((spsr & !mask) | (operand & mask));
+        }
+    }
+}
+
+pub fn instr_msr_imm(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let (operand, _) = Instruction::rotate_value(instr.get_rotate().unwrap(), instr.get_imm().unwrap(), 0);
+    instr_msr(registers_lock, instr, operand);
+    NOP
+}
+
+pub fn instr_msr_reg(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let operand = registers_lock.get_reg_register(instr.get_rm().unwrap());
+    instr_msr(registers_lock, instr, operand);
+    NOP
+}
+
+pub fn instr_nop(_ram_lock: &mut RAM, _registers_lock: &mut Registers, _instr: Instruction) -> InstrExecuteCondition {
+    NOP
+}
+
+// the exception is taken by CPU::step
+pub fn instr_undefined(_ram_lock: &mut RAM, _registers_lock: &mut Registers, _instr: Instruction) -> InstrExecuteCondition {
+    UND
+}
+
+/*
+Coprocessor executors
+
+An access the coprocessor does not implement is an Undefined Instruction
+*/
+
+// CP15 is only accessible from privileged modes
+fn coprocessor_permitted(registers_lock: &mut Registers, instr: &Instruction) -> bool {
+    instr.get_coprocessor().unwrap().coprocessor != 15 || registers_lock.get_cpsr_mode() != Mode::USR
+}
+
+pub fn instr_mcr_mrc(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    if !coprocessor_permitted(registers_lock, &instr) {
+        return UND
+    }
+
+    let op = instr.get_coprocessor().unwrap();
+    let rd = instr.get_rd().unwrap();
+
+    // MRC
+    if instr.get_l_bit().unwrap() {
+        let value = match ram_lock.read_coprocessor(&op) {
+            Some(value) => value,
+            None => return UND
+        };
+
+        // A4.1.32: MRC to r15 only sets the condition flags from bits [31:28]
+        if rd == Register::r15 {
+            registers_lock.set_n_flag(value & (1 << 31) != 0);
+            registers_lock.set_z_flag(value & (1 << 30) != 0);
+            registers_lock.set_c_flag(value & (1 << 29) != 0);
+            registers_lock.set_v_flag(value & (1 << 28) != 0);
+        } else {
+            registers_lock.set_reg_register(rd, value);
+        }
+        return NOP
+    }
+
+    // MCR
+    let value = registers_lock.get_reg_register(rd);
+    if !ram_lock.write_coprocessor(&op, value) {
+        return UND
+    }
+
+    NOP
+}
+
+pub fn instr_cdp(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    if !coprocessor_permitted(registers_lock, &instr) || !ram_lock.coprocessors.operation(&instr.get_coprocessor().unwrap()) {
+        return UND
+    }
+
+    NOP
+}
+
+// transfers the words the coprocessor asks for starting at the word-aligned address
+fn ldcstc(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction, length: usize) -> Result<(), MemoryError> {
+    let op = instr.get_coprocessor().unwrap();
+    let rn = instr.get_rn().unwrap();
+    let base = registers_lock.get_reg_register(rn);
+    let offset = instr.get_imm().unwrap() as Word * 4;
+    let indexed = if instr.get_add_sub().unwrap() { base.wrapping_add(offset) } else { base.wrapping_sub(offset) };
+
+    // A5.5: pre-indexed uses the offset address, post-indexed and unindexed use the base
+    let address = if instr.get_addr_mode().unwrap() { indexed } else { base } & !3;
+
+    if instr.get_ldr_str().unwrap() {
+        let mut words = Vec::with_capacity(length);
+        for i in 0..length {
+            words.push(ram_lock.load_word(address.wrapping_add(i as Word * 4))?);
+        }
+        ram_lock.coprocessors.load(&op, &words);
+    } else {
+        let words = ram_lock.coprocessors.store(&op);
+        for (i, word) in words.iter().take(length).enumerate() {
+            ram_lock.store_word(address.wrapping_add(i as Word * 4), *word)?;
+        }
+    }
+
+    if instr.get_writeback().unwrap() {
+        registers_lock.set_reg_register(rn, indexed);
+    }
+
+    Ok(())
+}
+
+pub fn instr_ldcstc(ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    if !coprocessor_permitted(registers_lock, &instr) {
+        return UND
+    }
+
+    match ram_lock.coprocessors.transfer_length(&instr.get_coprocessor().unwrap()) {
+        Some(length) => data_abort_on_error(ldcstc(ram_lock, registers_lock, instr, length)),
+        None => UND
+    }
+}
+
+/*
+ARMv5TE executors
+*/
+
+// BLX <target> always switches to Thumb state; the PC is compensated by one Thumb instruction
+pub fn instr_blx(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    registers_lock.set_reg_register(Register::r14, instr.get_pc_address() - 4);
+
+    let target_address = instr.get_pc_address().wrapping_add(instr.get_offset().unwrap() as Word);
+    registers_lock.set_t_flag(true);
+    registers_lock.set_pc(target_address + 2);
+
+    NOP
+}
+
+pub fn instr_clz(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+    registers_lock.set_reg_register(instr.get_rd().unwrap(), rm.leading_zeros());
+
+    NOP
+}
+
+// clamps to the signed 32-bit range; true when the value had to be clamped
+fn signed_saturate(value: i64) -> (Word, bool) {
+    let saturated = value.clamp(SignedWord::MIN as i64, SignedWord::MAX as i64);
+    (saturated as SignedWord as Word, saturated != value)
+}
+
+// QADD, QSUB, QDADD and QDSUB; Q is sticky, so it is only ever set here
+pub fn instr_qadd(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap()) as SignedWord as i64;
+    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap()) as SignedWord as i64;
+
+    let (operand, doubled_saturated) = match instr.get_double().unwrap() {
+        true  => signed_saturate(rn * 2),
+        false => (rn as Word, false)
+    };
+    let operand = operand as SignedWord as i64;
+
+    let (result, saturated) = match instr.get_add_sub().unwrap() {
+        true  /* add */ => signed_saturate(rm + operand),
+        false /* sub */ => signed_saturate(rm - operand)
+    };
+
+    registers_lock.set_reg_register(instr.get_rd().unwrap(), result);
+    if doubled_saturated || saturated {
+        registers_lock.set_q_flag(true);
+    }
+
+    NOP
+}
+
+// the signed halfword of a register selected by <x> or <y>
+fn get_half(value: Word, top: bool) -> SignedWord {
+    match top {
+        true  => (value >> 16) as i16 as SignedWord,
+        false => value as i16 as SignedWord
+    }
+}
+
+// the accumulate sets Q on signed overflow; the 16 x 16 product itself cannot overflow
+fn dsp_accumulate(registers_lock: &mut Registers, instr: Instruction, product: SignedWord) -> SignedWord {
+    if !instr.get_accumulate().unwrap() {
+        return product
+    }
+
+    let rn = registers_lock.get_reg_register(instr.get_rn().unwrap()) as SignedWord;
+    let (result, overflow) = product.overflowing_add(rn);
+    if overflow {
+        registers_lock.set_q_flag(true);
+    }
+
+    result
+}
+
+pub fn instr_smulxy(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+    let rs = registers_lock.get_reg_register(instr.get_rs().unwrap());
+
+    let product = get_half(rm, instr.get_top_x().unwrap()) * get_half(rs, instr.get_top_y().unwrap());
+    let result = dsp_accumulate(registers_lock, instr, product);
+    registers_lock.set_reg_register(instr.get_rd().unwrap(), result as Word);
+
+    NOP
+}
+
+pub fn instr_smulwy(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap()) as SignedWord as i64;
+    let rs = registers_lock.get_reg_register(instr.get_rs().unwrap());
+
+    let product = ((rm * get_half(rs, instr.get_top_y().unwrap()) as i64) >> 16) as SignedWord;
+    let result = dsp_accumulate(registers_lock, instr, product);
+    registers_lock.set_reg_register(instr.get_rd().unwrap(), result as Word);
+
+    NOP
+}
+
+pub fn instr_smlalxy(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let rm = registers_lock.get_reg_register(instr.get_rm().unwrap());
+    let rs = registers_lock.get_reg_register(instr.get_rs().unwrap());
+
+    let product = get_half(rm, instr.get_top_x().unwrap()) as i64 * get_half(rs, instr.get_top_y().unwrap()) as i64;
+    let hi = registers_lock.get_reg_register(instr.get_rd().unwrap()) as u64;
+    let lo = registers_lock.get_reg_register(instr.get_rn().unwrap()) as u64;
+    let result = (hi << 32 | lo).wrapping_add(product as u64);
+
+    registers_lock.set_reg_register(instr.get_rn().unwrap(), result as Word);
+    registers_lock.set_reg_register(instr.get_rd().unwrap(), (result >> 32) as Word);
+
+    NOP
+}
+
+// Thumb executors for the instructions that branch; the PC compensation is the same as
+//  instr_b but one Thumb instruction (2 bytes) wide
+
+pub fn thumb_instr_b(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let target_address: Word = instr.get_pc_address().wrapping_add(instr.get_offset().unwrap() as Word);
+    trace!("thumb_instr_b: {}pc {}offset {}target", instr.get_pc_address(), instr.get_offset().unwrap(), target_address);
+    registers_lock.set_pc(target_address + 2);
+
+    NOP
+}
+
+// A7.1.17: LR = PC + (offset << 12)
+pub fn thumb_instr_bl_prefix(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let address = instr.get_pc_address().wrapping_add(instr.get_offset().unwrap() as Word);
+    registers_lock.set_reg_register(Register::r14, address);
+
+    NOP
+}
+
+// PC = LR + (offset << 1), LR = address of the next instruction | 1
+pub fn thumb_instr_bl_suffix(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let lr = registers_lock.get_reg_register(Register::r14);
+    let target_address = lr.wrapping_add(instr.get_offset().unwrap() as Word);
+    let address_after_branch = instr.get_pc_address() - 2;
+
+    registers_lock.set_reg_register(Register::r14, address_after_branch | 1);
+    registers_lock.set_pc((target_address & 0xFFFFFFFE) + 2);
+
+    NOP
+}
+
+// ARMv5TE: PC = (LR + (offset << 1)) & ~3 in ARM state, LR = address of the next instruction | 1
+pub fn thumb_instr_blx_suffix(_ram_lock: &mut RAM, registers_lock: &mut Registers, instr: Instruction) -> InstrExecuteCondition {
+    let lr = registers_lock.get_reg_register(Register::r14);
+    let target_address = lr.wrapping_add(instr.get_offset().unwrap() as Word);
+    let address_after_branch = instr.get_pc_address() - 2;
+
+    registers_lock.set_reg_register(Register::r14, address_after_branch | 1);
+    registers_lock.set_t_flag(false);
+    registers_lock.set_pc((target_address & 0xFFFFFFFC) + 4);
+
+    NOP
+}
@@ -1,892 +1,1524 @@
-use tokio::sync::MutexGuard;
-
-use crate::{execute, util};
-use crate::memory::{Byte, Word, Register, RAM, Registers, AddressSize, SignedWord};
-use crate::cpu_enum::{Condition, ShiftType, DataOpcode, InstrType, LSH, LDMCode, InstrExecuteCondition};
-
-// This is the parent trait that contains all the default implementations
-// for each instruction; all the getters and setters are implemented directly
-// for the Instruction struct 
-pub trait TInstruction {
-    fn new(_type: InstrType) -> Self;
-
-    fn decode(&self);
-    fn encode(&self);
-
-    fn set_execute(&mut self, f: fn(&mut MutexGuard<'_, RAM>, &mut MutexGuard<'_, Registers>, Instruction) -> InstrExecuteCondition);
-    fn get_execute(&self) -> fn(&mut MutexGuard<'_, RAM>, &mut MutexGuard<'_, Registers>, Instruction) -> InstrExecuteCondition;
-
-    fn get_name(&self) -> String;
-    // fn get_instr(&self) -> Word;
-
-    fn get_type(&self) -> InstrType;
-    fn set_type(&mut self, _type: InstrType);
-
-    fn get_data_opcode(&self) -> Option<DataOpcode>;
-    fn set_data_opcode(&mut self, code: Word);
-
-    fn get_s_bit(&self) -> Option<bool>;
-    fn set_s_bit(&mut self, bit: Word);
-
-    fn get_shift_type(&self) -> Option<ShiftType>;
-    fn set_shift_type(&mut self, sh: Word);
-
-    fn get_imm_shift(&self) -> Option<Word>;
-    fn set_imm_shift(&mut self, imm: Word);
-
-    fn get_rotate(&self) -> Option<Byte>;
-    fn set_rotate(&mut self, rotate: Byte);
-
-    fn get_imm(&self) -> Option<Byte>;
-    fn set_imm(&mut self, imm: Byte);
-
-    fn get_writeback(&self) -> Option<bool>;
-    fn set_writeback(&mut self, op: bool);
-
-    fn get_addr_mode(&self) -> Option<bool>;
-    fn set_addr_mode(&mut self, op: bool);
-
-    fn get_swi(&self) -> Option<Word>;
-    fn set_swi(&mut self, num: Word);
-
-    fn get_l_bit(&self) -> Option<bool>;
-    fn set_l_bit(&mut self, bit: Word);
-
-    fn get_offset(&self) -> Option<SignedWord>;
-    fn set_offset(&mut self, offset: SignedWord);
-
-    fn get_lsh(&self) -> Option<LSH>;
-    fn set_lsh(&mut self, lsh: Word);
-    
-    fn get_add_sub(&self) -> Option<bool>;
-    fn set_add_sub(&mut self, op: bool);
-
-    fn get_byte_word(&self) -> Option<bool>;
-    fn set_byte_word(&mut self, op: bool);
-
-    fn get_ldr_str(&self) -> Option<bool>;
-    fn set_ldr_str(&mut self, op: bool);
-
-    fn get_ldm(&self) -> Option<LDMCode>;
-    fn set_ldm(&mut self, ldm: Word);
-
-    fn get_reg_list(&self) -> Option<Word>;
-    fn set_reg_list(&mut self, reg_list: Word);
-
-    fn get_last_char(&self) -> Option<char>;
-    fn set_last_char(&mut self, last_char: char);
-
-    // true for SPSR, false for CPSR
-    fn get_gpregister(&self) -> Option<bool>;
-    fn set_gpregister(&mut self, gpregister: bool);
-
-    fn get_field_mask(&self) -> Option<Byte>;
-    fn set_field_mask(&mut self, field_mask: Byte);
-
-    fn get_pc_address(&self) -> AddressSize;
-    fn set_pc_address(&mut self, address: AddressSize);
-
-    // shift value in immediate_shift field by shift amount
-    // https://developer.arm.com/documentation/dui0489/i/arm-and-thumb-instructions/operand2-as-a-register-with-optional-shift?lang=en
-    // ARM Manual A5.1
-    fn shift_value_by_imm(rm: Word, shift_imm: Word, shift_type: ShiftType, c_flag: Byte) -> (Word, Word) {
-        let rm_msb = util::get_bit(rm, 31);
-        match shift_type {
-            ShiftType::LSL => {
-                if shift_imm == 0 {
-                    (rm, c_flag as Word)
-                } else {
-                    (rm << shift_imm, util::get_bit(rm, 32 - shift_imm))
-                }
-            },
-            ShiftType::LSR => {
-                if shift_imm == 0 {
-                    (0, rm_msb)
-                } else {
-                    (rm >> shift_imm, util::get_bit(rm, shift_imm - 1))
-                }
-            },
-            ShiftType::ASR => {
-                if shift_imm == 0 {
-                    if rm_msb == 0 {
-                        (0, rm_msb)
-                    } else {
-                        (0xffffffff, rm_msb)
-                    }
-                } else {
-                    // cast to signed to force ASR in Rust
-                    (((rm as SignedWord) >> shift_imm) as Word, util::get_bit(rm, shift_imm - 1))
-                }
-            },
-            ShiftType::ROR => {
-                if shift_imm == 0 {
-                    (c_flag.checked_shl(31).unwrap_or(0) as Word | (rm >> 1), rm & 1)
-                } else {
-                    (rm.rotate_right(shift_imm), util::get_bit(rm, shift_imm - 1))
-                }
-            }
-        }
-    }
-
-    fn shift_value_by_reg(rm: Word, rs: Word, shift_type: ShiftType, c_flag: Byte) -> (Word, Word) {
-        let rs_lsb = rs & 0xff;
-        let rs_lsn = rs & 0xf;
-        let rm_msb = util::get_bit(rm, 31);
-
-        match shift_type {
-            ShiftType::LSL => {
-                if rs_lsb == 0 {
-                    (rm, c_flag as Word)
-                } else if rs_lsb < 32 {
-                    (rm << rs_lsb, util::get_bit(rm, 32 - rs_lsb))
-                } else if rs_lsb == 32 {
-                    (0, rm & 1)
-                } else {
-                    (0, 0)
-                }
-            },
-            ShiftType::LSR => {
-                if rs_lsb == 0 {
-                    (rm, c_flag as Word)
-                } else if rs_lsb < 32 {
-                    (rm >> rs_lsb, util::get_bit(rm, rs_lsb - 1))
-                } else if rs_lsb == 32 {
-                    (0, rm_msb)
-                } else {
-                    (0, 0)
-                }
-            },
-            ShiftType::ASR => {
-                if rs_lsb == 0 {
-                    (rm, c_flag as Word)
-                } else if rs_lsb < 32 {
-                    // cast to signed to force ASR in Rust
-                    (((rm as SignedWord) >> rs_lsb) as Word, util::get_bit(rm, rs_lsb - 1))
-                } else {
-                    if rm_msb == 0 {
-                        (0, rm_msb)
-                    } else {
-                        (0xffffffff, rm_msb)
-                    }
-                }
-            },
-            ShiftType::ROR => {
-                if rs_lsb == 0 {
-                    (rm, c_flag as Word)
-                } else if rs_lsn == 0 {
-                    (rm, rm_msb)
-                } else {
-                    (rm.rotate_right(rs), util::get_bit(rm, rs_lsn - 1))
-                }
-            }
-        }
-    }
-
-    // tuple: (shifter_operand, shifter_carry_out)
-    // A5.1.3
-    fn rotate_value(rotate_imm: Byte, immed_8: Byte, c_flag: Byte) -> (Word, Word) {
-        let immediate_to_rotate: Word = (immed_8 & 0xff) as Word;
-        let rotate_amount: Word = ((rotate_imm as Byte) * 2) as Word;
-        let shifter_operand = immediate_to_rotate.rotate_right(rotate_amount);
-        if rotate_imm == 0 {
-            (shifter_operand, c_flag as Word)
-        } else {
-            (shifter_operand, util::get_bit(shifter_operand, 31))
-        }
-    }
-
-    fn get_rd(&self) -> Option<Register>;
-    fn set_rd(&mut self, rd: Word);
-    fn get_rn(&self) -> Option<Register>;
-    fn set_rn(&mut self, rn: Word);
-    fn get_rm(&self) -> Option<Register>;
-    fn set_rm(&mut self, rm: Word);
-    fn get_rs(&self) -> Option<Register>;
-    fn set_rs(&mut self, rs: Word);
-
-    // NZCV flag
-    fn set_condition(&mut self, cond: Word);
-    fn get_condition(&self) -> Condition;
-}
-
-// the Instruction struct will hold flags for all possible Instruction types
-// to simplify how instructions are passed around the CPU; it increases the size
-// of the Instruction struct, but most of the data in the struct are just
-// Options with minimal data
-
-#[derive(Clone, Copy)]
-pub struct Instruction {
-    _type: InstrType,
-    execute: Option<fn(&mut MutexGuard<'_, RAM>, &mut MutexGuard<'_, Registers>, Instruction) -> InstrExecuteCondition>,
-    rn: Option<Register>,
-    rd: Option<Register>,
-    rm: Option<Register>,
-    rs: Option<Register>,
-    condition: Condition,
-    data_opcode: Option<DataOpcode>,
-    s_bit: Option<bool>,
-    shift_type: Option<ShiftType>,
-    imm_shift: Option<Word>,
-    rotate: Option<Byte>,
-    imm: Option<Byte>,
-    writeback: Option<bool>,
-    addr_mode: Option<bool>,
-    swi: Option<Word>,
-    add_sub: Option<bool>,
-    byte_word: Option<bool>,
-    ldr_str: Option<bool>,
-    l_bit: Option<bool>,
-    offset: Option<SignedWord>,
-    lsh: Option<LSH>,
-    ldm: Option<LDMCode>,
-    reg_list: Option<Word>,
-    last_char: Option<char>,
-    gpregister: Option<bool>,
-    field_mask: Option<Byte>,
-    pc_address: Word,
-}
-
-impl TInstruction for Instruction {
-    fn new(_type: InstrType) -> Self {
-        Instruction {
-            _type: _type,
-            execute: None,
-            rn: None,
-            rd: None,
-            rm: None,
-            rs: None,
-            condition: Condition::AL,
-            data_opcode: None,
-            s_bit: None,
-            shift_type: None,
-            imm_shift: None,
-            rotate: None,
-            imm: None,
-            writeback: None,
-            addr_mode: None,
-            swi: None,
-            add_sub: None,
-            byte_word: None,
-            ldr_str: None,
-            l_bit: None,
-            offset: None,
-            lsh: None,
-            ldm: None,
-            reg_list: None,
-            last_char: None,
-            gpregister: None,
-            field_mask: None,
-            pc_address: 0
-        }
-    }
-
-    fn get_type(&self) -> InstrType {
-        self._type
-    }
-
-    fn set_type(&mut self, _type: InstrType) {
-        self._type = _type;
-    }
-
-    fn decode(&self) {
-        todo!()
-    }
-
-    fn encode(&self) {
-        todo!()
-    }
-    
-    fn get_name(&self) -> String {
-        todo!()
-    }
-
-    fn get_rd(&self) -> Option<Register> {
-        self.rd
-    }
-
-    fn set_rd(&mut self, rd: Word) {
-        let rd: Register = num::FromPrimitive::from_u32(rd).unwrap();
-        self.rd = Some(rd);
-    }
-
-    fn get_rn(&self) -> Option<Register> {
-        self.rn
-    }
-
-    fn set_rn(&mut self, rn: Word) {
-        let rn: Register = num::FromPrimitive::from_u32(rn).unwrap();
-        self.rn = Some(rn);
-    }
-
-    fn get_rm(&self) -> Option<Register> {
-        self.rm
-    }
-
-    fn set_rm(&mut self, rm: Word) {
-        let rm: Register = num::FromPrimitive::from_u32(rm).unwrap();
-        self.rm = Some(rm);
-    }
-
-    fn get_rs(&self) -> Option<Register> {
-        self.rs
-    }
-
-    fn set_rs(&mut self, rs: Word) {
-        let rs: Register = num::FromPrimitive::from_u32(rs).unwrap();
-        self.rs = Some(rs);
-    }
-
-    fn set_condition(&mut self, cond: Word) {
-        let condition: Condition = num::FromPrimitive::from_u32(cond).unwrap();
-        self.condition = condition;
-    }
-
-    fn get_condition(&self) -> Condition {
-        self.condition
-    }
-
-    fn get_data_opcode(&self) -> Option<DataOpcode> {
-        self.data_opcode
-    }
-
-    fn set_data_opcode(&mut self, code: Word) {
-        let opcode: DataOpcode = num::FromPrimitive::from_u32(code).unwrap();
-        self.data_opcode = Some(opcode);
-    }
-
-    fn get_s_bit(&self) -> Option<bool> {
-        self.s_bit
-    }
-
-    fn set_s_bit(&mut self, bit: Word) {
-        match bit & 0x1 {
-            0 => self.s_bit = Some(false),
-            1 => self.s_bit = Some(true),
-            _ => self.s_bit = None
-        }
-    }
-
-    fn get_shift_type(&self) -> Option<ShiftType> {
-        self.shift_type
-    }
-
-    fn set_shift_type(&mut self, sh: Word) {
-        let shift: ShiftType = num::FromPrimitive::from_u32(sh).unwrap();
-        self.shift_type = Some(shift);
-    }
-
-    fn get_imm_shift(&self) -> Option<Word> {
-        self.imm_shift
-    }
-
-    fn set_imm_shift(&mut self, imm: Word) {
-        self.imm_shift = Some(imm);
-    }
-
-    fn get_imm(&self) -> Option<Byte> {
-        self.imm
-    }
-
-    fn set_imm(&mut self, imm: Byte) {
-        self.imm = Some(imm);
-    }
-
-    fn set_execute(&mut self, f: fn(&mut MutexGuard<'_, RAM>, &mut MutexGuard<'_, Registers>, Instruction) -> InstrExecuteCondition) {
-        self.execute = Some(f);
-    }
-
-    fn get_execute(&self) -> fn(&mut MutexGuard<'_, RAM>, &mut MutexGuard<'_, Registers>, Instruction) -> InstrExecuteCondition {
-        self.execute.unwrap()
-    }
-
-    fn get_rotate(&self) -> Option<Byte> {
-        self.rotate
-    }
-
-    fn set_rotate(&mut self, rotate: Byte) {
-        self.rotate = Some(rotate)
-    }
-
-    fn get_writeback(&self) -> Option<bool> {
-        self.writeback
-    }
-
-    fn set_writeback(&mut self, op: bool) {
-        self.writeback = Some(op)
-    }
-
-    fn get_addr_mode(&self) -> Option<bool> {
-        self.addr_mode
-    }
-
-    fn set_addr_mode(&mut self, op: bool) {
-        self.addr_mode = Some(op)
-    }
-
-    fn get_swi(&self) -> Option<Word> {
-        self.swi
-    }
-
-    fn set_swi(&mut self, num: Word) {
-        self.swi = Some(num)
-    }
-
-    fn get_add_sub(&self) -> Option<bool> {
-        self.add_sub
-    }
-
-    fn set_add_sub(&mut self, op: bool) {
-        self.add_sub = Some(op)
-    }
-
-    fn get_byte_word(&self) -> Option<bool> {
-        self.byte_word
-    }
-
-    fn set_byte_word(&mut self, op: bool) {
-        self.byte_word = Some(op)
-    }
-
-    fn get_ldr_str(&self) -> Option<bool> {
-        self.ldr_str
-    }
-
-    fn set_ldr_str(&mut self, op: bool) {
-        self.ldr_str = Some(op)
-    }
-
-    fn get_l_bit(&self) -> Option<bool> {
-        self.l_bit
-    }
-
-    fn set_l_bit(&mut self, bit: Word) {
-        match bit & 0x1 {
-            0 => self.l_bit = Some(false),
-            1 => self.l_bit = Some(true),
-            _ => self.l_bit = None
-        }
-    }
-
-    fn get_offset(&self) -> Option<SignedWord> {
-        self.offset
-    }
-
-    fn set_offset(&mut self, offset: SignedWord) {
-        self.offset = Some(offset)
-    }
-
-    fn get_lsh(&self) -> Option<LSH> {
-        self.lsh
-    }
-
-    fn set_lsh(&mut self, lsh: Word) {
-        let lsh_code: LSH = num::FromPrimitive::from_u32(lsh).unwrap();
-        self.lsh = Some(lsh_code);
-    }
-
-    fn get_ldm(&self) -> Option<LDMCode> {
-        self.ldm
-    }
-
-    fn set_ldm(&mut self, ldm: Word) {
-        let ldm_code: LDMCode = num::FromPrimitive::from_u32(ldm).unwrap();
-        self.ldm = Some(ldm_code);
-    }
-
-    fn get_reg_list(&self) -> Option<Word> {
-        self.reg_list
-    }
-
-    fn set_reg_list(&mut self, reg_list: Word) {
-        self.reg_list = Some(reg_list);
-    }
-
-    fn get_last_char(&self) -> Option<char> {
-        self.last_char
-    }
-
-    fn set_last_char(&mut self, last_char: char) {
-        self.last_char = Some(last_char);
-    }
-
-    fn get_gpregister(&self) -> Option<bool> {
-        self.gpregister
-    }
-
-    fn set_gpregister(&mut self, gpregister: bool) {
-        self.gpregister = Some(gpregister);
-    }
-
-    fn get_field_mask(&self) -> Option<Byte> {
-        self.field_mask
-    }
-
-    fn set_field_mask(&mut self, field_mask: Byte) {
-        self.field_mask = Some(field_mask);
-    }
-
-    fn get_pc_address(&self) -> AddressSize {
-        self.pc_address
-    }
-
-    fn set_pc_address(&mut self, address: AddressSize) {
-        self.pc_address = address;
-    }
-}
-
-/*
-Individual type constructs
-*/
-
-pub trait TTypeData: TInstruction {
-    fn get_opcode(&self) -> DataOpcode;
-    fn get_s_bit(&self) -> bool;
-    fn get_shift_type(&self) -> ShiftType;
-}
-
-/*
-Individual instruction factories
-yay java
-
-Each factory takes the bit patterns from the bitmatcher in the CPU decode step
-and produces an Instruction struct with all the necessary methods attached
-*/
-
-pub fn instr_data_reg_imm(condition: Word, opcode: Word, s_bit: Word, rn: Word, rd: Word, imm: Word, shift_type: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::DataRegImm);
-    instr.set_condition(condition);
-    instr.set_data_opcode(opcode);
-    instr.set_s_bit(s_bit);
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_imm_shift(imm);
-    instr.set_shift_type(shift_type);
-    instr.set_rm(rm);
-
-    instr.set_execute(execute::instr_data_reg_imm);
-
-    instr
-}
-
-pub fn instr_data_reg_reg(condition: Word, opcode: Word, s_bit: Word, rn: Word, rd: Word, rs: Word, shift_type: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::DataRegReg);
-    instr.set_condition(condition);
-    instr.set_data_opcode(opcode);
-    instr.set_s_bit(s_bit);
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_rs(rs);
-    instr.set_shift_type(shift_type);
-    instr.set_rm(rm);
-
-    instr.set_execute(execute::instr_data_reg_reg);
-
-    instr
-}
-
-pub fn instr_data_imm(condition: Word, opcode: Word, s_bit: Word, rn: Word, rd: Word, rotate: Word, imm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::DataImm);
-    instr.set_condition(condition);
-    instr.set_data_opcode(opcode);
-    instr.set_s_bit(s_bit);
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_rotate(rotate as Byte);
-    instr.set_imm(imm as Byte);
-    instr.set_execute(execute::instr_data_imm);
-
-    instr
-}
-
-pub fn instr_ldrstr_shifted_reg_pre(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, imm: Word, shift_type: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRSTRShiftRegPre);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(true);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_imm_shift(imm);
-    instr.set_shift_type(shift_type);
-    instr.set_rm(rm);
-    instr.set_execute(execute::instr_ldrstr_shifted_reg_pre);
-
-    instr
-}
-
-pub fn instr_ldrstr_shifted_reg_post(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, imm: Word, shift_type: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRSTRShiftRegPost);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(false);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_imm_shift(imm);
-    instr.set_shift_type(shift_type);
-    instr.set_rm(rm);
-    instr.set_execute(execute::instr_ldrstr_shifted_reg_post);
-
-    instr
-}
-
-pub fn instr_ldrstr_reg_pre(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRHSTRHRegPre);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(true);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_rm(rm);
-    instr.set_execute(execute::instr_ldrstr_reg_pre);
-
-    instr
-}
-
-pub fn instr_ldrstr_reg_post(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRHSTRHRegPost);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(false);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_rm(rm);
-    instr.set_execute(execute::instr_ldrstr_reg_post);
-
-    instr
-}
-
-pub fn instr_ldrstr_imm_pre(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, imm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRSTRImmPre);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(true);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_imm_shift(imm);
-    instr.set_execute(execute::instr_ldrstr_imm_pre);
-
-    instr
-}
-
-pub fn instr_ldrstr_imm_post(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, imm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRSTRImmPost);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(false);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_imm_shift(imm);
-    instr.set_execute(execute::instr_ldrstr_imm_post);
-
-    instr
-}
-
-pub fn instr_ldrhstrh_imm_pre(condition: Word, add_sub: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, high_bits: Word, lsh: Word, low_bits: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRHSTRHImmPre);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(true);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_lsh((ldr_str << 2) | lsh);
-    instr.set_imm(((high_bits as Byte) << 4) | (low_bits as Byte));
-    instr.set_execute(execute::instr_ldrhstrh_imm_pre);
-
-    instr
-}
-
-pub fn instr_ldrhstrh_imm_post(condition: Word, add_sub: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, high_bits: Word, lsh: Word, low_bits: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRHSTRHImmPost);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(false);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_lsh((ldr_str << 2) | lsh);
-    instr.set_imm(((high_bits as Byte) << 4) | (low_bits as Byte));
-    instr.set_execute(execute::instr_ldrhstrh_imm_post);
-
-    instr
-}
-
-pub fn instr_ldrhstrh_reg_pre(condition: Word, add_sub: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, lsh: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRHSTRHRegPre);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(true);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_lsh((ldr_str << 2) | lsh);
-    instr.set_rm(rm);
-    instr.set_execute(execute::instr_ldrhstrh_reg_pre);
-
-    instr
-}
-
-pub fn instr_ldrhstrh_reg_post(condition: Word, add_sub: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, lsh: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDRHSTRHRegPost);
-    instr.set_condition(condition); 
-    instr.set_addr_mode(false);
-    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_rd(rd);
-    instr.set_lsh((ldr_str << 2) | lsh);
-    instr.set_rm(rm);
-    instr.set_execute(execute::instr_ldrhstrh_reg_post);
-
-    instr
-}
-
-pub fn instr_b(condition: Word, l_bit: Word, offset: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::B);
-    instr.set_condition(condition); 
-    instr.set_l_bit(l_bit);
-
-    // sign extend manually
-    // if top bit is 1, extend
-    instr.set_offset(
-        if util::test_bit(offset, 23) {
-            ((offset | 0x3f000000) << 2) as SignedWord
-        } else {
-            (offset << 2) as SignedWord
-        }
-    );
-    instr.set_execute(execute::instr_b);
-
-    instr
-}
-
-pub fn instr_bx(condition: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::BX);
-    instr.set_condition(condition); 
-    instr.set_rm(rm);
-    instr.set_execute(execute::instr_bx);
-
-    instr
-}
-
-pub fn instr_ldmstm(condition: Word, ldm_code: Word, s_bit: Word, writeback: Word, ldr_str: Word, rn: Word, reg_list: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::LDMSTM);
-    instr.set_condition(condition); 
-    instr.set_ldm(ldm_code);
-    instr.set_s_bit(s_bit);
-    instr.set_writeback(util::word_lsb_to_bool(writeback));
-    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
-    instr.set_rn(rn);
-    instr.set_reg_list(reg_list);
-    instr.set_execute(execute::instr_ldmstm);
-
-    instr
-}
-
-pub fn instr_mul(condition: Word, s_bit: Word, rd: Word, rs: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::MUL);
-    instr.set_condition(condition);
-    instr.set_s_bit(s_bit);
-    instr.set_rd(rd);
-    instr.set_rs(rs);
-    instr.set_rm(rm);
-    instr.set_execute(execute::instr_mul);
-
-    instr
-}
-
-pub fn instr_swi(condition: Word, swi: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::SWI);
-    instr.set_condition(condition);
-    instr.set_swi(swi);
-    instr.set_execute(execute::instr_swi);
-
-    instr
-}
-
-pub fn instr_mrs(condition: Word, gpregister: Word, rd: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::MRS);
-
-    instr.set_condition(condition);
-    instr.set_gpregister(gpregister != 0);
-    instr.set_rd(rd);
-    instr.set_execute(execute::instr_mrs);
-
-    instr
-}
-
-pub fn instr_msr_imm(condition: Word, gpregister: Word, field_mask: Word, rotate_imm: Word, imm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::MSRImm);
-
-    instr.set_condition(condition);
-    instr.set_gpregister(gpregister != 0);
-    instr.set_field_mask(field_mask as Byte);
-    instr.set_rotate(rotate_imm as Byte);
-    instr.set_imm(imm as Byte);
-    instr.set_execute(execute::instr_msr_imm);
-
-    instr
-}
-
-pub fn instr_msr_reg(condition: Word, gpregister: Word, field_mask: Word, rm: Word) -> Instruction {
-    let mut instr = Instruction::new(InstrType::MSRReg);
-
-    instr.set_condition(condition);
-    instr.set_gpregister(gpregister != 0);
-    instr.set_field_mask(field_mask as Byte);
-    instr.set_rm(rm);
-    instr.set_execute(execute::instr_msr_reg);
-
-    instr
-}
-
-pub fn instr_nop() -> Instruction {
-    let mut instr = Instruction::new(InstrType::NOP);
-
-    instr.set_execute(execute::instr_nop);
-
-    instr
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_disassemble_instr_data_imm() {
-        let instr = instr_data_imm(0b1110, 0b1101, 0b0, 0b0000, 0b0010, 0b0000, 0b110000);
-        assert_eq!("mov r2, #48", instr.to_string())
-    }
-
-    #[test]
-    fn test_shift_value() {
-        todo!()
-    }
-
-    #[test]
-    fn test_rotate_value() {
-        todo!();
-    }
-
+use crate::{execute, encode, util, coprocessor::CoprocessorOp};
+use crate::memory::{Byte, Word, Register, RAM, Registers, AddressSize, SignedWord};
+use crate::cpu_enum::{Condition, ShiftType, DataOpcode, InstrType, LSH, LDMCode, InstrExecuteCondition};
+
+// This is the parent trait that contains all the default implementations
+// for each instruction; all the getters and setters are implemented directly
+// for the Instruction struct 
+pub trait TInstruction {
+    fn new(_type: InstrType) -> Self;
+
+    fn decode(&self);
+    // the machine word: an ARM instruction, or a Thumb halfword in the low 16 bits
+    fn encode(&self) -> Word;
+
+    fn set_execute(&mut self, f: fn(&mut RAM, &mut Registers, Instruction) -> InstrExecuteCondition);
+    fn get_execute(&self) -> fn(&mut RAM, &mut Registers, Instruction) -> InstrExecuteCondition;
+
+    fn get_name(&self) -> String;
+    // fn get_instr(&self) -> Word;
+
+    fn get_type(&self) -> InstrType;
+    fn set_type(&mut self, _type: InstrType);
+
+    fn get_data_opcode(&self) -> Option<DataOpcode>;
+    fn set_data_opcode(&mut self, code: Word);
+
+    fn get_s_bit(&self) -> Option<bool>;
+    fn set_s_bit(&mut self, bit: Word);
+
+    fn get_shift_type(&self) -> Option<ShiftType>;
+    fn set_shift_type(&mut self, sh: Word);
+
+    fn get_imm_shift(&self) -> Option<Word>;
+    fn set_imm_shift(&mut self, imm: Word);
+
+    fn get_rotate(&self) -> Option<Byte>;
+    fn set_rotate(&mut self, rotate: Byte);
+
+    fn get_imm(&self) -> Option<Byte>;
+    fn set_imm(&mut self, imm: Byte);
+
+    fn get_writeback(&self) -> Option<bool>;
+    fn set_writeback(&mut self, op: bool);
+
+    fn get_addr_mode(&self) -> Option<bool>;
+    fn set_addr_mode(&mut self, op: bool);
+
+    fn get_swi(&self) -> Option<Word>;
+    fn set_swi(&mut self, num: Word);
+
+    fn get_l_bit(&self) -> Option<bool>;
+    fn set_l_bit(&mut self, bit: Word);
+
+    fn get_offset(&self) -> Option<SignedWord>;
+    fn set_offset(&mut self, offset: SignedWord);
+
+    fn get_lsh(&self) -> Option<LSH>;
+    fn set_lsh(&mut self, lsh: Word);
+    
+    fn get_add_sub(&self) -> Option<bool>;
+    fn set_add_sub(&mut self, op: bool);
+
+    fn get_byte_word(&self) -> Option<bool>;
+    fn set_byte_word(&mut self, op: bool);
+
+    fn get_ldr_str(&self) -> Option<bool>;
+    fn set_ldr_str(&mut self, op: bool);
+
+    fn get_ldm(&self) -> Option<LDMCode>;
+    fn set_ldm(&mut self, ldm: Word);
+
+    fn get_reg_list(&self) -> Option<Word>;
+    fn set_reg_list(&mut self, reg_list: Word);
+
+
+    // true for SPSR, false for CPSR
+    fn get_gpregister(&self) -> Option<bool>;
+    fn set_gpregister(&mut self, gpregister: bool);
+
+    fn get_field_mask(&self) -> Option<Byte>;
+    fn set_field_mask(&mut self, field_mask: Byte);
+
+    // multiply A bit; true for MLA/UMLAL/SMLAL
+    fn get_accumulate(&self) -> Option<bool>;
+    fn set_accumulate(&mut self, op: bool);
+
+    // long multiply U bit; true for SMULL/SMLAL
+    fn get_signed(&self) -> Option<bool>;
+    fn set_signed(&mut self, op: bool);
+
+    // DSP multiplies: true when <x> (rm) or <y> (rs) selects the top halfword
+    fn get_top_x(&self) -> Option<bool>;
+    fn set_top_x(&mut self, op: bool);
+    fn get_top_y(&self) -> Option<bool>;
+    fn set_top_y(&mut self, op: bool);
+
+    // true for QDADD/QDSUB, which saturate rn * 2 before the add or subtract
+    fn get_double(&self) -> Option<bool>;
+    fn set_double(&mut self, op: bool);
+
+    // coprocessor number, opcodes and coprocessor registers of MCR/MRC, CDP and LDC/STC
+    fn get_coprocessor(&self) -> Option<CoprocessorOp>;
+    fn set_coprocessor(&mut self, op: CoprocessorOp);
+
+    fn get_pc_address(&self) -> AddressSize;
+    fn set_pc_address(&mut self, address: AddressSize);
+
+    // true when decoded from a 16-bit Thumb instruction
+    fn get_thumb(&self) -> bool;
+    fn set_thumb(&mut self, thumb: bool);
+
+    // the encoding of an undefined instruction, shown by the disassembly
+    fn get_raw(&self) -> Option<Word>;
+    fn set_raw(&mut self, raw: Word);
+
+    // shift value in immediate_shift field by shift amount
+    // https://developer.arm.com/documentation/dui0489/i/arm-and-thumb-instructions/operand2-as-a-register-with-optional-shift?lang=en
+    // ARM Manual A5.1
+    fn shift_value_by_imm(rm: Word, shift_imm: Word, shift_type: ShiftType, c_flag: Byte) -> (Word, Word) {
+        let rm_msb = util::get_bit(rm, 31);
+        match shift_type {
+            ShiftType::LSL => {
+                if shift_imm == 0 {
+                    (rm, c_flag as Word)
+                } else {
+                    (rm << shift_imm, util::get_bit(rm, 32 - shift_imm))
+                }
+            },
+            ShiftType::LSR => {
+                if shift_imm == 0 {
+                    (0, rm_msb)
+                } else {
+                    (rm >> shift_imm, util::get_bit(rm, shift_imm - 1))
+                }
+            },
+            ShiftType::ASR => {
+                if shift_imm == 0 {
+                    if rm_msb == 0 {
+                        (0, rm_msb)
+                    } else {
+                        (0xffffffff, rm_msb)
+                    }
+                } else {
+                    // cast to signed to force ASR in Rust
+                    (((rm as SignedWord) >> shift_imm) as Word, util::get_bit(rm, shift_imm - 1))
+                }
+            },
+            ShiftType::ROR if shift_imm != 0 => (rm.rotate_right(shift_imm), util::get_bit(rm, shift_imm - 1)),
+            // ROR #0 encodes RRX
+            ShiftType::ROR | ShiftType::RRX => (((c_flag as Word & 1) << 31) | (rm >> 1), rm & 1)
+        }
+    }
+
+    fn shift_value_by_reg(rm: Word, rs: Word, shift_type: ShiftType, c_flag: Byte) -> (Word, Word) {
+        let rs_lsb = rs & 0xff;
+        let rs_rotate = rs & 0x1f;
+        let rm_msb = util::get_bit(rm, 31);
+
+        match shift_type {
+            ShiftType::LSL => {
+                if rs_lsb == 0 {
+                    (rm, c_flag as Word)
+                } else if rs_lsb < 32 {
+                    (rm << rs_lsb, util::get_bit(rm, 32 - rs_lsb))
+                } else if rs_lsb == 32 {
+                    (0, rm & 1)
+                } else {
+                    (0, 0)
+                }
+            },
+            ShiftType::LSR => {
+                if rs_lsb == 0 {
+                    (rm, c_flag as Word)
+                } else if rs_lsb < 32 {
+                    (rm >> rs_lsb, util::get_bit(rm, rs_lsb - 1))
+                } else if rs_lsb == 32 {
+                    (0, rm_msb)
+                } else {
+                    (0, 0)
+                }
+            },
+            ShiftType::ASR => {
+                if rs_lsb == 0 {
+                    (rm, c_flag as Word)
+                } else if rs_lsb < 32 {
+                    // cast to signed to force ASR in Rust
+                    (((rm as SignedWord) >> rs_lsb) as Word, util::get_bit(rm, rs_lsb - 1))
+                } else {
+                    if rm_msb == 0 {
+                        (0, rm_msb)
+                    } else {
+                        (0xffffffff, rm_msb)
+                    }
+                }
+            },
+            ShiftType::ROR => {
+                if rs_lsb == 0 {
+                    (rm, c_flag as Word)
+                } else if rs_rotate == 0 {
+                    (rm, rm_msb)
+                } else {
+                    (rm.rotate_right(rs_rotate), util::get_bit(rm, rs_rotate - 1))
+                }
+            },
+            // only immediate shifts encode RRX
+            ShiftType::RRX => Self::shift_value_by_imm(rm, 0, ShiftType::RRX, c_flag)
+        }
+    }
+
+    // tuple: (shifter_operand, shifter_carry_out)
+    // A5.1.3
+    fn rotate_value(rotate_imm: Byte, immed_8: Byte, c_flag: Byte) -> (Word, Word) {
+        let immediate_to_rotate: Word = (immed_8 & 0xff) as Word;
+        let rotate_amount: Word = ((rotate_imm as Byte) * 2) as Word;
+        let shifter_operand = immediate_to_rotate.rotate_right(rotate_amount);
+        if rotate_imm == 0 {
+            (shifter_operand, c_flag as Word)
+        } else {
+            (shifter_operand, util::get_bit(shifter_operand, 31))
+        }
+    }
+
+    fn get_rd(&self) -> Option<Register>;
+    fn set_rd(&mut self, rd: Word);
+    fn get_rn(&self) -> Option<Register>;
+    fn set_rn(&mut self, rn: Word);
+    fn get_rm(&self) -> Option<Register>;
+    fn set_rm(&mut self, rm: Word);
+    fn get_rs(&self) -> Option<Register>;
+    fn set_rs(&mut self, rs: Word);
+
+    // NZCV flag
+    fn set_condition(&mut self, cond: Word);
+    fn get_condition(&self) -> Condition;
+}
+
+// the Instruction struct will hold flags for all possible Instruction types
+// to simplify how instructions are passed around the CPU; it increases the size
+// of the Instruction struct, but most of the data in the struct are just
+// Options with minimal data
+
+#[derive(Clone, Copy)]
+pub struct Instruction {
+    _type: InstrType,
+    execute: Option<fn(&mut RAM, &mut Registers, Instruction) -> InstrExecuteCondition>,
+    rn: Option<Register>,
+    rd: Option<Register>,
+    rm: Option<Register>,
+    rs: Option<Register>,
+    condition: Condition,
+    data_opcode: Option<DataOpcode>,
+    s_bit: Option<bool>,
+    shift_type: Option<ShiftType>,
+    imm_shift: Option<Word>,
+    rotate: Option<Byte>,
+    imm: Option<Byte>,
+    writeback: Option<bool>,
+    addr_mode: Option<bool>,
+    swi: Option<Word>,
+    add_sub: Option<bool>,
+    byte_word: Option<bool>,
+    ldr_str: Option<bool>,
+    l_bit: Option<bool>,
+    offset: Option<SignedWord>,
+    lsh: Option<LSH>,
+    ldm: Option<LDMCode>,
+    reg_list: Option<Word>,
+    gpregister: Option<bool>,
+    field_mask: Option<Byte>,
+    accumulate: Option<bool>,
+    signed: Option<bool>,
+    top_x: Option<bool>,
+    top_y: Option<bool>,
+    double: Option<bool>,
+    coprocessor: Option<CoprocessorOp>,
+    pc_address: Word,
+    thumb: bool,
+    raw: Option<Word>,
+}
+
+impl TInstruction for Instruction {
+    fn new(_type: InstrType) -> Self {
+        Instruction {
+            _type: _type,
+            execute: None,
+            rn: None,
+            rd: None,
+            rm: None,
+            rs: None,
+            condition: Condition::AL,
+            data_opcode: None,
+            s_bit: None,
+            shift_type: None,
+            imm_shift: None,
+            rotate: None,
+            imm: None,
+            writeback: None,
+            addr_mode: None,
+            swi: None,
+            add_sub: None,
+            byte_word: None,
+            ldr_str: None,
+            l_bit: None,
+            offset: None,
+            lsh: None,
+            ldm: None,
+            reg_list: None,
+            gpregister: None,
+            field_mask: None,
+            accumulate: None,
+            signed: None,
+            top_x: None,
+            top_y: None,
+            double: None,
+            coprocessor: None,
+            pc_address: 0,
+            thumb: false,
+            raw: None
+        }
+    }
+
+    fn get_type(&self) -> InstrType {
+        self._type
+    }
+
+    fn set_type(&mut self, _type: InstrType) {
+        self._type = _type;
+    }
+
+    fn decode(&self) {
+        todo!()
+    }
+
+    fn encode(&self) -> Word {
+        encode::encode(self)
+    }
+    
+    fn get_name(&self) -> String {
+        todo!()
+    }
+
+    fn get_rd(&self) -> Option<Register> {
+        self.rd
+    }
+
+    fn set_rd(&mut self, rd: Word) {
+        let rd: Register = num::FromPrimitive::from_u32(rd).unwrap();
+        self.rd = Some(rd);
+    }
+
+    fn get_rn(&self) -> Option<Register> {
+        self.rn
+    }
+
+    fn set_rn(&mut self, rn: Word) {
+        let rn: Register = num::FromPrimitive::from_u32(rn).unwrap();
+        self.rn = Some(rn);
+    }
+
+    fn get_rm(&self) -> Option<Register> {
+        self.rm
+    }
+
+    fn set_rm(&mut self, rm: Word) {
+        let rm: Register = num::FromPrimitive::from_u32(rm).unwrap();
+        self.rm = Some(rm);
+    }
+
+    fn get_rs(&self) -> Option<Register> {
+        self.rs
+    }
+
+    fn set_rs(&mut self, rs: Word) {
+        let rs: Register = num::FromPrimitive::from_u32(rs).unwrap();
+        self.rs = Some(rs);
+    }
+
+    fn set_condition(&mut self, cond: Word) {
+        let condition: Condition = num::FromPrimitive::from_u32(cond).unwrap();
+        self.condition = condition;
+    }
+
+    fn get_condition(&self) -> Condition {
+        self.condition
+    }
+
+    fn get_data_opcode(&self) -> Option<DataOpcode> {
+        self.data_opcode
+    }
+
+    fn set_data_opcode(&mut self, code: Word) {
+        let opcode: DataOpcode = num::FromPrimitive::from_u32(code).unwrap();
+        self.data_opcode = Some(opcode);
+    }
+
+    fn get_s_bit(&self) -> Option<bool> {
+        self.s_bit
+    }
+
+    fn set_s_bit(&mut self, bit: Word) {
+        match bit & 0x1 {
+            0 => self.s_bit = Some(false),
+            1 => self.s_bit = Some(true),
+            _ => self.s_bit = None
+        }
+    }
+
+    fn get_shift_type(&self) -> Option<ShiftType> {
+        self.shift_type
+    }
+
+    fn set_shift_type(&mut self, sh: Word) {
+        let shift: ShiftType = num::FromPrimitive::from_u32(sh).unwrap();
+        self.shift_type = Some(shift);
+    }
+
+    fn get_imm_shift(&self) -> Option<Word> {
+        self.imm_shift
+    }
+
+    fn set_imm_shift(&mut self, imm: Word) {
+        self.imm_shift = Some(imm);
+    }
+
+    fn get_imm(&self) -> Option<Byte> {
+        self.imm
+    }
+
+    fn set_imm(&mut self, imm: Byte) {
+        self.imm = Some(imm);
+    }
+
+    fn set_execute(&mut self, f: fn(&mut RAM, &mut Registers, Instruction) -> InstrExecuteCondition) {
+        self.execute = Some(f);
+    }
+
+    fn get_execute(&self) -> fn(&mut RAM, &mut Registers, Instruction) -> InstrExecuteCondition {
+        self.execute.unwrap()
+    }
+
+    fn get_rotate(&self) -> Option<Byte> {
+        self.rotate
+    }
+
+    fn set_rotate(&mut self, rotate: Byte) {
+        self.rotate = Some(rotate)
+    }
+
+    fn get_writeback(&self) -> Option<bool> {
+        self.writeback
+    }
+
+    fn set_writeback(&mut self, op: bool) {
+        self.writeback = Some(op)
+    }
+
+    fn get_addr_mode(&self) -> Option<bool> {
+        self.addr_mode
+    }
+
+    fn set_addr_mode(&mut self, op: bool) {
+        self.addr_mode = Some(op)
+    }
+
+    fn get_swi(&self) -> Option<Word> {
+        self.swi
+    }
+
+    fn set_swi(&mut self, num: Word) {
+        self.swi = Some(num)
+    }
+
+    fn get_add_sub(&self) -> Option<bool> {
+        self.add_sub
+    }
+
+    fn set_add_sub(&mut self, op: bool) {
+        self.add_sub = Some(op)
+    }
+
+    fn get_byte_word(&self) -> Option<bool> {
+        self.byte_word
+    }
+
+    fn set_byte_word(&mut self, op: bool) {
+        self.byte_word = Some(op)
+    }
+
+    fn get_ldr_str(&self) -> Option<bool> {
+        self.ldr_str
+    }
+
+    fn set_ldr_str(&mut self, op: bool) {
+        self.ldr_str = Some(op)
+    }
+
+    fn get_l_bit(&self) -> Option<bool> {
+        self.l_bit
+    }
+
+    fn set_l_bit(&mut self, bit: Word) {
+        match bit & 0x1 {
+            0 => self.l_bit = Some(false),
+            1 => self.l_bit = Some(true),
+            _ => self.l_bit = None
+        }
+    }
+
+    fn get_offset(&self) -> Option<SignedWord> {
+        self.offset
+    }
+
+    fn set_offset(&mut self, offset: SignedWord) {
+        self.offset = Some(offset)
+    }
+
+    fn get_lsh(&self) -> Option<LSH> {
+        self.lsh
+    }
+
+    fn set_lsh(&mut self, lsh: Word) {
+        let lsh_code: LSH = num::FromPrimitive::from_u32(lsh).unwrap();
+        self.lsh = Some(lsh_code);
+    }
+
+    fn get_ldm(&self) -> Option<LDMCode> {
+        self.ldm
+    }
+
+    fn set_ldm(&mut self, ldm: Word) {
+        let ldm_code: LDMCode = num::FromPrimitive::from_u32(ldm).unwrap();
+        self.ldm = Some(ldm_code);
+    }
+
+    fn get_reg_list(&self) -> Option<Word> {
+        self.reg_list
+    }
+
+    fn set_reg_list(&mut self, reg_list: Word) {
+        self.reg_list = Some(reg_list);
+    }
+
+
+    fn get_gpregister(&self) -> Option<bool> {
+        self.gpregister
+    }
+
+    fn set_gpregister(&mut self, gpregister: bool) {
+        self.gpregister = Some(gpregister);
+    }
+
+    fn get_field_mask(&self) -> Option<Byte> {
+        self.field_mask
+    }
+
+    fn set_field_mask(&mut self, field_mask: Byte) {
+        self.field_mask = Some(field_mask);
+    }
+
+    fn get_pc_address(&self) -> AddressSize {
+        self.pc_address
+    }
+
+    fn set_pc_address(&mut self, address: AddressSize) {
+        self.pc_address = address;
+    }
+
+    fn get_accumulate(&self) -> Option<bool> {
+        self.accumulate
+    }
+
+    fn set_accumulate(&mut self, op: bool) {
+        self.accumulate = Some(op);
+    }
+
+    fn get_signed(&self) -> Option<bool> {
+        self.signed
+    }
+
+    fn set_signed(&mut self, op: bool) {
+        self.signed = Some(op);
+    }
+
+    fn get_top_x(&self) -> Option<bool> {
+        self.top_x
+    }
+
+    fn set_top_x(&mut self, op: bool) {
+        self.top_x = Some(op);
+    }
+
+    fn get_top_y(&self) -> Option<bool> {
+        self.top_y
+    }
+
+    fn set_top_y(&mut self, op: bool) {
+        self.top_y = Some(op);
+    }
+
+    fn get_double(&self) -> Option<bool> {
+        self.double
+    }
+
+    fn set_double(&mut self, op: bool) {
+        self.double = Some(op);
+    }
+
+    fn get_coprocessor(&self) -> Option<CoprocessorOp> {
+        self.coprocessor
+    }
+
+    fn set_coprocessor(&mut self, op: CoprocessorOp) {
+        self.coprocessor = Some(op);
+    }
+
+    fn get_thumb(&self) -> bool {
+        self.thumb
+    }
+
+    fn set_thumb(&mut self, thumb: bool) {
+        self.thumb = thumb;
+    }
+
+    fn get_raw(&self) -> Option<Word> {
+        self.raw
+    }
+
+    fn set_raw(&mut self, raw: Word) {
+        self.raw = Some(raw);
+    }
+}
+
+/*
+Individual type constructs
+*/
+
+pub trait TTypeData: TInstruction {
+    fn get_opcode(&self) -> DataOpcode;
+    fn get_s_bit(&self) -> bool;
+    fn get_shift_type(&self) -> ShiftType;
+}
+
+/*
+Individual instruction factories
+yay java
+
+Each factory takes the bit patterns from the bitmatcher in the CPU decode step
+and produces an Instruction struct with all the necessary methods attached
+*/
+
+// an immediate shift of ROR #0 is RRX, a rotate right by one through the carry flag
+fn imm_shift_type(shift_type: Word, imm: Word) -> Word {
+    if shift_type == ShiftType::ROR as Word && imm == 0 {
+        ShiftType::RRX as Word
+    } else {
+        shift_type
+    }
+}
+
+pub fn instr_data_reg_imm(condition: Word, opcode: Word, s_bit: Word, rn: Word, rd: Word, imm: Word, shift_type: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::DataRegImm);
+    instr.set_condition(condition);
+    instr.set_data_opcode(opcode);
+    instr.set_s_bit(s_bit);
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_imm_shift(imm);
+    instr.set_shift_type(imm_shift_type(shift_type, imm));
+    instr.set_rm(rm);
+
+    instr.set_execute(execute::instr_data_reg_imm);
+
+    instr
+}
+
+pub fn instr_data_reg_reg(condition: Word, opcode: Word, s_bit: Word, rn: Word, rd: Word, rs: Word, shift_type: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::DataRegReg);
+    instr.set_condition(condition);
+    instr.set_data_opcode(opcode);
+    instr.set_s_bit(s_bit);
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_rs(rs);
+    instr.set_shift_type(shift_type);
+    instr.set_rm(rm);
+
+    instr.set_execute(execute::instr_data_reg_reg);
+
+    instr
+}
+
+pub fn instr_data_imm(condition: Word, opcode: Word, s_bit: Word, rn: Word, rd: Word, rotate: Word, imm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::DataImm);
+    instr.set_condition(condition);
+    instr.set_data_opcode(opcode);
+    instr.set_s_bit(s_bit);
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_rotate(rotate as Byte);
+    instr.set_imm(imm as Byte);
+    instr.set_execute(execute::instr_data_imm);
+
+    instr
+}
+
+pub fn instr_ldrstr_shifted_reg_pre(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, imm: Word, shift_type: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRSTRShiftRegPre);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(true);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_imm_shift(imm);
+    instr.set_shift_type(imm_shift_type(shift_type, imm));
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_ldrstr_shifted_reg_pre);
+
+    instr
+}
+
+pub fn instr_ldrstr_shifted_reg_post(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, imm: Word, shift_type: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRSTRShiftRegPost);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(false);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_imm_shift(imm);
+    instr.set_shift_type(imm_shift_type(shift_type, imm));
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_ldrstr_shifted_reg_post);
+
+    instr
+}
+
+pub fn instr_ldrstr_reg_pre(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRSTRRegPre);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(true);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_ldrstr_reg_pre);
+
+    instr
+}
+
+pub fn instr_ldrstr_reg_post(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRSTRRegPost);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(false);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_ldrstr_reg_post);
+
+    instr
+}
+
+pub fn instr_ldrstr_imm_pre(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, imm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRSTRImmPre);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(true);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_imm_shift(imm);
+    instr.set_execute(execute::instr_ldrstr_imm_pre);
+
+    instr
+}
+
+pub fn instr_ldrstr_imm_post(condition: Word, add_sub: Word, byte_word: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, imm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRSTRImmPost);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(false);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_imm_shift(imm);
+    instr.set_execute(execute::instr_ldrstr_imm_post);
+
+    instr
+}
+
+// LDRD/STRD transfer rd and rd + 1, so rd must be even and not r14; other pairs are
+//  unpredictable and are treated as undefined instructions
+fn check_register_pair(instr: Instruction) -> Instruction {
+    match instr.get_lsh().unwrap() {
+        LSH::LdrDoubleWord | LSH::StrDoubleWord => {
+            let rd = instr.get_rd().unwrap();
+            if rd as Word % 2 == 1 || rd == Register::r14 {
+                return instr_undefined();
+            }
+
+            instr
+        },
+        _ => instr
+    }
+}
+
+pub fn instr_ldrhstrh_imm_pre(condition: Word, add_sub: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, high_bits: Word, lsh: Word, low_bits: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRHSTRHImmPre);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(true);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_lsh((ldr_str << 2) | lsh);
+    instr.set_imm(((high_bits as Byte) << 4) | (low_bits as Byte));
+    instr.set_execute(execute::instr_ldrhstrh_imm_pre);
+
+    check_register_pair(instr)
+}
+
+pub fn instr_ldrhstrh_imm_post(condition: Word, add_sub: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, high_bits: Word, lsh: Word, low_bits: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRHSTRHImmPost);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(false);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_lsh((ldr_str << 2) | lsh);
+    instr.set_imm(((high_bits as Byte) << 4) | (low_bits as Byte));
+    instr.set_execute(execute::instr_ldrhstrh_imm_post);
+
+    check_register_pair(instr)
+}
+
+pub fn instr_ldrhstrh_reg_pre(condition: Word, add_sub: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, lsh: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRHSTRHRegPre);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(true);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_lsh((ldr_str << 2) | lsh);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_ldrhstrh_reg_pre);
+
+    check_register_pair(instr)
+}
+
+pub fn instr_ldrhstrh_reg_post(condition: Word, add_sub: Word, writeback: Word, ldr_str: Word, rn: Word, rd: Word, lsh: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDRHSTRHRegPost);
+    instr.set_condition(condition); 
+    instr.set_addr_mode(false);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_lsh((ldr_str << 2) | lsh);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_ldrhstrh_reg_post);
+
+    check_register_pair(instr)
+}
+
+pub fn instr_b(condition: Word, l_bit: Word, offset: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::B);
+    instr.set_condition(condition); 
+    instr.set_l_bit(l_bit);
+
+    // sign extend manually
+    // if top bit is 1, extend
+    instr.set_offset(
+        if util::test_bit(offset, 23) {
+            ((offset | 0x3f000000) << 2) as SignedWord
+        } else {
+            (offset << 2) as SignedWord
+        }
+    );
+    instr.set_execute(execute::instr_b);
+
+    instr
+}
+
+pub fn instr_bx(condition: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::BX);
+    instr.set_condition(condition); 
+    instr.set_l_bit(0);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_bx);
+
+    instr
+}
+
+pub fn instr_ldmstm(condition: Word, ldm_code: Word, s_bit: Word, writeback: Word, ldr_str: Word, rn: Word, reg_list: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::LDMSTM);
+    instr.set_condition(condition); 
+    instr.set_ldm(ldm_code);
+    instr.set_s_bit(s_bit);
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_reg_list(reg_list);
+    instr.set_execute(execute::instr_ldmstm);
+
+    instr
+}
+
+// MUL and MLA; rn is the accumulate register
+pub fn instr_mul(condition: Word, accumulate: Word, s_bit: Word, rd: Word, rn: Word, rs: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::MUL);
+    instr.set_condition(condition);
+    instr.set_accumulate(util::word_lsb_to_bool(accumulate));
+    instr.set_s_bit(s_bit);
+    instr.set_rd(rd);
+    instr.set_rn(rn);
+    instr.set_rs(rs);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_mul);
+
+    instr
+}
+
+// UMULL, UMLAL, SMULL and SMLAL; rd holds RdHi and rn holds RdLo
+pub fn instr_mull(condition: Word, signed: Word, accumulate: Word, s_bit: Word, rd_hi: Word, rd_lo: Word, rs: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::MULL);
+    instr.set_condition(condition);
+    instr.set_signed(util::word_lsb_to_bool(signed));
+    instr.set_accumulate(util::word_lsb_to_bool(accumulate));
+    instr.set_s_bit(s_bit);
+    instr.set_rd(rd_hi);
+    instr.set_rn(rd_lo);
+    instr.set_rs(rs);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_mull);
+
+    instr
+}
+
+pub fn instr_swp(condition: Word, byte_word: Word, rn: Word, rd: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::SWP);
+    instr.set_condition(condition);
+    instr.set_byte_word(util::word_lsb_to_bool(byte_word));
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_swp);
+
+    instr
+}
+
+pub fn instr_swi(condition: Word, swi: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::SWI);
+    instr.set_condition(condition);
+    instr.set_swi(swi);
+    instr.set_execute(execute::instr_swi);
+
+    instr
+}
+
+pub fn instr_mrs(condition: Word, gpregister: Word, rd: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::MRS);
+
+    instr.set_condition(condition);
+    instr.set_gpregister(gpregister != 0);
+    instr.set_rd(rd);
+    instr.set_execute(execute::instr_mrs);
+
+    instr
+}
+
+pub fn instr_msr_imm(condition: Word, gpregister: Word, field_mask: Word, rotate_imm: Word, imm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::MSRImm);
+
+    instr.set_condition(condition);
+    instr.set_gpregister(gpregister != 0);
+    instr.set_field_mask(field_mask as Byte);
+    instr.set_rotate(rotate_imm as Byte);
+    instr.set_imm(imm as Byte);
+    instr.set_execute(execute::instr_msr_imm);
+
+    instr
+}
+
+pub fn instr_msr_reg(condition: Word, gpregister: Word, field_mask: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::MSRReg);
+
+    instr.set_condition(condition);
+    instr.set_gpregister(gpregister != 0);
+    instr.set_field_mask(field_mask as Byte);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_msr_reg);
+
+    instr
+}
+
+pub fn instr_nop() -> Instruction {
+    let mut instr = Instruction::new(InstrType::NOP);
+
+    instr.set_execute(execute::instr_nop);
+
+    instr
+}
+
+// any encoding the decoder does not recognize; it always raises an Undefined Instruction exception
+//  since the condition field may itself be unallocated (0b1111)
+pub fn instr_undefined() -> Instruction {
+    let mut instr = Instruction::new(InstrType::Undefined);
+
+    instr.set_execute(execute::instr_undefined);
+
+    instr
+}
+
+/*
+Coprocessor instruction factories
+
+Executed by the coprocessor attached at the coprocessor number (RAM::coprocessors)
+*/
+
+// MCR (l = 0) and MRC (l = 1)
+pub fn instr_mcr_mrc(condition: Word, opcode_1: Word, l_bit: Word, crn: Word, rd: Word, coprocessor: Word, opcode_2: Word, crm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::MCRMRC);
+    instr.set_condition(condition);
+    instr.set_l_bit(l_bit);
+    instr.set_rd(rd);
+    instr.set_coprocessor(CoprocessorOp { coprocessor, opcode_1, opcode_2, crd: 0, crn, crm, long: false });
+    instr.set_execute(execute::instr_mcr_mrc);
+
+    instr
+}
+
+pub fn instr_cdp(condition: Word, opcode_1: Word, crn: Word, crd: Word, coprocessor: Word, opcode_2: Word, crm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::CDP);
+    instr.set_condition(condition);
+    instr.set_coprocessor(CoprocessorOp { coprocessor, opcode_1, opcode_2, crd, crn, crm, long: false });
+    instr.set_execute(execute::instr_cdp);
+
+    instr
+}
+
+// LDC/STC; the offset is a word count, P = 0 and W = 0 is the unindexed form
+//  where the offset is an option passed to the coprocessor instead
+pub fn instr_ldcstc(condition: Word, addr_mode: Word, add_sub: Word, long: Word, writeback: Word, ldr_str: Word, rn: Word, crd: Word, coprocessor: Word, imm: Word) -> Instruction {
+    // P = 0, U = 0, W = 0 is undefined
+    if addr_mode == 0 && add_sub == 0 && writeback == 0 {
+        return instr_undefined()
+    }
+
+    let mut instr = Instruction::new(InstrType::LDCSTC);
+    instr.set_condition(condition);
+    instr.set_addr_mode(util::word_lsb_to_bool(addr_mode));
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_writeback(util::word_lsb_to_bool(writeback));
+    instr.set_ldr_str(util::word_lsb_to_bool(ldr_str));
+    instr.set_rn(rn);
+    instr.set_imm(imm as Byte);
+    instr.set_coprocessor(CoprocessorOp { coprocessor, crd, long: util::word_lsb_to_bool(long), ..Default::default() });
+    instr.set_execute(execute::instr_ldcstc);
+
+    instr
+}
+
+/*
+ARMv5TE instruction factories
+
+Only decoded when the CPU targets Architecture::ARMv5TE (CPU::decode_v5te)
+*/
+
+// BLX <target>; the H bit adds a halfword so the Thumb target can be any halfword address
+pub fn instr_blx_imm(h: Word, offset: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::BLX);
+    instr.set_l_bit(1);
+    instr.set_offset((util::sign_extend(offset, 24) << 2) | (h << 1) as SignedWord);
+    instr.set_execute(execute::instr_blx);
+
+    instr
+}
+
+// BLX Rm: BX that also writes the return address to LR
+pub fn instr_blx_reg(condition: Word, rm: Word) -> Instruction {
+    let mut instr = instr_bx(condition, rm);
+    instr.set_l_bit(1);
+
+    instr
+}
+
+pub fn instr_clz(condition: Word, rd: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::CLZ);
+    instr.set_condition(condition);
+    instr.set_rd(rd);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_clz);
+
+    instr
+}
+
+// QADD, QSUB, QDADD and QDSUB; bit 0 of op selects subtract and bit 1 doubles rn
+pub fn instr_qadd(condition: Word, op: Word, rn: Word, rd: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::QADD);
+    instr.set_condition(condition);
+    instr.set_add_sub(op & 0b01 == 0);
+    instr.set_double(op & 0b10 != 0);
+    instr.set_rn(rn);
+    instr.set_rd(rd);
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_qadd);
+
+    instr
+}
+
+// SMLA<x><y> and SMUL<x><y>; rn is the accumulate register
+pub fn instr_smulxy(condition: Word, accumulate: Word, rd: Word, rn: Word, rs: Word, y: Word, x: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::SMULxy);
+    instr.set_condition(condition);
+    instr.set_accumulate(util::word_lsb_to_bool(accumulate));
+    instr.set_rd(rd);
+    instr.set_rn(rn);
+    instr.set_rs(rs);
+    instr.set_top_y(util::word_lsb_to_bool(y));
+    instr.set_top_x(util::word_lsb_to_bool(x));
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_smulxy);
+
+    instr
+}
+
+// SMLAW<y> and SMULW<y>: rm by a halfword of rs, keeping the top 32 bits of the 48-bit product
+pub fn instr_smulwy(condition: Word, accumulate: Word, rd: Word, rn: Word, rs: Word, y: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::SMULWy);
+    instr.set_condition(condition);
+    instr.set_accumulate(util::word_lsb_to_bool(accumulate));
+    instr.set_rd(rd);
+    instr.set_rn(rn);
+    instr.set_rs(rs);
+    instr.set_top_y(util::word_lsb_to_bool(y));
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_smulwy);
+
+    instr
+}
+
+// SMLAL<x><y>; as with instr_mull, rd holds RdHi and rn holds RdLo
+pub fn instr_smlalxy(condition: Word, rd_hi: Word, rd_lo: Word, rs: Word, y: Word, x: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::SMLALxy);
+    instr.set_condition(condition);
+    instr.set_rd(rd_hi);
+    instr.set_rn(rd_lo);
+    instr.set_rs(rs);
+    instr.set_top_y(util::word_lsb_to_bool(y));
+    instr.set_top_x(util::word_lsb_to_bool(x));
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_smlalxy);
+
+    instr
+}
+
+// PLD is a cache hint; the address is only kept for the disassembly
+pub fn instr_pld_imm(add_sub: Word, rn: Word, imm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::PLD);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_rn(rn);
+    instr.set_imm_shift(imm);
+    instr.set_execute(execute::instr_nop);
+
+    instr
+}
+
+pub fn instr_pld_reg(add_sub: Word, rn: Word, imm: Word, shift_type: Word, rm: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::PLD);
+    instr.set_add_sub(util::word_lsb_to_bool(add_sub));
+    instr.set_rn(rn);
+    instr.set_imm_shift(imm);
+    instr.set_shift_type(imm_shift_type(shift_type, imm));
+    instr.set_rm(rm);
+    instr.set_execute(execute::instr_nop);
+
+    instr
+}
+
+/*
+Thumb instruction factories
+
+Every Thumb-1 instruction has an ARM equivalent (A6.1), so most of these build the
+equivalent ARM instruction and only mark it as Thumb for the disassembler; the ones
+that read or write the PC differently in Thumb state get their own executors
+*/
+
+const THUMB_CONDITION: Word = Condition::AL as Word;
+
+fn thumb(mut instr: Instruction) -> Instruction {
+    instr.set_thumb(true);
+    instr
+}
+
+// LSL/LSR/ASR Rd, Rm, #imm5 => MOVS Rd, Rm, <shift> #imm5
+pub fn thumb_instr_shift_imm(shift_type: Word, imm: Word, rm: Word, rd: Word) -> Instruction {
+    thumb(instr_data_reg_imm(THUMB_CONDITION, DataOpcode::MOV as Word, 1, 0, rd, imm, shift_type, rm))
+}
+
+// ADD/SUB Rd, Rn, Rm => ADDS/SUBS Rd, Rn, Rm
+pub fn thumb_instr_add_sub_reg(sub: Word, rm: Word, rn: Word, rd: Word) -> Instruction {
+    let opcode = if sub == 1 { DataOpcode::SUB } else { DataOpcode::ADD };
+    thumb(instr_data_reg_imm(THUMB_CONDITION, opcode as Word, 1, rn, rd, 0, ShiftType::LSL as Word, rm))
+}
+
+// ADD/SUB Rd, Rn, #imm3 => ADDS/SUBS Rd, Rn, #imm3
+pub fn thumb_instr_add_sub_imm(sub: Word, imm: Word, rn: Word, rd: Word) -> Instruction {
+    let opcode = if sub == 1 { DataOpcode::SUB } else { DataOpcode::ADD };
+    thumb(instr_data_imm(THUMB_CONDITION, opcode as Word, 1, rn, rd, 0, imm))
+}
+
+// MOV/CMP/ADD/SUB Rd, #imm8 => MOVS/CMP/ADDS/SUBS Rd, Rd, #imm8
+pub fn thumb_instr_data_imm(opcode: Word, rd: Word, imm: Word) -> Instruction {
+    let opcode = match opcode {
+        0b00 => DataOpcode::MOV,
+        0b01 => DataOpcode::CMP,
+        0b10 => DataOpcode::ADD,
+        _    => DataOpcode::SUB
+    };
+    thumb(instr_data_imm(THUMB_CONDITION, opcode as Word, 1, rd, rd, 0, imm))
+}
+
+// format 4 ALU operations on the low registers; the opcodes that are not ARM
+//  data-processing opcodes map to a register shift, RSBS #0 or MULS
+pub fn thumb_instr_alu(opcode: Word, rm: Word, rd: Word) -> Instruction {
+    let instr = match opcode {
+        0b0010 => instr_data_reg_reg(THUMB_CONDITION, DataOpcode::MOV as Word, 1, 0, rd, rm, ShiftType::LSL as Word, rd),
+        0b0011 => instr_data_reg_reg(THUMB_CONDITION, DataOpcode::MOV as Word, 1, 0, rd, rm, ShiftType::LSR as Word, rd),
+        0b0100 => instr_data_reg_reg(THUMB_CONDITION, DataOpcode::MOV as Word, 1, 0, rd, rm, ShiftType::ASR as Word, rd),
+        0b0111 => instr_data_reg_reg(THUMB_CONDITION, DataOpcode::MOV as Word, 1, 0, rd, rm, ShiftType::ROR as Word, rd),
+        0b1001 => instr_data_imm(THUMB_CONDITION, DataOpcode::RSB as Word, 1, rm, rd, 0, 0), // NEG
+        0b1101 => instr_mul(THUMB_CONDITION, 0, 1, rd, 0, rd, rm),
+        // AND, EOR, ADC, SBC, TST, CMP, CMN, ORR, BIC and MVN share the ARM opcode numbers
+        _ => instr_data_reg_imm(THUMB_CONDITION, opcode, 1, rd, rd, 0, ShiftType::LSL as Word, rm)
+    };
+    thumb(instr)
+}
+
+// ADD/CMP/MOV with a high register; only CMP sets the flags
+pub fn thumb_instr_hi_reg(opcode: Word, h1: Word, rm: Word, rd: Word) -> Instruction {
+    let rd = (h1 << 3) | rd;
+    let (opcode, s_bit) = match opcode {
+        0b00 => (DataOpcode::ADD, 0),
+        0b01 => (DataOpcode::CMP, 1),
+        _    => (DataOpcode::MOV, 0)
+    };
+    // ADD/MOV pc branch through the ARM executor, as POP {pc} does
+    thumb(instr_data_reg_imm(THUMB_CONDITION, opcode as Word, s_bit, rd, rd, 0, ShiftType::LSL as Word, rm))
+}
+
+pub fn thumb_instr_bx(rm: Word) -> Instruction {
+    thumb(instr_bx(THUMB_CONDITION, rm))
+}
+
+// LDR Rd, [PC, #imm8 * 4]; the executor word-aligns the PC
+pub fn thumb_instr_ldr_pc(rd: Word, imm: Word) -> Instruction {
+    thumb(instr_ldrstr_imm_pre(THUMB_CONDITION, 1, 0, 0, 1, Register::r15 as Word, rd, imm << 2))
+}
+
+// LDR/STR/LDRB/STRB Rd, [Rn, Rm]
+pub fn thumb_instr_ldrstr_reg(ldr_str: Word, byte_word: Word, rm: Word, rn: Word, rd: Word) -> Instruction {
+    thumb(instr_ldrstr_reg_pre(THUMB_CONDITION, 1, byte_word, 0, ldr_str, rn, rd, rm))
+}
+
+// STRH/LDRH/LDRSB/LDRSH Rd, [Rn, Rm], selected by the H and S bits
+pub fn thumb_instr_ldrhstrh_reg(h: Word, s: Word, rm: Word, rn: Word, rd: Word) -> Instruction {
+    let (ldr_str, lsh) = match s {
+        0 => (h, 0b01),
+        _ => (1, 0b10 | h)
+    };
+    thumb(instr_ldrhstrh_reg_pre(THUMB_CONDITION, 1, 0, ldr_str, rn, rd, lsh, rm))
+}
+
+// LDR/STR Rd, [Rn, #imm5 * 4] and LDRB/STRB Rd, [Rn, #imm5]
+pub fn thumb_instr_ldrstr_imm(byte_word: Word, ldr_str: Word, imm: Word, rn: Word, rd: Word) -> Instruction {
+    let offset = if byte_word == 1 { imm } else { imm << 2 };
+    thumb(instr_ldrstr_imm_pre(THUMB_CONDITION, 1, byte_word, 0, ldr_str, rn, rd, offset))
+}
+
+// LDRH/STRH Rd, [Rn, #imm5 * 2]
+pub fn thumb_instr_ldrhstrh_imm(ldr_str: Word, imm: Word, rn: Word, rd: Word) -> Instruction {
+    let offset = imm << 1;
+    thumb(instr_ldrhstrh_imm_pre(THUMB_CONDITION, 1, 0, ldr_str, rn, rd, offset >> 4, 0b01, offset & 0xf))
+}
+
+// LDR/STR Rd, [SP, #imm8 * 4]
+pub fn thumb_instr_ldrstr_sp(ldr_str: Word, rd: Word, imm: Word) -> Instruction {
+    thumb(instr_ldrstr_imm_pre(THUMB_CONDITION, 1, 0, 0, ldr_str, Register::r13 as Word, rd, imm << 2))
+}
+
+// ADD Rd, PC/SP, #imm8 * 4; a rotate of 15 (ROR #30) is the same as LSL #2
+pub fn thumb_instr_add_pc_sp(sp: Word, rd: Word, imm: Word) -> Instruction {
+    let rn = if sp == 1 { Register::r13 } else { Register::r15 };
+    thumb(instr_data_imm(THUMB_CONDITION, DataOpcode::ADD as Word, 0, rn as Word, rd, 15, imm))
+}
+
+// ADD/SUB SP, #imm7 * 4
+pub fn thumb_instr_add_sp(sub: Word, imm: Word) -> Instruction {
+    let opcode = if sub == 1 { DataOpcode::SUB } else { DataOpcode::ADD };
+    thumb(instr_data_imm(THUMB_CONDITION, opcode as Word, 0, Register::r13 as Word, Register::r13 as Word, 15, imm))
+}
+
+// PUSH {reg_list, LR} => STMDB SP!, ... and POP {reg_list, PC} => LDMIA SP!, ...
+pub fn thumb_instr_push_pop(ldr_str: Word, r: Word, reg_list: Word) -> Instruction {
+    let instr = match ldr_str {
+        0 => instr_ldmstm(THUMB_CONDITION, LDMCode::DecBefore as Word, 0, 1, 0, Register::r13 as Word, reg_list | (r << 14)),
+        _ => instr_ldmstm(THUMB_CONDITION, LDMCode::IncAfter as Word, 0, 1, 1, Register::r13 as Word, reg_list | (r << 15))
+    };
+
+    thumb(instr)
+}
+
+// LDMIA/STMIA Rn!, {reg_list}
+pub fn thumb_instr_ldmstm(ldr_str: Word, rn: Word, reg_list: Word) -> Instruction {
+    thumb(instr_ldmstm(THUMB_CONDITION, LDMCode::IncAfter as Word, 0, 1, ldr_str, rn, reg_list))
+}
+
+pub fn thumb_instr_b_cond(condition: Word, offset: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::B);
+    instr.set_condition(condition);
+    instr.set_l_bit(0);
+    instr.set_offset(util::sign_extend(offset, 8) << 1);
+    instr.set_execute(execute::thumb_instr_b);
+
+    thumb(instr)
+}
+
+pub fn thumb_instr_b(offset: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::B);
+    instr.set_l_bit(0);
+    instr.set_offset(util::sign_extend(offset, 11) << 1);
+    instr.set_execute(execute::thumb_instr_b);
+
+    thumb(instr)
+}
+
+// BL is a pair of instructions: the prefix (H = 0) adds the high part of the offset
+//  to the PC in LR and the suffix (H = 1) adds the low part and branches
+pub fn thumb_instr_bl(h: Word, offset: Word) -> Instruction {
+    let mut instr = match h {
+        0 => {
+            let mut instr = Instruction::new(InstrType::ThumbBLPrefix);
+            instr.set_offset(util::sign_extend(offset, 11) << 12);
+            instr.set_execute(execute::thumb_instr_bl_prefix);
+            instr
+        },
+        _ => {
+            let mut instr = Instruction::new(InstrType::ThumbBLSuffix);
+            instr.set_offset((offset << 1) as SignedWord);
+            instr.set_execute(execute::thumb_instr_bl_suffix);
+            instr
+        }
+    };
+    instr.set_l_bit(1);
+
+    thumb(instr)
+}
+
+// ARMv5TE: BLX suffix; the target is word-aligned and entered in ARM state
+pub fn thumb_instr_blx_suffix(offset: Word) -> Instruction {
+    let mut instr = Instruction::new(InstrType::ThumbBLXSuffix);
+    instr.set_offset((offset << 1) as SignedWord);
+    instr.set_l_bit(1);
+    instr.set_execute(execute::thumb_instr_blx_suffix);
+
+    thumb(instr)
+}
+
+// ARMv5TE: BLX Rm
+pub fn thumb_instr_blx_reg(rm: Word) -> Instruction {
+    thumb(instr_blx_reg(THUMB_CONDITION, rm))
+}
+
+pub fn thumb_instr_swi(swi: Word) -> Instruction {
+    thumb(instr_swi(THUMB_CONDITION, swi))
+}
+
+pub fn thumb_instr_undefined() -> Instruction {
+    thumb(instr_undefined())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble_instr_data_imm() {
+        let instr = instr_data_imm(0b1110, 0b1101, 0b0, 0b0000, 0b0010, 0b0000, 0b110000);
+        assert_eq!("mov r2, #48", instr.to_string())
+    }
+
+    const SHIFT_TYPES: [ShiftType; 4] = [ShiftType::LSL, ShiftType::LSR, ShiftType::ASR, ShiftType::ROR];
+
+    // A5.1.5 - A5.1.12 written out with 64-bit arithmetic; amount is the bottom byte of Rs
+    fn reference_shift(rm: Word, amount: Word, shift_type: ShiftType, c_flag: Byte) -> (Word, Word) {
+        if amount == 0 {
+            return (rm, c_flag as Word)
+        }
+        let n = amount.min(63) as u64;
+        match shift_type {
+            ShiftType::LSL => ((((rm as u64) << n) & 0xffffffff) as Word, (((rm as u64) << n) >> 32 & 1) as Word),
+            ShiftType::LSR => (((rm as u64) >> n) as Word, (((rm as u64) << 1 >> n) & 1) as Word),
+            ShiftType::ASR => ((((rm as i32 as i64) >> n) as Word), (((rm as i32 as i64) >> (n.min(32) - 1)) & 1) as Word),
+            _ => {
+                let result = rm.rotate_right(amount % 32);
+                (result, result >> 31)
+            }
+        }
+    }
+
+    #[test]
+    fn test_shift_value() {
+        // (rm, shift, shift type, c flag, shifter operand, carry out)
+        let by_imm: [(Word, Word, ShiftType, Byte, Word, Word); 14] = [
+            (0x80000001, 0, ShiftType::LSL, 1, 0x80000001, 1),
+            (0x80000001, 1, ShiftType::LSL, 0, 0x00000002, 1),
+            (0x80000001, 31, ShiftType::LSL, 0, 0x80000000, 0),
+            (0x80000001, 0, ShiftType::LSR, 0, 0, 1),         // lsr #32
+            (0x80000001, 1, ShiftType::LSR, 0, 0x40000000, 1),
+            (0x80000001, 31, ShiftType::LSR, 0, 1, 0),
+            (0x80000000, 0, ShiftType::ASR, 0, 0xffffffff, 1), // asr #32
+            (0x7fffffff, 0, ShiftType::ASR, 1, 0, 0),
+            (0x80000002, 1, ShiftType::ASR, 0, 0xc0000001, 0),
+            (0x00000003, 1, ShiftType::ROR, 0, 0x80000001, 1),
+            (0x00000002, 31, ShiftType::ROR, 0, 0x00000004, 0),
+            (0x00000003, 0, ShiftType::ROR, 0, 0x00000001, 1), // rrx
+            (0x00000002, 0, ShiftType::ROR, 1, 0x80000001, 0),
+            (0x00000002, 0, ShiftType::RRX, 1, 0x80000001, 0),
+        ];
+        for (rm, shift, shift_type, c_flag, operand, carry) in by_imm {
+            assert_eq!(Instruction::shift_value_by_imm(rm, shift, shift_type, c_flag), (operand, carry), "{} #{} of 0x{:x}", shift_type, shift, rm);
+        }
+
+        let by_reg: [(Word, Word, ShiftType, Byte, Word, Word); 14] = [
+            (0x80000001, 0, ShiftType::LSL, 1, 0x80000001, 1),
+            (0x80000001, 32, ShiftType::LSL, 0, 0, 1),
+            (0x80000001, 33, ShiftType::LSL, 1, 0, 0),
+            (0x80000001, 0x100, ShiftType::LSL, 0, 0x80000001, 0), // only the bottom byte counts
+            (0x80000001, 32, ShiftType::LSR, 0, 0, 1),
+            (0x80000001, 33, ShiftType::LSR, 1, 0, 0),
+            (0x80000000, 32, ShiftType::ASR, 0, 0xffffffff, 1),
+            (0x80000000, 200, ShiftType::ASR, 0, 0xffffffff, 1),
+            (0x40000000, 255, ShiftType::ASR, 1, 0, 0),
+            (0x80000001, 0, ShiftType::ROR, 0, 0x80000001, 0),
+            (0x80000001, 32, ShiftType::ROR, 0, 0x80000001, 1),
+            (0x00000001, 33, ShiftType::ROR, 0, 0x80000000, 1),
+            (0x00010000, 16, ShiftType::ROR, 0, 0x00000001, 0),
+            (0x00008000, 48, ShiftType::ROR, 1, 0x80000000, 1),
+        ];
+        for (rm, rs, shift_type, c_flag, operand, carry) in by_reg {
+            assert_eq!(Instruction::shift_value_by_reg(rm, rs, shift_type, c_flag), (operand, carry), "{} by {} of 0x{:x}", shift_type, rs, rm);
+        }
+
+        // every type and amount against the reference
+        for rm in [0, 1, 0x7fffffff, 0x80000000, 0x80000001, 0xa5a5a5a5, 0xffffffff] {
+            for shift_type in SHIFT_TYPES {
+                for c_flag in [0, 1] {
+                    for amount in 0..=256 {
+                        let expected = reference_shift(rm, amount & 0xff, shift_type, c_flag);
+                        assert_eq!(Instruction::shift_value_by_reg(rm, amount, shift_type, c_flag), expected, "{} by {} of 0x{:x}", shift_type, amount, rm);
+                    }
+
+                    // immediate shifts of 1 to 31 act like register shifts; 0 is LSL #0, LSR/ASR #32 or RRX
+                    for shift in 1..32 {
+                        let expected = reference_shift(rm, shift, shift_type, c_flag);
+                        assert_eq!(Instruction::shift_value_by_imm(rm, shift, shift_type, c_flag), expected, "{} #{} of 0x{:x}", shift_type, shift, rm);
+                    }
+                    let expected = match shift_type {
+                        ShiftType::ROR => ((c_flag as Word) << 31 | rm >> 1, rm & 1),
+                        ShiftType::LSL => reference_shift(rm, 0, shift_type, c_flag),
+                        _ => reference_shift(rm, 32, shift_type, c_flag)
+                    };
+                    assert_eq!(Instruction::shift_value_by_imm(rm, 0, shift_type, c_flag), expected, "{} #0 of 0x{:x}", shift_type, rm);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_value() {
+        // (rotate_imm, immed_8, c flag, shifter operand, carry out)
+        let cases: [(Byte, Byte, Byte, Word, Word); 7] = [
+            (0, 0xff, 1, 0x000000ff, 1), // no rotation keeps the carry
+            (0, 0x80, 0, 0x00000080, 0),
+            (1, 0x01, 1, 0x40000000, 0),
+            (1, 0x02, 0, 0x80000000, 1),
+            (4, 0xff, 0, 0xff000000, 1),
+            (8, 0xab, 1, 0x00ab0000, 0),
+            (15, 0x3f, 1, 0x000000fc, 0),
+        ];
+        for (rotate_imm, immed_8, c_flag, operand, carry) in cases {
+            assert_eq!(Instruction::rotate_value(rotate_imm, immed_8, c_flag), (operand, carry), "#{} ror {}", immed_8, rotate_imm * 2);
+        }
+    }
+
+    #[test]
+    fn test_imm_shift_type() {
+        assert_eq!(instr_data_reg_imm(14, 13, 0, 0, 0, 0, 3, 1).get_shift_type(), Some(ShiftType::RRX));
+        assert_eq!(instr_data_reg_imm(14, 13, 0, 0, 0, 1, 3, 1).get_shift_type(), Some(ShiftType::ROR));
+        assert_eq!(instr_data_reg_reg(14, 13, 0, 0, 0, 2, 3, 1).get_shift_type(), Some(ShiftType::ROR));
+    }
+
 }
\ No newline at end of file
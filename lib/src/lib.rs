@@ -1,11 +1,21 @@
 pub mod memory;
 pub mod options;
 pub mod cpu;
-pub mod state;
 pub mod elf;
 pub mod instruction;
 pub mod cpu_enum;
 pub mod execute;
 pub mod util;
 pub mod trace;
-pub mod disassemble;
\ No newline at end of file
+pub mod disassemble;
+pub mod encode;
+pub mod machine;
+pub mod console;
+pub mod bus;
+pub mod devices;
+pub mod coprocessor;
+pub mod mmu;
+pub mod cache;
+pub mod timing;
+pub mod pipeline;
+pub mod alu;
\ No newline at end of file
@@ -0,0 +1,1202 @@
+use std::{path::Path, sync::{Arc, Mutex}};
+
+use log::{trace, error};
+use object::Endianness;
+
+use crate::{cpu::{CPU, CPUThreadWatcher}, console::{HostConsole, NullConsole}, devices::{DisplayDevice, KeyboardDevice, KeyboardFifo, TimerDevice}, memory::{RAM, Registers, Memory, Word, Register, DISPLAY_ADDR, KEYBOARD_ADDR, TIMER_ADDR}, trace::TraceFile, cpu_enum::{Mode, InstrExecuteCondition}, elf};
+
+// the Machine owns all of the simulator state (RAM, registers, CPU and trace log)
+// so that the core can be driven synchronously without a Tauri app;
+// the src-tauri commands are thin wrappers around a Machine behind a mutex
+pub struct Machine {
+    pub cpu: CPU,
+    pub ram: RAM,
+    pub registers: Registers,
+    pub trace: TraceFile,
+    pub console: Box<dyn HostConsole>,
+    // shared with the host so stop requests, IRQs and prompt input can be delivered
+    //  from another thread while the machine is running
+    watcher: Arc<Mutex<CPUThreadWatcher>>,
+    // receive buffer of the keyboard device, shared for the same reason
+    keyboard: KeyboardFifo
+}
+
+impl Machine {
+    pub fn new(memory_size: usize) -> Self {
+        let mut ram = RAM::new(memory_size, Endianness::Big);
+        ram.devices.map(DISPLAY_ADDR, 1, DisplayDevice::default());
+        let keyboard = KeyboardDevice::default();
+        let keyboard_fifo = keyboard.get_fifo();
+        ram.devices.map(KEYBOARD_ADDR, 2, keyboard);
+        ram.devices.map(TIMER_ADDR, 16, TimerDevice::default());
+
+        Self {
+            cpu: CPU::default(),
+            ram,
+            registers: Registers::default(),
+            trace: TraceFile::default(),
+            console: Box::new(NullConsole),
+            watcher: Arc::new(Mutex::new(CPUThreadWatcher::default())),
+            keyboard: keyboard_fifo
+        }
+    }
+
+    pub fn get_watcher(&self) -> Arc<Mutex<CPUThreadWatcher>> {
+        self.watcher.clone()
+    }
+
+    pub fn get_keyboard(&self) -> KeyboardFifo {
+        self.keyboard.clone()
+    }
+
+    pub fn set_console<C: HostConsole + 'static>(&mut self, console: C) {
+        self.console = Box::new(console);
+    }
+
+    // clears RAM, registers and the trace log, then loads the ELF binary and boots it
+    pub fn load_elf(&mut self, path: &Path) -> Result<(), std::io::Error> {
+        self.ram.clear();
+        self.ram.devices.reset();
+        self.ram.coprocessors.reset();
+        self.ram.mmu.reset();
+        if let Some(icache) = &mut self.ram.icache { icache.reset(); }
+        if let Some(dcache) = &mut self.ram.dcache { dcache.reset(); }
+        self.registers.clear();
+
+        // clear trace log and reset trace and cycle counters
+        self.trace.clear_trace_file()?;
+        self.cpu.reset_trace_step();
+        self.cpu.reset_cycles();
+
+        let (pc, endianness) = match elf::read_elf_file(path, &mut self.ram) {
+            Ok(res) => res,
+            Err(e) => {
+                error!("load_elf: error loading ELF: {}", e.to_string());
+                self.ram.loaded = false;
+                return Err(e)
+            }
+        };
+
+        self.ram.checksum = self.ram.calculate_checksum();
+        self.ram.loaded = true;
+        self.ram.endianness = endianness;
+
+        self.boot(pc);
+
+        Ok(())
+    }
+
+    // sets up the processor state to begin executing at the entry point
+    pub fn boot(&mut self, entry: Word) {
+        self.cpu.reset_pipeline();
+        self.registers.clear_nzcv();
+        self.registers.set_cpsr_mode(Mode::SVC);
+
+        // branch PC to address 0 if program is an OS (first memory location is nonzero)
+        if self.ram.get_memory_array()[0] != 0 {
+            self.registers.set_t_flag(false);
+            self.registers.set_pc(8);
+        } else {
+            self.registers.set_cpsr_mode(Mode::SYS);
+            // an odd entry point is Thumb code
+            self.registers.set_t_flag(entry & 1 == 1);
+            let size = self.registers.get_instr_size();
+            self.registers.set_pc((entry & !1) + size * 2);
+            self.registers.set_reg_register(Register::r13, 0x7000);
+        }
+    }
+
+    pub fn step(&mut self) -> InstrExecuteCondition {
+        self.cpu.step(&mut self.ram, &mut self.registers, &mut self.trace, &self.watcher, self.console.as_mut())
+    }
+
+    // steps until the program halts, a breakpoint is hit, the host stops the machine,
+    // or the `until` predicate returns true after a step
+    pub fn run_until<F>(&mut self, mut until: F) -> InstrExecuteCondition where F: FnMut(&mut Machine) -> bool {
+        self.watcher.lock().unwrap().set_running(true);
+
+        // fetch-decode-execute
+        let mut result = InstrExecuteCondition::NOP;
+        loop {
+            // stop when thread flag is updated
+            if !self.watcher.lock().unwrap().is_running() { break }
+
+            trace!("run_until: stepping...");
+            result = self.step();
+            if result == InstrExecuteCondition::HLT {
+                // stop when HLT instruction or other exception
+                trace!("run_until: hit HLT or exception");
+                break
+            }
+
+            // stop when pc hits breakpoint address
+            if self.cpu.is_breakpoint(&self.registers.get_pc_current_address()) {
+                trace!("run_until: hit breakpoint");
+                break
+            }
+
+            if until(self) { break }
+        }
+
+        self.stop();
+
+        trace!("run_until: cpu stopped");
+        result
+    }
+
+    pub fn run(&mut self) -> InstrExecuteCondition {
+        self.run_until(|_| false)
+    }
+
+    pub fn stop(&self) {
+        self.watcher.lock().unwrap().set_running(false);
+        trace!("stop: set running flag to false")
+    }
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine::new(crate::memory::DEFAULT_MEMORY_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{console::ScriptedConsole, cpu::{FaultPayload, VECTOR_SWI}, coprocessor::{CoprocessorOp, CP15_MAIN_ID, CONTROL_B, HIGH_VECTOR_BASE, FAULT_STATUS_EXTERNAL}, mmu::{FAULT_STATUS_TRANSLATION_PAGE, FAULT_STATUS_PERMISSION_SECTION}, cpu_enum::{Architecture, Fault, UndefinedPolicy}, memory::{AlignmentPolicy, HalfWord}, cache::{Cache, CacheConfig}, timing::Cycles, pipeline::Flush};
+
+    const PROGRAM_ADDR: Word = 0x1000;
+
+    fn load_program(machine: &mut Machine, program: &[Word]) {
+        for (i, word) in program.iter().enumerate() {
+            machine.ram.write_word(PROGRAM_ADDR + (i as Word) * 4, *word);
+        }
+        machine.boot(PROGRAM_ADDR);
+    }
+
+    // Thumb code is little-endian halfwords; the odd entry point boots in Thumb state
+    fn load_thumb_program(machine: &mut Machine, program: &[HalfWord]) {
+        machine.ram.endianness = Endianness::Little;
+        for (i, half_word) in program.iter().enumerate() {
+            machine.ram.write_half_word(PROGRAM_ADDR + (i as Word) * 2, *half_word);
+        }
+        machine.boot(PROGRAM_ADDR | 1);
+    }
+
+    #[test]
+    fn test_boot() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[]);
+
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SYS);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR);
+        assert_eq!(machine.registers.get_sp(), 0x7000);
+    }
+
+    #[test]
+    fn test_max_memory_size() {
+        let mut machine = Machine::new(crate::memory::MAX_MEMORY_SIZE);
+        load_program(&mut machine, &[
+            0xe3a02030, // mov r2, #48
+            0xef000011, // swi 0x11
+        ]);
+
+        // the sum of the addresses alone overflows a u32, as in load_elf
+        let expected = machine.ram.get_memory_array().iter().enumerate()
+            .fold(0u64, |sum, (address, byte)| sum + (*byte as u64 ^ address as u64)) as Word;
+        assert_eq!(machine.ram.calculate_checksum(), expected);
+
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.registers.get_reg_register(Register::r2), 48);
+    }
+
+    #[test]
+    fn test_step_mov() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a02030, // mov r2, #48
+        ]);
+
+        assert_eq!(machine.step(), InstrExecuteCondition::NOP);
+        assert_eq!(machine.registers.get_reg_register(Register::r2), 48);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 4);
+    }
+
+    #[test]
+    fn test_run_until_halt() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a00005, // mov r0, #5
+            0xe3a01007, // mov r1, #7
+            0xe0802001, // add r2, r0, r1
+            0xef000011, // swi 0x11
+        ]);
+
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.registers.get_reg_register(Register::r2), 12);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 12);
+        assert!(!machine.get_watcher().lock().unwrap().is_running());
+    }
+
+    #[test]
+    fn test_run_until_predicate() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe2800001, // add r0, r0, #1
+            0xeafffffd, // b PROGRAM_ADDR
+        ]);
+
+        machine.run_until(|m| m.registers.get_reg_register(Register::r0) == 3);
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 3);
+    }
+
+    #[test]
+    fn test_run_until_breakpoint() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a00001, // mov r0, #1
+            0xe3a00002, // mov r0, #2
+            0xef000011, // swi 0x11
+        ]);
+        machine.cpu.add_breakpoint(PROGRAM_ADDR + 4);
+
+        assert_eq!(machine.run(), InstrExecuteCondition::NOP);
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 1);
+    }
+
+    #[test]
+    fn test_step_putchar() {
+        let console = ScriptedConsole::new();
+        let mut machine = Machine::default();
+        machine.set_console(console.clone());
+        load_program(&mut machine, &[
+            0xe3a00041, // mov r0, #65
+            0xef000000, // swi 0x0
+        ]);
+
+        machine.step();
+        machine.step();
+        assert_eq!(console.get_output(), "A");
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SVC);
+    }
+
+    #[test]
+    fn test_step_readline() {
+        let console = ScriptedConsole::new();
+        console.push_input("hello");
+        let mut machine = Machine::default();
+        machine.set_console(console);
+        load_program(&mut machine, &[
+            0xe3a01a02, // mov r1, #0x2000
+            0xe3a02004, // mov r2, #4
+            0xef00006a, // swi 0x6a
+        ]);
+
+        for _ in 0..3 { machine.step(); }
+        // max_bytes includes the null terminator, so the line is truncated to 3 characters
+        assert_eq!(machine.ram.read_word(0x2000).to_be_bytes(), [b'h', b'e', b'l', 0]);
+    }
+
+    #[test]
+    fn test_step_poll_key_irq() {
+        let console = ScriptedConsole::new();
+        let mut machine = Machine::default();
+        machine.set_console(console.clone());
+        load_program(&mut machine, &[
+            0xe3a00001, // mov r0, #1
+            0xe3a00002, // mov r0, #2
+        ]);
+        // boot leaves IRQs disabled
+        machine.registers.set_i_flag(false);
+
+        console.push_key('k');
+        machine.step();
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::IRQ);
+        assert_eq!(machine.registers.get_pc_current_address(), 0x18);
+        assert_eq!(machine.ram.load_byte(KEYBOARD_ADDR), Ok(b'k'));
+    }
+
+    #[test]
+    fn test_keyboard_fifo_irq() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a00001, // mov r0, #1
+            0xe3a00002, // mov r0, #2
+        ]);
+        machine.registers.set_i_flag(false);
+
+        // keys pushed while the handler hasn't run yet are all kept
+        let keyboard = machine.get_keyboard();
+        keyboard.push(b'x');
+        keyboard.push(b'y');
+        machine.step();
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::IRQ);
+        assert_eq!(machine.ram.load_byte(KEYBOARD_ADDR + 1), Ok(1)); // ready
+        assert_eq!(machine.ram.load_byte(KEYBOARD_ADDR), Ok(b'x'));
+        assert_eq!(machine.ram.load_byte(KEYBOARD_ADDR), Ok(b'y'));
+        assert!(keyboard.is_empty());
+    }
+
+    #[test]
+    fn test_display_device() {
+        let console = ScriptedConsole::new();
+        let mut machine = Machine::default();
+        machine.set_console(console.clone());
+        load_program(&mut machine, &[
+            0xe3a01601, // mov r1, #0x100000 (DISPLAY_ADDR)
+            0xe3a00048, // mov r0, #72
+            0xe5c10000, // strb r0, [r1]
+            0xe3a00069, // mov r0, #105
+            0xe1c100b0, // strh r0, [r1]
+            0xe3a00021, // mov r0, #33
+            0xe8810001, // stm r1, {r0}
+        ]);
+
+        for _ in 0..7 { machine.step(); }
+        assert_eq!(console.get_output(), "Hi!");
+    }
+
+    #[test]
+    fn test_timer_irq() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a01601, // mov r1, #0x100000
+            0xe3811010, // orr r1, r1, #0x10 (TIMER_ADDR)
+            0xe3a00003, // mov r0, #3
+            0xe5810000, // str r0, [r1] (TIMER_LOAD)
+            0xe3a00003, // mov r0, #3 (enable, irq)
+            0xe5810008, // str r0, [r1, #8] (TIMER_CONTROL)
+            0xe3a02001, // mov r2, #1
+            0xe3a02002, // mov r2, #2
+            0xe3a02003, // mov r2, #3
+        ]);
+        machine.registers.set_i_flag(false);
+
+        // the enabling store is the first retired instruction the timer counts
+        for _ in 0..7 { machine.step(); }
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SYS);
+        machine.step();
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::IRQ);
+        assert_eq!(machine.registers.get_reg_register(Register::r2), 2);
+        assert_eq!(machine.registers.get_pc_current_address(), 0x18);
+    }
+
+    #[test]
+    fn test_fiq_priority() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a08008, // mov r8, #8
+            0xe3a08009, // mov r8, #9
+        ]);
+        machine.registers.set_i_flag(false);
+        machine.registers.set_f_flag(false);
+
+        {
+            let watcher = machine.get_watcher();
+            let mut watcher_lock = watcher.lock().unwrap();
+            watcher_lock.set_irq_flag();
+            watcher_lock.set_fiq_flag();
+        }
+        machine.step();
+
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::FIQ);
+        assert_eq!(machine.registers.get_pc_current_address(), 0x1c);
+        assert!(machine.registers.get_i_flag());
+        assert!(machine.registers.get_f_flag());
+        assert_eq!(machine.registers.get_reg_register(Register::r14), PROGRAM_ADDR + 8);
+        assert_eq!(machine.registers.get_spsr() & 0x1f, Mode::SYS as Word);
+        // r8 is banked, so the FIQ handler sees its own copy
+        assert_eq!(machine.registers.get_reg_register(Register::r8), 0);
+        assert_eq!(machine.registers.get_reg_register(Register::r8_fiq), 0);
+        // the IRQ stays pending until FIQs are handled and IRQs are re-enabled
+        assert!(machine.get_watcher().lock().unwrap().get_irq_flag());
+    }
+
+    #[test]
+    fn test_undefined_instruction() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe7f000f0, // undefined
+            0xe3a00001, // mov r0, #1
+        ]);
+        // handler returns to the next instruction
+        machine.ram.write_word(0x04, 0xe1b0f00e); // movs pc, lr
+
+        assert_eq!(machine.step(), InstrExecuteCondition::UND);
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::UND);
+        assert_eq!(machine.registers.get_pc_current_address(), 0x04);
+        assert_eq!(machine.registers.get_reg_register(Register::r14), PROGRAM_ADDR + 4);
+        assert_eq!(machine.registers.get_spsr() & 0x1f, Mode::SYS as Word);
+
+        machine.step();
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SYS);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 4);
+    }
+
+    #[test]
+    fn test_undefined_instruction_halt() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a00001, // mov r0, #1
+            0xe1000000, // tst without S, not a NOP
+        ]);
+        machine.ram.write_word(0x04, 0xe1b0f00e); // movs pc, lr
+        machine.cpu.set_undefined_policy(UndefinedPolicy::Halt);
+
+        // the CPU stops on the instruction even though a handler is installed
+        machine.step();
+        assert_eq!(machine.step(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SYS);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 4);
+        let fault = machine.cpu.take_fault().unwrap();
+        assert_eq!(fault, FaultPayload {
+            fault: Fault::Undefined,
+            address: PROGRAM_ADDR + 4,
+            pc: PROGRAM_ADDR + 4,
+            instruction: Some(0xe1000000),
+            handled: false
+        });
+        assert_eq!(fault.to_string(), "undefined instruction 0xe1000000 at 0x1004 (pc = 0x1004), halted");
+
+        machine.cpu.set_undefined_policy(UndefinedPolicy::Exception);
+        assert_eq!(machine.step(), InstrExecuteCondition::UND);
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::UND);
+        assert!(machine.cpu.take_fault().unwrap().handled);
+    }
+
+    #[test]
+    fn test_data_abort() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a01902, // mov r1, #0x8000
+            0xe5b10004, // ldr r0, [r1, #4]!
+        ]);
+        machine.ram.write_word(0x10, 0xe25ef008); // subs pc, lr, #8
+
+        machine.step();
+        assert_eq!(machine.step(), InstrExecuteCondition::DABT(0x8004));
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::ABT);
+        assert_eq!(machine.registers.get_pc_current_address(), 0x10);
+        assert_eq!(machine.registers.get_reg_register(Register::r14), PROGRAM_ADDR + 12);
+        assert!(machine.registers.get_i_flag());
+        assert!(machine.registers.get_cpsr_flag(8));
+        // the base register is not written back
+        assert_eq!(machine.registers.get_reg_register(Register::r1), 0x8000);
+        assert_eq!(machine.cpu.take_fault(), Some(FaultPayload {
+            fault: Fault::DataAbort,
+            address: 0x8004,
+            pc: PROGRAM_ADDR + 4,
+            instruction: None,
+            handled: true
+        }));
+        assert_eq!(machine.ram.coprocessors.cp15.get_fault_address(), 0x8004);
+        assert_eq!(machine.ram.coprocessors.cp15.get_fault_status(), FAULT_STATUS_EXTERNAL);
+    }
+
+    #[test]
+    fn test_data_abort_no_handler() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a01902, // mov r1, #0x8000
+            0xe5810000, // str r0, [r1]
+        ]);
+
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        // halted on the faulting instruction without entering ABT mode
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SYS);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 4);
+
+        let fault = machine.cpu.take_fault().unwrap();
+        assert!(!fault.handled);
+        assert_eq!(fault.to_string(), "data abort at 0x8000 (pc = 0x1004), halted");
+        assert_eq!(machine.cpu.take_fault(), None);
+    }
+
+    #[test]
+    fn test_data_abort_address_wraps() {
+        // a negative offset wraps past 0 and a positive one past 0xffffffff, to an address outside RAM
+        for (program, address) in [
+            (vec![0xe3a01000, 0xe5110004], 0xfffffffc),             // mov r1, #0; ldr r0, [r1, #-4]
+            (vec![0xe3a01000, 0xe4110004, 0xe5910000], 0xfffffffc), // mov r1, #0; ldr r0, [r1], #-4; ldr r0, [r1]
+            (vec![0xe3e01003, 0xe3a02801, 0xe7910002], 0xfffc),     // mvn r1, #3; mov r2, #0x10000; ldr r0, [r1, r2]
+        ] {
+            let mut machine = Machine::default();
+            load_program(&mut machine, &program);
+            machine.ram.write_word(0x10, 0xe25ef008); // subs pc, lr, #8
+
+            for _ in 1..program.len() {
+                machine.step();
+            }
+            assert_eq!(machine.step(), InstrExecuteCondition::DABT(address));
+            let fault = machine.cpu.take_fault().unwrap();
+            assert_eq!(fault.fault, Fault::DataAbort);
+            assert_eq!(fault.address, address);
+            assert!(fault.handled);
+        }
+    }
+
+    #[test]
+    fn test_prefetch_abort() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[]);
+        machine.ram.write_word(0x0c, 0xe25ef004); // subs pc, lr, #4
+        machine.registers.set_pc(0x8000 + 8);
+
+        machine.step();
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::ABT);
+        assert_eq!(machine.registers.get_pc_current_address(), 0x0c);
+        assert_eq!(machine.registers.get_reg_register(Register::r14), 0x8000 + 4);
+        assert_eq!(machine.registers.get_spsr() & 0x1f, Mode::SYS as Word);
+    }
+
+    #[test]
+    fn test_unaligned_rotate() {
+        let mut machine = Machine::default();
+        machine.ram.endianness = Endianness::Little;
+        load_program(&mut machine, &[
+            0xe3a01a02, // mov r1, #0x2000
+            0xe2811001, // add r1, r1, #1
+            0xe1d100b0, // ldrh r0, [r1]
+            0xe1d120f0, // ldrsh r2, [r1]
+            0xe5913000, // ldr r3, [r1]
+        ]);
+        machine.ram.write_word(0x2000, 0x8899aabb);
+
+        for _ in 0..5 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 0xbb0000aa);
+        assert_eq!(machine.registers.get_reg_register(Register::r2), 0xffffffaa);
+        assert_eq!(machine.registers.get_reg_register(Register::r3), 0xbb8899aa);
+    }
+
+    #[test]
+    fn test_unaligned_fault() {
+        let mut machine = Machine::default();
+        machine.ram.endianness = Endianness::Little;
+        load_program(&mut machine, &[
+            0xe3a01a02, // mov r1, #0x2000
+            0xe2811001, // add r1, r1, #1
+            0xe5913000, // ldr r3, [r1]
+            0xe8910003, // ldm r1, {r0, r1}
+        ]);
+        machine.ram.write_word(0x10, 0xe25ef004); // subs pc, lr, #4
+        machine.ram.set_alignment_policy(AlignmentPolicy::Fault);
+
+        machine.step();
+        machine.step();
+        assert_eq!(machine.step(), InstrExecuteCondition::DABT(0x2001));
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::ABT);
+
+        // LDM faults even when unaligned word accesses are supported
+        machine.boot(PROGRAM_ADDR);
+        machine.ram.set_alignment_policy(AlignmentPolicy::Unaligned);
+        machine.ram.write_word(0x2004, 0x8899aabb);
+        for _ in 0..3 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r3), 0xbb000000);
+        assert_eq!(machine.step(), InstrExecuteCondition::DABT(0x2001));
+    }
+
+    #[test]
+    fn test_long_multiply() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3e02000, // mvn r2, #0
+            0xe3a03003, // mov r3, #3
+            0xe0810392, // umull r0, r1, r2, r3
+            0xe0d54392, // smulls r4, r5, r2, r3
+            0xe0a54392, // umlal r4, r5, r2, r3
+            0xe0376392, // mlas r7, r2, r3, r6
+        ]);
+        machine.registers.set_reg_register(Register::r6, 3);
+
+        for _ in 0..3 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 0xfffffffd);
+        assert_eq!(machine.registers.get_reg_register(Register::r1), 2);
+
+        machine.step();
+        assert_eq!(machine.registers.get_reg_register(Register::r4), 0xfffffffd);
+        assert_eq!(machine.registers.get_reg_register(Register::r5), 0xffffffff);
+        assert!(machine.registers.get_n_flag());
+
+        machine.step();
+        assert_eq!(machine.registers.get_reg_register(Register::r4), 0xfffffffa);
+        assert_eq!(machine.registers.get_reg_register(Register::r5), 2);
+
+        machine.step();
+        assert_eq!(machine.registers.get_reg_register(Register::r7), 0);
+        assert!(machine.registers.get_z_flag());
+        assert!(!machine.registers.get_n_flag());
+    }
+
+    #[test]
+    fn test_signed_and_doubleword_transfers() {
+        let mut machine = Machine::default();
+        machine.ram.endianness = Endianness::Little;
+        load_program(&mut machine, &[
+            0xe3a01a02, // mov r1, #0x2000
+            0xe1d100d0, // ldrsb r0, [r1]
+            0xe1d120f2, // ldrsh r2, [r1, #2]
+            0xe1e140d8, // ldrd r4, r5, [r1, #8]!
+            0xe04160f8, // strd r6, r7, [r1], #-8
+        ]);
+        machine.ram.write_word(0x2000, 0x80017f80);
+        machine.ram.write_word(0x2008, 0x11111111);
+        machine.ram.write_word(0x200c, 0x22222222);
+        machine.registers.set_reg_register(Register::r6, 0x33333333);
+        machine.registers.set_reg_register(Register::r7, 0x44444444);
+
+        for _ in 0..3 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 0xffffff80);
+        assert_eq!(machine.registers.get_reg_register(Register::r2), 0xffff8001);
+
+        machine.step();
+        assert_eq!(machine.registers.get_reg_register(Register::r4), 0x11111111);
+        assert_eq!(machine.registers.get_reg_register(Register::r5), 0x22222222);
+        assert_eq!(machine.registers.get_reg_register(Register::r1), 0x2008);
+
+        machine.step();
+        assert_eq!(machine.ram.read_word(0x2008), 0x33333333);
+        assert_eq!(machine.ram.read_word(0x200c), 0x44444444);
+        assert_eq!(machine.registers.get_reg_register(Register::r1), 0x2000);
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut machine = Machine::default();
+        machine.ram.endianness = Endianness::Little;
+        load_program(&mut machine, &[
+            0xe3a01a02, // mov r1, #0x2000
+            0xe3a02001, // mov r2, #1
+            0xe1010092, // swp r0, r2, [r1]   ; take the lock
+            0xe1013092, // swp r3, r2, [r1]   ; already held
+            0xe3e05000, // mvn r5, #0
+            0xe1414095, // swpb r4, r5, [r1]
+        ]);
+
+        for _ in 0..3 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 0);
+        assert_eq!(machine.ram.read_word(0x2000), 1);
+
+        machine.step();
+        assert_eq!(machine.registers.get_reg_register(Register::r3), 1);
+
+        machine.step();
+        machine.step();
+        assert_eq!(machine.registers.get_reg_register(Register::r4), 1);
+        assert_eq!(machine.ram.read_word(0x2000), 0xff);
+    }
+
+    #[test]
+    fn test_system_control_coprocessor() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xee100f10, // mrc p15, 0, r0, c0, c0, 0
+            0xee111f10, // mrc p15, 0, r1, c1, c0, 0
+            0xe3811a02, // orr r1, r1, #0x2000
+            0xee011f10, // mcr p15, 0, r1, c1, c0, 0   ; high vectors
+            0xef000000, // swi 0
+        ]);
+
+        machine.step();
+        machine.step();
+        assert_eq!(machine.registers.get_reg_register(Register::r0), CP15_MAIN_ID);
+        // the B bit reflects the big-endian image
+        assert_eq!(machine.registers.get_reg_register(Register::r1), CONTROL_B | 0x70);
+
+        machine.step();
+        machine.step();
+        assert_eq!(machine.ram.endianness, Endianness::Big);
+        machine.step();
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SVC);
+        assert_eq!(machine.registers.get_pc_current_address(), HIGH_VECTOR_BASE + VECTOR_SWI);
+
+        // clearing the B bit switches data accesses to little-endian
+        let control = CoprocessorOp { coprocessor: 15, crn: 1, ..Default::default() };
+        assert!(machine.ram.write_coprocessor(&control, 0));
+        assert_eq!(machine.ram.endianness, Endianness::Little);
+
+        // CP15 is not accessible from User mode
+        machine.registers.set_cpsr_mode(Mode::USR);
+        machine.ram.write_word(PROGRAM_ADDR, 0xee100f10); // mrc p15, 0, r0, c0, c0, 0
+        machine.registers.set_pc(PROGRAM_ADDR + 8);
+        machine.step();
+        assert_eq!(machine.cpu.take_fault().unwrap().fault, Fault::Undefined);
+    }
+
+    #[test]
+    fn test_mmu() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a00901, // mov r0, #0x4000
+            0xee020f10, // mcr p15, 0, r0, c2, c0, 0   ; translation table base
+            0xe3a00001, // mov r0, #1
+            0xee030f10, // mcr p15, 0, r0, c3, c0, 0   ; domain 0 is a client
+            0xee110f10, // mrc p15, 0, r0, c1, c0, 0
+            0xe3800001, // orr r0, r0, #1
+            0xee010f10, // mcr p15, 0, r0, c1, c0, 0   ; enable the MMU
+            0xe3a01501, // mov r1, #0x400000
+            0xe5912008, // ldr r2, [r1, #8]
+            0xe2811a01, // add r1, r1, #0x1000
+            0xe5913000, // ldr r3, [r1]
+        ]);
+        // 0x00000000: flat-mapped section, 0x00400000: small page at 0x3000 through a coarse table at 0x3c00
+        machine.ram.write_word(0x4000, 0x00000c02);
+        machine.ram.write_word(0x4010, 0x00003c01);
+        machine.ram.write_word(0x3c00, 0x00003ff2);
+        machine.ram.write_word(0x3008, 0xcafef00d);
+
+        for _ in 0..9 { machine.step(); }
+        assert!(machine.ram.coprocessors.cp15.is_mmu_enabled());
+        assert_eq!(machine.registers.get_reg_register(Register::r2), 0xcafef00d);
+
+        // the next page is not mapped and no abort handler is installed
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.cpu.take_fault().unwrap().address, 0x401000);
+        assert_eq!(machine.ram.coprocessors.cp15.get_fault_status(), FAULT_STATUS_TRANSLATION_PAGE);
+        assert_eq!(machine.ram.coprocessors.cp15.get_fault_address(), 0x401000);
+        assert!(machine.ram.mmu.get_tlb_hits() > 0);
+
+        // fetching from an unmapped section is a prefetch abort
+        machine.registers.set_pc(0x500000 + 8);
+        assert_eq!(machine.step(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.cpu.take_fault().unwrap().fault, Fault::PrefetchAbort);
+
+        // User mode cannot fetch from a privileged-only section
+        machine.ram.write_word(0x4000, 0x00000402);
+        machine.ram.mmu.reset();
+        machine.registers.set_cpsr_mode(Mode::USR);
+        machine.registers.set_pc(PROGRAM_ADDR + 8);
+        assert_eq!(machine.step(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.cpu.take_fault().unwrap().fault, Fault::PrefetchAbort);
+        assert_eq!(machine.ram.coprocessors.cp15.get_instruction_fault_status(), FAULT_STATUS_PERMISSION_SECTION);
+    }
+
+    #[test]
+    fn test_caches() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a01a02, // mov r1, #0x2000
+            0xe3a02040, // mov r2, #64
+            0xe3a00000, // mov r0, #0
+            0xe4913004, // loop: ldr r3, [r1], #4
+            0xe0800003, // add r0, r0, r3
+            0xe2522001, // subs r2, r2, #1
+            0x1afffffb, // bne loop
+            0xe5810000, // str r0, [r1]
+        ]);
+        machine.ram.icache = Some(Cache::new("1k,16,2".parse::<CacheConfig>().unwrap()));
+        machine.ram.dcache = Some(Cache::new("1k,16,2".parse::<CacheConfig>().unwrap()));
+
+        for _ in 0..(3 + 64 * 4 + 1) { machine.step(); }
+        // 260 instructions, two fetches to fill the pipeline and two more after each of the 63 taken
+        //  branches; the fetch stage runs one line past the program. Each 16-byte line of the array misses once
+        let icache = machine.ram.icache.as_ref().unwrap().get_stats();
+        assert_eq!((icache.read_hits, icache.read_misses), (385, 3));
+        let dcache = machine.ram.dcache.as_ref().unwrap().get_stats();
+        assert_eq!((dcache.read_hits, dcache.read_misses), (48, 16));
+        assert_eq!((dcache.write_hits, dcache.write_misses), (0, 1));
+        assert!(machine.ram.dcache.as_ref().unwrap().contains(0x2100));
+
+        // mcr p15, 0, r0, c7, c6, 0 invalidates the data cache only
+        machine.ram.write_coprocessor(&CoprocessorOp { coprocessor: 15, crn: 7, crm: 6, ..Default::default() }, 0);
+        assert!(machine.ram.dcache.as_ref().unwrap().get_lines().iter().all(|line| !line.valid));
+        assert!(machine.ram.icache.as_ref().unwrap().contains(PROGRAM_ADDR));
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a00a02, // mov r0, #0x2000          ; 1S
+            0xe3a01003, // mov r1, #3               ; 1S
+            0xe0020191, // loop: mul r2, r1, r1     ; 1S + 1I (early termination)
+            0xe2511001, // subs r1, r1, #1          ; 1S
+            0x1afffffc, // bne loop                 ; 2S + 1N taken, 1S not taken
+            0xe5802000, // str r2, [r0]             ; 2N
+            0xe5903000, // ldr r3, [r0]             ; 1S + 1N + 1I
+        ]);
+
+        for _ in 0..13 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r3), 1);
+        assert_eq!(machine.cpu.get_cycles(), Cycles::new(14, 5, 4, 0));
+        assert_eq!(machine.cpu.get_cycles().get_total(), 23);
+    }
+
+    #[test]
+    fn test_alu_flags() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3e00000, // mvn r0, #0
+            0xe3a01000, // mov r1, #0
+            0xe3a02001, // mov r2, #1
+            0xe3a03000, // mov r3, #0
+            0xe0904002, // adds r4, r0, r2     ; 64-bit add of r1:r0 and r3:r2
+            0xe0a15003, // adc r5, r1, r3
+            0xe0546002, // subs r6, r4, r2     ; and back again
+            0xe0c57003, // sbc r7, r5, r3
+            0xe1520000, // cmp r2, r0
+        ]);
+
+        for _ in 0..9 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r4), 0);
+        assert_eq!(machine.registers.get_reg_register(Register::r5), 1);
+        assert_eq!(machine.registers.get_reg_register(Register::r6), 0xffffffff);
+        assert_eq!(machine.registers.get_reg_register(Register::r7), 0);
+        // 1 - 0xffffffff borrows without a signed overflow
+        assert_eq!(machine.registers.get_nzcv_tuple(), (false, false, false, false));
+
+        load_program(&mut machine, &[
+            0xe3a08102, // mov r8, #0x80000000
+            0xe2589001, // subs r9, r8, #1
+        ]);
+
+        for _ in 0..2 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r9), 0x7fffffff);
+        assert_eq!(machine.registers.get_nzcv_tuple(), (false, false, true, true));
+    }
+
+    #[test]
+    fn test_register_shift() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3e01000, // mvn r1, #0
+            0xe3a02001, // mov r2, #1
+            0xe1b00211, // movs r0, r1, lsl r2
+            0xe08f3212, // add r3, pc, r2, lsl r2   ; the PC reads as the address + 12
+            0xe08f4082, // add r4, pc, r2, lsl #1   ; and as the address + 8 otherwise
+        ]);
+
+        for _ in 0..3 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 0xfffffffe);
+        assert_eq!(machine.registers.get_nzcv_tuple(), (true, false, true, false));
+
+        for _ in 0..2 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r3), PROGRAM_ADDR + 26);
+        assert_eq!(machine.registers.get_reg_register(Register::r4), PROGRAM_ADDR + 26);
+
+        // msr cpsr_f, #0x20000000 rotates its immediate
+        load_program(&mut machine, &[0xe328f202]);
+        machine.step();
+        assert_eq!(machine.registers.get_nzcv_tuple(), (false, false, true, false));
+    }
+
+    #[test]
+    fn test_ldm_exception_return() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a0c002, // mov r12, #2
+            0xe3a00001, // mov r0, #1
+            0xe080100c, // add r1, r0, r12
+            0xef000011, // swi 0x11
+        ]);
+        machine.ram.write_word(0x18, 0xea000078); // b 0x200
+        for (i, word) in [
+            0xe24ee004, // sub lr, lr, #4
+            0xe92d5fff, // stmfd sp!, {r0-r12, lr}
+            0xe3a00055, // mov r0, #0x55
+            0xe3a0c066, // mov r12, #0x66
+            0xe8fd9fff, // ldmfd sp!, {r0-r12, pc}^
+        ].iter().enumerate() {
+            machine.ram.write_word(0x200 + i as Word * 4, *word);
+        }
+        machine.registers.set_reg_register(Register::r13_irq, 0x6000);
+        machine.registers.set_i_flag(false);
+
+        machine.step();
+        machine.get_watcher().lock().unwrap().set_irq_flag();
+        machine.step();
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::IRQ);
+        assert_eq!(machine.registers.get_reg_register(Register::r14), PROGRAM_ADDR + 12);
+
+        // the handler returns to the instruction after mov r0, #1 with the registers and CPSR restored
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SYS);
+        assert!(!machine.registers.get_i_flag());
+        assert_eq!(machine.registers.get_reg_register(Register::r1), 3);
+        assert_eq!(machine.registers.get_reg_register(Register::r13_irq), 0x6000);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 12);
+    }
+
+    #[test]
+    fn test_exception_return_invalid_spsr() {
+        // SPSR_svc is 0 after reset, which is not a mode; the return keeps SVC mode
+        for return_instr in [
+            0xe1b0f00e, // movs pc, lr
+            0xe8fd8000, // ldmfd sp!, {pc}^
+        ] {
+            let mut machine = Machine::default();
+            load_program(&mut machine, &[
+                return_instr,
+                0xe3a07007, // mov r7, #7
+                0xef000011, // swi 0x11
+            ]);
+            machine.registers.set_cpsr_mode(Mode::SVC);
+            machine.registers.set_spsr(0);
+            machine.registers.set_reg_register(Register::r14, PROGRAM_ADDR + 8);
+            machine.registers.set_reg_register(Register::r13, 0x2000);
+            machine.ram.write_word(0x2000, PROGRAM_ADDR + 8);
+
+            assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+            assert_eq!(machine.registers.get_cpsr_mode(), Mode::SVC);
+            assert_eq!(machine.registers.get_reg_register(Register::r7), 0);
+            assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 8);
+        }
+    }
+
+    #[test]
+    fn test_ldmstm_transfers() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe321f0d3, // msr cpsr_c, #0xd3
+            0xe8c06000, // stmia r0, {sp, lr}^
+            0xe8d12000, // ldmia r1, {sp}^
+            0xe8a2000c, // stmia r2!, {r2, r3}  ; r2 is the first register stored, so its original value is
+            0xe8a3000c, // stmia r3!, {r2, r3}  ; r3 is not, so the written back value is stored
+            0xe8b40030, // ldmia r4!, {r4, r5}  ; the loaded r4 wins over the written back one
+            0xe9270000, // stmdb r7!, {}        ; an empty list stores the PC and moves the base by 64
+            0xe8b60000, // ldmia r6!, {}        ; and loads the PC
+            0xe3a09001, // mov r9, #1
+            0xef000011, // swi 0x11
+        ]);
+        for (register, value) in [(Register::r0, 0x2000), (Register::r1, 0x2010), (Register::r2, 0x2100), (Register::r3, 0x2200),
+                                  (Register::r4, 0x2300), (Register::r6, 0x2400), (Register::r7, 0x2500),
+                                  (Register::r13, 0x7000), (Register::r14, 0xabc), (Register::r13_svc, 0x5000)] {
+            machine.registers.set_reg_register(register, value);
+        }
+        machine.ram.write_word(0x2010, 0x1234);
+        machine.ram.write_word(0x2300, 0x11);
+        machine.ram.write_word(0x2304, 0x22);
+        machine.ram.write_word(0x2400, PROGRAM_ADDR + 36);
+
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        // the S bit transfers the User mode sp and lr from SVC mode
+        assert_eq!(machine.ram.read_word(0x2000), 0x7000);
+        assert_eq!(machine.ram.read_word(0x2004), 0xabc);
+        assert_eq!(machine.registers.get_user_register(13), 0x1234);
+        assert_eq!(machine.registers.get_reg_register(Register::r13), 0x5000);
+
+        assert_eq!(machine.ram.read_word(0x2100), 0x2100);
+        assert_eq!(machine.ram.read_word(0x2204), 0x2208);
+        assert_eq!(machine.registers.get_reg_register(Register::r4), 0x11);
+        assert_eq!(machine.registers.get_reg_register(Register::r5), 0x22);
+
+        assert_eq!(machine.ram.read_word(0x24c0), PROGRAM_ADDR + 32);
+        assert_eq!(machine.registers.get_reg_register(Register::r7), 0x24c0);
+        assert_eq!(machine.registers.get_reg_register(Register::r6), 0x2440);
+        assert_eq!(machine.registers.get_reg_register(Register::r9), 0);
+    }
+
+    #[test]
+    fn test_pc_writes() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xeb000002, // bl func
+            0xe598f000, // ldr pc, [r8]
+            0xe3a07007, // mov r7, #7
+            0xe3a07007, // mov r7, #7
+            0xe1a0f00e, // func: mov pc, lr
+            0xe8bd8001, // pop {r0, pc}
+            0xe3a07007, // mov r7, #7
+            0xef000011, // swi 0x11
+        ]);
+        machine.registers.set_reg_register(Register::r8, 0x2000);
+        machine.registers.set_reg_register(Register::r13, 0x2100);
+        machine.ram.write_word(0x2000, PROGRAM_ADDR + 20);
+        machine.ram.write_word(0x2100, 5);
+        machine.ram.write_word(0x2104, PROGRAM_ADDR + 28);
+
+        // each write to the PC continues at the address written, not an instruction before it
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.registers.get_reg_register(Register::r7), 0);
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 5);
+        assert_eq!(machine.registers.get_sp(), 0x2108);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 28);
+    }
+
+    #[test]
+    fn test_pc_write_wraps() {
+        // a branch to the top of the address space is fetched from outside RAM
+        for (instr_raw, target) in [
+            (0xe3e0f003, 0xfffffffc), // mvn pc, #3
+            (0xe3e0f007, 0xfffffff8), // mvn pc, #7
+        ] {
+            let mut machine = Machine::default();
+            load_program(&mut machine, &[instr_raw]);
+
+            assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+            let fault = machine.cpu.take_fault().unwrap();
+            assert_eq!(fault.fault, Fault::PrefetchAbort);
+            assert_eq!(fault.address, target);
+            assert_eq!(machine.registers.get_pc_current_address(), target);
+        }
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let mut machine = Machine::default();
+        load_program(&mut machine, &[
+            0xe3a00000, // mov r0, #0
+            0xe50f1004, // str r1, [pc, #-4]   ; overwrites the next instruction with HLT
+            0xe2800001, // add r0, r0, #1      ; already fetched, so it still executes
+            0xeafffffd, // b 0x1008
+        ]);
+
+        machine.step();
+        let pipeline = machine.cpu.get_pipeline_payload();
+        assert_eq!(pipeline.execute.unwrap().address, PROGRAM_ADDR);
+        assert_eq!(pipeline.decode.unwrap().instruction, Some(0xe50f1004));
+        assert_eq!(pipeline.fetch.unwrap().address, PROGRAM_ADDR + 8);
+        assert_eq!(pipeline.flush, None);
+
+        for _ in 0..3 { machine.step(); }
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 1);
+        let pipeline = machine.cpu.get_pipeline_payload();
+        assert_eq!(pipeline.execute.unwrap().address, PROGRAM_ADDR + 12);
+        assert_eq!(pipeline.decode.unwrap().address, PROGRAM_ADDR + 16);
+        assert_eq!(pipeline.flush, Some(Flush::Branch));
+
+        // the branch refills the pipeline from memory
+        assert_eq!(machine.step(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 1);
+
+        // instructions fetched past the end of memory are flushed before they execute
+        let mut machine = Machine::new(PROGRAM_ADDR as usize + 8);
+        load_program(&mut machine, &[
+            0xe3a00001, // mov r0, #1
+            0xeafffffd, // b 0x1000
+        ]);
+        for _ in 0..4 { machine.step(); }
+        assert!(machine.cpu.take_fault().is_none());
+        assert!(machine.cpu.get_pipeline_payload().fetch.unwrap().instruction.is_none());
+    }
+
+    #[test]
+    fn test_armv5te() {
+        let mut machine = Machine::default();
+        machine.ram.endianness = Endianness::Little;
+        machine.cpu.set_architecture(Architecture::ARMv5TE);
+        load_program(&mut machine, &[
+            0xe3a01801, // mov r1, #0x10000
+            0xe16f0f11, // clz r0, r1
+            0xe3e02102, // mvn r2, #0x80000000
+            0xe1023052, // qadd r3, r2, r2
+            0xe3e05001, // mvn r5, #1
+            0xe3a06003, // mov r6, #3
+            0xe16706a5, // smultb r7, r5, r6
+            0xe1087685, // smlabb r8, r5, r6, r7
+            0xfb000004, // blx thumb_func
+            0xe28f9004, // adr r9, arm_func
+            0xe12fff39, // blx r9
+            0xef000011, // swi 0x11
+            0xe2800c01, // arm_func: add r0, r0, #0x100
+            0xe12fff1e, // bx lr
+            0x300146c0, // nop; thumb_func: adds r0, #1
+            0x00004770, // bx lr
+        ]);
+
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 0x110);
+        assert_eq!(machine.registers.get_reg_register(Register::r3), 0x7fffffff);
+        assert!(machine.registers.get_q_flag());
+        assert_eq!(machine.registers.get_reg_register(Register::r7), -3i32 as Word);
+        assert_eq!(machine.registers.get_reg_register(Register::r8), -9i32 as Word);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 0x2c);
+        assert!(!machine.registers.get_t_flag());
+    }
+
+    #[test]
+    fn test_armv5te_thumb_blx() {
+        let mut machine = Machine::default();
+        machine.cpu.set_architecture(Architecture::ARMv5TE);
+        load_thumb_program(&mut machine, &[
+            0x2001,         // movs r0, #1
+            0xf000, 0xe802, // blx arm_func
+            0xdf11,         // swi 0x11
+            0x0002, 0xe280, // arm_func: add r0, r0, #2
+            0xff1e, 0xe12f, // bx lr
+        ]);
+
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 3);
+        assert_eq!(machine.registers.get_reg_register(Register::r14), (PROGRAM_ADDR + 6) | 1);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 6);
+        assert!(machine.registers.get_t_flag());
+    }
+
+    #[test]
+    fn test_thumb_run() {
+        let mut machine = Machine::default();
+        load_thumb_program(&mut machine, &[
+            0x2000, // movs r0, #0
+            0x210a, // movs r1, #10
+            0x1840, // adds r0, r0, r1
+            0x3901, // subs r1, #1
+            0x2900, // cmp r1, #0
+            0xd1fb, // bne PROGRAM_ADDR + 4
+            0x0082, // lsls r2, r0, #2
+            0x4243, // negs r3, r0
+            0xdf11, // swi 0x11
+        ]);
+        assert!(machine.registers.get_t_flag());
+
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 55);
+        assert_eq!(machine.registers.get_reg_register(Register::r2), 220);
+        assert_eq!(machine.registers.get_reg_register(Register::r3), -55i32 as Word);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 16);
+    }
+
+    #[test]
+    fn test_thumb_interworking() {
+        let mut machine = Machine::default();
+        machine.ram.endianness = Endianness::Little;
+        load_program(&mut machine, &[
+            0xe28f0001, // add r0, pc, #1
+            0xe12fff10, // bx r0
+            0xf0002005, // movs r0, #5 ; bl.prefix
+            0x4904f805, // bl.suffix (func) ; ldr r1, [pc, #16]
+            0x44804688, // mov r8, r1 ; add r8, r0
+            0x4710a203, // add r2, pc, #12 (arm_code) ; bx r2
+            0x2407b510, // func: push {r4, lr} ; movs r4, #7
+            0xbd101900, // adds r0, r0, r4 ; pop {r4, pc}
+            0x00000100, // .word 0x100
+            0xe1a03008, // arm_code: mov r3, r8
+            0xef000011, // swi 0x11
+        ]);
+
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert!(!machine.registers.get_t_flag());
+        assert_eq!(machine.registers.get_reg_register(Register::r0), 12);
+        assert_eq!(machine.registers.get_reg_register(Register::r1), 0x100);
+        assert_eq!(machine.registers.get_reg_register(Register::r3), 0x10c);
+        // BL leaves the Thumb return address in LR
+        assert_eq!(machine.registers.get_reg_register(Register::r14), (PROGRAM_ADDR + 0xe) | 1);
+        assert_eq!(machine.registers.get_sp(), 0x7000);
+        assert_eq!(machine.registers.get_pc_current_address(), PROGRAM_ADDR + 0x28);
+    }
+
+    #[test]
+    fn test_thumb_swi_return() {
+        let console = ScriptedConsole::new();
+        let mut machine = Machine::default();
+        machine.set_console(console.clone());
+        load_thumb_program(&mut machine, &[
+            0x2041, // movs r0, #65
+            0xdf00, // swi 0x0
+            0x2101, // movs r1, #1
+            0xdf11, // swi 0x11
+        ]);
+        machine.ram.write_word(0x08, 0xe1b0f00e); // movs pc, lr
+
+        machine.step();
+        machine.step();
+        // the exception is taken in ARM state
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SVC);
+        assert!(!machine.registers.get_t_flag());
+
+        assert_eq!(machine.run(), InstrExecuteCondition::HLT);
+        assert_eq!(console.get_output(), "A");
+        assert_eq!(machine.registers.get_cpsr_mode(), Mode::SYS);
+        assert!(machine.registers.get_t_flag());
+        assert_eq!(machine.registers.get_reg_register(Register::r1), 1);
+    }
+}
@@ -1,1034 +1,1565 @@
-use core::fmt;
-
-use log::error;
-use num_derive::FromPrimitive;
-use object::Endianness;
-
-use crate::cpu_enum::Mode;
-
-pub type Byte = u8;
-pub type HalfWord = u16;
-pub type Word = u32;
-pub type SignedWord = i32;
-pub type AddressSize = u32;
-pub type Checksum = u32;
-
-pub const DEFAULT_MEMORY_SIZE: usize = 32768;
-
-pub const NUM_REGISTERS: usize = 23; // r0...r15, CPSR, SP_svc, LR_svc, SPSR_svc, SP_irq, LR_irq, SPSR_irq
-pub const REGISTER_BYTES: usize = 4; // 4byte = 32bit
-
-pub const CPSR_ADDR: AddressSize = (16 * REGISTER_BYTES) as AddressSize;
-pub const SPSR_SVC_ADDR: AddressSize = (19 * REGISTER_BYTES) as AddressSize;
-pub const SPSR_IRQ_ADDR: AddressSize = (22 * REGISTER_BYTES) as AddressSize;
-
-// used when calculating r13 and r14 in non-system modes to properly index the register array
-pub const MODE_OFFSET_SVC: usize = 4;
-pub const MODE_OFFSET_IRQ: usize = 7;
-
-pub const DISPLAY_ADDR: AddressSize  = 0x100000;
-pub const KEYBOARD_ADDR: AddressSize = 0x100001;
-
-
-#[allow(non_camel_case_types)]
-#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
-pub enum Register {
-    r0 = 0,
-    r1 = 1,
-    r2 = 2,
-    r3 = 3,
-    r4 = 4,
-    r5 = 5,
-    r6 = 6,
-    r7 = 7,
-    r8 = 8,
-    r9 = 9,
-    r10 = 10,
-    r11 = 11,
-    r12 = 12,
-    r13 = 13,
-    r14 = 14,
-    r15 = 15,
-    NOP_CPSR,
-    r13_svc,
-    r14_svc,
-    NOP_SPSR_svc,
-    r13_irq,
-    r14_irq,
-    NOP_SPSR_irq
-}
-
-impl fmt::Display for Register {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Register::r10 => write!(f, "sl"),
-            Register::r11 => write!(f, "fp"),
-            Register::r12 => write!(f, "il"),
-            Register::r13 => write!(f, "sp"),
-            Register::r14 => write!(f, "lr"),
-            Register::r15 => write!(f, "pc"),
-            _ => write!(f, "{:?}", self)
-        }
-    }
-}
-
-#[derive(Clone, serde::Serialize)]
-pub struct RegistersPayload {
-    pub register_array: Vec<Word>
-}
-
-impl Default for RegistersPayload {
-    fn default() -> Self {
-        RegistersPayload {
-            register_array: vec![0, 0]
-        }
-    }
-}
-
-#[derive(Clone, serde::Serialize)]
-pub struct FlagsPayload {
-    pub n: bool,
-    pub c: bool,
-    pub z: bool,
-    pub v: bool,
-    pub i: bool
-}
-
-impl Default for FlagsPayload {
-    fn default() -> Self {
-        FlagsPayload {
-            n: false,
-            c: false,
-            z: false,
-            v: false,
-            i: false,
-        }
-    }
-}
-
-#[derive(Clone, serde::Serialize)]
-pub struct RAMPayload {
-    pub checksum: Checksum,
-    pub memory_array: Vec<Vec<Byte>>
-}
-
-impl Default for RAMPayload {
-    fn default() -> Self {
-        RAMPayload {
-            checksum: 0,
-            memory_array: vec![vec![0; 0]; 0],
-        }
-    }
-}
-
-pub trait Memory {
-    fn new(size: usize, endianness: Endianness) -> Self;
-    fn get_size(&self) -> usize;
-    fn get_memory_array(&mut self) -> &mut Vec<Byte>;
-    fn get_endianness(&self) -> Endianness;
-    fn get_checksum(&self) -> Checksum;
-    fn set_checksum(&mut self, checksum: Checksum);
-    fn get_update_frontend_checksum(&self) -> bool;
-    fn set_update_frontend_checksum(&mut self, state: bool);
-
-    fn clear(&mut self) {
-        let sz = self.get_size();
-        self.get_memory_array().clear();
-        self.get_memory_array().resize(sz, 0);
-    }
-
-    fn read_word(&mut self, addr: AddressSize) -> Word {
-        if (addr + 3) as usize > self.get_size() {
-            panic!("Memory[read_word]: addr {:x} extends past memory size", addr);
-        }
-
-        if addr % 4 != 0 {
-            error!("Memory[read_word]: Word address not valid");
-            return 0
-        }
-
-        let w0: Word = *self.get_memory_array().get(addr as usize).unwrap() as Word;
-        let w1: Word = *self.get_memory_array().get((addr + 1) as usize).unwrap() as Word;
-        let w2: Word = *self.get_memory_array().get((addr + 2) as usize).unwrap() as Word;
-        let w3: Word = *self.get_memory_array().get((addr + 3) as usize).unwrap() as Word;
-
-        if self.get_endianness() == Endianness::Little {
-            (w3 << 24) | (w2 << 16) | (w1 << 8) | w0
-        } else {
-            (w0 << 24) | (w1 << 16) | (w2 << 8) | w3
-        }
-    }
-
-    
-    fn write_word(&mut self, addr: AddressSize, value: Word) {
-        if (addr + 3) as usize > self.get_size() {
-            panic!("Memory[write_word]: addr {:x} extends past memory size", addr);
-        }
-
-        if addr % 4 != 0 {
-            error!("Memory[write_word]: Word address not valid");
-            return
-        }
-
-        let b0: Byte = ((value >> 24) & 0xff) as Byte;
-        let b1: Byte = ((value >> 16) & 0xff) as Byte;
-        let b2: Byte = ((value >> 8) & 0xff) as Byte;
-        let b3: Byte = (value & 0xff) as Byte;
-
-        if self.get_endianness() == Endianness::Little {
-            self.get_memory_array()[addr as usize] = b3;
-            self.get_memory_array()[(addr + 1) as usize] = b2;
-            self.get_memory_array()[(addr + 2) as usize] = b1;
-            self.get_memory_array()[(addr + 3) as usize] = b0;
-        } else {
-            self.get_memory_array()[addr as usize] = b0;
-            self.get_memory_array()[(addr + 1) as usize] = b1;
-            self.get_memory_array()[(addr + 2) as usize] = b2;
-            self.get_memory_array()[(addr + 3) as usize] = b3;
-        }
-
-        let checksum = self.calculate_checksum();
-        self.set_checksum(checksum);
-    }
-
-    
-    fn read_half_word(&mut self, addr: AddressSize) -> HalfWord {
-        if (addr + 1) as usize > self.get_size() {
-            panic!("Memory[read_half_word]: addr {:x} extends past memory size", addr);
-        }
-
-        if addr % 2 != 0 {
-            error!("Memory[write_word]: Word address not valid");
-            return 0
-        }
-
-        let hw0: HalfWord = *self.get_memory_array().get(addr as usize).unwrap() as HalfWord;
-        let hw1: HalfWord = *self.get_memory_array().get((addr + 1) as usize).unwrap() as HalfWord;
-
-        if self.get_endianness() == Endianness::Little {
-            (hw1 << 8) | hw0
-        } else {
-            (hw0 << 8) | hw1
-        }
-    }
-
-    
-    fn write_half_word(&mut self, addr: AddressSize, value: HalfWord) {
-        if (addr + 1) as usize > self.get_size() {
-            panic!("Memory[write_half_word]: addr {:x} extends past memory size", addr);
-        }
-
-        if addr % 2 != 0 {
-            error!("Memory[write_half_word]: Word address not valid");
-            return
-        }
-
-        // example:
-        //  0x74 EC
-        //    b0 b1
-        let b0: Byte = ((value >> 8) & 0xff) as Byte;
-        let b1: Byte = (value & 0xff) as Byte;
-        
-        // big endian: b0 b1
-        // little endian: b1 b0
-        if self.get_endianness() == Endianness::Little {
-            self.get_memory_array()[addr as usize] = b1;
-            self.get_memory_array()[(addr + 1) as usize] = b0;
-        } else {
-            self.get_memory_array()[addr as usize] = b0;
-            self.get_memory_array()[(addr + 1) as usize] = b1;
-        }
-
-        let checksum = self.calculate_checksum();
-        self.set_checksum(checksum);
-    }
-
-    
-    fn read_byte(&mut self, addr: AddressSize) -> Byte {
-        if addr as usize > self.get_size() {
-            panic!("Memory[read_byte]: addr {:x} extends past memory size", addr);
-        }
-
-        *self.get_memory_array().get(addr as usize).unwrap() as Byte
-    }
-
-    
-    fn write_byte(&mut self, addr: AddressSize, value: Byte) {
-        if addr as usize > self.get_size() {
-            error!("Memory[write_byte]: addr {:x} extends past memory size", addr);
-            return
-        }
-
-        self.get_memory_array()[addr as usize] = value;
-
-        let checksum = self.calculate_checksum();
-        self.set_checksum(checksum);
-    }
-
-    
-    fn calculate_checksum(&mut self) -> Checksum {
-        let mut checksum: u32 = 0;
-    
-        for address in 0..self.get_memory_array().len() {
-            checksum += self.read_byte(address as AddressSize) as u32 ^ (address as u32);
-        }
-    
-        return checksum;
-    }
-
-    
-    fn test_flag(&mut self, addr: AddressSize, bit: u8) -> bool {
-        // bit is in the range of [0..31]
-        if bit > 31 {
-            panic!("Memory[test_flag]: bit is out of range")
-        }
-        
-        let w: Word = self.read_word(addr);
-        if (w >> bit) & 1 == 1 { true } else { false }
-    }
-
-    
-    fn set_flag(&mut self, addr: AddressSize, bit: u8, flag: bool) {
-        // bit is in the range of [0..31]
-        if bit > 31 {
-            panic!("Memory[set_flag]: bit is out of range")
-        }
-
-        let mut w: Word = self.read_word(addr);
-
-        if flag {
-            // set bit
-            w |= 1 << bit;
-
-        } else {
-            // clear bit
-            w &= !(1 << bit);
-        }
-
-        self.write_word(addr, w);
-    }
-
-    // static utility
-    fn extract_bits(w: Word, start_bit: u8, end_bit: u8) -> Word {
-        // bit is in the range of [0..31]
-        if start_bit > 31 || end_bit > 31{
-            panic!("Memory[extract_bits]: bit is out of range")
-        }
-
-        if start_bit > end_bit {
-            panic!("Memory[extract_bits]: startBit must be <= endBit");
-        }
-
-        let mut mask: Word = 0;
-        for i in start_bit..=end_bit {
-            let bit: Word = 1 << i;
-            mask |= bit;
-        }
-        mask & w
-    }
-}
-
-pub struct Registers {
-    pub endianness: Endianness,
-    pub memory_array: Vec<Byte>, // unsigned Byte array
-    pub size: usize
-}
-
-impl Registers {
-    // fix indices when attempting to access banked registers on non-system modes
-    pub fn get_register_mode_index(&mut self, index: usize) -> usize {
-        if index >= NUM_REGISTERS {
-            panic!("Registers[get_register_mode_index]: register index out of range");
-        }
-
-        match self.get_cpsr_mode() {
-            Mode::SVC => {
-                if index == 13 || index == 14 {
-                    index + MODE_OFFSET_SVC
-                } else {
-                    index
-                }
-            },
-            Mode::IRQ => {
-                if index == 13 || index == 14 {
-                    index + MODE_OFFSET_IRQ
-                } else {
-                    index
-                }
-            },
-            _ => index
-        }
-    }
-
-    pub fn set_register(&mut self, index: usize, value: Word) {
-        if index >= NUM_REGISTERS {
-            panic!("Registers[set_register]: register index out of range");
-        }
-
-        let address = (self.get_register_mode_index(index) * 4) as AddressSize;
-        self.write_word(address, value)
-    }
-
-    pub fn set_reg_register(&mut self, reg: Register, value: Word) {
-        self.set_register(reg as usize, value)
-    }
-
-    pub fn get_register(&mut self, index: usize) -> Word {
-        if index >= NUM_REGISTERS {
-            panic!("Registers[get_register]: register index out of range");
-        }
-
-        let address = (self.get_register_mode_index(index) * 4) as AddressSize;
-        self.read_word(address)
-    }
-
-    pub fn get_reg_register(&mut self, reg: Register) -> Word {
-        self.get_register(reg as usize)
-    }
-
-    pub fn get_all(&mut self) -> Vec<Word> {
-        let mut regs: Vec<Word> = vec![0; 0];
-        
-        // r0..r15
-        for i in 0..=15 {
-            regs.push(self.get_register(i));
-        }
-        regs
-    }
-
-    pub fn set_pc(&mut self, value: Word) {
-        self.set_register(15, value)
-    }
-
-    pub fn get_pc(&mut self) -> Word {
-        self.get_register(15)
-    }
-    
-    pub fn get_pc_current_address(&mut self) -> Word {
-        self.get_pc() - 8
-    }
-
-    pub fn dec_pc(&mut self) {
-        let next_addr = self.get_pc().checked_sub(4).unwrap_or(0);
-        self.set_register(15, next_addr)
-    }
-    
-    pub fn inc_pc(&mut self) {
-        let next_addr = self.get_pc() + 4;
-        self.set_register(15, next_addr)
-    }
-
-    pub fn get_sp(&mut self) -> Word {
-        self.get_register(13)
-    }
-
-    // CPSR register is last register
-    pub fn get_cpsr(&mut self) -> Word {
-        // have to manually read location since CPSR is not little- or big-endian
-        self.read_word(CPSR_ADDR)
-    }
-    
-    pub fn set_cpsr(&mut self, value: Word) {
-        self.write_word(CPSR_ADDR, value);
-    }
-    
-    pub fn set_cpsr_flag(&mut self, bit: u8, flag: bool) {
-        self.set_flag(CPSR_ADDR, bit, flag)
-    }
-
-    pub fn get_cpsr_flag(&mut self, bit: u8) -> bool {
-        self.test_flag(CPSR_ADDR, bit)
-    }
-
-    pub fn get_cpsr_mode(&mut self) -> Mode {
-        let mode_bits = Registers::extract_bits(self.get_cpsr(), 0, 4);
-        let mode: Mode = num::FromPrimitive::from_u32(mode_bits).unwrap();
-        mode
-    }
-
-    pub fn set_cpsr_mode(&mut self, mode: Mode) {
-        let mode_bits = mode as Byte;
-        let cleared_mode_byte = self.read_byte(CPSR_ADDR + 3) & 0b11100000;
-        self.write_byte(CPSR_ADDR + 3, cleared_mode_byte | mode_bits);
-    }
-
-    pub fn clear_nzcv(&mut self) {
-        self.set_flag(CPSR_ADDR, 31, false);
-        self.set_flag(CPSR_ADDR, 30, false);
-        self.set_flag(CPSR_ADDR, 29, false);
-        self.set_flag(CPSR_ADDR, 28, false);
-    }
-
-    pub fn get_n_flag(&mut self) -> bool {
-        self.test_flag(CPSR_ADDR, 31)
-    }
-
-    pub fn set_n_flag(&mut self, flag: bool) {
-        self.set_flag(CPSR_ADDR, 31, flag);
-    }
-
-    pub fn get_z_flag(&mut self) -> bool {
-        self.test_flag(CPSR_ADDR, 30)
-    }
-
-    pub fn set_z_flag(&mut self, flag: bool) {
-        self.set_flag(CPSR_ADDR, 30, flag);
-    }
-
-    pub fn get_c_flag(&mut self) -> bool {
-        self.test_flag(CPSR_ADDR, 29)
-    }
-
-    pub fn set_c_flag(&mut self, flag: bool) {
-        self.set_flag(CPSR_ADDR, 29, flag);
-    }
-
-    pub fn get_v_flag(&mut self) -> bool {
-        self.test_flag(CPSR_ADDR, 28)
-    }
-
-    pub fn set_v_flag(&mut self, flag: bool) {
-        self.set_flag(CPSR_ADDR, 28, flag);
-    }
-
-    pub fn get_i_flag(&mut self) -> bool {
-        self.test_flag(CPSR_ADDR, 7)
-    }
-
-    pub fn set_i_flag(&mut self, flag: bool) {
-        self.set_flag(CPSR_ADDR, 7, flag);
-    }
-
-    pub fn get_t_flag(&mut self) -> bool {
-        self.test_flag(CPSR_ADDR, 5)
-    }
-
-    pub fn set_t_flag(&mut self, flag: bool) {
-        self.set_flag(CPSR_ADDR, 5, flag);
-    }
-
-    pub fn get_cpsr_control_byte(&mut self) -> Byte {
-        self.read_byte(CPSR_ADDR + 3)
-    }
-
-    pub fn get_spsr(&mut self) -> Word {
-        match self.get_cpsr_mode() {
-            Mode::SVC => self.read_word(SPSR_SVC_ADDR),
-            Mode::IRQ => self.read_word(SPSR_IRQ_ADDR),
-            _ => self.read_word(CPSR_ADDR)
-        }
-    }
-
-    pub fn set_spsr(&mut self, value: Word) {
-        match self.get_cpsr_mode() {
-            Mode::SVC => self.write_word(SPSR_SVC_ADDR, value),
-            Mode::IRQ => self.write_word(SPSR_IRQ_ADDR, value),
-            _ => self.set_cpsr(value)
-        }
-    }
-
-    pub fn set_spsr_svc(&mut self, value: Word) {
-        self.write_word(SPSR_SVC_ADDR, value);
-    }
-
-    pub fn set_spsr_irq(&mut self, value: Word) {
-        self.write_word(SPSR_IRQ_ADDR, value);
-    }
-
-    pub fn current_mode_has_spsr(&mut self) -> bool {
-        match self.get_cpsr_mode() {
-            Mode::SVC => true,
-            Mode::IRQ => true,
-            _ => false
-        }
-    }
-
-    pub fn get_nzcv(&mut self) -> Byte {
-        let n = if self.get_n_flag() { 1 } else { 0 };
-        let z = if self.get_z_flag() { 1 } else { 0 };
-        let c = if self.get_c_flag() { 1 } else { 0 };
-        let v = if self.get_v_flag() { 1 } else { 0 };
-
-        n << 3 | z << 2 | c << 1 | v
-    }
-
-    pub fn get_nzcv_tuple(&mut self) -> (bool, bool, bool, bool) {
-        (self.get_n_flag(), self.get_z_flag(), self.get_c_flag(), self.get_v_flag())
-    }
-}
-
-impl Memory for Registers {
-    fn new(size: usize, endianness: Endianness) -> Self {
-        Self {
-            endianness,
-            memory_array: vec![0; size],
-            size: size
-        }
-    }
-
-    fn get_size(&self) -> usize {
-        self.size
-    }
-
-    fn get_memory_array(&mut self) -> &mut Vec<Byte> {
-        &mut self.memory_array
-    }
-
-    fn get_endianness(&self) -> Endianness {
-        self.endianness
-    }
-
-    // only to fulfill method stubs
-    fn get_checksum(&self) -> Checksum { 0 }
-    fn set_checksum(&mut self, _checksum: Checksum) { }
-    fn get_update_frontend_checksum(&self) -> bool { false }
-    fn set_update_frontend_checksum(&mut self, _state: bool) { }
-}
-
-impl Default for Registers {
-    fn default() -> Self {
-        Registers {
-            endianness: Endianness::Big,
-            memory_array: vec![0; NUM_REGISTERS * REGISTER_BYTES],
-            size: NUM_REGISTERS * REGISTER_BYTES
-        }
-    }
-}
-
-pub struct RAM {
-    pub checksum: Checksum,
-    pub update_frontend_checksum: bool, // flag whether the checksum has changed since the frontend last updated
-    pub endianness: Endianness,
-    pub loaded: bool, // this is included in the case that the frontend was loaded after the elf loader tried to emit an event
-    pub memory_array: Vec<Byte>, // unsigned Byte array
-    pub size: usize,
-    pub display_offset: AddressSize // offset used when computing chunks for the frontend
-}
-
-impl Memory for RAM {
-    fn new(size: usize, endianness: Endianness) -> Self {
-        Self {
-            checksum: 0,
-            update_frontend_checksum: false,
-            endianness,
-            loaded: false,
-            memory_array: vec![0; size],
-            size,
-            display_offset: 0
-        }
-    }
-
-    fn get_size(&self) -> usize {
-        self.size
-    }
-
-    fn get_memory_array(&mut self) -> &mut Vec<Byte> {
-        &mut self.memory_array
-    }
-
-    fn get_endianness(&self) -> Endianness {
-        self.endianness
-    }
-
-    fn get_checksum(&self) -> Checksum {
-        self.checksum
-    }
-
-    fn set_checksum(&mut self, checksum: Checksum) {
-        self.set_update_frontend_checksum(true);
-        self.checksum = checksum
-    }
-
-    fn get_update_frontend_checksum(&self) -> bool {
-        self.update_frontend_checksum
-    }
-
-    fn set_update_frontend_checksum(&mut self, state: bool) {
-        self.update_frontend_checksum = state;
-    }
-}
-
-impl Default for RAM {
-    fn default() -> Self {
-        RAM {
-            checksum: 0,
-            update_frontend_checksum: false,
-            endianness: Endianness::Big,
-            loaded: false,
-            memory_array: vec![0; DEFAULT_MEMORY_SIZE],
-            size: DEFAULT_MEMORY_SIZE,
-            display_offset: 0
-        }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    
-    #[test]
-    fn test_read_word() {
-        let mut mem = RAM::default();
-
-        mem.memory_array[0] = 0x05;
-        mem.memory_array[1] = 0xFF;
-        mem.memory_array[2] = 0x06;
-        mem.memory_array[3] = 0xA0;
-
-        let be = mem.read_word(0);
-        assert_eq!(be, 0x05FF06A0);
-        
-        mem.endianness = Endianness::Little;
-        assert_eq!(mem.read_word(0), 0xA006FF05);
-        
-    }
-
-    
-    #[test]
-    fn test_read_word_alignment_error() {
-        let mut mem = RAM::default();
-
-        mem.memory_array[3] = 0xFF;
-
-        assert_eq!(mem.read_word(3), 0);
-    }
-
-    #[test]
-    #[should_panic]
-    fn test_read_word_bounds_error() {
-        let mut mem = RAM::default();
-
-        mem.read_word(32768);
-    }
-    
-    
-    #[test]
-    fn test_write_word() {
-        let mut mem = RAM::default();
-
-        mem.write_word(0, 0x05FF06A0);
-        
-        assert_eq!(mem.memory_array[0], 0x05);
-        assert_eq!(mem.memory_array[1], 0xFF);
-        assert_eq!(mem.memory_array[2], 0x06);
-        assert_eq!(mem.memory_array[3], 0xA0);
-
-        mem.endianness = Endianness::Little;
-        mem.write_word(0, 0x05FF06A0);
-
-        assert_eq!(mem.memory_array[0], 0xA0);
-        assert_eq!(mem.memory_array[1], 0x06);
-        assert_eq!(mem.memory_array[2], 0xFF);
-        assert_eq!(mem.memory_array[3], 0x05);
-    }
-
-    #[test]
-    fn test_write_word_alignment_error() {
-        let mut mem = RAM::default();
-
-        mem.write_word(3, 1);
-
-        assert_ne!(6, 1);
-    }
-
-    #[test]
-    #[should_panic]
-    fn test_write_word_bounds_error() {
-        let mut mem = RAM::default();
-
-        mem.write_word(32768, 0);
-    }
-    
-    
-    #[test]
-    fn test_read_half_word() {
-        let mut mem = RAM::default();
-        
-        mem.memory_array[0] = 0x05;
-        mem.memory_array[1] = 0xFF;
-        
-        assert_eq!(mem.read_half_word(0), 0x05FF);
-        
-        mem.endianness = Endianness::Little;
-        assert_eq!(mem.read_half_word(0), 0xFF05);
-    }
-
-    
-    #[test]
-    fn test_read_half_word_alignment_error() {
-        let mut mem = RAM::default();
-
-        mem.memory_array[1] = 0xFF;
-
-        assert_eq!(mem.read_half_word(1), 0);
-    }
-
-    #[test]
-    #[should_panic]
-    fn test_read_half_word_bounds_error() {
-        let mut mem = RAM::default();
-
-        mem.read_half_word(32768);
-    }
-
-    
-    #[test]
-    fn test_write_half_word() {
-        let mut mem = RAM::default();
-
-        mem.write_half_word(0, 0x05FF);
-        
-        assert_eq!(mem.memory_array[0], 0x05);
-        assert_eq!(mem.memory_array[1], 0xFF);
-
-        mem.endianness = Endianness::Little;
-        mem.write_half_word(0, 0x05FF);
-
-        assert_eq!(mem.memory_array[0], 0xFF);
-        assert_eq!(mem.memory_array[1], 0x05);
-    }
-
-    #[test]
-    fn test_write_half_word_alignment_error() {
-        let mut mem = RAM::default();
-
-        mem.write_half_word(3, 1);
-
-        assert_ne!(4, 1);
-    }
-
-    #[test]
-    #[should_panic]
-    fn test_write_half_word_bounds_error() {
-        let mut mem = RAM::default();
-
-        mem.write_half_word(32768, 0);
-    }
-    
-    #[test]
-    fn test_read_byte() {
-        let mut mem = RAM::default();
-        
-        mem.memory_array[0] = 0x05;
-        
-        assert_eq!(mem.read_byte(0), 0x05);
-        
-        mem.endianness = Endianness::Little;
-        assert_eq!(mem.read_byte(0), 0x05);
-    }
-
-    #[test]
-    #[should_panic]
-    fn test_read_byte_bounds_error() {
-        let mut mem = RAM::default();
-
-        mem.read_byte(32768);
-    }
-
-    
-    #[test]
-    fn test_write_byte() {
-        let mut mem = RAM::default();
-        
-        mem.write_byte(0, 0x05);
-        
-        assert_eq!(mem.memory_array[0], 0x05);
-
-        mem.endianness = Endianness::Little;
-        mem.write_byte(0, 0x05);
-
-        assert_eq!(mem.memory_array[0], 0x05);
-    }
-
-    #[test]
-    #[should_panic]
-    fn test_write_byte_bounds_error() {
-        let mut mem = RAM::default();
-
-        mem.write_half_word(32768, 0);
-    }
-    
-    #[test]
-    fn test_calculate_checksum() {
-        let mut mem = RAM::default();
-        
-        mem.memory_array[0] = 0x01;
-        mem.memory_array[1] = 0x82;
-        mem.memory_array[2] = 0x03;
-        mem.memory_array[3] = 0x84;
-
-        assert_eq!(mem.calculate_checksum(), 536854790);
-    }
-
-    
-    #[test]
-    fn test_test_flag() {
-        let mut mem = RAM::default();
-
-        mem.memory_array[0] = 0x1C;
-        mem.memory_array[1] = 0xCB;
-        mem.memory_array[2] = 0x1D;
-        mem.memory_array[3] = 0x1A;
-
-        assert_eq!(mem.test_flag(0, 11), true);
-        assert_eq!(mem.test_flag(0, 13), false);
-    }
-
-    
-    #[test]
-    #[should_panic]
-    fn test_test_flag_bit_range_error() {
-        let mut mem = RAM::default();
-
-        mem.test_flag(0, 32);
-    }
-
-    
-    #[test]
-    fn test_set_flag() {
-        let mut mem = RAM::default();
-
-        mem.set_flag(0, 12, true);
-        assert_eq!(mem.memory_array[2], 0x10);
-
-        mem.set_flag(0, 12, false);
-        assert_eq!(mem.memory_array[2], 0x00);
-    }
-
-    
-    #[test]
-    #[should_panic]
-    fn test_set_flag_bit_range_error() {
-        let mut mem = RAM::default();
-
-        mem.set_flag(0, 32, true);
-    }
-
-    
-    #[test]
-    fn test_extract_bits() {
-        let w = RAM::extract_bits(0xC7A2511E, 5, 20);
-        assert_eq!(w, 0x25100);
-    }
-
-    
-    #[test]
-    #[should_panic]
-    fn test_extract_bits_invalid_start_bit() {
-        RAM::extract_bits(0x0, 32, 0);
-    }
-
-    
-    #[test]
-    #[should_panic]
-    fn test_extract_bits_invalid_end_bit() {
-        RAM::extract_bits(0x0, 0, 32);
-    }
-
-    
-    #[test]
-    #[should_panic]
-    fn test_extract_bits_bit_inequality() {
-        RAM::extract_bits(0x0, 12, 10);
-    }
-
-    #[test]
-    fn test_get_as_word() {
-        let mut regs = Registers::default();
-
-        // little-endian
-        regs.memory_array[4] = 0x45;
-        regs.memory_array[5] = 0x55;
-        regs.memory_array[6] = 0xAE;
-        regs.memory_array[7] = 0xFF;
-
-        assert_eq!(0xFFAE5545, regs.get_register(1));
-    }
-
-    #[test]
-    fn test_get_register() {
-        let mut regs = Registers::default();
-
-        regs.write_word(4, 0xAEBD056D);
-        assert_eq!(0xAEBD056D, regs.get_register(1));
-    }
-
-    #[test]
-    #[should_panic]
-    fn test_get_register_range_error() {
-        let mut regs = Registers::default();
-
-        regs.get_register(16);
-    }
-
-    #[test]
-    fn test_set_register() {
-        let mut regs = Registers::default();
-        regs.set_register(1, 5);
-
-        assert_eq!(5, regs.read_word(4));
-    }
-
-    #[test]
-    #[should_panic]
-    fn test_set_register_range_error() {
-        let mut regs = Registers::default();
-        regs.set_register(16, 0);
-    }
-
-    #[test]
-    fn test_get_all() {
-        let mut regs = Registers::default();
-
-        regs.set_register(0, 0xAEB);
-        regs.set_register(1, 5);
-        regs.set_register(13, 0x11FF11FF);
-
-        let rs = regs.get_all();
-        assert_eq!(0xAEB, rs[0]);
-        assert_eq!(5, rs[1]);
-        assert_eq!(0x11FF11FF, rs[13]);
-    }
-
-    #[test]
-    fn test_set_pc() {
-        let mut regs = Registers::default();
-
-        regs.set_pc(0x106);
-        assert_eq!(0x106, regs.get_register(15));
-    }
-
-    #[test]
-    fn test_get_pc() {
-        let mut regs = Registers::default();
-
-        regs.set_register(15, 0x106);
-        assert_eq!(0x106, regs.get_pc());
-    }
-
-    #[test]
-    fn test_inc_pc() {
-        let mut regs = Registers::default();
-
-        regs.set_pc(0x106);
-        regs.inc_pc();
-        assert_eq!(0x10a, regs.get_pc());
-    }
-
-    #[test]
-    fn test_get_cpsr() {
-        let mut regs = Registers::default();
-
-        regs.write_word(64, 0xFF11FF11);
-        assert_eq!(0xFF11FF11, regs.get_cpsr());
-    }
-
-    #[test]
-    fn test_get_cpsr_control_byte() {
-        let mut regs = Registers::default();
-
-        regs.write_word(64, 0xAA000000);
-        assert_eq!(0xAA, regs.get_cpsr_control_byte());
-    }
+use core::{fmt, str::FromStr};
+
+use log::error;
+use num_derive::FromPrimitive;
+use object::Endianness;
+
+use crate::{cpu_enum::Mode, bus::DeviceBus, coprocessor::{Coprocessors, CoprocessorOp, FAULT_STATUS_ALIGNMENT, FAULT_STATUS_EXTERNAL}, mmu::{Access, Mmu, MmuFault}, cache::Cache};
+
+pub type Byte = u8;
+pub type HalfWord = u16;
+pub type Word = u32;
+pub type SignedWord = i32;
+pub type AddressSize = u32;
+pub type Checksum = u32;
+
+pub const DEFAULT_MEMORY_SIZE: usize = 32768;
+pub const MAX_MEMORY_SIZE: usize = 1024000;
+
+pub const NUM_REGISTERS: usize = 37; // r0...r15, CPSR, SP_svc, LR_svc, SPSR_svc, SP_irq, LR_irq, SPSR_irq, r8_fiq...r14_fiq, SPSR_fiq,
+                                     //  SP_abt, LR_abt, SPSR_abt, SP_und, LR_und, SPSR_und
+pub const REGISTER_BYTES: usize = 4; // 4byte = 32bit
+
+pub const CPSR_ADDR: AddressSize = (16 * REGISTER_BYTES) as AddressSize;
+pub const SPSR_SVC_ADDR: AddressSize = (19 * REGISTER_BYTES) as AddressSize;
+pub const SPSR_IRQ_ADDR: AddressSize = (22 * REGISTER_BYTES) as AddressSize;
+pub const SPSR_FIQ_ADDR: AddressSize = (30 * REGISTER_BYTES) as AddressSize;
+pub const SPSR_ABT_ADDR: AddressSize = (33 * REGISTER_BYTES) as AddressSize;
+pub const SPSR_UND_ADDR: AddressSize = (36 * REGISTER_BYTES) as AddressSize;
+
+// used when calculating r13 and r14 in non-system modes to properly index the register array
+pub const MODE_OFFSET_SVC: usize = 4;
+pub const MODE_OFFSET_IRQ: usize = 7;
+// FIQ also banks r8..r12
+pub const MODE_OFFSET_FIQ: usize = 15;
+pub const MODE_OFFSET_ABT: usize = 18;
+pub const MODE_OFFSET_UND: usize = 21;
+
+// processor state on reset: SVC mode with IRQ and FIQ disabled
+pub const RESET_CPSR: Word = 0xd3;
+
+pub const DISPLAY_ADDR: AddressSize  = 0x100000;
+pub const KEYBOARD_ADDR: AddressSize = 0x100001; // data, followed by the status byte
+pub const TIMER_ADDR: AddressSize    = 0x100010; // 4 word registers, see devices::TimerDevice
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MemoryError {
+    OutOfRange(AddressSize), // no RAM or device at the address
+    Misaligned(AddressSize), // alignment fault under AlignmentPolicy::Fault
+    Mmu(AddressSize, MmuFault) // translation, domain or permission fault at the virtual address
+}
+
+impl MemoryError {
+    pub fn get_address(&self) -> AddressSize {
+        match self {
+            MemoryError::OutOfRange(addr) | MemoryError::Misaligned(addr) | MemoryError::Mmu(addr, _) => *addr
+        }
+    }
+
+    // status and domain reported in the CP15 fault status registers
+    pub fn get_fault_status(&self) -> Word {
+        match self {
+            MemoryError::OutOfRange(_) => FAULT_STATUS_EXTERNAL,
+            MemoryError::Misaligned(_) => FAULT_STATUS_ALIGNMENT,
+            MemoryError::Mmu(_, fault) => fault.status
+        }
+    }
+
+    pub fn get_fault_domain(&self) -> Word {
+        match self {
+            MemoryError::Mmu(_, fault) => fault.domain,
+            _ => 0
+        }
+    }
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MemoryError::OutOfRange(addr) => write!(f, "address 0x{:x} is out of range", addr),
+            MemoryError::Misaligned(addr) => write!(f, "address 0x{:x} is misaligned", addr),
+            MemoryError::Mmu(addr, fault) => write!(f, "{} at address 0x{:x}", fault, addr)
+        }
+    }
+}
+
+// how word and halfword accesses that are not naturally aligned behave
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AlignmentPolicy {
+    // ARMv4: loads read the aligned word and rotate it right by 8 bits per byte of misalignment
+    //  (on a little-endian system the addressed byte ends up in bits 7..0), stores ignore the low address bits
+    Rotate,
+    // every misaligned access raises a Data Abort (ARMv6 with SCTLR.A set)
+    Fault,
+    // ARMv6 unaligned support (SCTLR.U set): the access touches exactly the addressed bytes;
+    //  LDM/STM still fault
+    Unaligned
+}
+
+impl FromStr for AlignmentPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rotate" => Ok(AlignmentPolicy::Rotate),
+            "fault" => Ok(AlignmentPolicy::Fault),
+            "unaligned" => Ok(AlignmentPolicy::Unaligned),
+            _ => Err(format!("unknown alignment policy {} (expected rotate, fault or unaligned)", s))
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, FromPrimitive, PartialEq, Debug)]
+pub enum Register {
+    r0 = 0,
+    r1 = 1,
+    r2 = 2,
+    r3 = 3,
+    r4 = 4,
+    r5 = 5,
+    r6 = 6,
+    r7 = 7,
+    r8 = 8,
+    r9 = 9,
+    r10 = 10,
+    r11 = 11,
+    r12 = 12,
+    r13 = 13,
+    r14 = 14,
+    r15 = 15,
+    NOP_CPSR,
+    r13_svc,
+    r14_svc,
+    NOP_SPSR_svc,
+    r13_irq,
+    r14_irq,
+    NOP_SPSR_irq,
+    r8_fiq,
+    r9_fiq,
+    r10_fiq,
+    r11_fiq,
+    r12_fiq,
+    r13_fiq,
+    r14_fiq,
+    NOP_SPSR_fiq,
+    r13_abt,
+    r14_abt,
+    NOP_SPSR_abt,
+    r13_und,
+    r14_und,
+    NOP_SPSR_und
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Register::r10 => write!(f, "sl"),
+            Register::r11 => write!(f, "fp"),
+            Register::r12 => write!(f, "il"),
+            Register::r13 => write!(f, "sp"),
+            Register::r14 => write!(f, "lr"),
+            Register::r15 => write!(f, "pc"),
+            _ => write!(f, "{:?}", self)
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct RegistersPayload {
+    pub register_array: Vec<Word>
+}
+
+impl Default for RegistersPayload {
+    fn default() -> Self {
+        RegistersPayload {
+            register_array: vec![0, 0]
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct FlagsPayload {
+    pub n: bool,
+    pub c: bool,
+    pub z: bool,
+    pub v: bool,
+    pub q: bool,
+    pub i: bool,
+    pub f: bool
+}
+
+impl Default for FlagsPayload {
+    fn default() -> Self {
+        FlagsPayload {
+            n: false,
+            c: false,
+            z: false,
+            v: false,
+            q: false,
+            i: false,
+            f: false,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct RAMPayload {
+    pub checksum: Checksum,
+    pub memory_array: Vec<Vec<Byte>>
+}
+
+impl Default for RAMPayload {
+    fn default() -> Self {
+        RAMPayload {
+            checksum: 0,
+            memory_array: vec![vec![0; 0]; 0],
+        }
+    }
+}
+
+pub trait Memory {
+    fn new(size: usize, endianness: Endianness) -> Self;
+    fn get_size(&self) -> usize;
+    fn get_memory_array(&mut self) -> &mut Vec<Byte>;
+    fn get_endianness(&self) -> Endianness;
+    fn get_checksum(&self) -> Checksum;
+    fn set_checksum(&mut self, checksum: Checksum);
+    fn get_update_frontend_checksum(&self) -> bool;
+    fn set_update_frontend_checksum(&mut self, state: bool);
+    fn get_alignment_policy(&self) -> AlignmentPolicy;
+    fn set_alignment_policy(&mut self, policy: AlignmentPolicy);
+
+    fn clear(&mut self) {
+        let sz = self.get_size();
+        self.get_memory_array().clear();
+        self.get_memory_array().resize(sz, 0);
+    }
+
+    // fails when any byte of the access at addr lies past the end of memory
+    fn check_range(&self, addr: AddressSize, len: usize) -> Result<(), MemoryError> {
+        if addr as usize + len > self.get_size() {
+            Err(MemoryError::OutOfRange(addr))
+        } else {
+            Ok(())
+        }
+    }
+
+    // applies the alignment policy to an access of len bytes, returning the address actually accessed
+    fn align_address(&self, addr: AddressSize, len: AddressSize) -> Result<AddressSize, MemoryError> {
+        let offset = addr % len;
+        if offset == 0 {
+            return Ok(addr)
+        }
+
+        match self.get_alignment_policy() {
+            AlignmentPolicy::Rotate => Ok(addr - offset),
+            AlignmentPolicy::Fault => Err(MemoryError::Misaligned(addr)),
+            AlignmentPolicy::Unaligned => Ok(addr)
+        }
+    }
+
+    fn try_read_word(&mut self, addr: AddressSize) -> Result<Word, MemoryError> {
+        let aligned_addr = self.align_address(addr, 4)?;
+        self.check_range(aligned_addr, 4)?;
+
+        let w0: Word = *self.get_memory_array().get(aligned_addr as usize).unwrap() as Word;
+        let w1: Word = *self.get_memory_array().get((aligned_addr + 1) as usize).unwrap() as Word;
+        let w2: Word = *self.get_memory_array().get((aligned_addr + 2) as usize).unwrap() as Word;
+        let w3: Word = *self.get_memory_array().get((aligned_addr + 3) as usize).unwrap() as Word;
+
+        let w = if self.get_endianness() == Endianness::Little {
+            (w3 << 24) | (w2 << 16) | (w1 << 8) | w0
+        } else {
+            (w0 << 24) | (w1 << 16) | (w2 << 8) | w3
+        };
+
+        // A4.1.23: an unaligned load rotates the aligned word right by 8 * address[1:0]
+        Ok(w.rotate_right((addr - aligned_addr) * 8))
+    }
+
+    
+    fn try_write_word(&mut self, addr: AddressSize, value: Word) -> Result<(), MemoryError> {
+        let addr = self.align_address(addr, 4)?;
+        self.check_range(addr, 4)?;
+
+        let b0: Byte = ((value >> 24) & 0xff) as Byte;
+        let b1: Byte = ((value >> 16) & 0xff) as Byte;
+        let b2: Byte = ((value >> 8) & 0xff) as Byte;
+        let b3: Byte = (value & 0xff) as Byte;
+
+        if self.get_endianness() == Endianness::Little {
+            self.get_memory_array()[addr as usize] = b3;
+            self.get_memory_array()[(addr + 1) as usize] = b2;
+            self.get_memory_array()[(addr + 2) as usize] = b1;
+            self.get_memory_array()[(addr + 3) as usize] = b0;
+        } else {
+            self.get_memory_array()[addr as usize] = b0;
+            self.get_memory_array()[(addr + 1) as usize] = b1;
+            self.get_memory_array()[(addr + 2) as usize] = b2;
+            self.get_memory_array()[(addr + 3) as usize] = b3;
+        }
+
+        let checksum = self.calculate_checksum();
+        self.set_checksum(checksum);
+        Ok(())
+    }
+
+    
+    // under AlignmentPolicy::Rotate this returns the aligned halfword; the LDRH/LDRSH executors
+    //  apply the rest of the ARM7TDMI behavior since it depends on the 32-bit result
+    fn try_read_half_word(&mut self, addr: AddressSize) -> Result<HalfWord, MemoryError> {
+        let addr = self.align_address(addr, 2)?;
+        self.check_range(addr, 2)?;
+
+        let hw0: HalfWord = *self.get_memory_array().get(addr as usize).unwrap() as HalfWord;
+        let hw1: HalfWord = *self.get_memory_array().get((addr + 1) as usize).unwrap() as HalfWord;
+
+        if self.get_endianness() == Endianness::Little {
+            Ok((hw1 << 8) | hw0)
+        } else {
+            Ok((hw0 << 8) | hw1)
+        }
+    }
+
+    
+    fn try_write_half_word(&mut self, addr: AddressSize, value: HalfWord) -> Result<(), MemoryError> {
+        let addr = self.align_address(addr, 2)?;
+        self.check_range(addr, 2)?;
+
+        // example:
+        //  0x74 EC
+        //    b0 b1
+        let b0: Byte = ((value >> 8) & 0xff) as Byte;
+        let b1: Byte = (value & 0xff) as Byte;
+        
+        // big endian: b0 b1
+        // little endian: b1 b0
+        if self.get_endianness() == Endianness::Little {
+            self.get_memory_array()[addr as usize] = b1;
+            self.get_memory_array()[(addr + 1) as usize] = b0;
+        } else {
+            self.get_memory_array()[addr as usize] = b0;
+            self.get_memory_array()[(addr + 1) as usize] = b1;
+        }
+
+        let checksum = self.calculate_checksum();
+        self.set_checksum(checksum);
+        Ok(())
+    }
+
+    
+    fn try_read_byte(&mut self, addr: AddressSize) -> Result<Byte, MemoryError> {
+        self.check_range(addr, 1)?;
+
+        Ok(*self.get_memory_array().get(addr as usize).unwrap() as Byte)
+    }
+
+    
+    fn try_write_byte(&mut self, addr: AddressSize, value: Byte) -> Result<(), MemoryError> {
+        self.check_range(addr, 1)?;
+
+        self.get_memory_array()[addr as usize] = value;
+
+        let checksum = self.calculate_checksum();
+        self.set_checksum(checksum);
+        Ok(())
+    }
+
+    // the plain accessors are used by the host (ELF loader, frontend panels) and never panic:
+    //  an out-of-range read is logged and returns 0, an out-of-range write is logged and dropped
+    fn read_word(&mut self, addr: AddressSize) -> Word {
+        self.try_read_word(addr).unwrap_or_else(|err| {
+            error!("Memory[read_word]: {}", err);
+            0
+        })
+    }
+
+    fn write_word(&mut self, addr: AddressSize, value: Word) {
+        self.try_write_word(addr, value).unwrap_or_else(|err| error!("Memory[write_word]: {}", err))
+    }
+
+    fn read_half_word(&mut self, addr: AddressSize) -> HalfWord {
+        self.try_read_half_word(addr).unwrap_or_else(|err| {
+            error!("Memory[read_half_word]: {}", err);
+            0
+        })
+    }
+
+    fn write_half_word(&mut self, addr: AddressSize, value: HalfWord) {
+        self.try_write_half_word(addr, value).unwrap_or_else(|err| error!("Memory[write_half_word]: {}", err))
+    }
+
+    fn read_byte(&mut self, addr: AddressSize) -> Byte {
+        self.try_read_byte(addr).unwrap_or_else(|err| {
+            error!("Memory[read_byte]: {}", err);
+            0
+        })
+    }
+
+    fn write_byte(&mut self, addr: AddressSize, value: Byte) {
+        self.try_write_byte(addr, value).unwrap_or_else(|err| error!("Memory[write_byte]: {}", err))
+    }
+
+    
+    fn calculate_checksum(&mut self) -> Checksum {
+        let mut checksum: u32 = 0;
+    
+        for address in 0..self.get_memory_array().len() {
+            checksum = checksum.wrapping_add(self.read_byte(address as AddressSize) as u32 ^ (address as u32));
+        }
+    
+        return checksum;
+    }
+
+    
+    fn test_flag(&mut self, addr: AddressSize, bit: u8) -> bool {
+        // bit is in the range of [0..31]
+        if bit > 31 {
+            panic!("Memory[test_flag]: bit is out of range")
+        }
+        
+        let w: Word = self.read_word(addr);
+        if (w >> bit) & 1 == 1 { true } else { false }
+    }
+
+    
+    fn set_flag(&mut self, addr: AddressSize, bit: u8, flag: bool) {
+        // bit is in the range of [0..31]
+        if bit > 31 {
+            panic!("Memory[set_flag]: bit is out of range")
+        }
+
+        let mut w: Word = self.read_word(addr);
+
+        if flag {
+            // set bit
+            w |= 1 << bit;
+
+        } else {
+            // clear bit
+            w &= !(1 << bit);
+        }
+
+        self.write_word(addr, w);
+    }
+
+    // static utility
+    fn extract_bits(w: Word, start_bit: u8, end_bit: u8) -> Word {
+        // bit is in the range of [0..31]
+        if start_bit > 31 || end_bit > 31{
+            panic!("Memory[extract_bits]: bit is out of range")
+        }
+
+        if start_bit > end_bit {
+            panic!("Memory[extract_bits]: startBit must be <= endBit");
+        }
+
+        let mut mask: Word = 0;
+        for i in start_bit..=end_bit {
+            let bit: Word = 1 << i;
+            mask |= bit;
+        }
+        mask & w
+    }
+}
+
+pub struct Registers {
+    pub endianness: Endianness,
+    pub memory_array: Vec<Byte>, // unsigned Byte array
+    pub size: usize
+}
+
+impl Registers {
+    // fix indices when attempting to access banked registers on non-system modes
+    pub fn get_register_mode_index(&mut self, index: usize) -> usize {
+        if index >= NUM_REGISTERS {
+            panic!("Registers[get_register_mode_index]: register index out of range");
+        }
+
+        match self.get_cpsr_mode() {
+            Mode::SVC => {
+                if index == 13 || index == 14 {
+                    index + MODE_OFFSET_SVC
+                } else {
+                    index
+                }
+            },
+            Mode::IRQ => {
+                if index == 13 || index == 14 {
+                    index + MODE_OFFSET_IRQ
+                } else {
+                    index
+                }
+            },
+            Mode::FIQ => {
+                if (8..=14).contains(&index) {
+                    index + MODE_OFFSET_FIQ
+                } else {
+                    index
+                }
+            },
+            Mode::ABT => {
+                if index == 13 || index == 14 {
+                    index + MODE_OFFSET_ABT
+                } else {
+                    index
+                }
+            },
+            Mode::UND => {
+                if index == 13 || index == 14 {
+                    index + MODE_OFFSET_UND
+                } else {
+                    index
+                }
+            },
+            _ => index
+        }
+    }
+
+    // index is r0..r15 as seen by the current mode
+    pub fn set_register(&mut self, index: usize, value: Word) {
+        if index > 15 {
+            panic!("Registers[set_register]: register index out of range");
+        }
+
+        let address = (self.get_register_mode_index(index) * 4) as AddressSize;
+        self.write_word(address, value)
+    }
+
+    // banked registers named explicitly (r14_svc, r8_fiq, ...) are accessed regardless of the current mode
+    pub fn set_reg_register(&mut self, reg: Register, value: Word) {
+        if (reg as usize) <= 15 {
+            self.set_register(reg as usize, value)
+        } else {
+            self.write_word((reg as usize * REGISTER_BYTES) as AddressSize, value)
+        }
+    }
+
+    pub fn get_register(&mut self, index: usize) -> Word {
+        if index > 15 {
+            panic!("Registers[get_register]: register index out of range");
+        }
+
+        let address = (self.get_register_mode_index(index) * 4) as AddressSize;
+        self.read_word(address)
+    }
+
+    pub fn get_reg_register(&mut self, reg: Register) -> Word {
+        if (reg as usize) <= 15 {
+            self.get_register(reg as usize)
+        } else {
+            self.read_word((reg as usize * REGISTER_BYTES) as AddressSize)
+        }
+    }
+
+    // the User mode registers whatever the current mode; LDM/STM with the S bit transfer these
+    pub fn set_user_register(&mut self, index: usize, value: Word) {
+        if index > 15 {
+            panic!("Registers[set_user_register]: register index out of range");
+        }
+
+        self.write_word((index * REGISTER_BYTES) as AddressSize, value)
+    }
+
+    pub fn get_user_register(&mut self, index: usize) -> Word {
+        if index > 15 {
+            panic!("Registers[get_user_register]: register index out of range");
+        }
+
+        self.read_word((index * REGISTER_BYTES) as AddressSize)
+    }
+
+    pub fn get_all(&mut self) -> Vec<Word> {
+        let mut regs: Vec<Word> = vec![0; 0];
+        
+        // r0..r15
+        for i in 0..=15 {
+            regs.push(self.get_register(i));
+        }
+        regs
+    }
+
+    pub fn set_pc(&mut self, value: Word) {
+        self.set_register(15, value)
+    }
+
+    pub fn get_pc(&mut self) -> Word {
+        self.get_register(15)
+    }
+    
+    // 2 bytes in Thumb state, 4 bytes in ARM state
+    pub fn get_instr_size(&mut self) -> Word {
+        if self.get_t_flag() { 2 } else { 4 }
+    }
+
+    // the PC reads two instructions ahead: +8 in ARM state and +4 in Thumb state
+    pub fn get_pc_current_address(&mut self) -> Word {
+        let size = self.get_instr_size();
+        self.get_pc().wrapping_sub(size * 2)
+    }
+
+    pub fn dec_pc(&mut self) {
+        let size = self.get_instr_size();
+        let next_addr = self.get_pc().wrapping_sub(size);
+        self.set_register(15, next_addr)
+    }
+    
+    pub fn inc_pc(&mut self) {
+        let size = self.get_instr_size();
+        let next_addr = self.get_pc().wrapping_add(size);
+        self.set_register(15, next_addr)
+    }
+
+    pub fn get_sp(&mut self) -> Word {
+        self.get_register(13)
+    }
+
+    pub fn get_cpsr(&mut self) -> Word {
+        self.read_word(CPSR_ADDR)
+    }
+    
+    pub fn set_cpsr(&mut self, value: Word) {
+        self.write_word(CPSR_ADDR, value);
+    }
+    
+    pub fn set_cpsr_flag(&mut self, bit: u8, flag: bool) {
+        self.set_flag(CPSR_ADDR, bit, flag)
+    }
+
+    pub fn get_cpsr_flag(&mut self, bit: u8) -> bool {
+        self.test_flag(CPSR_ADDR, bit)
+    }
+
+    pub fn get_cpsr_mode(&mut self) -> Mode {
+        let mode_bits = Registers::extract_bits(self.get_cpsr(), 0, 4);
+        let mode: Mode = num::FromPrimitive::from_u32(mode_bits).unwrap();
+        mode
+    }
+
+    pub fn set_cpsr_mode(&mut self, mode: Mode) {
+        let cpsr = self.get_cpsr();
+        self.set_cpsr((cpsr & !0b11111) | mode as Word);
+    }
+
+    pub fn clear_nzcv(&mut self) {
+        self.set_flag(CPSR_ADDR, 31, false);
+        self.set_flag(CPSR_ADDR, 30, false);
+        self.set_flag(CPSR_ADDR, 29, false);
+        self.set_flag(CPSR_ADDR, 28, false);
+    }
+
+    pub fn get_n_flag(&mut self) -> bool {
+        self.test_flag(CPSR_ADDR, 31)
+    }
+
+    pub fn set_n_flag(&mut self, flag: bool) {
+        self.set_flag(CPSR_ADDR, 31, flag);
+    }
+
+    pub fn get_z_flag(&mut self) -> bool {
+        self.test_flag(CPSR_ADDR, 30)
+    }
+
+    pub fn set_z_flag(&mut self, flag: bool) {
+        self.set_flag(CPSR_ADDR, 30, flag);
+    }
+
+    pub fn get_c_flag(&mut self) -> bool {
+        self.test_flag(CPSR_ADDR, 29)
+    }
+
+    pub fn set_c_flag(&mut self, flag: bool) {
+        self.set_flag(CPSR_ADDR, 29, flag);
+    }
+
+    pub fn get_v_flag(&mut self) -> bool {
+        self.test_flag(CPSR_ADDR, 28)
+    }
+
+    pub fn set_v_flag(&mut self, flag: bool) {
+        self.set_flag(CPSR_ADDR, 28, flag);
+    }
+
+    // ARMv5TE sticky overflow flag; set by saturating arithmetic and only cleared through MSR
+    pub fn get_q_flag(&mut self) -> bool {
+        self.test_flag(CPSR_ADDR, 27)
+    }
+
+    pub fn set_q_flag(&mut self, flag: bool) {
+        self.set_flag(CPSR_ADDR, 27, flag);
+    }
+
+    pub fn get_i_flag(&mut self) -> bool {
+        self.test_flag(CPSR_ADDR, 7)
+    }
+
+    pub fn set_i_flag(&mut self, flag: bool) {
+        self.set_flag(CPSR_ADDR, 7, flag);
+    }
+
+    pub fn get_f_flag(&mut self) -> bool {
+        self.test_flag(CPSR_ADDR, 6)
+    }
+
+    pub fn set_f_flag(&mut self, flag: bool) {
+        self.set_flag(CPSR_ADDR, 6, flag);
+    }
+
+    pub fn get_t_flag(&mut self) -> bool {
+        self.test_flag(CPSR_ADDR, 5)
+    }
+
+    pub fn set_t_flag(&mut self, flag: bool) {
+        self.set_flag(CPSR_ADDR, 5, flag);
+    }
+
+    pub fn get_cpsr_control_byte(&mut self) -> Byte {
+        self.read_byte(CPSR_ADDR + 3)
+    }
+
+    pub fn get_spsr(&mut self) -> Word {
+        match self.get_cpsr_mode() {
+            Mode::SVC => self.read_word(SPSR_SVC_ADDR),
+            Mode::IRQ => self.read_word(SPSR_IRQ_ADDR),
+            Mode::FIQ => self.read_word(SPSR_FIQ_ADDR),
+            Mode::ABT => self.read_word(SPSR_ABT_ADDR),
+            Mode::UND => self.read_word(SPSR_UND_ADDR),
+            _ => self.read_word(CPSR_ADDR)
+        }
+    }
+
+    pub fn set_spsr(&mut self, value: Word) {
+        match self.get_cpsr_mode() {
+            Mode::SVC => self.write_word(SPSR_SVC_ADDR, value),
+            Mode::IRQ => self.write_word(SPSR_IRQ_ADDR, value),
+            Mode::FIQ => self.write_word(SPSR_FIQ_ADDR, value),
+            Mode::ABT => self.write_word(SPSR_ABT_ADDR, value),
+            Mode::UND => self.write_word(SPSR_UND_ADDR, value),
+            _ => self.set_cpsr(value)
+        }
+    }
+
+    pub fn set_spsr_svc(&mut self, value: Word) {
+        self.write_word(SPSR_SVC_ADDR, value);
+    }
+
+    pub fn set_spsr_irq(&mut self, value: Word) {
+        self.write_word(SPSR_IRQ_ADDR, value);
+    }
+
+    pub fn set_spsr_fiq(&mut self, value: Word) {
+        self.write_word(SPSR_FIQ_ADDR, value);
+    }
+
+    pub fn set_spsr_abt(&mut self, value: Word) {
+        self.write_word(SPSR_ABT_ADDR, value);
+    }
+
+    pub fn set_spsr_und(&mut self, value: Word) {
+        self.write_word(SPSR_UND_ADDR, value);
+    }
+
+    pub fn current_mode_has_spsr(&mut self) -> bool {
+        match self.get_cpsr_mode() {
+            Mode::SVC => true,
+            Mode::IRQ => true,
+            Mode::FIQ => true,
+            Mode::ABT => true,
+            Mode::UND => true,
+            _ => false
+        }
+    }
+
+    pub fn get_nzcv(&mut self) -> Byte {
+        let n = if self.get_n_flag() { 1 } else { 0 };
+        let z = if self.get_z_flag() { 1 } else { 0 };
+        let c = if self.get_c_flag() { 1 } else { 0 };
+        let v = if self.get_v_flag() { 1 } else { 0 };
+
+        n << 3 | z << 2 | c << 1 | v
+    }
+
+    pub fn get_nzcv_tuple(&mut self) -> (bool, bool, bool, bool) {
+        (self.get_n_flag(), self.get_z_flag(), self.get_c_flag(), self.get_v_flag())
+    }
+}
+
+impl Memory for Registers {
+    fn new(size: usize, endianness: Endianness) -> Self {
+        Self {
+            endianness,
+            memory_array: vec![0; size],
+            size: size
+        }
+    }
+
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    fn get_memory_array(&mut self) -> &mut Vec<Byte> {
+        &mut self.memory_array
+    }
+
+    fn get_endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    // registers come out of a clear in the reset state so the current mode is always valid
+    fn clear(&mut self) {
+        self.memory_array.clear();
+        self.memory_array.resize(self.size, 0);
+        self.set_cpsr(RESET_CPSR);
+    }
+
+    // only to fulfill method stubs
+    fn get_checksum(&self) -> Checksum { 0 }
+    fn set_checksum(&mut self, _checksum: Checksum) { }
+    fn get_update_frontend_checksum(&self) -> bool { false }
+    fn set_update_frontend_checksum(&mut self, _state: bool) { }
+    fn get_alignment_policy(&self) -> AlignmentPolicy { AlignmentPolicy::Fault }
+    fn set_alignment_policy(&mut self, _policy: AlignmentPolicy) { }
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        let mut registers = Registers {
+            endianness: Endianness::Little,
+            memory_array: vec![0; NUM_REGISTERS * REGISTER_BYTES],
+            size: NUM_REGISTERS * REGISTER_BYTES
+        };
+        registers.set_cpsr(RESET_CPSR);
+        registers
+    }
+}
+
+pub struct RAM {
+    pub checksum: Checksum,
+    pub update_frontend_checksum: bool, // flag whether the checksum has changed since the frontend last updated
+    pub endianness: Endianness,
+    pub loaded: bool, // this is included in the case that the frontend was loaded after the elf loader tried to emit an event
+    pub memory_array: Vec<Byte>, // unsigned Byte array
+    pub size: usize,
+    pub display_offset: AddressSize, // offset used when computing chunks for the frontend
+    pub devices: DeviceBus, // memory-mapped peripherals, checked before RAM on CPU data accesses
+    pub coprocessors: Coprocessors, // reached from the coprocessor instruction executors
+    pub mmu: Mmu, // translates CPU loads, stores and fetches while the CP15 M bit is set
+    pub icache: Option<Cache>, // hit/miss models of the instruction and data caches, None when not simulated
+    pub dcache: Option<Cache>,
+    pub alignment: AlignmentPolicy
+}
+
+// CPU loads and stores go through the device bus first so MMIO works for every addressing mode;
+// the Memory trait read/write methods always access RAM directly (ELF loader, frontend panels)
+// an access outside both the devices and RAM fails with a MemoryError, which the CPU turns into a Data Abort
+impl RAM {
+    pub fn load_byte(&mut self, addr: AddressSize) -> Result<Byte, MemoryError> {
+        let result = self.translate(addr, Access::Read).and_then(|addr| match self.devices.read_byte(addr) {
+            Some(value) => Ok(value),
+            None => {
+                let value = self.try_read_byte(addr)?;
+                self.cache_access(addr, Access::Read);
+                Ok(value)
+            }
+        });
+        self.data_abort(result)
+    }
+
+    pub fn store_byte(&mut self, addr: AddressSize, value: Byte) -> Result<(), MemoryError> {
+        let result = self.translate(addr, Access::Write).and_then(|addr| match self.devices.write_byte(addr, value) {
+            true => Ok(()),
+            false => {
+                self.try_write_byte(addr, value)?;
+                self.cache_access(addr, Access::Write);
+                Ok(())
+            }
+        });
+        self.data_abort(result)
+    }
+
+    pub fn load_half_word(&mut self, addr: AddressSize) -> Result<HalfWord, MemoryError> {
+        let result = self.translate(addr, Access::Read).and_then(|addr| match self.devices.read_half_word(addr) {
+            Some(value) => Ok(value),
+            None => {
+                let value = self.try_read_half_word(addr)?;
+                self.cache_access(addr, Access::Read);
+                Ok(value)
+            }
+        });
+        self.data_abort(result)
+    }
+
+    pub fn store_half_word(&mut self, addr: AddressSize, value: HalfWord) -> Result<(), MemoryError> {
+        let result = self.translate(addr, Access::Write).and_then(|addr| match self.devices.write_half_word(addr, value) {
+            true => Ok(()),
+            false => {
+                self.try_write_half_word(addr, value)?;
+                self.cache_access(addr, Access::Write);
+                Ok(())
+            }
+        });
+        self.data_abort(result)
+    }
+
+    pub fn load_word(&mut self, addr: AddressSize) -> Result<Word, MemoryError> {
+        let result = self.translate(addr, Access::Read).and_then(|addr| match self.devices.read_word(addr) {
+            Some(value) => Ok(value),
+            None => {
+                let value = self.try_read_word(addr)?;
+                self.cache_access(addr, Access::Read);
+                Ok(value)
+            }
+        });
+        self.data_abort(result)
+    }
+
+    pub fn store_word(&mut self, addr: AddressSize, value: Word) -> Result<(), MemoryError> {
+        let result = self.translate(addr, Access::Write).and_then(|addr| match self.devices.write_word(addr, value) {
+            true => Ok(()),
+            false => {
+                self.try_write_word(addr, value)?;
+                self.cache_access(addr, Access::Write);
+                Ok(())
+            }
+        });
+        self.data_abort(result)
+    }
+
+    // instruction fetches bypass the device bus; a failed fetch only aborts if the
+    //  instruction reaches execute, so the CPU records it with prefetch_abort
+    pub fn fetch_word(&mut self, addr: AddressSize) -> Result<Word, MemoryError> {
+        self.translate(addr, Access::Fetch).and_then(|addr| {
+            let value = self.try_read_word(addr)?;
+            self.cache_access(addr, Access::Fetch);
+            Ok(value)
+        })
+    }
+
+    pub fn fetch_half_word(&mut self, addr: AddressSize) -> Result<HalfWord, MemoryError> {
+        self.translate(addr, Access::Fetch).and_then(|addr| {
+            let value = self.try_read_half_word(addr)?;
+            self.cache_access(addr, Access::Fetch);
+            Ok(value)
+        })
+    }
+
+    // records a successful RAM access in the I-cache (fetches) or D-cache model, if configured;
+    // the caches are physically addressed and device accesses are uncached
+    fn cache_access(&mut self, addr: AddressSize, access: Access) {
+        match (access, &mut self.icache, &mut self.dcache) {
+            (Access::Fetch, Some(icache), _) => { icache.read(addr); },
+            (Access::Read, _, Some(dcache)) => { dcache.read(addr); },
+            (Access::Write, _, Some(dcache)) => { dcache.write(addr); },
+            _ => ()
+        }
+    }
+
+    // virtual to physical address; page table walks read word-aligned descriptors from RAM directly
+    pub fn translate(&mut self, addr: AddressSize, access: Access) -> Result<AddressSize, MemoryError> {
+        let RAM { mmu, coprocessors, memory_array, endianness, .. } = self;
+        mmu.translate(&coprocessors.cp15, addr, access, |descriptor| {
+            let bytes: [Byte; 4] = memory_array.get(descriptor as usize..descriptor as usize + 4)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or(MemoryError::OutOfRange(descriptor))?;
+            Ok(match endianness {
+                Endianness::Little => Word::from_le_bytes(bytes),
+                Endianness::Big => Word::from_be_bytes(bytes)
+            })
+        })
+    }
+
+    // failed accesses are recorded in the CP15 fault status (and for data, fault address) registers
+    fn data_abort<T>(&mut self, result: Result<T, MemoryError>) -> Result<T, MemoryError> {
+        if let Err(err) = &result {
+            self.coprocessors.cp15.data_abort(err.get_fault_status(), err.get_fault_domain(), err.get_address());
+        }
+        result
+    }
+
+    pub fn prefetch_abort(&mut self, err: &MemoryError) {
+        self.coprocessors.cp15.prefetch_abort(err.get_fault_status(), err.get_fault_domain());
+    }
+
+    // MRC/MCR; CP15's B bit is kept in step with the endianness used for data accesses
+    pub fn read_coprocessor(&mut self, op: &CoprocessorOp) -> Option<Word> {
+        self.coprocessors.cp15.set_big_endian(self.endianness == Endianness::Big);
+        self.coprocessors.read(op)
+    }
+
+    pub fn write_coprocessor(&mut self, op: &CoprocessorOp, value: Word) -> bool {
+        self.coprocessors.cp15.set_big_endian(self.endianness == Endianness::Big);
+        let handled = self.coprocessors.write(op, value);
+        if handled && op.coprocessor == 15 {
+            self.endianness = if self.coprocessors.cp15.is_big_endian() { Endianness::Big } else { Endianness::Little };
+            // c7 invalidate operations: c5 the I-cache, c6 the D-cache, c7 both
+            if op.crn == 7 && op.opcode_2 == 0 {
+                if let (5 | 7, Some(icache)) = (op.crm, &mut self.icache) { icache.invalidate(); }
+                if let (6 | 7, Some(dcache)) = (op.crm, &mut self.dcache) { dcache.invalidate(); }
+            }
+            if op.crn == 8 {
+                self.mmu.invalidate(op, value);
+            }
+        }
+        handled
+    }
+}
+
+impl Memory for RAM {
+    fn new(size: usize, endianness: Endianness) -> Self {
+        Self {
+            checksum: 0,
+            update_frontend_checksum: false,
+            endianness,
+            loaded: false,
+            memory_array: vec![0; size],
+            size,
+            display_offset: 0,
+            devices: DeviceBus::default(),
+            coprocessors: Coprocessors::default(),
+            mmu: Mmu::default(),
+            icache: None,
+            dcache: None,
+            alignment: AlignmentPolicy::Rotate
+        }
+    }
+
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    fn get_memory_array(&mut self) -> &mut Vec<Byte> {
+        &mut self.memory_array
+    }
+
+    fn get_endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    fn get_checksum(&self) -> Checksum {
+        self.checksum
+    }
+
+    fn set_checksum(&mut self, checksum: Checksum) {
+        self.set_update_frontend_checksum(true);
+        self.checksum = checksum
+    }
+
+    fn get_update_frontend_checksum(&self) -> bool {
+        self.update_frontend_checksum
+    }
+
+    fn set_update_frontend_checksum(&mut self, state: bool) {
+        self.update_frontend_checksum = state;
+    }
+
+    fn get_alignment_policy(&self) -> AlignmentPolicy {
+        self.alignment
+    }
+
+    fn set_alignment_policy(&mut self, policy: AlignmentPolicy) {
+        self.alignment = policy;
+    }
+}
+
+impl Default for RAM {
+    fn default() -> Self {
+        RAM {
+            checksum: 0,
+            update_frontend_checksum: false,
+            endianness: Endianness::Big,
+            loaded: false,
+            memory_array: vec![0; DEFAULT_MEMORY_SIZE],
+            size: DEFAULT_MEMORY_SIZE,
+            display_offset: 0,
+            devices: DeviceBus::default(),
+            coprocessors: Coprocessors::default(),
+            mmu: Mmu::default(),
+            icache: None,
+            dcache: None,
+            alignment: AlignmentPolicy::Rotate
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    
+    #[test]
+    fn test_read_word() {
+        let mut mem = RAM::default();
+
+        mem.memory_array[0] = 0x05;
+        mem.memory_array[1] = 0xFF;
+        mem.memory_array[2] = 0x06;
+        mem.memory_array[3] = 0xA0;
+
+        let be = mem.read_word(0);
+        assert_eq!(be, 0x05FF06A0);
+        
+        mem.endianness = Endianness::Little;
+        assert_eq!(mem.read_word(0), 0xA006FF05);
+        
+    }
+
+    
+    #[test]
+    fn test_read_word_alignment_error() {
+        let mut mem = RAM { endianness: Endianness::Little, ..Default::default() };
+
+        mem.memory_array[0] = 0x11;
+        mem.memory_array[1] = 0x22;
+        mem.memory_array[2] = 0x33;
+        mem.memory_array[3] = 0x44;
+        mem.memory_array[4] = 0x55;
+
+        // rotated load of the aligned word
+        assert_eq!(mem.read_word(1), 0x11443322);
+        assert_eq!(mem.read_word(3), 0x33221144);
+
+        mem.set_alignment_policy(AlignmentPolicy::Fault);
+        assert_eq!(mem.try_read_word(3), Err(MemoryError::Misaligned(3)));
+        assert_eq!(mem.read_word(3), 0);
+
+        mem.set_alignment_policy(AlignmentPolicy::Unaligned);
+        assert_eq!(mem.read_word(1), 0x55443322);
+        assert_eq!(mem.try_read_word(32766), Err(MemoryError::OutOfRange(32766)));
+    }
+
+    #[test]
+    fn test_read_word_bounds_error() {
+        let mut mem = RAM::default();
+
+        assert_eq!(mem.try_read_word(32768), Err(MemoryError::OutOfRange(32768)));
+        assert_eq!(mem.read_word(32768), 0);
+    }
+    
+    
+    #[test]
+    fn test_write_word() {
+        let mut mem = RAM::default();
+
+        mem.write_word(0, 0x05FF06A0);
+        
+        assert_eq!(mem.memory_array[0], 0x05);
+        assert_eq!(mem.memory_array[1], 0xFF);
+        assert_eq!(mem.memory_array[2], 0x06);
+        assert_eq!(mem.memory_array[3], 0xA0);
+
+        mem.endianness = Endianness::Little;
+        mem.write_word(0, 0x05FF06A0);
+
+        assert_eq!(mem.memory_array[0], 0xA0);
+        assert_eq!(mem.memory_array[1], 0x06);
+        assert_eq!(mem.memory_array[2], 0xFF);
+        assert_eq!(mem.memory_array[3], 0x05);
+    }
+
+    #[test]
+    fn test_write_word_alignment_error() {
+        let mut mem = RAM { endianness: Endianness::Little, ..Default::default() };
+
+        // the low address bits are ignored
+        mem.write_word(3, 0x44332211);
+        assert_eq!(mem.memory_array[0..5], [0x11, 0x22, 0x33, 0x44, 0x00]);
+
+        mem.set_alignment_policy(AlignmentPolicy::Fault);
+        assert_eq!(mem.try_write_word(5, 0), Err(MemoryError::Misaligned(5)));
+
+        mem.set_alignment_policy(AlignmentPolicy::Unaligned);
+        mem.write_word(1, 0xaabbccdd);
+        assert_eq!(mem.memory_array[0..5], [0x11, 0xdd, 0xcc, 0xbb, 0xaa]);
+    }
+
+    #[test]
+    fn test_write_word_bounds_error() {
+        let mut mem = RAM::default();
+
+        assert_eq!(mem.try_write_word(32768, 0), Err(MemoryError::OutOfRange(32768)));
+        mem.write_word(32768, 0);
+    }
+    
+    
+    #[test]
+    fn test_read_half_word() {
+        let mut mem = RAM::default();
+        
+        mem.memory_array[0] = 0x05;
+        mem.memory_array[1] = 0xFF;
+        
+        assert_eq!(mem.read_half_word(0), 0x05FF);
+        
+        mem.endianness = Endianness::Little;
+        assert_eq!(mem.read_half_word(0), 0xFF05);
+    }
+
+    
+    #[test]
+    fn test_read_half_word_alignment_error() {
+        let mut mem = RAM::default();
+
+        mem.memory_array[0] = 0x11;
+        mem.memory_array[1] = 0xFF;
+        mem.memory_array[2] = 0x22;
+
+        // the aligned halfword, the LDRH executor applies the rotation
+        assert_eq!(mem.read_half_word(1), 0x11FF);
+
+        mem.set_alignment_policy(AlignmentPolicy::Fault);
+        assert_eq!(mem.try_read_half_word(1), Err(MemoryError::Misaligned(1)));
+
+        mem.set_alignment_policy(AlignmentPolicy::Unaligned);
+        assert_eq!(mem.read_half_word(1), 0xFF22);
+    }
+
+    #[test]
+    fn test_read_half_word_bounds_error() {
+        let mut mem = RAM::default();
+
+        assert_eq!(mem.try_read_half_word(32768), Err(MemoryError::OutOfRange(32768)));
+        assert_eq!(mem.read_half_word(32768), 0);
+    }
+
+    
+    #[test]
+    fn test_write_half_word() {
+        let mut mem = RAM::default();
+
+        mem.write_half_word(0, 0x05FF);
+        
+        assert_eq!(mem.memory_array[0], 0x05);
+        assert_eq!(mem.memory_array[1], 0xFF);
+
+        mem.endianness = Endianness::Little;
+        mem.write_half_word(0, 0x05FF);
+
+        assert_eq!(mem.memory_array[0], 0xFF);
+        assert_eq!(mem.memory_array[1], 0x05);
+    }
+
+    #[test]
+    fn test_write_half_word_alignment_error() {
+        let mut mem = RAM::default();
+
+        mem.write_half_word(3, 0x0102);
+        assert_eq!(mem.memory_array[2..4], [0x01, 0x02]);
+
+        mem.set_alignment_policy(AlignmentPolicy::Fault);
+        assert_eq!(mem.try_write_half_word(3, 0), Err(MemoryError::Misaligned(3)));
+
+        mem.set_alignment_policy(AlignmentPolicy::Unaligned);
+        mem.write_half_word(5, 0x0304);
+        assert_eq!(mem.memory_array[4..7], [0x00, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn test_write_half_word_bounds_error() {
+        let mut mem = RAM::default();
+
+        assert_eq!(mem.try_write_half_word(32768, 0), Err(MemoryError::OutOfRange(32768)));
+        mem.write_half_word(32768, 0);
+    }
+    
+    #[test]
+    fn test_read_byte() {
+        let mut mem = RAM::default();
+        
+        mem.memory_array[0] = 0x05;
+        
+        assert_eq!(mem.read_byte(0), 0x05);
+        
+        mem.endianness = Endianness::Little;
+        assert_eq!(mem.read_byte(0), 0x05);
+    }
+
+    #[test]
+    fn test_read_byte_bounds_error() {
+        let mut mem = RAM::default();
+
+        assert_eq!(mem.try_read_byte(32768), Err(MemoryError::OutOfRange(32768)));
+        assert_eq!(mem.read_byte(32768), 0);
+    }
+
+    
+    #[test]
+    fn test_write_byte() {
+        let mut mem = RAM::default();
+        
+        mem.write_byte(0, 0x05);
+        
+        assert_eq!(mem.memory_array[0], 0x05);
+
+        mem.endianness = Endianness::Little;
+        mem.write_byte(0, 0x05);
+
+        assert_eq!(mem.memory_array[0], 0x05);
+    }
+
+    #[test]
+    fn test_write_byte_bounds_error() {
+        let mut mem = RAM::default();
+
+        assert_eq!(mem.try_write_byte(32768, 0), Err(MemoryError::OutOfRange(32768)));
+        mem.write_byte(32768, 0);
+    }
+    
+    #[test]
+    fn test_calculate_checksum() {
+        let mut mem = RAM::default();
+        
+        mem.memory_array[0] = 0x01;
+        mem.memory_array[1] = 0x82;
+        mem.memory_array[2] = 0x03;
+        mem.memory_array[3] = 0x84;
+
+        assert_eq!(mem.calculate_checksum(), 536854790);
+    }
+
+    
+    #[test]
+    fn test_test_flag() {
+        let mut mem = RAM::default();
+
+        mem.memory_array[0] = 0x1C;
+        mem.memory_array[1] = 0xCB;
+        mem.memory_array[2] = 0x1D;
+        mem.memory_array[3] = 0x1A;
+
+        assert_eq!(mem.test_flag(0, 11), true);
+        assert_eq!(mem.test_flag(0, 13), false);
+    }
+
+    
+    #[test]
+    #[should_panic]
+    fn test_test_flag_bit_range_error() {
+        let mut mem = RAM::default();
+
+        mem.test_flag(0, 32);
+    }
+
+    
+    #[test]
+    fn test_set_flag() {
+        let mut mem = RAM::default();
+
+        mem.set_flag(0, 12, true);
+        assert_eq!(mem.memory_array[2], 0x10);
+
+        mem.set_flag(0, 12, false);
+        assert_eq!(mem.memory_array[2], 0x00);
+    }
+
+    
+    #[test]
+    #[should_panic]
+    fn test_set_flag_bit_range_error() {
+        let mut mem = RAM::default();
+
+        mem.set_flag(0, 32, true);
+    }
+
+    
+    #[test]
+    fn test_extract_bits() {
+        let w = RAM::extract_bits(0xC7A2511E, 5, 20);
+        assert_eq!(w, 0x25100);
+    }
+
+    
+    #[test]
+    #[should_panic]
+    fn test_extract_bits_invalid_start_bit() {
+        RAM::extract_bits(0x0, 32, 0);
+    }
+
+    
+    #[test]
+    #[should_panic]
+    fn test_extract_bits_invalid_end_bit() {
+        RAM::extract_bits(0x0, 0, 32);
+    }
+
+    
+    #[test]
+    #[should_panic]
+    fn test_extract_bits_bit_inequality() {
+        RAM::extract_bits(0x0, 12, 10);
+    }
+
+    #[test]
+    fn test_get_as_word() {
+        let mut regs = Registers::default();
+
+        // little-endian
+        regs.memory_array[4] = 0x45;
+        regs.memory_array[5] = 0x55;
+        regs.memory_array[6] = 0xAE;
+        regs.memory_array[7] = 0xFF;
+
+        assert_eq!(0xFFAE5545, regs.get_register(1));
+    }
+
+    #[test]
+    fn test_get_register() {
+        let mut regs = Registers::default();
+
+        regs.write_word(4, 0xAEBD056D);
+        assert_eq!(0xAEBD056D, regs.get_register(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_register_range_error() {
+        let mut regs = Registers::default();
+
+        regs.get_register(16);
+    }
+
+    #[test]
+    fn test_set_register() {
+        let mut regs = Registers::default();
+        regs.set_register(1, 5);
+
+        assert_eq!(5, regs.read_word(4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_register_range_error() {
+        let mut regs = Registers::default();
+        regs.set_register(16, 0);
+    }
+
+    #[test]
+    fn test_get_all() {
+        let mut regs = Registers::default();
+
+        regs.set_register(0, 0xAEB);
+        regs.set_register(1, 5);
+        regs.set_register(13, 0x11FF11FF);
+
+        let rs = regs.get_all();
+        assert_eq!(0xAEB, rs[0]);
+        assert_eq!(5, rs[1]);
+        assert_eq!(0x11FF11FF, rs[13]);
+    }
+
+    #[test]
+    fn test_set_pc() {
+        let mut regs = Registers::default();
+
+        regs.set_pc(0x106);
+        assert_eq!(0x106, regs.get_register(15));
+    }
+
+    #[test]
+    fn test_get_pc() {
+        let mut regs = Registers::default();
+
+        regs.set_register(15, 0x106);
+        assert_eq!(0x106, regs.get_pc());
+    }
+
+    #[test]
+    fn test_inc_pc() {
+        let mut regs = Registers::default();
+
+        regs.set_pc(0x106);
+        regs.inc_pc();
+        assert_eq!(0x10a, regs.get_pc());
+    }
+
+    #[test]
+    fn test_inc_pc_thumb() {
+        let mut regs = Registers::default();
+
+        regs.set_t_flag(true);
+        regs.set_pc(0x104);
+        assert_eq!(0x100, regs.get_pc_current_address());
+        regs.inc_pc();
+        assert_eq!(0x106, regs.get_pc());
+        regs.dec_pc();
+        assert_eq!(0x104, regs.get_pc());
+    }
+
+    #[test]
+    fn test_get_cpsr() {
+        let mut regs = Registers::default();
+
+        regs.write_word(64, 0xFF11FF11);
+        assert_eq!(0xFF11FF11, regs.get_cpsr());
+    }
+
+    #[test]
+    fn test_fiq_banked_registers() {
+        let mut regs = Registers::default();
+        regs.set_cpsr_mode(Mode::SYS);
+        for i in 0..=14 {
+            regs.set_register(i, i as Word);
+        }
+
+        regs.set_cpsr_mode(Mode::FIQ);
+        // r0..r7 are shared, r8..r14 are banked
+        assert_eq!(regs.get_register(7), 7);
+        assert_eq!(regs.get_register(8), 0);
+        regs.set_register(8, 0x88);
+        regs.set_register(14, 0xee);
+        regs.set_spsr(0x1f);
+
+        regs.set_cpsr_mode(Mode::SYS);
+        assert_eq!(regs.get_register(8), 8);
+        assert_eq!(regs.get_register(14), 14);
+        assert_eq!(regs.get_reg_register(Register::r8_fiq), 0x88);
+        assert_eq!(regs.get_reg_register(Register::r14_fiq), 0xee);
+        assert_eq!(regs.read_word(SPSR_FIQ_ADDR), 0x1f);
+    }
+
+    #[test]
+    fn test_abt_und_banked_registers() {
+        let mut regs = Registers::default();
+        regs.set_cpsr_mode(Mode::SYS);
+        regs.set_register(13, 0x7000);
+        regs.set_register(14, 0x1000);
+
+        regs.set_cpsr_mode(Mode::ABT);
+        assert_eq!(regs.get_register(13), 0);
+        regs.set_register(13, 0x6000);
+        regs.set_register(14, 0x2000);
+        regs.set_spsr(0x1f);
+
+        regs.set_cpsr_mode(Mode::UND);
+        assert_eq!(regs.get_register(13), 0);
+        regs.set_register(13, 0x5000);
+        regs.set_spsr(0x10);
+
+        regs.set_cpsr_mode(Mode::SYS);
+        assert_eq!(regs.get_register(13), 0x7000);
+        assert_eq!(regs.get_register(14), 0x1000);
+        assert_eq!(regs.get_reg_register(Register::r13_abt), 0x6000);
+        assert_eq!(regs.get_reg_register(Register::r14_abt), 0x2000);
+        assert_eq!(regs.get_reg_register(Register::r13_und), 0x5000);
+        assert_eq!(regs.read_word(SPSR_ABT_ADDR), 0x1f);
+        assert_eq!(regs.read_word(SPSR_UND_ADDR), 0x10);
+    }
+
+    #[test]
+    fn test_set_cpsr_mode() {
+        let mut regs = Registers::default();
+        regs.set_cpsr(0xf00000d3);
+
+        regs.set_cpsr_mode(Mode::FIQ);
+        assert_eq!(regs.get_cpsr(), 0xf00000d1);
+        assert_eq!(regs.get_cpsr_mode(), Mode::FIQ);
+    }
+
+    #[test]
+    fn test_get_cpsr_control_byte() {
+        let mut regs = Registers::default();
+
+        regs.write_word(64, 0xAA000000);
+        assert_eq!(0xAA, regs.get_cpsr_control_byte());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,345 @@
+use std::fmt;
+
+use log::trace;
+
+use crate::{coprocessor::{CoprocessorOp, SystemControl, CONTROL_R, CONTROL_S}, memory::{AddressSize, MemoryError, Word}};
+
+// ARM920T-sized TLB
+pub const TLB_ENTRIES: usize = 64;
+
+// B4.6: fault status encodings for faults detected by the MMU
+pub const FAULT_STATUS_TRANSLATION_SECTION: Word = 0b0101;
+pub const FAULT_STATUS_TRANSLATION_PAGE: Word    = 0b0111;
+pub const FAULT_STATUS_DOMAIN_SECTION: Word      = 0b1001;
+pub const FAULT_STATUS_DOMAIN_PAGE: Word         = 0b1011;
+pub const FAULT_STATUS_PERMISSION_SECTION: Word  = 0b1101;
+pub const FAULT_STATUS_PERMISSION_PAGE: Word     = 0b1111;
+pub const FAULT_STATUS_EXTERNAL_LEVEL1: Word     = 0b1100;
+pub const FAULT_STATUS_EXTERNAL_LEVEL2: Word     = 0b1110;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Access {
+    Read,
+    Write,
+    Fetch
+}
+
+// an access the MMU refused; status and domain are reported in the CP15 fault status registers
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MmuFault {
+    pub status: Word,
+    pub domain: Word
+}
+
+impl fmt::Display for MmuFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.status {
+            FAULT_STATUS_TRANSLATION_SECTION | FAULT_STATUS_TRANSLATION_PAGE => write!(f, "translation fault"),
+            FAULT_STATUS_DOMAIN_SECTION | FAULT_STATUS_DOMAIN_PAGE => write!(f, "domain fault (domain {})", self.domain),
+            FAULT_STATUS_PERMISSION_SECTION | FAULT_STATUS_PERMISSION_PAGE => write!(f, "permission fault (domain {})", self.domain),
+            _ => write!(f, "external abort on translation")
+        }
+    }
+}
+
+// a translation cached by the TLB; sections have a single AP field,
+// large and small pages have four, one per subpage
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TlbEntry {
+    pub virtual_base: AddressSize,
+    pub physical_base: AddressSize,
+    pub size: AddressSize,
+    pub domain: Word,
+    pub access_permissions: [Word; 4],
+    pub section: bool
+}
+
+impl TlbEntry {
+    fn contains(&self, mva: AddressSize) -> bool {
+        mva & !(self.size - 1) == self.virtual_base
+    }
+
+    fn get_access_permission(&self, mva: AddressSize) -> Word {
+        if self.section {
+            self.access_permissions[0]
+        } else {
+            // each subpage is a quarter of the page
+            self.access_permissions[((mva & (self.size - 1)) / (self.size / 4)) as usize]
+        }
+    }
+}
+
+// B3: ARMv5 virtual memory system; sections and coarse page tables (large and small pages),
+// fine page tables are not supported and fault like an invalid descriptor
+// the MMU is enabled by the CP15 M bit; with it disabled addresses are flat-mapped
+pub struct Mmu {
+    // whether accesses are made from a privileged mode; set by CPU::step before each instruction
+    privileged: bool,
+    tlb: Vec<TlbEntry>,
+    next_victim: usize, // round-robin replacement
+    hits: u64,
+    misses: u64
+}
+
+impl Mmu {
+    pub fn set_privileged(&mut self, privileged: bool) {
+        self.privileged = privileged;
+    }
+
+    pub fn get_tlb(&self) -> &[TlbEntry] {
+        &self.tlb
+    }
+
+    pub fn get_tlb_hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn get_tlb_misses(&self) -> u64 {
+        self.misses
+    }
+
+    // translates a virtual address, walking the page tables through read_descriptor on a TLB miss
+    pub fn translate<F>(&mut self, cp15: &SystemControl, va: AddressSize, access: Access, read_descriptor: F) -> Result<AddressSize, MemoryError>
+        where F: FnMut(AddressSize) -> Result<Word, MemoryError> {
+        if !cp15.is_mmu_enabled() {
+            return Ok(va)
+        }
+
+        // B6: the FCSE relocates the bottom 32MB by the process ID
+        let mva = if va < 0x0200_0000 { va | cp15.get_process_id() } else { va };
+
+        let entry = match self.tlb.iter().find(|e| e.contains(mva)) {
+            Some(entry) => {
+                self.hits += 1;
+                *entry
+            },
+            None => {
+                self.misses += 1;
+                let entry = walk(cp15.get_translation_table_base(), mva, read_descriptor)
+                    .map_err(|fault| MemoryError::Mmu(va, fault))?;
+                self.insert(entry);
+                entry
+            }
+        };
+
+        self.check_access(cp15, &entry, mva, access).map_err(|fault| MemoryError::Mmu(va, fault))?;
+        Ok(entry.physical_base | (mva & (entry.size - 1)))
+    }
+
+    // B4.4: domain and access permission checks
+    fn check_access(&self, cp15: &SystemControl, entry: &TlbEntry, mva: AddressSize, access: Access) -> Result<(), MmuFault> {
+        let (domain_fault, permission_fault) = if entry.section {
+            (FAULT_STATUS_DOMAIN_SECTION, FAULT_STATUS_PERMISSION_SECTION)
+        } else {
+            (FAULT_STATUS_DOMAIN_PAGE, FAULT_STATUS_PERMISSION_PAGE)
+        };
+
+        match (cp15.get_domain_access() >> (entry.domain * 2)) & 0b11 {
+            // manager: no permission checks
+            0b11 => return Ok(()),
+            // client
+            0b01 => (),
+            // no access, reserved
+            _ => return Err(MmuFault { status: domain_fault, domain: entry.domain })
+        }
+
+        let control = cp15.get_control();
+        let write = access == Access::Write;
+        let permitted = match (entry.get_access_permission(mva), control & CONTROL_S != 0, control & CONTROL_R != 0) {
+            (0b00, true, false) => self.privileged && !write,
+            (0b00, false, true) => !write,
+            (0b00, _, _)        => false,
+            (0b01, _, _)        => self.privileged,
+            (0b10, _, _)        => self.privileged || !write,
+            _                   => true
+        };
+
+        if permitted {
+            Ok(())
+        } else {
+            Err(MmuFault { status: permission_fault, domain: entry.domain })
+        }
+    }
+
+    fn insert(&mut self, entry: TlbEntry) {
+        if self.tlb.len() < TLB_ENTRIES {
+            self.tlb.push(entry);
+        } else {
+            self.tlb[self.next_victim] = entry;
+            self.next_victim = (self.next_victim + 1) % TLB_ENTRIES;
+        }
+    }
+
+    // CP15 c8 operations: opcode_2 = 1 invalidates the entry for the MVA in value, the rest invalidate everything
+    pub fn invalidate(&mut self, op: &CoprocessorOp, value: Word) {
+        if op.opcode_2 == 1 {
+            self.tlb.retain(|e| !e.contains(value));
+        } else {
+            self.tlb.clear();
+        }
+        self.next_victim = 0;
+        trace!("invalidate: {} TLB entries left", self.tlb.len());
+    }
+
+    pub fn reset(&mut self) {
+        *self = Mmu::default();
+    }
+}
+
+impl Default for Mmu {
+    fn default() -> Self {
+        Self {
+            privileged: true,
+            tlb: Vec::with_capacity(TLB_ENTRIES),
+            next_victim: 0,
+            hits: 0,
+            misses: 0
+        }
+    }
+}
+
+// B3.3: first-level descriptors select a 1MB section or a second-level (coarse) table,
+// coarse table descriptors select a 64KB large page or a 4KB small page
+fn walk<F>(ttb: Word, mva: AddressSize, mut read_descriptor: F) -> Result<TlbEntry, MmuFault>
+    where F: FnMut(AddressSize) -> Result<Word, MemoryError> {
+    let level1 = read_descriptor(ttb | ((mva >> 20) << 2))
+        .map_err(|_| MmuFault { status: FAULT_STATUS_EXTERNAL_LEVEL1, domain: 0 })?;
+    let domain = (level1 >> 5) & 0xf;
+    trace!("walk: 0x{:x} level 1 descriptor 0x{:x}", mva, level1);
+
+    match level1 & 0b11 {
+        // section
+        0b10 => Ok(TlbEntry {
+            virtual_base: mva & 0xfff00000,
+            physical_base: level1 & 0xfff00000,
+            size: 0x100000,
+            domain,
+            access_permissions: [(level1 >> 10) & 0b11; 4],
+            section: true
+        }),
+        // coarse page table
+        0b01 => {
+            let level2 = read_descriptor((level1 & 0xfffffc00) | ((mva >> 10) & 0x3fc))
+                .map_err(|_| MmuFault { status: FAULT_STATUS_EXTERNAL_LEVEL2, domain })?;
+            trace!("walk: 0x{:x} level 2 descriptor 0x{:x}", mva, level2);
+
+            let access_permissions = [(level2 >> 4) & 0b11, (level2 >> 6) & 0b11, (level2 >> 8) & 0b11, (level2 >> 10) & 0b11];
+            let size = match level2 & 0b11 {
+                0b01 => 0x10000,
+                0b10 => 0x1000,
+                // tiny pages only exist in fine page tables
+                _ => return Err(MmuFault { status: FAULT_STATUS_TRANSLATION_PAGE, domain })
+            };
+
+            Ok(TlbEntry {
+                virtual_base: mva & !(size - 1),
+                physical_base: level2 & !(size - 1),
+                size,
+                domain,
+                access_permissions,
+                section: false
+            })
+        },
+        // fault and fine page table descriptors
+        _ => Err(MmuFault { status: FAULT_STATUS_TRANSLATION_SECTION, domain: 0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coprocessor::{Coprocessor, CONTROL_M};
+
+    const TTB: Word = 0x4000;
+
+    // translation table with 0x00000000 as a section, 0x00100000 through a coarse table
+    //  (a small page at 0x00100000 and a large page at 0x00110000) and nothing else
+    fn read_table(addr: AddressSize) -> Result<Word, MemoryError> {
+        match addr {
+            0x4000 => Ok((0b11 << 10) | (1 << 5) | 0b10),
+            0x4004 => Ok(0x00008000 | (2 << 5) | 0b01),
+            0x8000 => Ok(0x00200000 | (0b10 << 10) | (0b10 << 8) | (0b01 << 6) | (0b11 << 4) | 0b10),
+            0x8040..=0x807c => Ok(0x00300000 | (0b01 << 4) | 0b01),
+            0x0000..=0x83ff => Ok(0),
+            _ => Err(MemoryError::OutOfRange(addr))
+        }
+    }
+
+    fn system_control(domain_access: Word, control: Word) -> SystemControl {
+        let mut cp15 = SystemControl::default();
+        let op = |crn| CoprocessorOp { coprocessor: 15, crn, ..Default::default() };
+        cp15.write(&op(2), TTB);
+        cp15.write(&op(3), domain_access);
+        cp15.write(&op(1), control);
+        cp15
+    }
+
+    #[test]
+    fn test_translate() {
+        let mut mmu = Mmu::default();
+        // domains 1 and 2 are clients
+        let cp15 = system_control(0b01_01_00, CONTROL_M);
+
+        assert_eq!(mmu.translate(&cp15, 0x1234, Access::Read, read_table), Ok(0x1234));
+        assert_eq!(mmu.translate(&cp15, 0x100ffc, Access::Write, read_table), Ok(0x200ffc));
+        assert_eq!(mmu.translate(&cp15, 0x112344, Access::Read, read_table), Ok(0x302344));
+        assert_eq!(mmu.translate(&cp15, 0x1238, Access::Fetch, read_table), Ok(0x1238));
+        assert_eq!(mmu.get_tlb().len(), 3);
+        assert_eq!((mmu.get_tlb_hits(), mmu.get_tlb_misses()), (1, 3));
+
+        // unmapped
+        assert_eq!(mmu.translate(&cp15, 0x300000, Access::Read, read_table),
+            Err(MemoryError::Mmu(0x300000, MmuFault { status: FAULT_STATUS_TRANSLATION_SECTION, domain: 0 })));
+        assert_eq!(mmu.translate(&cp15, 0x120000, Access::Read, read_table),
+            Err(MemoryError::Mmu(0x120000, MmuFault { status: FAULT_STATUS_TRANSLATION_PAGE, domain: 2 })));
+
+        // disabled MMU is a flat mapping
+        assert_eq!(mmu.translate(&system_control(0, 0), 0x300000, Access::Read, read_table), Ok(0x300000));
+    }
+
+    #[test]
+    fn test_permissions() {
+        let mut mmu = Mmu::default();
+        let cp15 = system_control(0b01_01_00, CONTROL_M);
+
+        // small page subpages: AP 11, 01, 10, 10
+        mmu.set_privileged(false);
+        assert!(mmu.translate(&cp15, 0x100000, Access::Write, read_table).is_ok());
+        assert_eq!(mmu.translate(&cp15, 0x100400, Access::Read, read_table),
+            Err(MemoryError::Mmu(0x100400, MmuFault { status: FAULT_STATUS_PERMISSION_PAGE, domain: 2 })));
+        assert!(mmu.translate(&cp15, 0x100800, Access::Read, read_table).is_ok());
+        assert!(mmu.translate(&cp15, 0x100c00, Access::Write, read_table).is_err());
+        // large page AP 01
+        assert!(mmu.translate(&cp15, 0x110000, Access::Fetch, read_table).is_err());
+
+        mmu.set_privileged(true);
+        assert!(mmu.translate(&cp15, 0x100400, Access::Write, read_table).is_ok());
+
+        // domain 2 with no access, domain 1 as a manager
+        let cp15 = system_control(0b00_11_00, CONTROL_M);
+        assert_eq!(mmu.translate(&cp15, 0x100000, Access::Read, read_table),
+            Err(MemoryError::Mmu(0x100000, MmuFault { status: FAULT_STATUS_DOMAIN_PAGE, domain: 2 })));
+        assert!(mmu.translate(&cp15, 0x0, Access::Write, read_table).is_ok());
+    }
+
+    #[test]
+    fn test_tlb() {
+        let mut mmu = Mmu::default();
+        let cp15 = system_control(0b01_01_00, CONTROL_M);
+
+        for page in 0..(TLB_ENTRIES as Word + 1) {
+            mmu.translate(&cp15, 0x110000 + (page & 0xf) * 0x1000 + (page >> 4) * 0x10000, Access::Read, |addr| {
+                // every coarse table entry maps a small page
+                if addr >= 0x8000 { Ok((addr << 12) | 0b1111_1111_0010) } else { read_table(addr) }
+            }).ok();
+        }
+        assert_eq!(mmu.get_tlb().len(), TLB_ENTRIES);
+
+        let invalidate_entry = CoprocessorOp { coprocessor: 15, crn: 8, crm: 7, opcode_2: 1, ..Default::default() };
+        mmu.invalidate(&invalidate_entry, mmu.get_tlb()[1].virtual_base + 4);
+        assert_eq!(mmu.get_tlb().len(), TLB_ENTRIES - 1);
+
+        mmu.invalidate(&CoprocessorOp { opcode_2: 0, ..invalidate_entry }, 0);
+        assert!(mmu.get_tlb().is_empty());
+    }
+}
@@ -1,75 +1,88 @@
-use tauri::{api::cli::Matches};
-use log::{trace, error};
-
-pub struct Options {
-    pub memory_size: Option<usize>,
-    pub elf_file: Option<String>,
-    pub exec: bool,
-    pub traceall: bool
-}
-
-impl Options {
-    pub fn parse(&mut self, matches: Matches) {
-        // matches { args, subcommand }
-        // args HashMap<String, ArgData>, ArgData { value, occurances }
-        
-        match matches.args.get("help") {
-            Some(arg) => {
-                println!("{}", arg.value.to_string());
-                std::process::exit(0)
-            }
-            _ => { }
-        }
-
-        for (name, arg) in matches.args {
-            // skip empty arguments
-            if arg.occurrences == 0 {
-                continue
-            }
-            
-            if name == "mem" {
-                trace!("parse: mem {}", arg.value.to_string());
-                let arg_value_normalized = String::from(arg.value.to_string().trim_matches(&['"', '\''] as &[_]));
-
-                self.memory_size = Some(match arg_value_normalized.parse::<usize>() {
-                    Ok(u) => {
-                        if u > 1024000 {
-                            error!("parse: --mem must be <= 1MB (1024000b)");
-                            std::process::exit(1)
-                        }
-
-                        trace!("parse: mem {}", u);
-                        u as usize
-                    }
-                    Err(_) => {
-                        panic!("parse: --mem option value incompatible {}", arg.value);
-                    }
-                });
-            } else if name == "elf-file" {
-                trace!("parse: elf_file {}", arg.value.to_string());
-                
-                // remove chars possibly passed by shell
-                // https://stackoverflow.com/a/49856591
-                self.elf_file = Some(String::from(arg.value.to_string().trim_matches(&['"', '\'', ' '] as &[_])));
-            } else if name == "exec" {
-                trace!("parse: exec {}", arg.value.to_string());
-
-                self.exec = true;
-            } else if name == "traceall" {
-                trace!("parse: traceall enabled");
-                self.traceall = true;
-            }
-        }
-    }
-}
-
-impl Default for Options {
-    fn default() -> Self {
-        Options {
-            memory_size: None,
-            elf_file: None,
-            exec: false,
-            traceall: false
-        }
-    }
+use log::trace;
+
+use crate::{memory::{AlignmentPolicy, MAX_MEMORY_SIZE}, cpu_enum::{Architecture, UndefinedPolicy}, cache::CacheConfig};
+
+pub struct Options {
+    pub memory_size: Option<usize>,
+    pub elf_file: Option<String>,
+    pub exec: bool,
+    pub traceall: bool,
+    pub trace_cycles: bool,
+    pub trace_file: Option<String>,
+    pub alignment: Option<AlignmentPolicy>,
+    pub architecture: Option<Architecture>,
+    pub undefined: Option<UndefinedPolicy>,
+    pub icache: Option<CacheConfig>,
+    pub dcache: Option<CacheConfig>
+}
+
+impl Options {
+    // applies a single named command-line argument, or returns an error message for an invalid value;
+    // the Tauri CLI matches (src-tauri) and the headless runner (cli) forward each argument here
+    pub fn set_arg(&mut self, name: &str, value: &str) -> Result<(), String> {
+        if name == "mem" {
+            trace!("set_arg: mem {}", value);
+            let arg_value_normalized = String::from(value.trim_matches(&['"', '\''] as &[_]));
+
+            let u = arg_value_normalized.parse::<usize>().map_err(|_| format!("--mem option value incompatible {}", value))?;
+            if u > MAX_MEMORY_SIZE {
+                return Err(format!("--mem must be <= 1MB ({}b)", MAX_MEMORY_SIZE))
+            }
+
+            trace!("set_arg: mem {}", u);
+            self.memory_size = Some(u);
+        } else if name == "elf-file" {
+            trace!("set_arg: elf_file {}", value);
+            
+            // remove chars possibly passed by shell
+            // https://stackoverflow.com/a/49856591
+            self.elf_file = Some(String::from(value.trim_matches(&['"', '\'', ' '] as &[_])));
+        } else if name == "exec" {
+            trace!("set_arg: exec {}", value);
+
+            self.exec = true;
+        } else if name == "traceall" {
+            trace!("set_arg: traceall enabled");
+            self.traceall = true;
+        } else if name == "trace-cycles" {
+            trace!("set_arg: trace_cycles enabled");
+            self.trace_cycles = true;
+        } else if name == "trace-file" {
+            trace!("set_arg: trace_file {}", value);
+            self.trace_file = Some(String::from(value.trim_matches(&['"', '\'', ' '] as &[_])));
+        } else if name == "alignment" {
+            trace!("set_arg: alignment {}", value);
+            self.alignment = Some(value.trim_matches(&['"', '\'', ' '] as &[_]).parse::<AlignmentPolicy>()?);
+        } else if name == "arch" {
+            trace!("set_arg: arch {}", value);
+            self.architecture = Some(value.trim_matches(&['"', '\'', ' '] as &[_]).parse::<Architecture>()?);
+        } else if name == "undefined" {
+            trace!("set_arg: undefined {}", value);
+            self.undefined = Some(value.trim_matches(&['"', '\'', ' '] as &[_]).parse::<UndefinedPolicy>()?);
+        } else if name == "icache" || name == "dcache" {
+            trace!("set_arg: {} {}", name, value);
+            let config = value.trim_matches(&['"', '\'', ' '] as &[_]).parse::<CacheConfig>()?;
+            if name == "icache" { self.icache = Some(config) } else { self.dcache = Some(config) }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            memory_size: None,
+            elf_file: None,
+            exec: false,
+            traceall: false,
+            trace_cycles: false,
+            trace_file: None,
+            alignment: None,
+            architecture: None,
+            undefined: None,
+            icache: None,
+            dcache: None
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+use crate::memory::{AddressSize, MemoryError, Word, RAM};
+
+/*
+ARM7TDMI three-stage pipeline
+
+While the instruction at X executes, the one at X + size is being decoded and the one at
+X + 2 * size fetched, which is why the PC reads as X + 8 (X + 4 in Thumb state). Fetched
+words travel through the stages, so an instruction executes as it was fetched: a store to
+one of the next two instructions does not change what runs, and a failed fetch only
+raises a Prefetch Abort if the instruction reaches execute. Branches, other writes to the
+PC and exceptions flush the decode and fetch stages, and the next step refills them
+*/
+
+// one fetched instruction
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PipelineEntry {
+    pub address: AddressSize,
+    pub thumb: bool,
+    pub fetched: Result<Word, MemoryError>
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, serde::Serialize)]
+pub enum Flush {
+    Branch,    // a branch or another write to the PC
+    Exception  // an exception or interrupt entry
+}
+
+#[derive(Default)]
+pub struct Pipeline {
+    execute: Option<PipelineEntry>,
+    decode: Option<PipelineEntry>,
+    fetch: Option<PipelineEntry>,
+    flush: Option<Flush> // why the decode and fetch stages were discarded after the last instruction
+}
+
+impl Pipeline {
+    pub fn get_execute(&self) -> Option<PipelineEntry> {
+        self.execute
+    }
+
+    pub fn get_decode(&self) -> Option<PipelineEntry> {
+        self.decode
+    }
+
+    pub fn get_fetch(&self) -> Option<PipelineEntry> {
+        self.fetch
+    }
+
+    pub fn get_flush(&self) -> Option<Flush> {
+        self.flush
+    }
+
+    // moves every instruction one stage along and fetches the next one, returning the
+    //  instruction to execute; the stages are refilled first when they do not hold the
+    //  instruction at address, e.g. after a flush or when the host moved the PC
+    pub fn advance(&mut self, ram_lock: &mut RAM, address: AddressSize, thumb: bool) -> PipelineEntry {
+        let primed = self.flush.is_none() && self.decode.is_some_and(|entry| entry.address == address && entry.thumb == thumb);
+        if !primed {
+            self.flush = None;
+            self.decode = Some(fetch(ram_lock, address, thumb));
+            self.fetch = Some(fetch(ram_lock, address.wrapping_add(size(thumb)), thumb));
+        }
+
+        let entry = self.decode.unwrap();
+        self.execute = Some(entry);
+        self.decode = self.fetch;
+        self.fetch = Some(fetch(ram_lock, address.wrapping_add(size(thumb) * 2), thumb));
+
+        entry
+    }
+
+    // the decode and fetch stages are kept until the next step so that the host can show them
+    pub fn flush(&mut self, reason: Flush) {
+        self.flush = Some(reason);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Pipeline::default();
+    }
+}
+
+fn size(thumb: bool) -> AddressSize {
+    if thumb { 2 } else { 4 }
+}
+
+// in Thumb state only the halfword at the address is read
+fn fetch(ram_lock: &mut RAM, address: AddressSize, thumb: bool) -> PipelineEntry {
+    let fetched = if thumb {
+        ram_lock.fetch_half_word(address).map(|half_word| half_word as Word)
+    } else {
+        ram_lock.fetch_word(address)
+    };
+
+    PipelineEntry { address, thumb, fetched }
+}
+
+#[cfg(test)]
+mod tests {
+    use object::Endianness;
+
+    use crate::memory::Memory;
+
+    use super::*;
+
+    #[test]
+    fn test_advance() {
+        let mut ram = RAM::new(0x100, Endianness::Big);
+        ram.write_word(0x10, 0xe3a00001);
+        ram.write_word(0x14, 0xe3a00002);
+        ram.write_word(0x18, 0xe3a00003);
+
+        let mut pipeline = Pipeline::default();
+        let entry = pipeline.advance(&mut ram, 0x10, false);
+        assert_eq!((entry.address, entry.fetched), (0x10, Ok(0xe3a00001)));
+        assert_eq!(pipeline.get_decode().unwrap().address, 0x14);
+        assert_eq!(pipeline.get_fetch().unwrap().fetched, Ok(0xe3a00003));
+
+        // the fetched word executes even if memory changes afterwards
+        ram.write_word(0x14, 0);
+        assert_eq!(pipeline.advance(&mut ram, 0x14, false).fetched, Ok(0xe3a00002));
+
+        // a flush or a different address refills the stages
+        pipeline.flush(Flush::Branch);
+        assert_eq!(pipeline.get_flush(), Some(Flush::Branch));
+        assert_eq!(pipeline.advance(&mut ram, 0x18, false).fetched, Ok(0xe3a00003));
+        assert_eq!(pipeline.get_flush(), None);
+        assert_eq!(pipeline.advance(&mut ram, 0x10, false).fetched, Ok(0xe3a00001));
+
+        // fetching past the end of memory only fails the stage that fetched it
+        let entry = pipeline.advance(&mut ram, 0xfc, false);
+        assert_eq!(entry.fetched, Ok(0));
+        assert_eq!(pipeline.get_decode().unwrap().fetched, Err(MemoryError::OutOfRange(0x100)));
+
+        // Thumb halfwords
+        ram.endianness = Endianness::Little;
+        ram.write_half_word(0x20, 0x2001);
+        ram.write_half_word(0x22, 0x2102);
+        let entry = pipeline.advance(&mut ram, 0x20, true);
+        assert_eq!(entry.fetched, Ok(0x2001));
+        assert_eq!(pipeline.get_decode().unwrap().fetched, Ok(0x2102));
+        assert_eq!(pipeline.get_fetch().unwrap().address, 0x24);
+    }
+}
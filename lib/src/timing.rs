@@ -0,0 +1,158 @@
+use std::{fmt, ops::AddAssign};
+
+use crate::{cpu_enum::{InstrType, LSH}, instruction::{Instruction, TInstruction}, memory::Word};
+
+/*
+ARM7TDMI instruction timing (ARM7TDMI Technical Reference Manual, chapter 6)
+
+Every instruction is counted in S (sequential), N (non-sequential), I (internal) and
+C (coprocessor) cycles with zero wait states. The counts here cover the instruction
+itself; a write to the PC refills the pipeline (1S + 1N) on top, see refill()
+*/
+
+#[derive(Copy, Clone, Default, PartialEq, Debug, serde::Serialize)]
+pub struct Cycles {
+    pub s: u64,
+    pub n: u64,
+    pub i: u64,
+    pub c: u64
+}
+
+impl Cycles {
+    pub fn new(s: u64, n: u64, i: u64, c: u64) -> Self {
+        Cycles { s, n, i, c }
+    }
+
+    pub fn get_total(&self) -> u64 {
+        self.s + self.n + self.i + self.c
+    }
+
+    // fetching from the branch target: the extra cycles of a branch or a write to the PC
+    pub fn refill() -> Self {
+        Cycles::new(1, 1, 0, 0)
+    }
+
+    // entering an interrupt or prefetch abort handler, which has no instruction of its own
+    pub fn exception() -> Self {
+        Cycles::new(2, 1, 0, 0)
+    }
+}
+
+impl AddAssign for Cycles {
+    fn add_assign(&mut self, other: Self) {
+        self.s += other.s;
+        self.n += other.n;
+        self.i += other.i;
+        self.c += other.c;
+    }
+}
+
+impl fmt::Display for Cycles {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} cycles ({}S {}N {}I {}C)", self.get_total(), self.s, self.n, self.i, self.c)
+    }
+}
+
+// 6.6: the multiplier consumes 8 bits of Rs per cycle and terminates early once the
+//  remaining bits are all zeros, or for signed multiplies all zeros or all ones
+pub fn multiply_cycles(rs: Word, signed: bool) -> u64 {
+    for (m, shift) in [(1, 8), (2, 16), (3, 24)] {
+        let top = rs >> shift;
+        if top == 0 || (signed && top == Word::MAX >> shift) {
+            return m
+        }
+    }
+    4
+}
+
+// an empty list transfers the PC
+fn registers_transferred(instr: &Instruction) -> u64 {
+    instr.get_reg_list().unwrap_or(0).count_ones().max(1) as u64
+}
+
+// cycles of an instruction that passed its condition, before any pipeline refill;
+//  rs is the value of Rs for multiplies and transfer_length the number of words a
+//  coprocessor transfers for LDC/STC
+pub fn instruction_cycles(instr: &Instruction, rs: Word, transfer_length: usize) -> Cycles {
+    let load = instr.get_ldr_str().unwrap_or(false);
+    let accumulate = instr.get_accumulate().unwrap_or(false) as u64;
+
+    match instr.get_type() {
+        // a shift by a register needs an extra cycle to read Rs
+        InstrType::DataRegReg => Cycles::new(1, 0, 1, 0),
+        InstrType::MUL => Cycles::new(1, 0, multiply_cycles(rs, true) + accumulate, 0),
+        InstrType::MULL => Cycles::new(1, 0, multiply_cycles(rs, instr.get_signed().unwrap()) + 1 + accumulate, 0),
+        // the ARMv5TE multiplies use 16-bit halves of Rs, so they always finish in one or two passes
+        InstrType::SMULxy | InstrType::SMULWy => Cycles::new(1, 0, 1, 0),
+        InstrType::SMLALxy => Cycles::new(1, 0, 2, 0),
+        InstrType::LDRSTRShiftRegPre | InstrType::LDRSTRShiftRegPost |
+        InstrType::LDRSTRRegPre | InstrType::LDRSTRRegPost |
+        InstrType::LDRSTRImmPre | InstrType::LDRSTRImmPost |
+        InstrType::LDRHSTRHImmPre | InstrType::LDRHSTRHImmPost |
+        InstrType::LDRHSTRHRegPre | InstrType::LDRHSTRHRegPost => {
+            // LDRD/STRD transfer a second word sequentially
+            match instr.get_lsh() {
+                Some(LSH::LdrDoubleWord) => Cycles::new(2, 1, 1, 0),
+                Some(LSH::StrDoubleWord) => Cycles::new(1, 2, 0, 0),
+                _ if load => Cycles::new(1, 1, 1, 0),
+                _ => Cycles::new(0, 2, 0, 0)
+            }
+        },
+        InstrType::LDMSTM => {
+            let n = registers_transferred(instr);
+            if load { Cycles::new(n, 1, 1, 0) } else { Cycles::new(n.saturating_sub(1), 2, 0, 0) }
+        },
+        InstrType::SWP => Cycles::new(1, 2, 1, 0),
+        // the undefined instruction trap takes an internal cycle before the exception is entered
+        InstrType::Undefined => Cycles::new(1, 0, 1, 0),
+        InstrType::CDP => Cycles::new(1, 0, 0, 0),
+        InstrType::MCRMRC => Cycles::new(1, 0, instr.get_l_bit().unwrap() as u64, 1),
+        InstrType::LDCSTC => Cycles::new((transfer_length as u64).saturating_sub(1), 2, 0, 0),
+        _ => Cycles::new(1, 0, 0, 0)
+    }
+}
+
+// branches always refill the pipeline, even when the target is the next instruction
+pub fn is_branch(instr: &Instruction) -> bool {
+    matches!(instr.get_type(), InstrType::B | InstrType::BX | InstrType::BLX | InstrType::ThumbBLSuffix | InstrType::ThumbBLXSuffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instruction::{instr_data_reg_reg, instr_data_imm, instr_mul, instr_mull, instr_ldmstm, instr_ldrstr_imm_pre, instr_swp};
+
+    use super::*;
+
+    #[test]
+    fn test_multiply_cycles() {
+        assert_eq!(multiply_cycles(0x000000ff, false), 1);
+        assert_eq!(multiply_cycles(0x0000ffff, false), 2);
+        assert_eq!(multiply_cycles(0x00ffffff, false), 3);
+        assert_eq!(multiply_cycles(0x01000000, false), 4);
+        assert_eq!(multiply_cycles(0xffffff80, false), 4);
+        assert_eq!(multiply_cycles(0xffffff80, true), 1);
+        assert_eq!(multiply_cycles(0xffff8000, true), 2);
+        assert_eq!(multiply_cycles(0x80000000, true), 4);
+    }
+
+    #[test]
+    fn test_instruction_cycles() {
+        // add r0, r1, #1 and add r0, r1, r2, lsl r3
+        assert_eq!(instruction_cycles(&instr_data_imm(14, 4, 0, 1, 0, 0, 1), 0, 0), Cycles::new(1, 0, 0, 0));
+        assert_eq!(instruction_cycles(&instr_data_reg_reg(14, 4, 0, 1, 0, 3, 0, 2), 0, 0), Cycles::new(1, 0, 1, 0));
+
+        // mla r0, r1, r2, r3 with r2 = 0x1234 and umlal r0, r1, r2, r3 with r3 = 0xffffffff
+        assert_eq!(instruction_cycles(&instr_mul(14, 1, 0, 0, 3, 2, 1), 0x1234, 0), Cycles::new(1, 0, 3, 0));
+        assert_eq!(instruction_cycles(&instr_mull(14, 0, 1, 0, 1, 0, 3, 2), 0xffffffff, 0), Cycles::new(1, 0, 6, 0));
+
+        // ldr r0, [r1] and str r0, [r1]
+        assert_eq!(instruction_cycles(&instr_ldrstr_imm_pre(14, 1, 0, 0, 1, 1, 0, 0), 0, 0), Cycles::new(1, 1, 1, 0));
+        assert_eq!(instruction_cycles(&instr_ldrstr_imm_pre(14, 1, 0, 0, 0, 1, 0, 0), 0, 0), Cycles::new(0, 2, 0, 0));
+
+        // ldmia r0, {r1-r4} and stmia r0, {r1-r4}
+        assert_eq!(instruction_cycles(&instr_ldmstm(14, 0b01, 0, 0, 1, 0, 0b11110), 0, 0), Cycles::new(4, 1, 1, 0));
+        assert_eq!(instruction_cycles(&instr_ldmstm(14, 0b01, 0, 0, 0, 0, 0b11110), 0, 0), Cycles::new(3, 2, 0, 0));
+
+        assert_eq!(instruction_cycles(&instr_swp(14, 0, 0, 1, 2), 0, 0), Cycles::new(1, 2, 1, 0));
+    }
+}
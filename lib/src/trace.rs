@@ -1,68 +1,102 @@
-use std::{fs::{ File, self }, io::Write};
-use log::trace;
-
-use crate::{memory::{Checksum, Word, Byte}, cpu_enum::Mode};
-
-const TRACE_LOG_FILENAME: &str = "trace.log";
-
-pub struct TraceFile {
-    f: Option<File>,
-    traceall: bool
-}
-
-impl TraceFile {
-    pub fn set_traceall(&mut self) {
-        self.traceall = true;
-    }
-
-    pub fn clear_trace_file(&self) -> Result<(), std::io::Error> {
-        fs::write(TRACE_LOG_FILENAME, "")
-    }
-
-    pub fn open_trace_file(&mut self) -> Option<std::io::Error> {
-        trace!("open_trace_file: opening trace file");
-        
-        match File::options().append(true).open(TRACE_LOG_FILENAME) {
-            Ok(f) => { self.f = Some(f); None },
-            Err(e) => Some(e)
-        }
-    }
-    
-    pub fn close_trace_file(&mut self) {
-        trace!("close_trace_file: closing trace file");
-        drop(self.f.as_ref().unwrap());
-        self.f = None;
-    }
-    
-    pub fn append_trace_file_line(&mut self, trace_step: Word, pc: Word, checksum: Checksum, n: Byte, c: Byte, z: Byte, v: Byte, mode: Mode, regs: Vec<Word>) { 
-        if self.f.is_none() {
-            return
-        }
-
-        // if --traceall is disabled, only log SYS instructions
-        if !self.traceall && mode != Mode::SYS {
-            return
-        }
-        
-        trace!("append_trace_file: adding trace file entry");
-
-        // step_number program_counter checksum nzcv mode r0 r1 r2 r3 r4 r5 r6 r7 r8 r9 r10 r11 r12 r13 r14
-        let mut i = 0;
-
-        let regs_string = regs.iter()
-            .map(|val| (format!("{}={:08X}", i, val), i += 1))
-            .map(|tuple| tuple.0)
-            .collect::<Vec<String>>()
-            .join(" ");
-
-        writeln!(self.f.as_ref().unwrap(), "{:06} {:08X} {:08X} {}{}{}{} {} {} ", trace_step, pc, checksum, n, c, z, v, mode.to_string(), regs_string).unwrap();
-
-        self.f.as_ref().unwrap().flush().unwrap();
-    }
-}
-
-impl Default for TraceFile {
-    fn default() -> Self {
-        Self { f: None, traceall: false }
-    }
+use std::{fs::{ File, self }, io::Write};
+use log::trace;
+
+use crate::{memory::{Checksum, Word, Byte}, cpu_enum::Mode};
+
+pub const TRACE_LOG_FILENAME: &str = "trace.log";
+
+// the processor state after one instruction, as written to a line of the trace log
+pub struct TraceLine {
+    pub trace_step: Word,
+    pub pc: Word,
+    pub checksum: Checksum,
+    pub n: Byte,
+    pub z: Byte,
+    pub c: Byte,
+    pub v: Byte,
+    pub mode: Mode,
+    pub regs: Vec<Word>,
+    pub cycles: u64
+}
+
+pub struct TraceFile {
+    f: Option<File>,
+    path: String,
+    traceall: bool,
+    cycles: bool
+}
+
+impl TraceFile {
+    pub fn set_traceall(&mut self) {
+        self.traceall = true;
+    }
+
+    // appends the total cycle count to every line
+    pub fn set_cycles(&mut self) {
+        self.cycles = true;
+    }
+
+    // must be set before the trace file is cleared or opened
+    pub fn set_path(&mut self, path: &str) {
+        self.path = String::from(path);
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn clear_trace_file(&self) -> Result<(), std::io::Error> {
+        fs::write(&self.path, "")
+    }
+
+    pub fn open_trace_file(&mut self) -> Option<std::io::Error> {
+        trace!("open_trace_file: opening trace file {}", self.path);
+        
+        match File::options().append(true).open(&self.path) {
+            Ok(f) => { self.f = Some(f); None },
+            Err(e) => Some(e)
+        }
+    }
+    
+    pub fn close_trace_file(&mut self) {
+        trace!("close_trace_file: closing trace file");
+        drop(self.f.as_ref().unwrap());
+        self.f = None;
+    }
+    
+    pub fn append_trace_file_line(&mut self, line: TraceLine) {
+        if self.f.is_none() {
+            return
+        }
+
+        // if --traceall is disabled, only log SYS instructions
+        if !self.traceall && line.mode != Mode::SYS {
+            return
+        }
+        
+        trace!("append_trace_file: adding trace file entry");
+
+        // step_number program_counter checksum nzcv mode r0 r1 r2 r3 r4 r5 r6 r7 r8 r9 r10 r11 r12 r13 r14
+        let mut i = 0;
+
+        let regs_string = line.regs.iter()
+            .map(|val| (format!("{}={:08X}", i, val), i += 1))
+            .map(|tuple| tuple.0)
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        write!(self.f.as_ref().unwrap(), "{:06} {:08X} {:08X} {}{}{}{} {} {} ", line.trace_step, line.pc, line.checksum, line.n, line.z, line.c, line.v, line.mode, regs_string).unwrap();
+        if self.cycles {
+            write!(self.f.as_ref().unwrap(), "cycles={} ", line.cycles).unwrap();
+        }
+        writeln!(self.f.as_ref().unwrap()).unwrap();
+
+        self.f.as_ref().unwrap().flush().unwrap();
+    }
+}
+
+impl Default for TraceFile {
+    fn default() -> Self {
+        Self { f: None, path: String::from(TRACE_LOG_FILENAME), traceall: false, cycles: false }
+    }
 }
\ No newline at end of file
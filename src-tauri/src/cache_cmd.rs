@@ -0,0 +1,26 @@
+use lib::cache::CachesPayload;
+use log::trace;
+use tauri::{AppHandle, Manager};
+
+use crate::state::MachineState;
+
+pub async fn build_cache_payload(app_handle: AppHandle) -> CachesPayload {
+    trace!("build_cache_payload: attempting to lock state...");
+    let machine_state: MachineState = app_handle.state();
+    let machine_lock = &mut *machine_state.lock().await;
+    let ram_lock = &machine_lock.ram;
+    trace!("build_cache_payload: obtained state locks");
+
+    // caches are only simulated when configured with --icache/--dcache
+    CachesPayload {
+        icache: ram_lock.icache.as_ref().map(|cache| cache.get_payload()),
+        dcache: ram_lock.dcache.as_ref().map(|cache| cache.get_payload())
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_get_cache(app_handle: AppHandle) -> Result<CachesPayload, ()> {
+    trace!("cmd_get_cache: grabbing cache contents...");
+
+    Ok(build_cache_payload(app_handle.clone()).await)
+}
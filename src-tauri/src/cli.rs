@@ -0,0 +1,33 @@
+/*
+    cli.rs
+    Forwards Tauri CLI matches to the core Options
+*/
+
+use log::error;
+use lib::options::Options;
+use tauri::api::cli::Matches;
+
+pub fn parse_matches(options: &mut Options, matches: Matches) {
+    // matches { args, subcommand }
+    // args HashMap<String, ArgData>, ArgData { value, occurances }
+    
+    match matches.args.get("help") {
+        Some(arg) => {
+            println!("{}", arg.value.to_string());
+            std::process::exit(0)
+        }
+        _ => { }
+    }
+
+    for (name, arg) in matches.args {
+        // skip empty arguments
+        if arg.occurrences == 0 {
+            continue
+        }
+
+        if let Err(e) = options.set_arg(&name, &arg.value.to_string()) {
+            error!("parse_matches: {}", e);
+            std::process::exit(1)
+        }
+    }
+}
@@ -1,17 +1,26 @@
-use lib::cpu::CPUPayload;
-use lib::state::CPUState;
-use lib::state::RegistersState;
-use log::trace;
-
-#[tauri::command]
-pub async fn cmd_get_cpu(cpu_state: CPUState<'_>, registers_state: RegistersState<'_>) -> Result<CPUPayload, ()> {
-    trace!("cmd_get_cpu: cpu state...");
-    
-    let cpu_lock = cpu_state.lock().await;
-    let registers_lock = &mut registers_state.lock().await;
-    
-    Ok(CPUPayload {
-        trace: cpu_lock.get_trace(),
-        mode: registers_lock.get_cpsr_mode()
-    })
-}
+use lib::cpu::{CPUPayload, PipelinePayload};
+use log::trace;
+
+use crate::state::MachineState;
+
+#[tauri::command]
+pub async fn cmd_get_cpu(machine_state: MachineState<'_>) -> Result<CPUPayload, ()> {
+    trace!("cmd_get_cpu: cpu state...");
+    
+    let machine_lock = &mut machine_state.lock().await;
+    
+    Ok(CPUPayload {
+        trace: machine_lock.cpu.get_trace(),
+        mode: machine_lock.registers.get_cpsr_mode(),
+        cycles: machine_lock.cpu.get_cycles()
+    })
+}
+
+#[tauri::command]
+pub async fn cmd_get_pipeline(machine_state: MachineState<'_>) -> Result<PipelinePayload, ()> {
+    trace!("cmd_get_pipeline: pipeline stages...");
+
+    let machine_lock = &mut machine_state.lock().await;
+
+    Ok(machine_lock.cpu.get_pipeline_payload())
+}
@@ -1,58 +1,68 @@
-use lib::instruction::TInstruction;
-use lib::memory::{Word, Memory};
-use lib::state::{RegistersState, CPUState, RAMState};
-use log::trace;
-use tauri::{AppHandle, Manager};
-
-pub type DisassemblyInstruction = (bool, Word, Word, String);
-
-#[derive(Clone, serde::Serialize)]
-pub struct DisassemblyPayload {
-    pc: Word,
-	instructions: Vec<DisassemblyInstruction>
-}
-
-pub async fn build_disassembly_payload(app_handle: AppHandle) -> DisassemblyPayload {
-    trace!("build_dissassembly_payload: attempting to lock state...");
-
-    let cpu_state: CPUState = app_handle.state();
-    let cpu_lock = &mut cpu_state.lock().await;
-    let registers_state: RegistersState = app_handle.state();
-    let registers_lock = &mut registers_state.lock().await;
-    let ram_state: RAMState = app_handle.state();
-    let ram_lock = &mut ram_state.lock().await;
-
-    trace!("build_dissassembly_payload: obtained state locks");
-
-    let mut disassembly_instructions: Vec<DisassemblyInstruction> = Vec::new();
-
-    // get 7 instructions: 3 before pc + pc + 3 after pc (each instruction is 4 bytes)
-    let pc = registers_lock.get_pc_current_address();
-    let mut address = pc.checked_sub(12).unwrap_or(0);
-    loop {
-        let instr_raw = ram_lock.read_word(address);
-        let mut instr = cpu_lock.decode(instr_raw);
-        instr.set_pc_address(address + 8);
-        let instr_str = instr.to_string();
-        let breakpoint_set = cpu_lock.is_breakpoint(&address);
-        disassembly_instructions.push((breakpoint_set, address, instr_raw, instr_str));
-        
-        address += 4; // word is 4 bytes
-        if address as usize >= ram_lock.get_size() { break }
-        if address > pc + 12 { break }
-    }
-
-    trace!("build_dissassembly_payload: finished");
-
-    DisassemblyPayload {
-        pc,
-        instructions: disassembly_instructions.clone()
-    }
-}
-
-#[tauri::command]
-pub async fn cmd_get_disassembly(app_handle: AppHandle) -> Result<DisassemblyPayload, ()> {
-    trace!("cmd_get_disassembly: grabbing disassembly...");
-    
-    Ok(build_disassembly_payload(app_handle.clone()).await)
+use lib::instruction::TInstruction;
+use lib::memory::{Word, Memory};
+use log::trace;
+use tauri::{AppHandle, Manager};
+
+use crate::state::MachineState;
+
+pub type DisassemblyInstruction = (bool, Word, Word, String);
+
+#[derive(Clone, serde::Serialize)]
+pub struct DisassemblyPayload {
+    pc: Word,
+    thumb: bool,
+	instructions: Vec<DisassemblyInstruction>
+}
+
+pub async fn build_disassembly_payload(app_handle: AppHandle) -> DisassemblyPayload {
+    trace!("build_dissassembly_payload: attempting to lock state...");
+
+    let machine_state: MachineState = app_handle.state();
+    let machine_lock = &mut *machine_state.lock().await;
+    let cpu_lock = &mut machine_lock.cpu;
+    let registers_lock = &mut machine_lock.registers;
+    let ram_lock = &mut machine_lock.ram;
+
+    trace!("build_dissassembly_payload: obtained state locks");
+
+    let mut disassembly_instructions: Vec<DisassemblyInstruction> = Vec::new();
+
+    // get 7 instructions: 3 before pc + pc + 3 after pc
+    // (each instruction is 4 bytes in ARM state and 2 bytes in Thumb state)
+    let pc = registers_lock.get_pc_current_address();
+    let thumb = registers_lock.get_t_flag();
+    let size = registers_lock.get_instr_size();
+    let mut address = pc.checked_sub(size * 3).unwrap_or(0);
+    loop {
+        let (instr_raw, mut instr) = if thumb {
+            let instr_raw = ram_lock.read_half_word(address) as Word;
+            (instr_raw, cpu_lock.decode_thumb(instr_raw))
+        } else {
+            let instr_raw = ram_lock.read_word(address);
+            (instr_raw, cpu_lock.decode(instr_raw))
+        };
+        instr.set_pc_address(address + size * 2);
+        let instr_str = instr.to_string();
+        let breakpoint_set = cpu_lock.is_breakpoint(&address);
+        disassembly_instructions.push((breakpoint_set, address, instr_raw, instr_str));
+        
+        address += size;
+        if address as usize >= ram_lock.get_size() { break }
+        if address > pc + size * 3 { break }
+    }
+
+    trace!("build_dissassembly_payload: finished");
+
+    DisassemblyPayload {
+        pc,
+        thumb,
+        instructions: disassembly_instructions.clone()
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_get_disassembly(app_handle: AppHandle) -> Result<DisassemblyPayload, ()> {
+    trace!("cmd_get_disassembly: grabbing disassembly...");
+    
+    Ok(build_disassembly_payload(app_handle.clone()).await)
 }
\ No newline at end of file
@@ -1,18 +1,22 @@
-use lib::memory::FlagsPayload;
-use lib::state::RegistersState;
-use log::trace;
-
-#[tauri::command]
-pub async fn cmd_get_flags(registers_state: RegistersState<'_>) -> Result<FlagsPayload, ()> {
-    trace!("cmd_get_flags: grabbing flags...");
-    
-    let mut registers_lock = registers_state.lock().await;
-    
-    Ok(FlagsPayload {
-        n: registers_lock.get_n_flag(),
-        z: registers_lock.get_z_flag(),
-        c: registers_lock.get_c_flag(),
-        v: registers_lock.get_v_flag(),
-        i: registers_lock.get_i_flag(),
-    })
+use lib::memory::FlagsPayload;
+use log::trace;
+
+use crate::state::MachineState;
+
+#[tauri::command]
+pub async fn cmd_get_flags(machine_state: MachineState<'_>) -> Result<FlagsPayload, ()> {
+    trace!("cmd_get_flags: grabbing flags...");
+    
+    let machine_lock = &mut machine_state.lock().await;
+    let registers_lock = &mut machine_lock.registers;
+    
+    Ok(FlagsPayload {
+        n: registers_lock.get_n_flag(),
+        z: registers_lock.get_z_flag(),
+        c: registers_lock.get_c_flag(),
+        v: registers_lock.get_v_flag(),
+        q: registers_lock.get_q_flag(),
+        i: registers_lock.get_i_flag(),
+        f: registers_lock.get_f_flag(),
+    })
 }
\ No newline at end of file
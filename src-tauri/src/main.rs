@@ -17,15 +17,14 @@ mod disassembly_cmd;
 mod cpu_cmd;
 mod terminal_cmd;
 mod stack_cmd;
+mod cache_cmd;
+mod state;
+mod cli;
 
-use lib::memory;
+use lib::memory::{self, Memory};
 use lib::options;
-use lib::cpu;
-use lib::state::CPUState;
-use lib::state::OptionsState;
-use lib::state::RAMState;
-use lib::state::TraceFileState;
-use lib::trace;
+use lib::machine::Machine;
+use lib::cache::Cache;
 use log::trace;
 use tauri::{async_runtime::{Mutex, spawn}, Manager};
 use tauri_plugin_log::{fern::colors::ColoredLevelConfig, LogTarget, LoggerBuilder};
@@ -46,24 +45,12 @@ fn main() {
     tauri::Builder::default()
         .setup(|app| {
             
-            // setup state used by app handler here since Builder::setup runs before calls to Builder::manage
-            app.manage(Mutex::new(memory::Registers::default()));
-            app.manage(Mutex::new(memory::RAM::default()));
-            app.manage(Mutex::new(options::Options::default()));
-            app.manage(Mutex::new(cpu::CPU::default()));
-            app.manage(Mutex::new(cpu::CPUThreadWatcher::default()));
-            app.manage(Mutex::new(trace::TraceFile::default()));
+            let mut opts = options::Options::default();
 
             match app.get_cli_matches() {
                 Ok(matches) => {
                     trace!("{:?}", matches);
-
-                    let handle = app.app_handle();
-                    let opts: OptionsState = handle.state();
-                    let mut opts_lock = opts.blocking_lock();
-                    opts_lock.parse(matches);
-                    drop(opts_lock);
-
+                    cli::parse_matches(&mut opts, matches);
                 }
                 Err(e) => {
                     trace!("{}", e)
@@ -71,42 +58,56 @@ fn main() {
             }
             
             let handle = app.app_handle();
-            let opts_elf_file;
-            
-            // drop locks immediately
-            {
-                let opts: OptionsState = handle.state();
-                let cpu: CPUState = handle.state();
-                let memory: RAMState = handle.state();
-                let trace: TraceFileState = handle.state();
-                
-                let opts_lock = opts.blocking_lock();
-                let mut cpu_lock = cpu.blocking_lock();
-                let mut memory_lock = memory.blocking_lock();
-                let mut trace_lock = trace.blocking_lock();
-                
-                // copy here to pass to loader after locks are freed
-                opts_elf_file = opts_lock.elf_file.clone().unwrap_or("".to_string());
-
-                // enable CPU step tracing if --exec is provided and an elf-file is provided
-                if opts_lock.exec && opts_lock.elf_file.is_some() { cpu_lock.toggle_trace(); }
-
-                // enable traceall if option enabled
-                if opts_lock.traceall { trace_lock.set_traceall(); }
-                
-                // create RAM using memsize
-                let opts_memsize = match opts_lock.memory_size {
-                    Some(size) => size,
-                    None => memory::DEFAULT_MEMORY_SIZE,
-                };
-                memory_lock.size = opts_memsize;
-                memory_lock.memory_array.resize(opts_memsize, 0);
-                
-                // debug information
-                trace!("OPTIONS: {}bytes, {}", opts_memsize, opts_elf_file);
-                trace!("RAM Details: {}bytes, {}actual", opts_memsize, memory_lock.memory_array.len());
 
-            }
+            // copy here to pass to loader after state is managed
+            let opts_elf_file = opts.elf_file.clone().unwrap_or("".to_string());
+
+            // create RAM using memsize
+            let opts_memsize = match opts.memory_size {
+                Some(size) => size,
+                None => memory::DEFAULT_MEMORY_SIZE,
+            };
+            let mut machine = Machine::new(opts_memsize);
+
+            // enable CPU step tracing if --exec is provided and an elf-file is provided
+            if opts.exec && opts.elf_file.is_some() { machine.cpu.toggle_trace(); }
+
+            // enable traceall if option enabled
+            if opts.traceall { machine.trace.set_traceall(); }
+
+            // append cycle counts to the trace log
+            if opts.trace_cycles { machine.trace.set_cycles(); }
+
+            // write the trace log somewhere other than trace.log
+            if let Some(path) = &opts.trace_file { machine.trace.set_path(path); }
+
+            // unaligned load/store behavior
+            if let Some(policy) = opts.alignment { machine.ram.set_alignment_policy(policy); }
+
+            // opt in to the ARMv5TE instruction set
+            if let Some(architecture) = opts.architecture { machine.cpu.set_architecture(architecture); }
+
+            // halt on undefined instructions instead of entering the handler
+            if let Some(policy) = opts.undefined { machine.cpu.set_undefined_policy(policy); }
+
+            // simulate instruction and data caches in front of RAM
+            if let Some(config) = opts.icache { machine.ram.icache = Some(Cache::new(config)); }
+            if let Some(config) = opts.dcache { machine.ram.dcache = Some(Cache::new(config)); }
+
+            // route terminal I/O to the frontend
+            let cpu_thread_watcher = machine.get_watcher();
+            let keyboard = machine.get_keyboard();
+            machine.set_console(terminal_cmd::TauriConsole::new(handle.clone(), cpu_thread_watcher.clone()));
+
+            // debug information
+            trace!("OPTIONS: {}bytes, {}", opts_memsize, opts_elf_file);
+            trace!("RAM Details: {}bytes, {}actual", opts_memsize, machine.ram.memory_array.len());
+
+            // setup state used by app handler here since Builder::setup runs before calls to Builder::manage
+            app.manage(Mutex::new(machine));
+            app.manage(Mutex::new(opts));
+            app.manage(cpu_thread_watcher);
+            app.manage(keyboard);
             
             // if a cmd-line argument file was passed
             if !opts_elf_file.is_empty() {
@@ -119,9 +120,11 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             stack_cmd::cmd_get_stack,
+            cache_cmd::cmd_get_cache,
             terminal_cmd::cmd_terminal_input_interrupt,
             terminal_cmd::cmd_terminal_prompt_input,
             cpu_cmd::cmd_get_cpu,
+            cpu_cmd::cmd_get_pipeline,
             loader_cmd::cmd_get_elf,
             loader_cmd::cmd_load_elf,
             registers_cmd::cmd_get_registers,
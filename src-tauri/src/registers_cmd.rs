@@ -1,14 +1,15 @@
-use lib::memory::{RegistersPayload};
-use lib::state::RegistersState;
-use log::trace;
-
-#[tauri::command]
-pub async fn cmd_get_registers(registers_state: RegistersState<'_>) -> Result<RegistersPayload, ()> {
-    trace!("cmd_get_registers: grabbing register r0..r15...");
-    
-    let mut registers_lock = registers_state.lock().await;
-    
-    Ok(RegistersPayload {
-        register_array: registers_lock.get_all()
-    })
+use lib::memory::{RegistersPayload};
+use log::trace;
+
+use crate::state::MachineState;
+
+#[tauri::command]
+pub async fn cmd_get_registers(machine_state: MachineState<'_>) -> Result<RegistersPayload, ()> {
+    trace!("cmd_get_registers: grabbing register r0..r15...");
+    
+    let mut machine_lock = machine_state.lock().await;
+    
+    Ok(RegistersPayload {
+        register_array: machine_lock.registers.get_all()
+    })
 }
\ No newline at end of file
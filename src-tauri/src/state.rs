@@ -0,0 +1,13 @@
+use std::sync::{Arc, Mutex as SyncMutex};
+
+use lib::cpu::CPUThreadWatcher;
+use lib::devices::KeyboardFifo;
+use lib::machine::Machine;
+use lib::options::Options;
+use tauri::{ State, async_runtime::Mutex };
+
+pub type MachineState<'a> = State<'a, Mutex<Machine>>;
+pub type OptionsState<'a> = State<'a, Mutex<Options>>;
+// shared with the Machine so it can be updated while the CPU is running
+pub type CPUThreadWatcherState<'a> = State<'a, Arc<SyncMutex<CPUThreadWatcher>>>;
+pub type KeyboardState<'a> = State<'a, KeyboardFifo>;
@@ -1,27 +1,73 @@
-use lib::memory::{Word, Byte};
-use lib::state::{CPUThreadWatcherState};
-use log::trace;
-
-#[tauri::command]
-pub async fn cmd_terminal_input_interrupt(last_char: Word, cpu_state: CPUThreadWatcherState<'_>) -> Result<(), ()> {
-    trace!("cmd_terminal_input_interrupt: user terminal input, setting CPUThreadWatcher IRQ flag...");
-    
-    trace!("cmd_terminal_input_interrupt: attempting to lock state...");
-    let cpu_thread_watcher_lock = &mut cpu_state.lock().await;
-    trace!("cmd_terminal_input_interrupt: obtained state lock");
-    cpu_thread_watcher_lock.set_irq_flag();
-    cpu_thread_watcher_lock.set_irq_last_char(((last_char & 0xff) as Byte) as char);
-    
-    Ok(())
-}
-
-#[tauri::command]
-pub async fn cmd_terminal_prompt_input(prompt_input: String, cpu_thread_watcher_state: CPUThreadWatcherState<'_>) -> Result<(), ()> {
-    trace!("cmd_terminal_prompt_input: prompt completed, notifying CPU...");
-    
-    trace!("cmd_terminal_prompt_input: attempting to lock state...");
-    (&mut cpu_thread_watcher_state.lock().await).set_prompt_input(prompt_input.clone());
-    trace!("cmd_terminal_prompt_input: obtained state lock");
-
-    Ok(())
-}
+use std::sync::{Arc, Mutex as SyncMutex};
+
+use lib::console::HostConsole;
+use lib::cpu::{CPUThreadWatcher, TerminalPutcharPayload, TerminalReadlinePayload};
+use lib::memory::{Word, Byte};
+use crate::state::{CPUThreadWatcherState, KeyboardState};
+use log::trace;
+use tauri::{AppHandle, Manager};
+
+// routes the CPU's terminal I/O to the frontend terminal panel
+pub struct TauriConsole {
+    app_handle: AppHandle,
+    cpu_thread_watcher: Arc<SyncMutex<CPUThreadWatcher>>
+}
+
+impl TauriConsole {
+    pub fn new(app_handle: AppHandle, cpu_thread_watcher: Arc<SyncMutex<CPUThreadWatcher>>) -> Self {
+        Self { app_handle, cpu_thread_watcher }
+    }
+}
+
+impl HostConsole for TauriConsole {
+    fn write_char(&mut self, c: char) {
+        self.app_handle.emit_all("terminal_append", TerminalPutcharPayload {
+            char: c
+        }).unwrap();
+    }
+
+    fn read_line(&mut self, max_bytes: Word) -> String {
+        self.app_handle.emit_all("terminal_prompt", TerminalReadlinePayload {
+            max_bytes
+        }).unwrap();
+
+        // wait for frontend to return
+        // frontend will update CPUThreadWatcher state through cmd_terminal_prompt_input
+        loop {
+            // check thread state and drop immediately
+            if self.cpu_thread_watcher.lock().unwrap().get_prompt_flag() {
+                break;
+            }
+        }
+
+        let cpu_thread_watcher_lock = &mut self.cpu_thread_watcher.lock().unwrap();
+        let input = cpu_thread_watcher_lock.get_prompt_input();
+        cpu_thread_watcher_lock.clear_prompt_flag();
+        input
+    }
+
+    // keypresses are pushed straight into the keyboard FIFO by cmd_terminal_input_interrupt
+    fn poll_key(&mut self) -> Option<char> {
+        None
+    }
+}
+
+#[tauri::command]
+pub async fn cmd_terminal_input_interrupt(last_char: Word, keyboard_state: KeyboardState<'_>) -> Result<(), ()> {
+    trace!("cmd_terminal_input_interrupt: user terminal input, pushing key into keyboard FIFO...");
+    
+    keyboard_state.push((last_char & 0xff) as Byte);
+    
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn cmd_terminal_prompt_input(prompt_input: String, cpu_thread_watcher_state: CPUThreadWatcherState<'_>) -> Result<(), ()> {
+    trace!("cmd_terminal_prompt_input: prompt completed, notifying CPU...");
+    
+    trace!("cmd_terminal_prompt_input: attempting to lock state...");
+    (&mut cpu_thread_watcher_state.lock().unwrap()).set_prompt_input(prompt_input.clone());
+    trace!("cmd_terminal_prompt_input: obtained state lock");
+
+    Ok(())
+}
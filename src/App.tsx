@@ -12,6 +12,8 @@ import styles from './css/App.module.css';
 import MemoryPanel from './MemoryPanel';
 import RegisterPanel from './RegisterPanel';
 import StackPanel from './StackPanel';
+import CachePanel from './CachePanel';
+import PipelinePanel from './PipelinePanel';
 import TerminalPanel from './TerminalPanel';
 import DisassemblyPanel from './DisassemblyPanel';
 import FlagsPanel from './FlagsPanel';
@@ -43,6 +45,18 @@ const App: Component = () => {
 		setFilename("")
 	})
 	
+	listen('cpu_fault', ({ payload }: { payload: IFaultPayload }) => {
+		const address = payload.address.toString(16).padStart(8, '0')
+		const pc = payload.pc.toString(16).padStart(8, '0')
+		const instruction = payload.instruction === null ? '' : ` 0x${payload.instruction.toString(16)}`
+		log.trace(`SolidJS[App]: ${payload.fault}${instruction} at 0x${address} (pc = 0x${pc})`)
+
+		// the CPU halted on the faulting instruction instead of entering the handler
+		if (!payload.handled) {
+			alert(`Unhandled ${payload.fault}${instruction} at 0x${address} (pc = 0x${pc}).`)
+		}
+	})
+	
 	onMount(async () => {
 		// check if a binary has been loaded by command-line args
 		try {
@@ -94,10 +108,12 @@ const App: Component = () => {
 					<div class="flex flex-col p-1 flex-1 overflow-x-hidden">
 						<MemoryPanel/>
 						<DisassemblyPanel/>
+						<PipelinePanel/>
 					</div>
 					<div class="flex flex-col p-1 flex-0 overflow-x-hidden">
 						<RegisterPanel/>
 						<StackPanel/>
+						<CachePanel/>
 					</div>
 					<div class="flex flex-col p-1 flex-1 overflow-x-hidden">
 						<FlagsPanel />
@@ -0,0 +1,59 @@
+import { listen } from '@tauri-apps/api/event';
+import { Component, createEffect, createSignal } from 'solid-js'
+import { trace } from 'tauri-plugin-log-api';
+import { filename } from './state';
+
+const CacheTable: Component<ICacheProp> = (prop: ICacheProp) => {
+    return (
+        <div class="p-2 mb-1 rounded-sm bg-gray-700">
+            <p class="font-mono text-sm">
+                {prop.name}: {prop.cache.stats.read_hits + prop.cache.stats.write_hits} hits, {prop.cache.stats.read_misses + prop.cache.stats.write_misses} misses, {prop.cache.stats.writebacks} writebacks
+            </p>
+            <table class="font-mono w-full">
+                <thead>
+                    <tr class="bg-gray-700">
+                        <td class="pl-6">Set</td>
+                        <td class="pl-6">Line</td>
+                        <td class="pl-6">Dirty</td>
+                    </tr>
+                </thead>
+                <tbody>
+                    {prop.cache.lines.map((line: ICacheLine) => {
+                        return (
+                            <tr class="bg-gray-800">
+                                <td class="pl-6">{line.set}</td>
+                                <td class="pl-6">{line.address.toString(16).padStart(8, '0')}</td>
+                                <td class="pl-6">{line.dirty ? "D" : ""}</td>
+                            </tr>
+                        )
+                    })}
+                </tbody>
+            </table>
+        </div>
+    )
+}
+
+const CachePanel: Component = () => {
+    const [icache, setICache] = createSignal<ICachePayload | null>(null)
+    const [dcache, setDCache] = createSignal<ICachePayload | null>(null)
+
+    listen("cache_update", ({payload}: { payload: ICachesPayload }) => {
+        trace("SolidJS[CachePanel.listen]: updating caches...")
+        setICache(payload.icache)
+        setDCache(payload.dcache)
+    })
+
+    // clear the contents on filename change
+    createEffect(() => { if (filename()) { setICache(null); setDCache(null) } })
+
+    return (
+        <section>
+            <h3>Cache</h3>
+            {icache() ? <CacheTable name="I-cache" cache={icache()!}/> : ""}
+            {dcache() ? <CacheTable name="D-cache" cache={dcache()!}/> : ""}
+            {!icache() && !dcache() ? <p class="font-mono text-sm">Disabled (--icache/--dcache)</p> : ""}
+        </section>
+    )
+}
+
+export default CachePanel
@@ -0,0 +1,59 @@
+import { listen } from '@tauri-apps/api/event';
+import { Component, createEffect, createSignal } from 'solid-js'
+import { trace } from 'tauri-plugin-log-api';
+import { filename } from './state';
+
+const PipelinePanel: Component<IPipelineProp> = (prop: IPipelineProp) => {
+    const [pipeline, setPipeline] = createSignal<IPipelinePayload | null>(null)
+
+    listen("pipeline_update", ({payload}: { payload: IPipelinePayload }) => {
+        trace("SolidJS[PipelinePanel.listen]: updating pipeline...")
+        setPipeline(payload)
+    })
+
+    // clear the output on filename change
+    createEffect(() => { filename() ? setPipeline(null) : "" })
+
+    // the decode and fetch stages are discarded when the executed instruction flushed the pipeline
+    const stages = (): Array<[string, IPipelineStage | null, boolean]> => {
+        const payload = pipeline()
+        if (!payload) return []
+        const flushed = payload.flush !== null
+        return [
+            ["Fetch", payload.fetch, flushed],
+            ["Decode", payload.decode, flushed],
+            ["Execute", payload.execute, false]
+        ]
+    }
+
+    return (
+        <section>
+            <h3>Pipeline</h3>
+            <div class="p-2 rounded-sm bg-gray-700">
+                <table class="font-mono w-full">
+                    <thead>
+                        <tr class="bg-gray-700">
+                            <td class="pl-2">Stage</td>
+                            <td class="pl-6">Address</td>
+                            <td class="pl-6">Assembly</td>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {stages().map(([name, stage, flushed]) => {
+                            return (
+                                <tr class={name === "Execute" ? 'bg-blue-900' : 'bg-gray-800'} classList={ {['line-through opacity-50']: flushed} }>
+                                    <td class="pl-2">{name}</td>
+                                    <td class="pl-6">{stage ? stage.address.toString(16).padStart(8, '0') : ""}</td>
+                                    <td class="pl-6">{stage ? stage.disassembly : ""}</td>
+                                </tr>
+                            )
+                        })}
+                    </tbody>
+                </table>
+                {pipeline()?.flush ? <p class="pl-2 text-sm italic">flushed ({pipeline()?.flush === "Branch" ? "branch" : "exception"})</p> : ""}
+            </div>
+        </section>
+    )
+}
+
+export default PipelinePanel
@@ -1,5 +1,5 @@
-import { createSignal } from "solid-js"
-
-const [filename, setFilename] = createSignal("None.")
-
+import { createSignal } from "solid-js"
+
+const [filename, setFilename] = createSignal("None.")
+
 export {filename, setFilename}
\ No newline at end of file
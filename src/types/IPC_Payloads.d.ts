@@ -1,56 +1,117 @@
-// from backend
-
-interface IELFPayload {
-	loaded: boolean
-	error: string
-	filename: string
-}
-
-interface IRegistersPayload {
-	register_array: Array<number>
-}
-
-interface IRAMPayload {
-	checksum: number
-	memory_array: Array<Array<number>>
-}
-
-interface IFlagsPayload {
-	n: boolean
-    z: boolean
-    c: boolean
-    v: boolean
-    i: boolean
-}
-
-interface ICPUPayload {
-	trace: boolean
-	mode: string
-}
-
-interface ITerminalPutcharPayload {
-	char: string
-}
-
-interface ITerminalReadlinePayload {
-	max_bytes: number
-}
-
-// array containing tuple, each with
-// - instruction address (focuses at current PC): Word/number
-// - instruction at that address: Word/number
-// - disassembled representation: String/string
-interface IDisassemblyInstruction extends Array<number | string | boolean> { 0: boolean, 1: number, 2: number, 3: string }
-interface IDisassemblyPayload {
-	pc: number
-	instructions: Array<IDisassemblyInstruction>
-}
-
-// array containing tuple, each with
-// - address (focuses at current SP): Word/number
-// - value at that address: Word/number
-interface IStackAddress extends Array<number> { 0: number, 1: number }
-interface IStackPayload {
-	sp: number
-	addresses: Array<IStackAddress>
+// from backend
+
+interface IELFPayload {
+	loaded: boolean
+	error: string
+	filename: string
+}
+
+interface IRegistersPayload {
+	register_array: Array<number>
+}
+
+interface IRAMPayload {
+	checksum: number
+	memory_array: Array<Array<number>>
+}
+
+interface IFlagsPayload {
+	n: boolean
+    z: boolean
+    c: boolean
+    v: boolean
+    q: boolean
+    i: boolean
+    f: boolean
+}
+
+interface ICycles {
+	s: number
+	n: number
+	i: number
+	c: number
+}
+interface ICPUPayload {
+	trace: boolean
+	mode: string
+	cycles: ICycles
+}
+
+interface IFaultPayload {
+	fault: string
+	address: number
+	pc: number
+	instruction: number | null
+	handled: boolean
+}
+
+interface ITerminalPutcharPayload {
+	char: string
+}
+
+interface ITerminalReadlinePayload {
+	max_bytes: number
+}
+
+// array containing tuple, each with
+// - instruction address (focuses at current PC): Word/number
+// - instruction at that address: Word/number
+// - disassembled representation: String/string
+interface IDisassemblyInstruction extends Array<number | string | boolean> { 0: boolean, 1: number, 2: number, 3: string }
+interface IDisassemblyPayload {
+	pc: number
+	thumb: boolean
+	instructions: Array<IDisassemblyInstruction>
+}
+
+// array containing tuple, each with
+// - address (focuses at current SP): Word/number
+// - value at that address: Word/number
+interface IStackAddress extends Array<number> { 0: number, 1: number }
+interface IStackPayload {
+	sp: number
+	addresses: Array<IStackAddress>
+}
+
+interface ICacheStats {
+	read_hits: number
+	read_misses: number
+	write_hits: number
+	write_misses: number
+	writebacks: number
+}
+// only valid lines are sent
+interface ICacheLine {
+	set: number
+	address: number
+	valid: boolean
+	dirty: boolean
+}
+interface ICachePayload {
+	stats: ICacheStats
+	sets: number
+	ways: number
+	lines: Array<ICacheLine>
+}
+// a cache is null when it isn't simulated
+interface ICachesPayload {
+	icache: ICachePayload | null
+	dcache: ICachePayload | null
+}
+
+// address and disassembly of the instruction in a pipeline stage; a failed fetch has
+//  no instruction and its disassembly describes the error
+interface IPipelineStage {
+	address: number
+	instruction: number | null
+	disassembly: string
+	thumb: boolean
+}
+// the stages while the last instruction executed; flush is set when the decode and
+//  fetch stages were discarded
+interface IPipelinePayload {
+	fetch: IPipelineStage | null
+	decode: IPipelineStage | null
+	execute: IPipelineStage | null
+	flush: "Branch" | "Exception" | null
 }
\ No newline at end of file
@@ -1,27 +1,36 @@
-interface IMemoryProp {
-
-}
-
-interface IDisassemblyProp {
-
-}
-
-interface IFlagsProp {
-    
-}
-
-interface IRegisterProp {
-    
-}
-
-interface IFlagsProp {
-    
-}
-
-interface ITerminalProp {
-    
-}
-
-interface IStackProp {
-    
+interface IMemoryProp {
+
+}
+
+interface IDisassemblyProp {
+
+}
+
+interface IFlagsProp {
+    
+}
+
+interface IRegisterProp {
+    
+}
+
+interface IFlagsProp {
+    
+}
+
+interface ITerminalProp {
+    
+}
+
+interface IStackProp {
+    
+}
+
+interface ICacheProp {
+    name: string
+    cache: ICachePayload
+}
+
+interface IPipelineProp {
+    
 }
\ No newline at end of file
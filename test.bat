@@ -1,22 +1,18 @@
-@echo off
-
-set tests1="btest" "ctest"
-set tests2="branch" "quicksort_no_io" "cmp" "locals_no_io" "mersenne_no_io"
-
-(for %%a in (%tests1%) do ( 
-    @REM .\src-tauri\target\debug\armsim.exe --traceall --exec ..\cps310-class_files\tests\sim1\\%%a.exe
-    .\src-tauri\target\release\armsim.exe --traceall --exec ..\cps310-class_files\tests\sim1\\%%a.exe
-    rename trace.log %%a_trace.log
-    move %%a_trace.log .\tests\logs
-    echo %%a trace log generated
-)
-)
-
-(for %%a in (%tests2%) do ( 
-    @REM .\src-tauri\target\debug\armsim.exe --traceall --exec ..\cps310-class_files\tests\sim2\\%%a.exe
-    .\src-tauri\target\release\armsim.exe --traceall --exec ..\cps310-class_files\tests\sim2\\%%a.exe
-    rename trace.log %%a_trace.log
-    move %%a_trace.log .\tests\logs
-    echo %%a trace log generated
-)
+@echo off
+
+REM build with: cd cli && cargo build --release
+
+set tests1="btest" "ctest"
+set tests2="branch" "quicksort_no_io" "cmp" "locals_no_io" "mersenne_no_io"
+
+(for %%a in (%tests1%) do ( 
+    .\cli\target\release\armsim-cli.exe --traceall --trace-file .\tests\logs\%%a_trace.log ..\cps310-class_files\tests\sim1\\%%a.exe
+    echo %%a trace log generated
+)
+)
+
+(for %%a in (%tests2%) do ( 
+    .\cli\target\release\armsim-cli.exe --traceall --trace-file .\tests\logs\%%a_trace.log ..\cps310-class_files\tests\sim2\\%%a.exe
+    echo %%a trace log generated
+)
 )
\ No newline at end of file